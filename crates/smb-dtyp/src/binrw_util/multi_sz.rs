@@ -13,7 +13,7 @@ use binrw::{Endian, NullWideString};
 /// null-terminated wide strings, ending with an additional null string.
 ///
 /// Similar to the Registry [`REG_MULTI_SZ`](https://learn.microsoft.com/en-us/windows/win32/sysinfo/registry-value-types) type.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct MultiWSz(Vec<NullWideString>);
 
 impl BinRead for MultiWSz {
@@ -20,7 +20,7 @@ use crate::binrw_util::prelude::*;
 /// assert_eq!(sid_string, SID_STRING);
 /// ```
 #[binrw::binrw]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 #[brw(little)]
 pub struct SID {
     #[bw(calc = 1)]
@@ -31,7 +31,7 @@ type NextEntryOffsetType = u32;
 /// This struct provides conversion to and from [`Vec<T>`] for ease of use.
 ///
 /// The struct supports data of length 0, and puts an empty vector in that case.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ChainedItemList<T, const OFFSET_PAD: u32 = CHAINED_ITEM_DEFAULT_OFFSET_PAD> {
     values: Vec<T>,
 }
@@ -179,6 +179,15 @@ impl<T, const OFFSET_PAD: u32> From<Vec<T>> for ChainedItemList<T, OFFSET_PAD> {
     }
 }
 
+impl<T, const OFFSET_PAD: u32> IntoIterator for ChainedItemList<T, OFFSET_PAD> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
 impl<T, const OFFSET_PAD: u32> FromIterator<T> for ChainedItemList<T, OFFSET_PAD> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let values = iter.into_iter().collect();
@@ -40,7 +40,7 @@ pub struct FileBasicInformation {
 ///
 /// [MS-FSCC 2.4.16](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-fscc/0eb94f48-6aac-41df-a878-79f4dcfd8989>)
 #[binrw::binrw]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct FileFullEaInformationInner {
     /// Can contain zero or more of the following flag values. Unused bit fields should be set to 0.
     pub flags: EaFlags,
@@ -184,7 +184,7 @@ impl From<&str> for FileNameInformation {
 ///
 /// [MS-FSCC 2.1.2.1](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-fscc/c8e77b37-3909-4fe6-a4ea-2b9d423b1ee4>):
 #[binrw::binrw]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[repr(u32)]
 #[brw(repr(u32))]
 pub enum ReparseTag {
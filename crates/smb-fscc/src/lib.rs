@@ -13,6 +13,7 @@
 //! * Directory query types [`QueryDirectoryInfo`]
 //! * Change notifications [`FileNotifyInformation`]
 //! * Access masks [`FileAccessMask`], [`DirAccessMask`]
+//! * Reparse point data [`ReparseDataBuffer`]
 
 #![allow(unused_parens)]
 #![forbid(unsafe_code)]
@@ -28,6 +29,7 @@ mod info_classes;
 mod notify;
 mod query_file_info;
 mod quota;
+mod reparse;
 mod set_file_info;
 
 pub use access_masks::*;
@@ -41,4 +43,5 @@ pub use info_classes::*;
 pub use notify::*;
 pub use query_file_info::*;
 pub use quota::*;
+pub use reparse::*;
 pub use set_file_info::*;
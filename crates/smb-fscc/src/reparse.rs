@@ -0,0 +1,312 @@
+//! Reparse point data buffers.
+//!
+//! Reparse points are used to implement symbolic links, mount points and special files.
+//! They are set and queried using FSCTL_SET_REPARSE_POINT and FSCTL_GET_REPARSE_POINT.
+//!
+//! [MS-FSCC 2.1.2.2](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-fscc/c3a420cb-8a72-4adf-87e8-eee95379d78f>)
+
+use binrw::{io::TakeSeekExt, prelude::*};
+use modular_bitfield::prelude::*;
+
+use smb_dtyp::binrw_util::prelude::*;
+
+use crate::ReparseTag;
+
+/// Contains the data of a reparse point, as set and returned by the reparse point FSCTLs.
+///
+/// [MS-FSCC 2.1.2.2](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-fscc/c3a420cb-8a72-4adf-87e8-eee95379d78f>)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ReparseDataBuffer {
+    #[bw(calc = data.tag())]
+    #[br(temp)]
+    reparse_tag: u32,
+    #[bw(calc = PosMarker::default())]
+    #[br(temp)]
+    reparse_data_length: PosMarker<u16>,
+    #[bw(calc = 0)]
+    #[br(temp)]
+    _reserved: u16,
+    /// The reparse data, as specified by the reparse tag.
+    #[br(args(reparse_tag), map_stream = |s| s.take_seek(reparse_data_length.value as u64))]
+    #[bw(write_with = PosMarker::write_size, args(&reparse_data_length))]
+    pub data: ReparseData,
+}
+
+impl From<ReparseData> for ReparseDataBuffer {
+    fn from(data: ReparseData) -> Self {
+        Self { data }
+    }
+}
+
+/// The tag-specific data of a reparse point.
+///
+/// Reparse points with tags that are not interpreted by this crate are kept as raw data.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[br(import(reparse_tag: u32))]
+pub enum ReparseData {
+    /// A mount point (junction), see [`MountPointReparseBuffer`].
+    #[br(pre_assert(reparse_tag == ReparseTag::MountPoint as u32))]
+    MountPoint(MountPointReparseBuffer),
+    /// A symbolic link, see [`SymbolicLinkReparseBuffer`].
+    #[br(pre_assert(reparse_tag == ReparseTag::Symlink as u32))]
+    Symlink(SymbolicLinkReparseBuffer),
+    /// A symbolic link or a special file, as created by NFS servers and clients.
+    #[br(pre_assert(reparse_tag == ReparseTag::NFS as u32))]
+    Nfs(NfsReparseBuffer),
+    /// A symbolic link, as created by the Windows Subsystem for Linux.
+    #[br(pre_assert(reparse_tag == ReparseTag::LxSymlink as u32))]
+    LxSymlink(LxSymlinkReparseBuffer),
+    /// A UNIX domain socket. Has no data.
+    #[br(pre_assert(reparse_tag == ReparseTag::AfUnix as u32))]
+    AfUnix,
+    /// A FIFO (named pipe), as created by the Windows Subsystem for Linux. Has no data.
+    #[br(pre_assert(reparse_tag == ReparseTag::LxFifo as u32))]
+    LxFifo,
+    /// A character device, as created by the Windows Subsystem for Linux.
+    /// Has no data; the device numbers are stored in the `$LXDEV` extended attribute.
+    #[br(pre_assert(reparse_tag == ReparseTag::LxChr as u32))]
+    LxChr,
+    /// A block device, as created by the Windows Subsystem for Linux.
+    /// Has no data; the device numbers are stored in the `$LXDEV` extended attribute.
+    #[br(pre_assert(reparse_tag == ReparseTag::LxBlk as u32))]
+    LxBlk,
+    /// Any other reparse point.
+    ///
+    /// For non-Microsoft tags, the data starts with the GUID of the reparse point owner.
+    Other {
+        #[br(calc = reparse_tag)]
+        #[bw(ignore)]
+        tag: u32,
+        #[br(parse_with = binrw::helpers::until_eof)]
+        data: Vec<u8>,
+    },
+}
+
+impl ReparseData {
+    /// Returns the reparse tag of the data.
+    pub fn tag(&self) -> u32 {
+        match self {
+            ReparseData::MountPoint(_) => ReparseTag::MountPoint as u32,
+            ReparseData::Symlink(_) => ReparseTag::Symlink as u32,
+            ReparseData::Nfs(_) => ReparseTag::NFS as u32,
+            ReparseData::LxSymlink(_) => ReparseTag::LxSymlink as u32,
+            ReparseData::AfUnix => ReparseTag::AfUnix as u32,
+            ReparseData::LxFifo => ReparseTag::LxFifo as u32,
+            ReparseData::LxChr => ReparseTag::LxChr as u32,
+            ReparseData::LxBlk => ReparseTag::LxBlk as u32,
+            ReparseData::Other { tag, .. } => *tag,
+        }
+    }
+
+    /// Returns the target of a symbolic link, mount point, or NFS/WSL symbolic link.
+    ///
+    /// Returns `None` for any other reparse point.
+    pub fn link_target(&self) -> Option<&str> {
+        match self {
+            ReparseData::MountPoint(mount_point) => Some(&mount_point.substitute_name),
+            ReparseData::Symlink(symlink) => Some(&symlink.substitute_name),
+            ReparseData::Nfs(NfsReparseBuffer::Symlink { target }) => Some(target),
+            ReparseData::LxSymlink(symlink) => Some(&symlink.target),
+            _ => None,
+        }
+    }
+}
+
+/// Symbolic link reparse data.
+///
+/// The substitute name is the actual target of the link. The print name is a user-friendly
+/// form of the target, and is not used for resolving the link.
+///
+/// [MS-FSCC 2.1.2.4](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-fscc/b41f1cbf-10df-4a47-98d4-1c52a833d913>)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SymbolicLinkReparseBuffer {
+    #[bw(calc = 0)]
+    #[br(temp)]
+    substitute_name_offset: u16,
+    #[bw(try_calc = utf16_size(substitute_name))]
+    #[br(temp)]
+    substitute_name_length: u16,
+    #[bw(try_calc = utf16_size(substitute_name))]
+    #[br(temp)]
+    print_name_offset: u16,
+    #[bw(try_calc = utf16_size(print_name))]
+    #[br(temp)]
+    print_name_length: u16,
+    pub flags: SymbolicLinkFlags,
+    #[bw(calc = path_buffer(&[substitute_name, print_name], false))]
+    #[br(temp, parse_with = binrw::helpers::until_eof)]
+    path_buffer: Vec<u8>,
+
+    /// The target of the link.
+    ///
+    /// Absolute targets are NT paths, for example `\??\C:\Target` or `\??\UNC\server\share\target`.
+    #[br(try_calc = utf16_from_buffer(&path_buffer, substitute_name_offset, substitute_name_length))]
+    #[bw(ignore)]
+    pub substitute_name: String,
+    /// The target of the link, as displayed to the user.
+    #[br(try_calc = utf16_from_buffer(&path_buffer, print_name_offset, print_name_length))]
+    #[bw(ignore)]
+    pub print_name: String,
+}
+
+impl SymbolicLinkReparseBuffer {
+    /// Creates symbolic link data pointing at `target`.
+    ///
+    /// Relative targets are resolved relative to the directory containing the link.
+    pub fn new(target: &str, relative: bool) -> Self {
+        Self {
+            flags: SymbolicLinkFlags::new().with_relative(relative),
+            substitute_name: target.to_string(),
+            print_name: target.to_string(),
+        }
+    }
+}
+
+/// Flags of a [`SymbolicLinkReparseBuffer`].
+#[smb_dtyp::mbitfield]
+pub struct SymbolicLinkFlags {
+    /// The substitute name is a path relative to the directory containing the symbolic link.
+    pub relative: bool,
+    #[skip]
+    __: B31,
+}
+
+/// Mount point (junction) reparse data.
+///
+/// [MS-FSCC 2.1.2.5](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-fscc/ca069dad-ed16-42aa-b057-b6b207f447cc>)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MountPointReparseBuffer {
+    #[bw(calc = 0)]
+    #[br(temp)]
+    substitute_name_offset: u16,
+    #[bw(try_calc = utf16_size(substitute_name))]
+    #[br(temp)]
+    substitute_name_length: u16,
+    // Names are null-terminated, and the terminator is not included in the length.
+    #[bw(try_calc = utf16_size(substitute_name).map(|s| s + 2))]
+    #[br(temp)]
+    print_name_offset: u16,
+    #[bw(try_calc = utf16_size(print_name))]
+    #[br(temp)]
+    print_name_length: u16,
+    #[bw(calc = path_buffer(&[substitute_name, print_name], true))]
+    #[br(temp, parse_with = binrw::helpers::until_eof)]
+    path_buffer: Vec<u8>,
+
+    /// The target of the mount point, for example `\??\C:\Target`.
+    #[br(try_calc = utf16_from_buffer(&path_buffer, substitute_name_offset, substitute_name_length))]
+    #[bw(ignore)]
+    pub substitute_name: String,
+    /// The target of the mount point, as displayed to the user.
+    #[br(try_calc = utf16_from_buffer(&path_buffer, print_name_offset, print_name_length))]
+    #[bw(ignore)]
+    pub print_name: String,
+}
+
+/// Network File System (NFS) reparse data, representing symbolic links and special files.
+///
+/// [MS-FSCC 2.1.2.6](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-fscc/ff4df658-7f27-476a-8025-4074c0121eec>)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum NfsReparseBuffer {
+    /// A symbolic link. The target is a UNIX path.
+    #[brw(magic(0x00000000014B4E4Cu64))]
+    Symlink {
+        #[br(parse_with = binrw::helpers::until_eof, try_map = |v: Vec<u16>| String::from_utf16(&v))]
+        #[bw(map = |t: &String| t.encode_utf16().collect::<Vec<u16>>())]
+        target: String,
+    },
+    /// A character device.
+    #[brw(magic(0x0000000000524843u64))]
+    CharDevice { major: u32, minor: u32 },
+    /// A block device.
+    #[brw(magic(0x00000000004B4C42u64))]
+    BlockDevice { major: u32, minor: u32 },
+    /// A FIFO (named pipe).
+    #[brw(magic(0x000000004F464946u64))]
+    Fifo,
+    /// A UNIX domain socket.
+    #[brw(magic(0x000000004B434F53u64))]
+    Socket,
+}
+
+/// Symbolic link reparse data, as created by the Windows Subsystem for Linux.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LxSymlinkReparseBuffer {
+    #[bw(calc = Self::VERSION)]
+    #[br(temp, assert(version == Self::VERSION))]
+    version: u32,
+    /// The target of the link, a UNIX path.
+    #[br(parse_with = binrw::helpers::until_eof, try_map = String::from_utf8)]
+    #[bw(map = |t: &String| t.as_bytes().to_vec())]
+    pub target: String,
+}
+
+impl LxSymlinkReparseBuffer {
+    const VERSION: u32 = 2;
+}
+
+fn utf16_size(value: &str) -> Result<u16, std::num::TryFromIntError> {
+    (value.encode_utf16().count() * size_of::<u16>()).try_into()
+}
+
+fn path_buffer(names: &[&String], null_terminated: bool) -> Vec<u8> {
+    let terminator = null_terminated.then_some(0u16);
+    names
+        .iter()
+        .flat_map(|name| name.encode_utf16().chain(terminator))
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+fn utf16_from_buffer(buffer: &[u8], offset: u16, length: u16) -> Result<String, String> {
+    let (offset, length) = (offset as usize, length as usize);
+    let name = buffer
+        .get(offset..offset + length)
+        .filter(|name| name.len() % 2 == 0)
+        .ok_or_else(|| format!("Invalid name range {offset}+{length} in reparse path buffer"))?;
+    let name: Vec<u16> = name
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16(&name).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use smb_tests::*;
+
+    test_binrw! {
+        ReparseDataBuffer: ReparseDataBuffer::from(ReparseData::Symlink(SymbolicLinkReparseBuffer::new("..\\target", true)))
+            => "0c0000a0300000000000120012001200010000002e002e005c007400610072006700650074002e002e005c00740061007200670065007400"
+    }
+
+    test_binrw! {
+        ReparseDataBuffer => mount_point: ReparseDataBuffer::from(ReparseData::MountPoint(MountPointReparseBuffer {
+            substitute_name: "\\??\\C:\\t".into(),
+            print_name: "C:\\t".into(),
+        })) => "030000a02400000000001000120008005c003f003f005c0043003a005c007400000043003a005c0074000000"
+    }
+
+    test_binrw! {
+        ReparseDataBuffer => nfs_symlink: ReparseDataBuffer::from(ReparseData::Nfs(NfsReparseBuffer::Symlink {
+            target: "../t".into(),
+        })) => "14000080100000004c4e4b01000000002e002e002f007400"
+    }
+
+    test_binrw! {
+        ReparseDataBuffer => lx_symlink: ReparseDataBuffer::from(ReparseData::LxSymlink(LxSymlinkReparseBuffer {
+            target: "/tmp".into(),
+        })) => "1d0000a008000000020000002f746d70"
+    }
+
+    test_binrw! {
+        ReparseDataBuffer => af_unix: ReparseDataBuffer::from(ReparseData::AfUnix) => "2300008000000000"
+    }
+}
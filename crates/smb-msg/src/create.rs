@@ -18,7 +18,7 @@ use smb_msg_derive::*;
 
 /// 2.2.14.1: SMB2_FILEID
 #[binrw::binrw]
-#[derive(PartialEq, Eq, Clone, Copy, Default)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default)]
 pub struct FileId {
     pub persistent: u64,
     pub volatile: u64,
@@ -120,7 +120,7 @@ pub struct CreateRequest {
 ///
 /// Reference: MS-SMB2 2.2.13
 #[smb_request_binrw]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default)]
 #[brw(repr(u32))]
 pub enum ImpersonationLevel {
     /// The application-requested impersonation level is Anonymous
@@ -128,6 +128,7 @@ pub enum ImpersonationLevel {
     /// The application-requested impersonation level is Identification
     Identification = 0x1,
     /// The application-requested impersonation level is Impersonation
+    #[default]
     Impersonation = 0x2,
     /// The application-requested impersonation level is Delegate
     Delegate = 0x3,
@@ -327,6 +328,7 @@ macro_rules! create_context_half {
 #[[<smb_ $struct_name:lower _binrw>]]
 #[bw(import(is_last: bool))]
 #[allow(clippy::manual_non_exhaustive)]
+#[derive(Clone)]
 pub struct [<CreateContext $struct_name:camel>]
 {
     #[bw(calc = PosMarker::default())]
@@ -369,6 +371,7 @@ pub trait [<CreateContextData $struct_name Value>] : Into<[<CreateContext $struc
 #[doc = concat!("The [`Create", stringify!($struct_name), "`] Context data enum. ")]
 #[[<smb_ $struct_name:lower _binrw>]]
 #[br(import(name: &Vec<u8>))]
+#[derive(Clone)]
 pub enum [<CreateContext $struct_name Data>] {
     $(
         #[br(pre_assert(name.as_slice() == CreateContextType::[<$context_type:upper>].name()))]
@@ -503,7 +506,7 @@ make_create_context!(
     /// The client is requesting the open to be durable
     dhnq: b"DHnQ", DurableHandleRequest, DurableHandleResponse;
     /// The client is requesting to reconnect to a durable open after being disconnected
-    dhnc: b"DHNc", DurableHandleReconnect;
+    dhnc: b"DHnC", DurableHandleReconnect;
     /// The data contains the required allocation size of the newly created file.
     alsi: b"AlSi", AllocationSize;
     /// The client is requesting that the server return maximal access information.
@@ -531,6 +534,7 @@ make_create_context!(
 ///
 /// Reference: MS-SMB2 2.2.13.2.3
 #[smb_request_binrw]
+#[derive(Clone)]
 pub struct DurableHandleRequest {
     reserved: u128,
 }
@@ -539,6 +543,7 @@ pub struct DurableHandleRequest {
 ///
 /// Reference: MS-SMB2 2.2.14.2.3
 #[smb_response_binrw]
+#[derive(Clone)]
 pub struct DurableHandleResponse {
     reserved: u64,
 }
@@ -547,6 +552,7 @@ pub struct DurableHandleResponse {
 ///
 /// Reference: MS-SMB2 2.2.13.2.4
 #[smb_request_binrw]
+#[derive(Clone)]
 pub struct DurableHandleReconnect {
     /// The file ID for the open that is being reestablished
     pub durable_request: FileId,
@@ -555,7 +561,7 @@ pub struct DurableHandleReconnect {
 ///
 /// Reference: MS-SMB2 2.2.13.2.5
 #[smb_request_binrw]
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct QueryMaximalAccessRequest {
     /// Optional timestamp for the query
     #[br(parse_with = binread_if_has_data)]
@@ -566,6 +572,7 @@ pub struct QueryMaximalAccessRequest {
 ///
 /// Reference: MS-SMB2 2.2.13.2.6
 #[smb_request_binrw]
+#[derive(Clone)]
 pub struct AllocationSize {
     /// The size, in bytes, that the newly created file must have reserved on disk
     pub allocation_size: u64,
@@ -575,6 +582,7 @@ pub struct AllocationSize {
 ///
 /// Reference: MS-SMB2 2.2.13.2.7
 #[smb_request_binrw]
+#[derive(Clone)]
 pub struct TimewarpToken {
     /// The timestamp of the version of the file to be opened
     pub timestamp: FileTime,
@@ -585,6 +593,7 @@ pub struct TimewarpToken {
 ///
 /// Reference: MS-SMB2 2.2.13.2.8, 2.2.13.2.10, 2.2.14.2.10, 2.2.14.2.11
 #[smb_message_binrw]
+#[derive(Clone)]
 pub enum RequestLease {
     RqLsReqv1(RequestLeaseV1),
     RqLsReqv2(RequestLeaseV2),
@@ -595,6 +604,7 @@ pub enum RequestLease {
 ///
 /// Reference: MS-SMB2 2.2.13.2.8, 2.2.14.2.10
 #[smb_message_binrw]
+#[derive(Clone)]
 pub struct RequestLeaseV1 {
     /// Client-generated key that identifies the owner of the lease
     pub lease_key: u128,
@@ -613,6 +623,7 @@ pub struct RequestLeaseV1 {
 ///
 /// Reference: MS-SMB2 2.2.13.2.10, 2.2.14.2.11
 #[smb_message_binrw]
+#[derive(Clone)]
 pub struct RequestLeaseV2 {
     /// Client-generated key that identifies the owner of the lease
     pub lease_key: u128,
@@ -647,12 +658,14 @@ pub struct LeaseFlags {
 ///
 /// Reference: MS-SMB2 2.2.13.2.9
 #[smb_request_binrw]
+#[derive(Clone)]
 pub struct QueryOnDiskIdReq;
 
 /// Request for a durable or persistent handle (SMB 3.x dialect family only).
 ///
 /// Reference: MS-SMB2 2.2.13.2.11
 #[smb_request_binrw]
+#[derive(Clone)]
 pub struct DurableHandleRequestV2 {
     /// Time in milliseconds for which the server reserves the handle after failover
     pub timeout: u32,
@@ -680,19 +693,21 @@ pub struct DurableHandleV2Flags {
 ///
 /// Reference: MS-SMB2 2.2.13.2.12
 #[smb_request_binrw]
+#[derive(Clone)]
 pub struct DurableHandleReconnectV2 {
     /// The file ID for the open that is being reestablished
-    file_id: FileId,
+    pub file_id: FileId,
     /// Unique ID that identifies the create request
-    create_guid: Guid,
+    pub create_guid: Guid,
     /// Flags indicating whether a persistent handle is requested
-    flags: DurableHandleV2Flags,
+    pub flags: DurableHandleV2Flags,
 }
 
 /// Application instance identifier (SMB 3.x dialect family only).
 ///
 /// Reference: MS-SMB2 2.2.13.2.13
 #[smb_request_response(size = 20)]
+#[derive(Clone)]
 pub struct AppInstanceId {
     reserved: u16,
     /// Unique ID that identifies an application instance
@@ -703,6 +718,7 @@ pub struct AppInstanceId {
 ///
 /// Reference: MS-SMB2 2.2.13.2.15
 #[smb_request_response(size = 24)]
+#[derive(Clone)]
 pub struct AppInstanceVersion {
     reserved: u16,
     reserved: u16,
@@ -717,6 +733,7 @@ pub struct AppInstanceVersion {
 ///
 /// Reference: MS-SMB2 2.2.13.2.14, MS-RSVD 2.2.4.12, 2.2.4.32
 #[smb_message_binrw]
+#[derive(Clone)]
 pub enum SvhdxOpenDeviceContext {
     V1(SvhdxOpenDeviceContextV1),
     V2(SvhdxOpenDeviceContextV2),
@@ -726,6 +743,7 @@ pub enum SvhdxOpenDeviceContext {
 ///
 /// Reference: MS-RSVD 2.2.4.12
 #[smb_message_binrw]
+#[derive(Clone)]
 pub struct SvhdxOpenDeviceContextV1 {
     pub version: u32,
     pub has_initiator_id: Boolean,
@@ -743,6 +761,7 @@ pub struct SvhdxOpenDeviceContextV1 {
 ///
 /// Reference: MS-RSVD 2.2.4.32
 #[smb_message_binrw]
+#[derive(Clone)]
 pub struct SvhdxOpenDeviceContextV2 {
     pub version: u32,
     pub has_initiator_id: Boolean,
@@ -762,6 +781,7 @@ pub struct SvhdxOpenDeviceContextV2 {
 }
 
 #[smb_response_binrw]
+#[derive(Clone)]
 pub struct QueryMaximalAccessResponse {
     // MS-SMB2, 2.2.14.2.5: "MaximalAccess field is valid only if QueryStatus is STATUS_SUCCESS.
     // he status code MUST be one of those defined in [MS-ERREF] section 2.3"
@@ -794,6 +814,7 @@ impl QueryMaximalAccessResponse {
 ///
 /// Reference: MS-SMB2 2.2.14.2.9
 #[smb_response_binrw]
+#[derive(Clone)]
 pub struct QueryOnDiskIdResp {
     /// 64-bit file identifier for the open on disk
     pub file_id: u64,
@@ -807,6 +828,7 @@ pub struct QueryOnDiskIdResp {
 ///
 /// Reference: MS-SMB2 2.2.14.2.12
 #[smb_response_binrw]
+#[derive(Clone)]
 pub struct DH2QResp {
     /// Time in milliseconds the server waits for client reconnect after failover
     pub timeout: u32,
@@ -970,7 +992,6 @@ mod tests {
 
     /*
     Tests to add for contexts:
-    dhnc: b"DHNc", DurableHandleReconnect, DurableHandleReconnect,
     dh2c: b"DH2C", DurableHandleReconnectV2, DurableHandleReconnectV2,
     appinstid: b"\x45\xBC\xA6\x6A\xEF\xA7\xF7\x4A\x90\x08\xFA\x46\x2E\x14\x4D\x74", AppInstanceId, AppInstanceId,
     appinstver: b"\xB9\x82\xD0\xB7\x3B\x56\x07\x4F\xA0\x7B\x52\x4A\x81\x16\xA0\x10", AppInstanceVersion, AppInstanceVersion,
@@ -992,6 +1013,12 @@ mod tests {
         struct DurableHandleResponse {} => "0000000000000000"
     }
 
+    test_binrw_request! {
+        struct DurableHandleReconnect {
+            durable_request: guid!("000000b3-0008-0000-dd00-000008000000").into(),
+        } => "b300000008000000dd00000008000000"
+    }
+
     test_binrw_request! {
         struct QueryMaximalAccessRequest {
             timestamp: None,
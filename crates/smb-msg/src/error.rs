@@ -5,6 +5,7 @@ use binrw::prelude::*;
 #[cfg(feature = "client")]
 use binrw::io::TakeSeekExt;
 use smb_dtyp::binrw_util::prelude::*;
+use smb_fscc::SymbolicLinkReparseBuffer;
 use smb_msg_derive::*;
 
 /// The SMB2 ERROR Response packet is sent by the server to respond to a request
@@ -28,11 +29,34 @@ pub struct ErrorResponse {
 
     /// Variable-length data field that contains extended error information.
     /// For SMB 3.1.1 with nonzero ErrorContextCount, formatted as SMB2 ERROR Context structures.
-    #[br(count = _error_context_count, map_stream = |s| s.take_seek(_byte_count.value.into()))]
+    ///
+    /// Error data of other dialects is read as a single [`ErrorId::Default`] context.
+    #[br(parse_with = read_error_data, args(_error_context_count, _byte_count.value), map_stream = |s| s.take_seek(_byte_count.value.into()))]
     #[bw(write_with = PosMarker::write_size, args(&_byte_count))]
     pub error_data: Vec<ErrorResponseContext>,
 }
 
+/// Reads the error data of an [`ErrorResponse`], which is formatted as error contexts
+/// only if the context count is nonzero.
+#[cfg(feature = "client")]
+#[binrw::parser(reader, endian)]
+fn read_error_data(context_count: u8, byte_count: u32) -> BinResult<Vec<ErrorResponseContext>> {
+    if context_count == 0 {
+        if byte_count == 0 {
+            return Ok(vec![]);
+        }
+        let mut error_data = vec![];
+        reader.read_to_end(&mut error_data)?;
+        return Ok(vec![ErrorResponseContext {
+            error_id: ErrorId::Default,
+            error_data,
+        }]);
+    }
+    (0..context_count)
+        .map(|_| ErrorResponseContext::read_options(reader, endian, ()))
+        .collect()
+}
+
 /// For SMB dialect 3.1.1, error data is formatted as an array of SMB2 ERROR Context structures.
 /// Each error context contains an identifier for the error context followed by the error data.
 /// Each context must start at an 8-byte aligned boundary relative to the start of the SMB2 ERROR Response.
@@ -46,6 +70,7 @@ pub struct ErrorResponseContext {
     #[brw(align_before = 8)]
     /// The length, in bytes, of the ErrorContextData field
     #[bw(try_calc = error_data.len().try_into())]
+    #[br(temp)]
     _error_data_length: u32,
     /// An identifier for the error context
     pub error_id: ErrorId,
@@ -77,6 +102,14 @@ impl ErrorResponseContext {
         }
     }
 
+    /// Interprets the error data as a [`SymbolicLinkErrorResponse`].
+    #[cfg(feature = "client")]
+    pub fn as_symlink_error(&self) -> crate::Result<SymbolicLinkErrorResponse> {
+        Ok(SymbolicLinkErrorResponse::read_le(
+            &mut binrw::io::Cursor::new(&self.error_data),
+        )?)
+    }
+
     /// Interprets the error data as a u64, if possible.
     /// Returns an error if the data length is not 8 bytes.
     pub fn as_u64(&self) -> crate::Result<u64> {
@@ -92,6 +125,42 @@ impl ErrorResponseContext {
     }
 }
 
+/// The error data of a response with the [`Status::StoppedOnSymlink`][crate::Status::StoppedOnSymlink] status,
+/// returned when the path of a create request crosses a symbolic link.
+///
+/// Reference: MS-SMB2 2.2.2.2.1
+#[smb_response_binrw]
+pub struct SymbolicLinkErrorResponse {
+    #[bw(try_calc = (Self::HEADER_SIZE + Self::reparse_data_size(link)).try_into())]
+    #[br(temp)]
+    _sym_link_length: u32,
+    // SymLinkErrorTag and ReparseTag (IO_REPARSE_TAG_SYMLINK)
+    #[brw(magic(b"SYML\x0c\x00\x00\xa0"))]
+    #[bw(try_calc = Self::reparse_data_size(link).try_into())]
+    #[br(temp)]
+    _reparse_data_length: u16,
+    /// The length, in bytes, of the unparsed portion of the path.
+    /// The unparsed portion is at the end of the path, and follows the symbolic link.
+    pub unparsed_path_length: u16,
+    /// The symbolic link data.
+    #[br(map_stream = |s| s.take_seek(_reparse_data_length.into()))]
+    pub link: SymbolicLinkReparseBuffer,
+}
+
+#[cfg(feature = "server")]
+impl SymbolicLinkErrorResponse {
+    /// The size of the fields following SymLinkLength, up to the reparse data.
+    const HEADER_SIZE: usize = 12;
+    /// The size of the fixed fields of the symbolic link reparse data.
+    const LINK_HEADER_SIZE: usize = 12;
+
+    fn reparse_data_size(link: &SymbolicLinkReparseBuffer) -> usize {
+        Self::LINK_HEADER_SIZE
+            + (link.substitute_name.encode_utf16().count() + link.print_name.encode_utf16().count())
+                * size_of::<u16>()
+    }
+}
+
 /// An identifier for the error context in SMB2 ERROR Context structures.
 ///
 /// Reference: MS-SMB2 2.2.2.1
@@ -107,10 +176,30 @@ pub enum ErrorId {
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use smb_fscc::SymbolicLinkReparseBuffer;
 
     test_response! {
         error_simple, Command::Cancel => Error { error_data: vec![], } => "0900000000000000"
     }
 
     // TODO(TEST): Add a test with added context items.
+
+    const SYMLINK_ERROR: &str = "3000000053594d4c0c0000a024000a0000000c000c000c0001000000740061007200670065007400740061007200670065007400";
+
+    test_binrw_response! {
+        struct SymbolicLinkErrorResponse {
+            unparsed_path_length: 10,
+            link: SymbolicLinkReparseBuffer::new("target", true),
+        } => SYMLINK_ERROR
+    }
+
+    // Error data of dialects other than 3.1.1 is not formatted as contexts.
+    test_response_read! {
+        error_symlink, Command::Create => Error {
+            error_data: vec![ErrorResponseContext {
+                error_id: ErrorId::Default,
+                error_data: smb_tests::hex_to_u8_array! { SYMLINK_ERROR }.to_vec(),
+            }],
+        } => const_format::concatcp!("0900000034000000", SYMLINK_ERROR)
+    }
 }
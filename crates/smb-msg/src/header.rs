@@ -154,11 +154,16 @@ make_status! {
     ObjectNameNotFound = 0xC0000034: "Object Name Not Found",
     ObjectNameCollision = 0xC0000035: "Object Name Collision",
     SharingViolation = 0xC0000043: "Sharing Violation",
+    FileLockConflict = 0xC0000054: "File Lock Conflict",
+    LockNotGranted = 0xC0000055: "Lock Not Granted",
     ObjectPathNotFound = 0xC000003A: "Object Path Not Found",
-    NoEasOnFile = 0xC0000044: "No EAs on File",
+    NoEasOnFile = 0xC0000052: "No EAs on File",
+    DeletePending = 0xC0000056: "Delete Pending",
     LogonFailure = 0xC000006D: "Logon Failure",
     NotMapped = 0xC0000073: "Not Mapped",
+    RangeNotLocked = 0xC000007E: "Range Not Locked",
     BadImpersonationLevel = 0xC00000A5: "Bad Impersonation Level",
+    PipeDisconnected = 0xC00000B0: "Pipe Disconnected",
     IoTimeout = 0xC00000B5: "I/O Timeout",
     FileIsADirectory = 0xC00000BA: "File is a Directory",
     NotSupported = 0xC00000BB: "Not Supported",
@@ -166,13 +171,17 @@ make_status! {
     BadNetworkName = 0xC00000CC: "Bad Network Name",
     RequestNotAccepted = 0xC00000D0: "Request Not Accepted",
     DirectoryNotEmpty = 0xC0000101: "Directory Not Empty",
+    NotADirectory = 0xC0000103: "Not a Directory",
     Cancelled = 0xC0000120: "Cancelled",
+    CannotDelete = 0xC0000121: "Cannot Delete",
+    PipeBroken = 0xC000014B: "Pipe Broken",
     UserSessionDeleted = 0xC0000203: "User Session Deleted",
     UserAccountLockedOut = 0xC0000234: "User Account Locked Out",
     PathNotCovered = 0xC0000257: "Path Not Covered",
     NetworkSessionExpired = 0xC000035C: "Network Session Expired",
     SmbTooManyUids = 0xC000205A: "SMB Too Many UIDs",
     DeviceFeatureNotSupported = 0xC0000463: "Device Feature Not Supported",
+    HashNotPresent = 0xC000A100: "Hash Not Present",
 }
 
 /// SMB2 Packet Header.
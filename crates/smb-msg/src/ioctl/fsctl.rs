@@ -6,7 +6,7 @@ use modular_bitfield::prelude::*;
 use smb_dtyp::binrw_util::prelude::*;
 use smb_msg_derive::{smb_message_binrw, smb_request_binrw, smb_response_binrw};
 
-use crate::{Dialect, NegotiateSecurityMode};
+use crate::{Dialect, FileId, NegotiateSecurityMode};
 
 use crate::dfsc::{ReqGetDfsReferral, ReqGetDfsReferralEx, RespGetDfsReferral};
 use smb_dtyp::*;
@@ -33,10 +33,16 @@ pub enum FsctlCodes {
     LmrRequestResiliency = 0x001401D4,
     QueryNetworkInterfaceInfo = 0x001401FC,
     SetReparsePoint = 0x000900A4,
+    GetReparsePoint = 0x000900A8,
     DfsGetReferralsEx = 0x000601B0,
     FileLevelTrim = 0x00098208,
     ValidateNegotiateInfo = 0x00140204,
     QueryAllocatedRanges = 0x000940CF,
+    SetSparse = 0x000900C4,
+    SetZeroData = 0x000980C8,
+    OffloadWrite = 0x00098268,
+    DuplicateExtentsToFile = 0x00098344,
+    DuplicateExtentsToFileEx = 0x000983E8,
 }
 
 /// Request packet for initiating a server-side copy of data.
@@ -110,11 +116,15 @@ pub struct SrvReadHashReq {
     pub hash_version: u32,
     /// Indicates the nature of the offset field and how it should be interpreted.
     pub hash_retrieval_type: SrvHashRetrievalType,
+    /// The length, in bytes, of the data to retrieve.
+    pub length: u32,
+    /// The offset to retrieve from, interpreted according to [`SrvReadHashReq::hash_retrieval_type`].
+    pub offset: u64,
 }
 
 impl IoctlRequestContent for SrvReadHashReq {
     fn get_bin_size(&self) -> u32 {
-        size_of::<u32>() as u32 * 3
+        (size_of::<u32>() * 4 + size_of::<u64>()) as u32
     }
 }
 
@@ -286,11 +296,11 @@ pub struct SrvCopychunkResponse {
 
 impl_fsctl_response!(SrvCopychunk, SrvCopychunkResponse);
 
-/// Response packet for SRV_READ_HASH requests.
-/// Returned by the server in an SMB2 IOCTL Response for FSCTL_SRV_READ_HASH request.
+/// The header of the Content Information File retrieved by FSCTL_SRV_READ_HASH requests (HASH_HEADER).
+/// The hash blob, located by the header, contains the [`ContentInformation`][crate::ContentInformation].
 /// The response is not valid for the SMB 2.0.2 dialect.
 ///
-/// Reference: MS-SMB2 2.2.32.4
+/// Reference: MS-SMB2 2.2.32.4.1
 #[smb_response_binrw]
 pub struct SrvReadHashRes {
     /// The hash type of the response. Must be set to SRV_HASH_TYPE_PEER_DIST for branch caching.
@@ -301,23 +311,23 @@ pub struct SrvReadHashRes {
     /// Must be version 1 (branch cache version 1) or version 2 (branch cache version 2).
     #[br(assert((1..=2).contains(&hash_version)))]
     #[bw(assert((1..=2).contains(hash_version)))]
-    hash_version: u32,
+    pub hash_version: u32,
     /// The last change time of the source file.
-    source_file_change_time: FileTime,
+    pub source_file_change_time: FileTime,
     /// The size of the source file in bytes.
-    source_file_size: u64,
-    /// Position marker for the length of the hash blob.
-    hash_blob_length: PosMarker<u32>,
-    /// Position marker for the offset of the hash blob.
-    hash_blob_offset: PosMarker<u32>,
+    pub source_file_size: u64,
+    /// The length of the hash blob, in bytes.
+    pub hash_blob_length: u32,
+    /// The offset of the hash blob, in bytes, from the start of this header.
+    pub hash_blob_offset: u32,
     /// Indicates whether the file has been modified since the Content Information was generated.
-    dirty: u16,
+    pub dirty: u16,
     /// The length of the source file name in bytes.
     #[bw(try_calc = source_file_name.len().try_into())]
     source_file_name_length: u16,
     /// The name of the source file.
     #[br(count = source_file_name_length)]
-    source_file_name: Vec<u8>,
+    pub source_file_name: Vec<u8>,
 }
 
 impl_fsctl_response!(SrvReadHash, SrvReadHashRes);
@@ -336,9 +346,9 @@ pub struct SrvHashRetrieveHashBased {
     buffer_length: u32,
     reserved: u32,
     /// A variable-length buffer that contains the retrieved portion of the Content Information File.
-    /// TODO: Parse as Content Information File as specified in MS-PCCRC section 2.3.
+    /// Once retrieved entirely, the file may be parsed as [`SrvReadHashRes`], followed by [`ContentInformation`][crate::ContentInformation].
     #[br(count = buffer_length)]
-    blob: Vec<u8>,
+    pub blob: Vec<u8>,
 }
 
 impl_fsctl_response!(SrvReadHash, SrvHashRetrieveHashBased);
@@ -360,11 +370,13 @@ pub struct SrvHashRetrieveFileBased {
     buffer_length: u32,
     reserved: u32,
     /// A variable-length buffer that contains the retrieved portion of the Content Information File.
-    /// TODO: Parse as Content Information File as specified in MS-PCCRC section 2.4.
+    /// May be parsed as [`ContentInformation`][crate::ContentInformation].
     #[br(count = buffer_length)]
     pub buffer: Vec<u8>,
 }
 
+impl_fsctl_response!(SrvReadHash, SrvHashRetrieveFileBased);
+
 pub type NetworkInterfacesInfo = ChainedItemList<NetworkInterfaceInfo>;
 
 impl_fsctl_response!(QueryNetworkInterfaceInfo, NetworkInterfacesInfo);
@@ -523,6 +535,39 @@ impl From<Vec<QueryAllocRangesItem>> for QueryAllocRangesResult {
 
 impl_fsctl_response!(QueryAllocatedRanges, QueryAllocRangesResult);
 
+/// Sets or clears the sparse attribute of a file (FILE_SET_SPARSE_BUFFER).
+///
+/// [MSDN](https://learn.microsoft.com/en-us/windows/win32/api/winioctl/ns-winioctl-file_set_sparse_buffer)
+#[smb_request_binrw]
+pub struct SetSparseRequest {
+    /// Whether to set the file as sparse (TRUE), or as non-sparse (FALSE).
+    pub set_sparse: Boolean,
+}
+
+impl IoctlRequestContent for SetSparseRequest {
+    fn get_bin_size(&self) -> u32 {
+        size_of::<Boolean>() as u32
+    }
+}
+
+/// Sets a range of a file to zero (FILE_ZERO_DATA_INFORMATION).
+/// In sparse files, the range is deallocated where possible.
+///
+/// [MSDN](https://learn.microsoft.com/en-us/windows/win32/api/winioctl/ns-winioctl-file_zero_data_information)
+#[smb_request_binrw]
+pub struct SetZeroDataRequest {
+    /// The offset, in bytes, of the start of the range to set to zero.
+    pub file_offset: u64,
+    /// The offset, in bytes, of the first byte beyond the range to set to zero.
+    pub beyond_final_zero: u64,
+}
+
+impl IoctlRequestContent for SetZeroDataRequest {
+    fn get_bin_size(&self) -> u32 {
+        (size_of::<u64>() * 2) as u32
+    }
+}
+
 /// The FSCTL_PIPE_WAIT Request requests that the server wait until either a time-out interval elapses,
 /// or an instance of the specified named pipe is available for connection.
 ///
@@ -576,6 +621,28 @@ pub struct SetReparsePointRequest {
     pub reparse_data: Vec<u8>,
 }
 
+impl SetReparsePointRequest {
+    /// Creates a request setting the specified reparse point data.
+    ///
+    /// Only Microsoft reparse tags are supported, since other tags require a GUID.
+    #[cfg(feature = "client")]
+    pub fn new(data: &ReparseData) -> crate::Result<Self> {
+        if data.tag() & 0x80000000 == 0 {
+            return Err(crate::SmbMsgError::InvalidData(format!(
+                "Reparse tag {:#x} is not a Microsoft tag",
+                data.tag()
+            )));
+        }
+        let mut reparse_data = binrw::io::Cursor::new(Vec::new());
+        data.write_le(&mut reparse_data)?;
+        Ok(Self {
+            reparse_tag: data.tag(),
+            reparse_guid: None,
+            reparse_data: reparse_data.into_inner(),
+        })
+    }
+}
+
 impl IoctlRequestContent for SetReparsePointRequest {
     fn get_bin_size(&self) -> u32 {
         (size_of::<u32>()
@@ -619,15 +686,15 @@ impl IoctlRequestContent for FileLevelTrimRequest {
 pub struct PipePeekResponse {
     /// The current state of the pipe
     pub named_pipe_state: NamedPipeState,
-    #[bw(calc = data.len() as u32)]
     /// The size, in bytes, of the data available to read from the pipe.
-    read_data_available: u32,
+    /// May exceed the length of [`PipePeekResponse::data`], if the output buffer was too small.
+    pub read_data_available: u32,
     /// Specifies the number of messages available in the pipe if the pipe has been created as a message-type pipe. Otherwise, this field is 0
     pub number_of_messages: u32,
     /// Specifies the length of the first message available in the pipe if the pipe has been created as a message-type pipe. Otherwise, this field is 0.
     pub message_length: u32,
     /// The data from the pipe.
-    #[br(count = read_data_available as u64)]
+    #[br(parse_with = binrw::helpers::until_eof)]
     pub data: Vec<u8>,
 }
 
@@ -647,6 +714,10 @@ pub struct SrvEnumerateSnapshotsResponse {
     #[br(temp)]
     snap_shot_array_size: PosMarker<u32>,
     /// A list of snapshots, described as strings, that take on the following form: @GMT-YYYY.MM.DD-HH.MM.SS
+    ///
+    /// Not returned if the output buffer is too small to hold all the snapshots,
+    /// in which case [`number_of_snap_shots_returned`][Self::number_of_snap_shots_returned] is zero.
+    #[br(if(number_of_snap_shots_returned > 0))]
     #[br(map_stream = |s| s.take_seek(snap_shot_array_size.value as u64))]
     #[bw(write_with = PosMarker::write_size, args(&snap_shot_array_size))]
     pub snap_shots: MultiWSz,
@@ -715,6 +786,114 @@ pub struct OffloadReadResponse {
 
 impl_fsctl_response!(OffloadRead, OffloadReadResponse);
 
+/// Requests that the data represented by a token, generated by an FSCTL_OFFLOAD_READ request,
+/// is written to a range of the file.
+///
+/// [MSDN](https://learn.microsoft.com/en-us/windows/win32/api/winioctl/ns-winioctl-fsctl_offload_write_input)
+#[smb_request_binrw]
+pub struct OffloadWriteRequest {
+    #[bw(calc = 0x20)]
+    #[br(assert(_size == 0x20))]
+    #[br(temp)]
+    _size: u32,
+    /// The flags to be set for this operation. Currently, no flags are defined.
+    pub flags: u32,
+    /// The file offset, in bytes, of the start of the range to write to.
+    /// MUST be aligned to a logical sector boundary on the volume.
+    pub file_offset: u64,
+    /// The length, in bytes, of the range to write to.
+    /// MUST be aligned to a logical sector boundary on the volume.
+    pub copy_length: u64,
+    /// The offset, in bytes, from the start of the data represented by the token, to write from.
+    pub transfer_offset: u64,
+    /// The token returned by the FSCTL_OFFLOAD_READ request. Must be 512 bytes long.
+    #[br(count = 512)]
+    #[bw(assert(token.len() == 512))]
+    pub token: Vec<u8>,
+}
+
+impl IoctlRequestContent for OffloadWriteRequest {
+    fn get_bin_size(&self) -> u32 {
+        (size_of::<u32>() * 2 + size_of::<u64>() * 3 + self.token.len()) as u32
+    }
+}
+
+/// [MSDN](https://learn.microsoft.com/en-us/windows/win32/api/winioctl/ns-winioctl-fsctl_offload_write_output)
+#[smb_response_binrw]
+pub struct OffloadWriteResponse {
+    #[bw(calc = 0x10)]
+    #[br(assert(_size == 0x10))]
+    #[br(temp)]
+    _size: u32,
+    /// The flags of the response. Currently, no flags are defined.
+    pub flags: u32,
+    /// The number of bytes written. May be smaller than the requested length,
+    /// in which case the rest of the range may be written by another request.
+    pub length_written: u64,
+}
+
+impl_fsctl_response!(OffloadWrite, OffloadWriteResponse);
+
+/// Requests that a range of a source file is cloned into a range of the file the request is sent on.
+/// The ranges share their storage until either of them is modified.
+///
+/// [MSDN](https://learn.microsoft.com/en-us/windows/win32/api/winioctl/ns-winioctl-duplicate_extents_data)
+#[smb_request_binrw]
+pub struct DuplicateExtentsToFileRequest {
+    /// The file ID of the source file, which must be opened on the same share.
+    pub source_file_id: FileId,
+    /// The offset, in bytes, of the start of the range to clone from the source file.
+    pub source_file_offset: u64,
+    /// The offset, in bytes, of the start of the range to clone to in this file.
+    pub target_file_offset: u64,
+    /// The number of bytes to clone.
+    pub byte_count: u64,
+}
+
+impl IoctlRequestContent for DuplicateExtentsToFileRequest {
+    fn get_bin_size(&self) -> u32 {
+        (size_of::<u64>() * 5) as u32
+    }
+}
+
+/// The extended version of [`DuplicateExtentsToFileRequest`], which supports flags.
+///
+/// Reference: MS-FSCC, FSCTL_DUPLICATE_EXTENTS_TO_FILE_EX Request
+#[smb_request_binrw]
+pub struct DuplicateExtentsToFileExRequest {
+    #[bw(calc = 0x30)]
+    #[br(assert(_size == 0x30))]
+    #[br(temp)]
+    _size: u64,
+    /// The file ID of the source file, which must be opened on the same share.
+    pub source_file_id: FileId,
+    /// The offset, in bytes, of the start of the range to clone from the source file.
+    pub source_file_offset: u64,
+    /// The offset, in bytes, of the start of the range to clone to in this file.
+    pub target_file_offset: u64,
+    /// The number of bytes to clone.
+    pub byte_count: u64,
+    pub flags: DuplicateExtentsDataExFlags,
+    reserved: u32,
+}
+
+impl IoctlRequestContent for DuplicateExtentsToFileExRequest {
+    fn get_bin_size(&self) -> u32 {
+        (size_of::<u64>() * 6 + size_of::<u32>() * 2) as u32
+    }
+}
+
+/// Flags of the [`DuplicateExtentsToFileExRequest`].
+///
+/// Reference: MS-FSCC, FSCTL_DUPLICATE_EXTENTS_TO_FILE_EX Request
+#[smb_dtyp::mbitfield]
+pub struct DuplicateExtentsDataExFlags {
+    /// When set, the source file is not modified while the range is cloned.
+    pub source_atomic: bool,
+    #[skip]
+    __: B31,
+}
+
 /// This macro wraps an existing type into a newtype that implements the `IoctlRequestContent` trait.
 /// It also provides a constructor and implements `From` and `Deref` traits for the new type.
 ///
@@ -779,6 +958,7 @@ make_req_newtype!(pub SrvRequestResumeKeyRequest(()));
 make_req_newtype!(pub QueryNetworkInterfaceInfoRequest(()));
 make_req_newtype!(pub PipeTransceiveRequest(IoctlBuffer));
 make_req_newtype!(pub SrvCopyChunkCopyWrite(SrvCopychunkCopy));
+make_req_newtype!(pub GetReparsePointRequest(()));
 
 make_res_newtype!(
     PipeWait: pub PipeWaitResponse(())
@@ -786,13 +966,31 @@ make_res_newtype!(
 make_res_newtype!(
     PipeTransceive: pub PipeTransceiveResponse(IoctlBuffer)
 );
+make_res_newtype!(
+    SrvReadHash: pub SrvReadHashResponse(IoctlBuffer)
+);
 make_res_newtype!(
     SetReparsePoint: pub SetReparsePointResponse(())
 );
+make_res_newtype!(
+    GetReparsePoint: pub GetReparsePointResponse(ReparseDataBuffer)
+);
 
 make_res_newtype!(
     LmrRequestResiliency: pub LmrRequestResiliencyResponse(())
 );
+make_res_newtype!(
+    SetSparse: pub SetSparseResponse(())
+);
+make_res_newtype!(
+    SetZeroData: pub SetZeroDataResponse(())
+);
+make_res_newtype!(
+    DuplicateExtentsToFile: pub DuplicateExtentsToFileResponse(())
+);
+make_res_newtype!(
+    DuplicateExtentsToFileEx: pub DuplicateExtentsToFileExResponse(())
+);
 
 #[cfg(test)]
 mod tests {
@@ -855,6 +1053,27 @@ mod tests {
         } => "0a00000000000000c8f39e00"
     }
 
+    test_binrw_response! {
+        struct SrvEnumerateSnapshotsResponse {
+            number_of_snap_shots: 2,
+            number_of_snap_shots_returned: 2,
+            snap_shots: ["@GMT-2025.01.02-03.04.05", "@GMT-2024.12.31-23.59.59"]
+                .into_iter()
+                .collect(),
+        } => "020000000200000066000000400047004d0054002d0032003000320035002e00300031002e0030003200
+        2d00300033002e00300034002e00300035000000400047004d0054002d0032003000320034002e0031003200
+        2e00330031002d00320033002e00350039002e003500390000000000"
+    }
+
+    // The output buffer is too small to hold the snapshots - only their count and size are returned.
+    smb_tests::test_binrw_read! {
+        struct SrvEnumerateSnapshotsResponse => too_small {
+            number_of_snap_shots: 2,
+            number_of_snap_shots_returned: 0,
+            snap_shots: Default::default(),
+        } => "020000000000000066000000"
+    }
+
     test_binrw_response! {
         struct QueryAllocRangesResult {
             values: vec![
@@ -870,6 +1089,70 @@ mod tests {
         } => "000000000000000000100000000000000020000000000000d1b6000000000000"
     }
 
+    test_binrw_response! {
+        struct OffloadWriteResponse {
+            flags: 0,
+            length_written: 0x100000,
+        } => "10000000000000000000100000000000"
+    }
+
+    test_binrw_response! {
+        struct PipePeekResponse {
+            named_pipe_state: NamedPipeState::Connected,
+            read_data_available: 8,
+            number_of_messages: 2,
+            message_length: 5,
+            data: b"hello".to_vec(),
+        } => "0300000008000000020000000500000068656c6c6f"
+    }
+
+    test_binrw_request! {
+        struct SrvReadHashReq {
+            hash_version: 2,
+            hash_retrieval_type: SrvHashRetrievalType::FileBased,
+            length: 0x10000,
+            offset: 0x20000,
+        } => "010000000200000002000000000001000000020000000000"
+    }
+
+    test_binrw_request! {
+        struct DuplicateExtentsToFileRequest {
+            source_file_id: FileId {
+                persistent: 0x11,
+                volatile: 0x22,
+            },
+            source_file_offset: 0x10000,
+            target_file_offset: 0x20000,
+            byte_count: 0x100000,
+        } => "11000000000000002200000000000000000001000000000000000200000000000000100000000000"
+    }
+
+    test_binrw_request! {
+        struct DuplicateExtentsToFileExRequest {
+            source_file_id: FileId {
+                persistent: 0x11,
+                volatile: 0x22,
+            },
+            source_file_offset: 0,
+            target_file_offset: 0x10000,
+            byte_count: 0x10000,
+            flags: DuplicateExtentsDataExFlags::new().with_source_atomic(true),
+        } => "3000000000000000110000000000000022000000000000000000000000000000000001000000000000000100000000000100000000000000"
+    }
+
+    test_binrw_request! {
+        struct SetSparseRequest {
+            set_sparse: true.into(),
+        } => "01"
+    }
+
+    test_binrw_request! {
+        struct SetZeroDataRequest {
+            file_offset: 0x10000,
+            beyond_final_zero: 0x30000,
+        } => "00000100000000000000030000000000"
+    }
+
     test_binrw_response! {
         NetworkInterfacesInfo: NetworkInterfacesInfo::from(vec![
                 NetworkInterfaceInfo {
@@ -148,17 +148,23 @@ ioctl_req_data! {
     QueryNetworkInterfaceInfo: QueryNetworkInterfaceInfoRequest, NetworkInterfacesInfo,
     SrvCopychunk: SrvCopychunkCopy, SrvCopychunkResponse,
     SrvCopychunkWrite: SrvCopyChunkCopyWrite, SrvCopychunkResponse,
-    SrvReadHash: SrvReadHashReq, SrvReadHashRes,
+    SrvReadHash: SrvReadHashReq, SrvReadHashResponse,
     LmrRequestResiliency: NetworkResiliencyRequest, LmrRequestResiliencyResponse,
     ValidateNegotiateInfo: ValidateNegotiateInfoRequest, ValidateNegotiateInfoResponse,
     DfsGetReferrals: ReqGetDfsReferral, RespGetDfsReferral,
     PipeWait: PipeWaitRequest, PipeWaitResponse,
     PipeTransceive: PipeTransceiveRequest, PipeTransceiveResponse,
     SetReparsePoint: SetReparsePointRequest, SetReparsePointResponse,
+    GetReparsePoint: GetReparsePointRequest, GetReparsePointResponse,
     DfsGetReferralsEx: ReqGetDfsReferralEx, RespGetDfsReferral,
     FileLevelTrim: FileLevelTrimRequest, FileLevelTrimResponse,
     QueryAllocatedRanges: QueryAllocRangesItem, QueryAllocRangesResult,
     OffloadRead: OffloadReadRequest, OffloadReadResponse,
+    SetSparse: SetSparseRequest, SetSparseResponse,
+    SetZeroData: SetZeroDataRequest, SetZeroDataResponse,
+    OffloadWrite: OffloadWriteRequest, OffloadWriteResponse,
+    DuplicateExtentsToFile: DuplicateExtentsToFileRequest, DuplicateExtentsToFileResponse,
+    DuplicateExtentsToFileEx: DuplicateExtentsToFileExRequest, DuplicateExtentsToFileExResponse,
 }
 
 /// Flags field indicating how to process the IOCTL operation.
@@ -18,6 +18,7 @@ pub mod message;
 pub mod negotiate;
 pub mod notify;
 pub mod oplock;
+pub mod pccrc;
 pub mod plain;
 pub mod query_dir;
 pub mod session_setup;
@@ -40,6 +41,7 @@ pub use message::*;
 pub use negotiate::*;
 pub use notify::*;
 pub use oplock::*;
+pub use pccrc::*;
 pub use plain::*;
 pub use query_dir::*;
 pub use session_setup::*;
@@ -85,6 +85,27 @@ pub struct LockResponse {
 
 #[cfg(test)]
 mod tests {
+    use crate::*;
 
-    // TODO: tests
+    use super::*;
+
+    test_binrw_request! {
+        struct LockRequest {
+            lock_sequence: LockSequence::new().with_number(1).with_index(1),
+            file_id: [
+                0x14, 0x04, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x51, 0x00, 0x10, 0x00, 0x0c, 0x00,
+                0x00, 0x00,
+            ]
+            .into(),
+            locks: vec![LockElement {
+                offset: 0x10,
+                length: 0x20,
+                flags: LockFlag::new().with_exclusive(true).with_fail_immediately(true),
+            }],
+        } => "3000010011000000140400000c000000510010000c000000100000000000000020000000000000000a00000000000000"
+    }
+
+    test_binrw_response! {
+        struct LockResponse {} => "04000000"
+    }
 }
@@ -12,16 +12,16 @@ use smb_msg_derive::*;
 /// and response (server to client) operations. The structure is identical for all three operations.
 ///
 /// Reference: MS-SMB2 2.2.23.1, 2.2.24.1, 2.2.25.1
-#[smb_request_response(size = 12)]
+#[smb_request_response(size = 24)]
 pub struct OplockBreakMsg {
     /// The oplock level. For notifications, this is the maximum level the server will accept.
     /// For acknowledgments, this is the lowered level the client accepts.
     /// For responses, this is the granted level.
-    oplock_level: u8,
+    pub oplock_level: OplockLevel,
     reserved: u8,
     reserved: u32,
     /// The file identifier on which the oplock break occurred.
-    file_id: FileId,
+    pub file_id: FileId,
 }
 
 /// Lease Break Notification message.
@@ -34,15 +34,15 @@ pub struct OplockBreakMsg {
 pub struct LeaseBreakNotify {
     /// A 16-bit unsigned integer indicating a lease state change by the server.
     /// Only valid for SMB 3.x dialect family. For SMB 2.1, this field is reserved.
-    new_epoch: u16,
+    pub new_epoch: u16,
     /// Flag indicating whether a Lease Break Acknowledgment is required.
-    ack_required: u32,
+    pub ack_required: u32,
     /// The client-generated key that identifies the owner of the lease.
-    lease_key: Guid,
+    pub lease_key: Guid,
     /// The current lease state of the open.
-    current_lease_state: LeaseState,
+    pub current_lease_state: LeaseState,
     /// The new lease state for the open.
-    new_lease_state: LeaseState,
+    pub new_lease_state: LeaseState,
     #[bw(calc = 0)]
     #[br(assert(break_reason == 0))]
     #[br(temp)]
@@ -57,10 +57,11 @@ pub struct LeaseBreakNotify {
     share_mask_hint: u32,
 }
 
-/// Oplock level values used in oplock break operations.
+/// Oplock level values used in create and oplock break operations.
 ///
-/// Reference: MS-SMB2 2.2.23.1
+/// Reference: MS-SMB2 2.2.13, 2.2.23.1
 #[smb_message_binrw]
+#[derive(Clone, Copy)]
 #[brw(repr(u8))]
 pub enum OplockLevel {
     /// No oplock is available.
@@ -68,7 +69,11 @@ pub enum OplockLevel {
     /// A level II oplock is available.
     II = 1,
     /// Exclusive oplock is available.
-    Exclusive = 2,
+    Exclusive = 8,
+    /// Batch oplock is available.
+    Batch = 9,
+    /// A lease is requested or granted, using the lease create context.
+    Lease = 0xff,
 }
 
 /// Lease state bitfield representing different types of caching permissions.
@@ -111,10 +116,10 @@ pub struct LeaseBreakAckResponse {
     reserved: u32,
 
     /// The client-generated key that identifies the owner of the lease.
-    lease_key: Guid,
+    pub lease_key: Guid,
     /// The lease state. For acknowledgments, this must be a subset of the lease state
     /// granted by the server. For responses, this is the requested lease state.
-    lease_state: LeaseState,
+    pub lease_state: LeaseState,
 
     /// Lease duration (reserved)
     reserved: u64,
@@ -135,6 +140,16 @@ mod tests {
 
     use super::*;
 
+    test_binrw_request! {
+        struct OplockBreakAck {
+            oplock_level: OplockLevel::II,
+            file_id: [
+                0x41, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x51, 0x0, 0x10, 0x0, 0x0c, 0x0, 0x0, 0x0,
+            ]
+            .into(),
+        } => "18000100000000004100000000000000510010000c000000"
+    }
+
     test_binrw_response! {
         struct LeaseBreakNotify {
             new_epoch: 2,
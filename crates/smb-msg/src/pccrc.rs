@@ -0,0 +1,304 @@
+//! Peer Content Caching and Retrieval: Content Identification (MS-PCCRC) structures.
+//!
+//! Content information describes the content of a file by the hashes of its segments
+//! (and in version 1, of the blocks of each segment), as retrieved using FSCTL_SRV_READ_HASH
+//! (see [`SrvReadHashReq`][crate::SrvReadHashReq]).
+//! Comparing hashes allows detecting unchanged ranges of a file without reading its data.
+
+use binrw::prelude::*;
+
+/// Content information of either version, distinguished by its leading version field.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ContentInformation {
+    /// Version 1.0, used by BranchCache version 1.
+    #[brw(magic = 0x0100u16)]
+    V1(ContentInformationV1),
+    /// Version 2.0, used by BranchCache version 2.
+    #[brw(magic = 0x0200u16)]
+    V2(ContentInformationV2),
+}
+
+impl ContentInformation {
+    /// Returns the segments described by the content information, in file order.
+    ///
+    /// Segment offsets are relative to the start of the content the information was generated for.
+    pub fn segments(&self) -> Vec<ContentSegment<'_>> {
+        match self {
+            ContentInformation::V1(info) => info
+                .segments
+                .iter()
+                .zip(info.blocks.iter())
+                .map(|(segment, blocks)| ContentSegment {
+                    offset: segment.offset_in_content,
+                    length: segment.segment_size as u64,
+                    hash_of_data: &segment.segment_hash_of_data,
+                    secret: &segment.segment_secret,
+                    block_size: Some(segment.block_size),
+                    block_hashes: blocks.block_hashes.iter().map(Vec::as_slice).collect(),
+                })
+                .collect(),
+            ContentInformation::V2(info) => {
+                let mut offset = 0;
+                info.chunks
+                    .iter()
+                    .flat_map(|chunk| chunk.segments.iter())
+                    .map(|segment| {
+                        let result = ContentSegment {
+                            offset,
+                            length: segment.segment_size as u64,
+                            hash_of_data: &segment.segment_hash_of_data,
+                            secret: &segment.segment_secret,
+                            block_size: None,
+                            block_hashes: vec![],
+                        };
+                        offset += segment.segment_size as u64;
+                        result
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A segment of content, as described by either version of [`ContentInformation`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ContentSegment<'a> {
+    /// The offset of the segment in the content.
+    pub offset: u64,
+    /// The length of the segment, in bytes.
+    pub length: u64,
+    /// The hash of the data of the segment (HoD).
+    pub hash_of_data: &'a [u8],
+    /// The secret of the segment (Kp), derived from the hash of its data.
+    pub secret: &'a [u8],
+    /// The size of the blocks of the segment. Only present in version 1.
+    pub block_size: Option<u32>,
+    /// The hashes of the blocks of the segment. Empty in version 2.
+    pub block_hashes: Vec<&'a [u8]>,
+}
+
+/// Hash algorithms of [`ContentInformationV1`].
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[brw(repr(u32))]
+pub enum HashAlgorithmV1 {
+    Sha256 = 0x800C,
+    Sha384 = 0x800D,
+    Sha512 = 0x800E,
+}
+
+impl HashAlgorithmV1 {
+    /// Returns the size of the hashes of the algorithm, in bytes.
+    pub fn hash_size(&self) -> usize {
+        match self {
+            HashAlgorithmV1::Sha256 => 32,
+            HashAlgorithmV1::Sha384 => 48,
+            HashAlgorithmV1::Sha512 => 64,
+        }
+    }
+}
+
+/// Content Information Data Structure Version 1.0.
+///
+/// Reference: MS-PCCRC 2.3
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[brw(little)]
+pub struct ContentInformationV1 {
+    pub hash_algorithm: HashAlgorithmV1,
+    /// The offset of the content range in the first segment.
+    pub offset_in_first_segment: u32,
+    /// The number of bytes of the content range in the last segment.
+    pub read_bytes_in_last_segment: u32,
+    #[bw(try_calc = segments.len().try_into())]
+    segment_count: u32,
+    #[br(count = segment_count, args { inner: (hash_algorithm.hash_size(),) })]
+    pub segments: Vec<SegmentDescriptionV1>,
+    /// The block hashes of each segment, in the order of [`ContentInformationV1::segments`].
+    #[br(count = segment_count, args { inner: (hash_algorithm.hash_size(),) })]
+    #[bw(assert(blocks.len() == segments.len()))]
+    pub blocks: Vec<SegmentContentBlocks>,
+}
+
+/// Reference: MS-PCCRC 2.3.1.1
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[brw(little)]
+#[br(import(hash_size: usize))]
+pub struct SegmentDescriptionV1 {
+    /// The offset of the segment in the content.
+    pub offset_in_content: u64,
+    /// The length of the segment, in bytes.
+    pub segment_size: u32,
+    /// The length of each block of the segment, in bytes, except the last one.
+    pub block_size: u32,
+    #[br(count = hash_size)]
+    pub segment_hash_of_data: Vec<u8>,
+    #[br(count = hash_size)]
+    pub segment_secret: Vec<u8>,
+}
+
+/// Reference: MS-PCCRC 2.3.1.2
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[brw(little)]
+#[br(import(hash_size: usize))]
+pub struct SegmentContentBlocks {
+    #[bw(try_calc = block_hashes.len().try_into())]
+    block_count: u32,
+    #[br(count = block_count, args { inner: binrw::VecArgs { count: hash_size, inner: () } })]
+    pub block_hashes: Vec<Vec<u8>>,
+}
+
+/// Hash algorithms of [`ContentInformationV2`].
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[brw(repr(u8))]
+pub enum HashAlgorithmV2 {
+    /// SHA-512, truncated to 32 bytes.
+    Sha512Truncated = 0x04,
+}
+
+impl HashAlgorithmV2 {
+    /// The size of the hashes of version 2, in bytes.
+    pub const HASH_SIZE: usize = 32;
+}
+
+/// Content Information Data Structure Version 2.0.
+///
+/// Unlike version 1, the fields of version 2 are big-endian, and segments are not divided into blocks.
+///
+/// Reference: MS-PCCRC 2.4
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[brw(big)]
+pub struct ContentInformationV2 {
+    pub hash_algorithm: HashAlgorithmV2,
+    /// The offset of the content range in the first segment.
+    pub offset_in_first_segment: u32,
+    /// The number of bytes of the content range in the last segment.
+    pub read_bytes_in_last_segment: u32,
+    /// The length of the content, in bytes.
+    pub stream_length: u64,
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub chunks: Vec<ChunkDescription>,
+}
+
+/// A chunk of segment descriptions in [`ContentInformationV2`].
+///
+/// Reference: MS-PCCRC 2.4.1.1
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[brw(big)]
+pub struct ChunkDescription {
+    /// The type of the chunk. Only 0 (segment descriptions) is defined.
+    pub chunk_type: u8,
+    #[bw(try_calc = (segments.len() * SegmentDescriptionV2::SIZE).try_into())]
+    #[br(assert(chunk_data_length as usize % SegmentDescriptionV2::SIZE == 0))]
+    chunk_data_length: u32,
+    #[br(count = chunk_data_length as usize / SegmentDescriptionV2::SIZE)]
+    pub segments: Vec<SegmentDescriptionV2>,
+}
+
+/// Reference: MS-PCCRC 2.4.1.2
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[brw(big)]
+pub struct SegmentDescriptionV2 {
+    /// The length of the segment, in bytes.
+    pub segment_size: u32,
+    pub segment_hash_of_data: [u8; HashAlgorithmV2::HASH_SIZE],
+    pub segment_secret: [u8; HashAlgorithmV2::HASH_SIZE],
+}
+
+impl SegmentDescriptionV2 {
+    const SIZE: usize = size_of::<u32>() + HashAlgorithmV2::HASH_SIZE * 2;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use smb_tests::*;
+
+    fn content_information_v1() -> ContentInformation {
+        ContentInformation::V1(ContentInformationV1 {
+            hash_algorithm: HashAlgorithmV1::Sha256,
+            offset_in_first_segment: 0,
+            read_bytes_in_last_segment: 0x10,
+            segments: vec![SegmentDescriptionV1 {
+                offset_in_content: 0,
+                segment_size: 0x10,
+                block_size: 0x10000,
+                segment_hash_of_data: vec![0x11; 32],
+                segment_secret: vec![0x22; 32],
+            }],
+            blocks: vec![SegmentContentBlocks {
+                block_hashes: vec![vec![0x33; 32]],
+            }],
+        })
+    }
+
+    test_binrw! {
+        ContentInformation => v1: content_information_v1() => "00010c80000000000000100000000100000000000000000000001000000000000100\
+            1111111111111111111111111111111111111111111111111111111111111111\
+            2222222222222222222222222222222222222222222222222222222222222222\
+            01000000\
+            3333333333333333333333333333333333333333333333333333333333333333"
+    }
+
+    fn content_information_v2() -> ContentInformation {
+        ContentInformation::V2(ContentInformationV2 {
+            hash_algorithm: HashAlgorithmV2::Sha512Truncated,
+            offset_in_first_segment: 0,
+            read_bytes_in_last_segment: 0x100,
+            stream_length: 0x20100,
+            chunks: vec![ChunkDescription {
+                chunk_type: 0,
+                segments: vec![
+                    SegmentDescriptionV2 {
+                        segment_size: 0x20000,
+                        segment_hash_of_data: [0x11; 32],
+                        segment_secret: [0x22; 32],
+                    },
+                    SegmentDescriptionV2 {
+                        segment_size: 0x100,
+                        segment_hash_of_data: [0x33; 32],
+                        segment_secret: [0x44; 32],
+                    },
+                ],
+            }],
+        })
+    }
+
+    test_binrw! {
+        ContentInformation => v2: content_information_v2() => "000204000000000000010000000000000201000000000088\
+            00020000\
+            1111111111111111111111111111111111111111111111111111111111111111\
+            2222222222222222222222222222222222222222222222222222222222222222\
+            00000100\
+            3333333333333333333333333333333333333333333333333333333333333333\
+            4444444444444444444444444444444444444444444444444444444444444444"
+    }
+
+    #[test]
+    fn test_content_segments() {
+        let info = content_information_v1();
+        let segments = info.segments();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].length, 0x10);
+        assert_eq!(segments[0].block_size, Some(0x10000));
+        assert_eq!(segments[0].block_hashes, [[0x33; 32].as_slice()]);
+
+        let info = content_information_v2();
+        let segments = info.segments();
+        assert_eq!(
+            segments
+                .iter()
+                .map(|segment| (segment.offset, segment.length))
+                .collect::<Vec<_>>(),
+            [(0, 0x20000), (0x20000, 0x100)]
+        );
+        assert_eq!(segments[1].hash_of_data, [0x33; 32]);
+    }
+}
@@ -1,5 +1,7 @@
 mod base;
+mod lsarpc;
 mod srvsvc;
 
 pub use base::*;
+pub use lsarpc::*;
 pub use srvsvc::*;
@@ -1,7 +1,7 @@
 use binrw::prelude::*;
 use maybe_async::*;
 
-use crate::pdu::DceRpcSyntaxId;
+use crate::{pdu::DceRpcSyntaxId, syntax::TransferSyntax};
 
 pub trait RpcInterface<T>
 where
@@ -9,6 +9,8 @@ where
 {
     const SYNTAX_ID: DceRpcSyntaxId;
     fn new(bound_pipe: T) -> Self;
+    /// Returns the bound connection of the interface, e.g. to close it.
+    fn into_inner(self) -> T;
 }
 
 pub trait RpcCall: for<'a> BinWrite<Args<'a> = ()> {
@@ -33,6 +35,9 @@ pub trait RpcCall: for<'a> BinWrite<Args<'a> = ()> {
 #[maybe_async(AFIT)]
 #[allow(async_fn_in_trait)]
 pub trait BoundRpcConnection {
+    /// Returns the transfer syntax negotiated on bind, that stub data must be encoded with.
+    fn transfer_syntax(&self) -> TransferSyntax;
+
     async fn send_receive<S>(&mut self, stub_input: S) -> crate::Result<S::ResponseType>
     where
         S: RpcCall,
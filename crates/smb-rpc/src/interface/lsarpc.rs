@@ -0,0 +1,996 @@
+#![allow(unused_parens)]
+
+use std::{fmt::Display, marker::PhantomData};
+
+use crate::{interface::*, pdu::DceRpcSyntaxId};
+use smb_dtyp::{Guid, SID, make_guid};
+
+use crate::ndr20::Ndr20;
+use crate::ndr64::*;
+use crate::syntax::{NdrSize, NdrSyntax, TransferSyntax};
+use binrw::prelude::*;
+use maybe_async::maybe_async;
+
+/// `RPC_UNICODE_STRING` (MS-DTYP): a counted, UTF-16 string,
+/// which is not null-terminated.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+struct RpcUnicodeString<S: NdrSyntax = Ndr64> {
+    // Length and MaximumLength of the buffer, in bytes.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[bw(calc = NdrArrayStructureElement::from(
+        buffer.as_ref().map_or(0, |b| (b.data.len() * size_of::<u16>()) as u16)
+    ))]
+    #[br(temp, args(prev.map(|_| &0)))]
+    length: NdrArrayStructureElement<u16, NDR_SIZE_ALIGNMENT, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[bw(calc = NdrArrayStructureElement::from(
+        buffer.as_ref().map_or(0, |b| (b.data.len() * size_of::<u16>()) as u16)
+    ))]
+    #[br(temp, args(prev.map(|_| &0)))]
+    maximum_length: NdrArrayStructureElement<u16, 2, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.buffer), NdrPtrReadMode::WithArraySupport, ()))]
+    buffer: NdrPtr<NdrString<u16, 0, S>, S>,
+}
+
+impl<S: NdrSyntax> From<&str> for RpcUnicodeString<S> {
+    fn from(value: &str) -> Self {
+        let data: Vec<u16> = value.encode_utf16().collect();
+        Self {
+            buffer: NdrString { data: data.into() }.into(),
+        }
+    }
+}
+
+impl<S: NdrSyntax> Display for RpcUnicodeString<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.buffer.as_ref() {
+            Some(buffer) => {
+                let value = String::from_utf16_lossy(&buffer.data);
+                write!(f, "{}", value.trim_end_matches('\0'))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// `RPC_SID` (MS-DTYP): a [`SID`], as a conformant structure,
+/// preceded by the count of its sub-authorities.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct RpcSid<S: NdrSyntax = Ndr64> {
+    sid: SID,
+    _syntax: PhantomData<S>,
+}
+
+impl<S: NdrSyntax> BinRead for RpcSid<S> {
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self> {
+        let max_count = S::read_size(reader, endian)?;
+        let pos = reader.stream_position()?;
+        let sid = SID::read_options(reader, endian, ())?;
+        if sid.sub_authority.len() as u64 != max_count {
+            return Err(binrw::Error::AssertFail {
+                pos,
+                message: format!(
+                    "RPC_SID conformance ({max_count}) does not match its sub-authority count ({})",
+                    sid.sub_authority.len()
+                ),
+            });
+        }
+        Ok(sid.into())
+    }
+}
+
+impl<S: NdrSyntax> BinWrite for RpcSid<S> {
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        S::write_size(self.sid.sub_authority.len() as u64, writer, endian)?;
+        self.sid.write_options(writer, endian, ())
+    }
+}
+
+impl<S: NdrSyntax> NdrAligned for RpcSid<S> {}
+
+impl<S: NdrSyntax> From<SID> for RpcSid<S> {
+    fn from(sid: SID) -> Self {
+        Self {
+            sid,
+            _syntax: PhantomData,
+        }
+    }
+}
+
+/// A handle to an opened LSA policy (`LSAPR_HANDLE`, MS-LSAD).
+///
+/// Returned by [`LsaRpc::lsar_open_policy2`], and released by [`LsaRpc::lsar_close`].
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct PolicyHandle {
+    attributes: u32,
+    uuid: Guid,
+}
+
+impl NdrAligned for PolicyHandle {}
+
+/// `LSAPR_OBJECT_ATTRIBUTES` (MS-LSAD).
+///
+/// The server ignores this structure, so it is always sent zeroed,
+/// with the pointers it contains being null.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Default)]
+struct ObjectAttributes<S: NdrSyntax> {
+    length: NdrAlign<u32, NDR_SIZE_ALIGNMENT, S>,
+    root_directory: NdrSize<S>,
+    object_name: NdrSize<S>,
+    attributes: NdrAlign<u32, 4, S>,
+    security_descriptor: NdrSize<S>,
+    security_quality_of_service: NdrSize<S>,
+}
+
+/// `POLICY_VIEW_LOCAL_INFORMATION | POLICY_LOOKUP_NAMES` (MS-LSAD),
+/// the access required to query the policy domains, and to translate SIDs and names.
+const POLICY_LOOKUP_ACCESS: u32 = 0x00000001 | 0x00000800;
+
+/// `POLICY_INFORMATION_CLASS` (MS-LSAD), of the information
+/// that is supported by [`LsaRpc::lsar_query_information_policy`].
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[brw(repr(u16))]
+pub enum PolicyInformationClass {
+    /// The domain the server is a member of.
+    PrimaryDomain = 3,
+    /// The domain of the server's own accounts: the server itself,
+    /// or its domain, for a domain controller.
+    AccountDomain = 5,
+}
+
+/// `LSAPR_POLICY_INFORMATION` (MS-LSAD), preceded by its discriminant.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct PolicyInformation<S: NdrSyntax> {
+    #[bw(calc = policy_info.class().into())]
+    class: NdrEnum16<PolicyInformationClass, S>,
+    #[br(args(*class))]
+    policy_info: PolicyInformationUnion<S>,
+}
+
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[br(import(class: PolicyInformationClass))]
+enum PolicyInformationUnion<S: NdrSyntax> {
+    /// `LSAPR_POLICY_PRIMARY_DOM_INFO`, which is laid out as [`TrustInformation`].
+    #[br(pre_assert(class == PolicyInformationClass::PrimaryDomain))]
+    PrimaryDomain(NdrStructure<TrustInformation<S>, S>),
+    /// `LSAPR_POLICY_ACCOUNT_DOM_INFO`, which is laid out as [`TrustInformation`].
+    #[br(pre_assert(class == PolicyInformationClass::AccountDomain))]
+    AccountDomain(NdrStructure<TrustInformation<S>, S>),
+}
+
+impl<S: NdrSyntax> PolicyInformationUnion<S> {
+    fn class(&self) -> PolicyInformationClass {
+        match self {
+            PolicyInformationUnion::PrimaryDomain(_) => PolicyInformationClass::PrimaryDomain,
+            PolicyInformationUnion::AccountDomain(_) => PolicyInformationClass::AccountDomain,
+        }
+    }
+}
+
+/// `SID_NAME_USE` (MS-LSAT): the type of account a SID or a name refers to.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[brw(repr(u16))]
+pub enum SidNameUse {
+    User = 1,
+    Group = 2,
+    Domain = 3,
+    Alias = 4,
+    WellKnownGroup = 5,
+    DeletedAccount = 6,
+    Invalid = 7,
+    Unknown = 8,
+    Computer = 9,
+    Label = 10,
+}
+
+/// `LSAP_LOOKUP_LEVEL` (MS-LSAT)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[brw(repr(u16))]
+enum LookupLevel {
+    /// Look up in all the domains the server trusts, as a workstation would.
+    Workstation = 1,
+}
+
+/// `LSA_CLIENT_REVISION_2` (MS-LSAT)
+const CLIENT_REVISION: u32 = 2;
+
+/// The maximum number of SIDs in a single LsarLookupSids2 request (`[range(0,20480)]` in MS-LSAT).
+const LOOKUP_SIDS_MAX_COUNT: usize = 20480;
+/// The maximum number of names in a single LsarLookupNames3 request (`[range(0,1000)]` in MS-LSAT).
+const LOOKUP_NAMES_MAX_COUNT: usize = 1000;
+
+/// An element of an array in an [`EntriesBuffer`].
+trait LsaEntry:
+    for<'a> BinRead<Args<'a> = (Option<&'a Self>,)>
+    + for<'a> BinWrite<Args<'a> = (NdrPtrWriteStage,)>
+    + 'static
+{
+}
+
+/// A count of entries, and a pointer to their array, such as
+/// `LSAPR_SID_ENUM_BUFFER` or `LSAPR_TRANSLATED_NAMES_EX` (MS-LSAT).
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct EntriesBuffer<T, S = Ndr64>
+where
+    T: LsaEntry,
+    S: NdrSyntax,
+{
+    #[bw(calc = (entries.as_ref().map_or(0, |x| x.len() as u32)).into())]
+    count: NdrAlign<u32, NDR_SIZE_ALIGNMENT, S>,
+    #[br(args(None, NdrPtrReadMode::NoArraySupport, (*count as u64,)))]
+    entries: NdrPtr<NdrArray<T, S>, S>,
+}
+
+impl<T: LsaEntry, S: NdrSyntax> EntriesBuffer<T, S> {
+    fn new(entries: Vec<T>) -> Self {
+        Self {
+            entries: NdrArray::from(entries).into(),
+        }
+    }
+
+    /// An empty buffer, for output parameters.
+    fn empty() -> Self {
+        Self {
+            entries: NdrPtr::from(None),
+        }
+    }
+
+    fn into_entries(self) -> Vec<T> {
+        self.entries
+            .into_value()
+            .map(|entries| entries.data.into_iter().map(|x| x.value).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// `LSAPR_SID_INFORMATION` (MS-LSAT)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+struct SidInformation<S: NdrSyntax = Ndr64> {
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.sid), NdrPtrReadMode::WithArraySupport, ()))]
+    sid: NdrPtr<RpcSid<S>, S>,
+}
+
+/// `LSAPR_TRANSLATED_NAME_EX` (MS-LSAT)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+struct TranslatedNameEx<S: NdrSyntax = Ndr64> {
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.name_use)))]
+    name_use: NdrArrayStructureElement<NdrEnum16<SidNameUse, S>, NDR_SIZE_ALIGNMENT, S>,
+    #[bw(args(stage))]
+    #[br(args(prev.map(|x| &x.name)))]
+    name: RpcUnicodeString<S>,
+    /// The index of the domain of the account in the referenced domain list, or -1.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.domain_index)))]
+    domain_index: NdrArrayStructureElement<i32, 4, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.flags)))]
+    flags: NdrArrayStructureElement<u32, 4, S>,
+}
+
+/// `LSAPR_TRANSLATED_SID_EX2` (MS-LSAT)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+struct TranslatedSidEx2<S: NdrSyntax = Ndr64> {
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.name_use)))]
+    name_use: NdrArrayStructureElement<NdrEnum16<SidNameUse, S>, NDR_SIZE_ALIGNMENT, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.sid), NdrPtrReadMode::WithArraySupport, ()))]
+    sid: NdrPtr<RpcSid<S>, S>,
+    /// The index of the domain of the account in the referenced domain list, or -1.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.domain_index)))]
+    domain_index: NdrArrayStructureElement<i32, 4, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.flags)))]
+    flags: NdrArrayStructureElement<u32, 4, S>,
+}
+
+/// `LSAPR_TRUST_INFORMATION` (MS-LSAT): the name and the SID of a domain.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+struct TrustInformation<S: NdrSyntax = Ndr64> {
+    #[bw(args(stage))]
+    #[br(args(prev.map(|x| &x.name)))]
+    name: RpcUnicodeString<S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.sid), NdrPtrReadMode::WithArraySupport, ()))]
+    sid: NdrPtr<RpcSid<S>, S>,
+}
+
+/// `LSAPR_REFERENCED_DOMAIN_LIST` (MS-LSAT): the domains of translated accounts.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+struct ReferencedDomainList<S: NdrSyntax = Ndr64> {
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.entries)))]
+    entries: NdrArrayStructureElement<u32, NDR_SIZE_ALIGNMENT, S>,
+    // Unlike an EntriesBuffer, the array is deferred after the rest of the structure.
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.domains), NdrPtrReadMode::WithArraySupport, (**entries as u64,)))]
+    domains: NdrPtr<NdrArray<TrustInformation<S>, S>, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.max_entries)))]
+    max_entries: NdrArrayStructureElement<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> LsaEntry for SidInformation<S> {}
+impl<S: NdrSyntax> LsaEntry for TranslatedNameEx<S> {}
+impl<S: NdrSyntax> LsaEntry for TranslatedSidEx2<S> {}
+
+/// The name and the SID of a domain.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LsaDomainInfo {
+    pub name: String,
+    pub sid: Option<SID>,
+}
+
+impl<S: NdrSyntax> From<TrustInformation<S>> for LsaDomainInfo {
+    fn from(value: TrustInformation<S>) -> Self {
+        Self {
+            name: value.name.to_string(),
+            sid: value.sid.into_value().map(|x| x.sid),
+        }
+    }
+}
+
+/// An account, such as a user or a group, that a SID or a name was translated to.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LsaAccount {
+    pub sid: SID,
+    /// The name of the account, without its domain.
+    pub name: String,
+    /// The name of the domain of the account; well-known accounts, such as Everyone, have none.
+    pub domain: Option<String>,
+    pub kind: SidNameUse,
+}
+
+impl LsaAccount {
+    /// Returns the account of a translated SID or name,
+    /// or `None` if it could not be mapped to one.
+    fn translated(
+        sid: Option<SID>,
+        name: String,
+        kind: SidNameUse,
+        domain_index: i32,
+        domains: &[LsaDomainInfo],
+    ) -> Option<Self> {
+        if matches!(kind, SidNameUse::Invalid | SidNameUse::Unknown) {
+            return None;
+        }
+        let domain = usize::try_from(domain_index)
+            .ok()
+            .and_then(|index| domains.get(index))
+            .map(|domain| domain.name.clone())
+            .filter(|name| !name.is_empty());
+        Some(Self {
+            sid: sid?,
+            name,
+            domain,
+            kind,
+        })
+    }
+}
+
+impl Display for LsaAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.domain {
+            Some(domain) => write!(f, "{domain}\\{}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Input arguments for LsarOpenPolicy2 (MS-LSAD)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct LsarOpenPolicy2In<S: NdrSyntax = Ndr64> {
+    system_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    object_attributes: ObjectAttributes<S>,
+    desired_access: NdrAlign<u32, 4, S>,
+}
+
+/// Return value and out params of LsarOpenPolicy2 (MS-LSAD)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct LsarOpenPolicy2Out<S: NdrSyntax = Ndr64> {
+    policy_handle: NdrAlign<PolicyHandle, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for LsarOpenPolicy2In<S> {
+    const OPNUM: u16 = 0x2c;
+
+    type ResponseType = LsarOpenPolicy2Out<S>;
+}
+
+/// Input arguments for LsarClose (MS-LSAD)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct LsarCloseIn<S: NdrSyntax = Ndr64> {
+    object_handle: NdrAlign<PolicyHandle, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for LsarCloseIn<S> {
+    const OPNUM: u16 = 0x0;
+
+    type ResponseType = LsarOpenPolicy2Out<S>;
+}
+
+/// Input arguments for LsarQueryInformationPolicy (MS-LSAD)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct LsarQueryInformationPolicyIn<S: NdrSyntax = Ndr64> {
+    policy_handle: NdrAlign<PolicyHandle, 4, S>,
+    information_class: NdrEnum16<PolicyInformationClass, S>,
+}
+
+/// Return value and out params of LsarQueryInformationPolicy (MS-LSAD)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct LsarQueryInformationPolicyOut<S: NdrSyntax = Ndr64> {
+    policy_information: NdrAlign<NdrPtr<PolicyInformation<S>, S>, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for LsarQueryInformationPolicyIn<S> {
+    const OPNUM: u16 = 0x7;
+
+    type ResponseType = LsarQueryInformationPolicyOut<S>;
+}
+
+/// Input arguments for LsarLookupSids2 (MS-LSAT)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct LsarLookupSids2In<S: NdrSyntax = Ndr64> {
+    policy_handle: NdrAlign<PolicyHandle, 4, S>,
+    sid_enum_buffer: EntriesBuffer<SidInformation<S>, S>,
+    translated_names: EntriesBuffer<TranslatedNameEx<S>, S>,
+    lookup_level: NdrEnum16<LookupLevel, S>,
+    mapped_count: NdrAlign<u32, 4, S>,
+    lookup_options: NdrAlign<u32, 4, S>,
+    client_revision: NdrAlign<u32, 4, S>,
+}
+
+/// Return value and out params of LsarLookupSids2 (MS-LSAT)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct LsarLookupSids2Out<S: NdrSyntax = Ndr64> {
+    referenced_domains: NdrAlign<NdrPtr<NdrStructure<ReferencedDomainList<S>, S>, S>, 4, S>,
+    translated_names: EntriesBuffer<TranslatedNameEx<S>, S>,
+    mapped_count: NdrAlign<u32, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for LsarLookupSids2In<S> {
+    const OPNUM: u16 = 0x39;
+
+    type ResponseType = LsarLookupSids2Out<S>;
+}
+
+/// Input arguments for LsarLookupNames3 (MS-LSAT)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct LsarLookupNames3In<S: NdrSyntax = Ndr64> {
+    policy_handle: NdrAlign<PolicyHandle, 4, S>,
+    #[bw(calc = (names.len() as u32).into())]
+    #[br(temp)]
+    count: NdrAlign<u32, 4, S>,
+    #[br(args(*count as u64))]
+    names: NdrArray<RpcUnicodeString<S>, S>,
+    translated_sids: EntriesBuffer<TranslatedSidEx2<S>, S>,
+    lookup_level: NdrEnum16<LookupLevel, S>,
+    mapped_count: NdrAlign<u32, 4, S>,
+    lookup_options: NdrAlign<u32, 4, S>,
+    client_revision: NdrAlign<u32, 4, S>,
+}
+
+/// Return value and out params of LsarLookupNames3 (MS-LSAT)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct LsarLookupNames3Out<S: NdrSyntax = Ndr64> {
+    referenced_domains: NdrAlign<NdrPtr<NdrStructure<ReferencedDomainList<S>, S>, S>, 4, S>,
+    translated_sids: EntriesBuffer<TranslatedSidEx2<S>, S>,
+    mapped_count: NdrAlign<u32, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for LsarLookupNames3In<S> {
+    const OPNUM: u16 = 0x44;
+
+    type ResponseType = LsarLookupNames3Out<S>;
+}
+
+/// `STATUS_NONE_MAPPED`: a lookup call could not translate any of its SIDs or names.
+const STATUS_NONE_MAPPED: u32 = 0xc0000073;
+
+/// Fails on an `NTSTATUS` that is an error or a warning, returned by a call.
+///
+/// Informational statuses, such as `STATUS_SOME_NOT_MAPPED`, are successful.
+fn check_status(status: u32) -> crate::Result<()> {
+    match status >> 30 {
+        0 | 1 => Ok(()),
+        _ => Err(crate::SmbRpcError::CallFailed(status)),
+    }
+}
+
+fn referenced_domains<S: NdrSyntax>(
+    domains: NdrPtr<NdrStructure<ReferencedDomainList<S>, S>, S>,
+) -> Vec<LsaDomainInfo> {
+    domains
+        .into_value()
+        .and_then(|list| list.value.domains.into_value())
+        .map(|domains| {
+            domains
+                .data
+                .into_iter()
+                .map(|x| LsaDomainInfo::from(x.value))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The Local Security Authority (LSA) remote protocol, over the `lsarpc` pipe,
+/// for translating between SIDs and account names.
+pub struct LsaRpc<T>
+where
+    T: BoundRpcConnection,
+{
+    bound_pipe: T,
+}
+
+impl<T> LsaRpc<T>
+where
+    T: BoundRpcConnection,
+{
+    /// Opens the LSA policy of the server (LsarOpenPolicy2),
+    /// with the access required to query its domains, and to translate SIDs and names.
+    #[maybe_async]
+    pub async fn lsar_open_policy2(&mut self, system_name: &str) -> crate::Result<PolicyHandle> {
+        let (status, policy_handle) = match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                let result = self
+                    .bound_pipe
+                    .send_receive(LsarOpenPolicy2In::<Ndr64> {
+                        system_name: NdrPtr::from(system_name.parse::<NdrString<u16>>().unwrap())
+                            .into(),
+                        object_attributes: Default::default(),
+                        desired_access: POLICY_LOOKUP_ACCESS.into(),
+                    })
+                    .await?;
+                (*result.status, *result.policy_handle)
+            }
+            TransferSyntax::Ndr20 => {
+                let result = self
+                    .bound_pipe
+                    .send_receive(LsarOpenPolicy2In::<Ndr20> {
+                        system_name: NdrPtr::from(
+                            system_name.parse::<NdrString<u16, 0, Ndr20>>().unwrap(),
+                        )
+                        .into(),
+                        object_attributes: Default::default(),
+                        desired_access: POLICY_LOOKUP_ACCESS.into(),
+                    })
+                    .await?;
+                (*result.status, *result.policy_handle)
+            }
+        };
+        check_status(status)?;
+        Ok(policy_handle)
+    }
+
+    /// Closes a policy handle (LsarClose).
+    #[maybe_async]
+    pub async fn lsar_close(&mut self, policy_handle: PolicyHandle) -> crate::Result<()> {
+        let status = match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                *self
+                    .bound_pipe
+                    .send_receive(LsarCloseIn::<Ndr64> {
+                        object_handle: policy_handle.into(),
+                    })
+                    .await?
+                    .status
+            }
+            TransferSyntax::Ndr20 => {
+                *self
+                    .bound_pipe
+                    .send_receive(LsarCloseIn::<Ndr20> {
+                        object_handle: policy_handle.into(),
+                    })
+                    .await?
+                    .status
+            }
+        };
+        check_status(status)
+    }
+
+    /// Returns the name and the SID of a domain of the policy (LsarQueryInformationPolicy).
+    #[maybe_async]
+    pub async fn lsar_query_information_policy(
+        &mut self,
+        policy_handle: &PolicyHandle,
+        information_class: PolicyInformationClass,
+    ) -> crate::Result<LsaDomainInfo> {
+        match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                self.lsar_query_information_policy_with::<Ndr64>(policy_handle, information_class)
+                    .await
+            }
+            TransferSyntax::Ndr20 => {
+                self.lsar_query_information_policy_with::<Ndr20>(policy_handle, information_class)
+                    .await
+            }
+        }
+    }
+
+    #[maybe_async]
+    async fn lsar_query_information_policy_with<S: NdrSyntax>(
+        &mut self,
+        policy_handle: &PolicyHandle,
+        information_class: PolicyInformationClass,
+    ) -> crate::Result<LsaDomainInfo> {
+        let result = self
+            .bound_pipe
+            .send_receive(LsarQueryInformationPolicyIn::<S> {
+                policy_handle: (*policy_handle).into(),
+                information_class: information_class.into(),
+            })
+            .await?;
+        check_status(*result.status)?;
+        let policy_information = result.policy_information.value.into_value().ok_or(
+            crate::SmbRpcError::InvalidResponseData("LsarQueryInformationPolicy returned no data"),
+        )?;
+        match policy_information.policy_info {
+            PolicyInformationUnion::PrimaryDomain(info)
+            | PolicyInformationUnion::AccountDomain(info) => Ok(info.value.into()),
+        }
+    }
+
+    /// Translates SIDs to account names (LsarLookupSids2).
+    ///
+    /// Returns the account of each SID, by order, or `None` for SIDs that could not be translated.
+    /// Many SIDs are split into multiple requests, as limited by the protocol.
+    #[maybe_async]
+    pub async fn lsar_lookup_sids2(
+        &mut self,
+        policy_handle: &PolicyHandle,
+        sids: &[SID],
+    ) -> crate::Result<Vec<Option<LsaAccount>>> {
+        let mut accounts = Vec::with_capacity(sids.len());
+        for batch in sids.chunks(LOOKUP_SIDS_MAX_COUNT) {
+            let batch_accounts = match self.bound_pipe.transfer_syntax() {
+                TransferSyntax::Ndr64 => {
+                    self.lsar_lookup_sids2_with::<Ndr64>(policy_handle, batch)
+                        .await?
+                }
+                TransferSyntax::Ndr20 => {
+                    self.lsar_lookup_sids2_with::<Ndr20>(policy_handle, batch)
+                        .await?
+                }
+            };
+            accounts.extend(batch_accounts);
+        }
+        Ok(accounts)
+    }
+
+    #[maybe_async]
+    async fn lsar_lookup_sids2_with<S: NdrSyntax>(
+        &mut self,
+        policy_handle: &PolicyHandle,
+        sids: &[SID],
+    ) -> crate::Result<Vec<Option<LsaAccount>>> {
+        let sid_infos = sids
+            .iter()
+            .map(|sid| SidInformation {
+                sid: RpcSid::from(sid.clone()).into(),
+            })
+            .collect();
+        let result = self
+            .bound_pipe
+            .send_receive(LsarLookupSids2In::<S> {
+                policy_handle: (*policy_handle).into(),
+                sid_enum_buffer: EntriesBuffer::new(sid_infos),
+                translated_names: EntriesBuffer::empty(),
+                lookup_level: LookupLevel::Workstation.into(),
+                mapped_count: 0.into(),
+                lookup_options: 0.into(),
+                client_revision: CLIENT_REVISION.into(),
+            })
+            .await?;
+        if *result.status == STATUS_NONE_MAPPED {
+            return Ok(vec![None; sids.len()]);
+        }
+        check_status(*result.status)?;
+
+        let domains = referenced_domains(result.referenced_domains.value);
+        let names = result.translated_names.into_entries();
+        if names.len() != sids.len() {
+            return Err(crate::SmbRpcError::InvalidResponseData(
+                "LsarLookupSids2 returned a different number of names than requested",
+            ));
+        }
+        Ok(sids
+            .iter()
+            .zip(names)
+            .map(|(sid, name)| {
+                LsaAccount::translated(
+                    Some(sid.clone()),
+                    name.name.to_string(),
+                    ***name.name_use,
+                    **name.domain_index,
+                    &domains,
+                )
+            })
+            .collect())
+    }
+
+    /// Translates account names to SIDs (LsarLookupNames3).
+    ///
+    /// Names may be qualified by their domain (`DOMAIN\name`), or not.
+    /// Returns the account of each name, by order, or `None` for names that could not be translated.
+    /// The [`LsaAccount::name`] is as requested.
+    /// Many names are split into multiple requests, as limited by the protocol.
+    #[maybe_async]
+    pub async fn lsar_lookup_names3(
+        &mut self,
+        policy_handle: &PolicyHandle,
+        names: &[&str],
+    ) -> crate::Result<Vec<Option<LsaAccount>>> {
+        let mut accounts = Vec::with_capacity(names.len());
+        for batch in names.chunks(LOOKUP_NAMES_MAX_COUNT) {
+            let batch_accounts = match self.bound_pipe.transfer_syntax() {
+                TransferSyntax::Ndr64 => {
+                    self.lsar_lookup_names3_with::<Ndr64>(policy_handle, batch)
+                        .await?
+                }
+                TransferSyntax::Ndr20 => {
+                    self.lsar_lookup_names3_with::<Ndr20>(policy_handle, batch)
+                        .await?
+                }
+            };
+            accounts.extend(batch_accounts);
+        }
+        Ok(accounts)
+    }
+
+    #[maybe_async]
+    async fn lsar_lookup_names3_with<S: NdrSyntax>(
+        &mut self,
+        policy_handle: &PolicyHandle,
+        names: &[&str],
+    ) -> crate::Result<Vec<Option<LsaAccount>>> {
+        let rpc_names: Vec<RpcUnicodeString<S>> = names
+            .iter()
+            .map(|name| RpcUnicodeString::from(*name))
+            .collect();
+        let result = self
+            .bound_pipe
+            .send_receive(LsarLookupNames3In::<S> {
+                policy_handle: (*policy_handle).into(),
+                names: rpc_names.into(),
+                translated_sids: EntriesBuffer::empty(),
+                lookup_level: LookupLevel::Workstation.into(),
+                mapped_count: 0.into(),
+                lookup_options: 0.into(),
+                client_revision: CLIENT_REVISION.into(),
+            })
+            .await?;
+        if *result.status == STATUS_NONE_MAPPED {
+            return Ok(vec![None; names.len()]);
+        }
+        check_status(*result.status)?;
+
+        let domains = referenced_domains(result.referenced_domains.value);
+        let sids = result.translated_sids.into_entries();
+        if sids.len() != names.len() {
+            return Err(crate::SmbRpcError::InvalidResponseData(
+                "LsarLookupNames3 returned a different number of SIDs than requested",
+            ));
+        }
+        Ok(names
+            .iter()
+            .zip(sids)
+            .map(|(name, sid)| {
+                let account_name = name.rsplit('\\').next().unwrap_or(name);
+                LsaAccount::translated(
+                    sid.sid.into_value().map(|x| x.sid),
+                    account_name.to_string(),
+                    ***sid.name_use,
+                    **sid.domain_index,
+                    &domains,
+                )
+            })
+            .collect())
+    }
+}
+
+impl<T> super::base::RpcInterface<T> for LsaRpc<T>
+where
+    T: BoundRpcConnection,
+{
+    const SYNTAX_ID: DceRpcSyntaxId = DceRpcSyntaxId {
+        uuid: make_guid!("12345778-1234-abcd-ef00-0123456789ab"),
+        version: 0,
+    };
+
+    fn new(bound_pipe: T) -> Self {
+        LsaRpc { bound_pipe }
+    }
+
+    fn into_inner(self) -> T {
+        self.bound_pipe
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use smb_tests::*;
+
+    use super::*;
+
+    const POLICY_HANDLE: PolicyHandle = PolicyHandle {
+        attributes: 0,
+        uuid: make_guid!("11223344-5566-7788-99aa-bbccddeeff00"),
+    };
+
+    fn rpc_sid<S: NdrSyntax>(sid: &str) -> NdrPtr<RpcSid<S>, S> {
+        RpcSid::from(SID::from_str(sid).unwrap()).into()
+    }
+
+    test_binrw_write! {
+        struct LsarOpenPolicy2In {
+            system_name: NdrPtr::from(r"\\S".parse::<NdrString<u16>>().unwrap()).into(),
+            object_attributes: Default::default(),
+            desired_access: POLICY_LOOKUP_ACCESS.into(),
+        } => "0000020000000000 0400000000000000 0000000000000000 0400000000000000 5c005c0053000000
+              0000000000000000 0000000000000000 0000000000000000 0000000000000000 0000000000000000 0000000000000000
+              01080000"
+    }
+
+    test_binrw! {
+        struct LsarQueryInformationPolicyOut {
+            policy_information: NdrPtr::<_, Ndr64>::from(PolicyInformation {
+                policy_info: PolicyInformationUnion::AccountDomain(NdrStructure::from(
+                    TrustInformation {
+                        name: "DOM".into(),
+                        sid: rpc_sid("S-1-5-21-1-2-3"),
+                    },
+                )),
+            })
+            .into(),
+            status: 0.into(),
+        } => "0000020000000000 0500000000000000 0600060000000000 0000020000000000 0000020000000000
+              0300000000000000 0000000000000000 0300000000000000 44004f004d000000
+              0400000000000000 010400000000000515000000010000000200000003000000 00000000"
+    }
+
+    test_binrw_write! {
+        LsarLookupSids2In => ndr20: LsarLookupSids2In::<Ndr20> {
+            policy_handle: POLICY_HANDLE.into(),
+            sid_enum_buffer: EntriesBuffer::new(vec![SidInformation {
+                sid: rpc_sid("S-1-5-32-544"),
+            }]),
+            translated_names: EntriesBuffer::empty(),
+            lookup_level: LookupLevel::Workstation.into(),
+            mapped_count: 0.into(),
+            lookup_options: 0.into(),
+            client_revision: CLIENT_REVISION.into(),
+        } => "00000000443322116655887799aabbccddeeff00 01000000 00000200 01000000 00000200
+              02000000 0102000000000005 20000000 20020000 00000000 00000000 01000000 00000000 00000000 02000000"
+    }
+
+    type LsarLookupSids2OutNdr20 = LsarLookupSids2Out<Ndr20>;
+
+    test_binrw! {
+        LsarLookupSids2OutNdr20: LsarLookupSids2Out::<Ndr20> {
+            referenced_domains: NdrPtr::from(NdrStructure::from(ReferencedDomainList {
+                entries: 1.into(),
+                domains: NdrArray::from(vec![TrustInformation {
+                    name: "BUILTIN".into(),
+                    sid: rpc_sid("S-1-5-32"),
+                }])
+                .into(),
+                max_entries: 32.into(),
+            }))
+            .into(),
+            translated_names: EntriesBuffer::new(vec![TranslatedNameEx {
+                name_use: NdrEnum16::from(SidNameUse::Alias).into(),
+                name: "Administrators".into(),
+                domain_index: 0.into(),
+                flags: 0.into(),
+            }]),
+            mapped_count: 1.into(),
+            status: 0.into(),
+        } => "00000200 01000000 00000200 20000000 01000000 0e000e00 00000200 00000200
+              07000000 00000000 07000000 4200550049004c00540049004e000000
+              01000000 0101000000000005 20000000
+              01000000 00000200 01000000 04000000 1c001c00 00000200 00000000 00000000
+              0e000000 00000000 0e000000 410064006d0069006e006900730074007200610074006f0072007300
+              01000000 00000000"
+    }
+
+    test_binrw_write! {
+        LsarLookupNames3In => ndr20: LsarLookupNames3In::<Ndr20> {
+            policy_handle: POLICY_HANDLE.into(),
+            names: vec![RpcUnicodeString::from(r"D\u")].into(),
+            translated_sids: EntriesBuffer::empty(),
+            lookup_level: LookupLevel::Workstation.into(),
+            mapped_count: 0.into(),
+            lookup_options: 0.into(),
+            client_revision: CLIENT_REVISION.into(),
+        } => "00000000443322116655887799aabbccddeeff00 01000000 01000000 06000600 00000200
+              03000000 00000000 03000000 44005c0075000000 00000000 00000000 01000000 00000000 00000000 02000000"
+    }
+
+    test_binrw! {
+        struct LsarLookupNames3Out {
+            referenced_domains: NdrPtr::<_, Ndr64>::from(NdrStructure::from(ReferencedDomainList {
+                entries: 1.into(),
+                domains: NdrArray::from(vec![TrustInformation {
+                    name: "D".into(),
+                    sid: rpc_sid("S-1-5-21-1-2-3"),
+                }])
+                .into(),
+                max_entries: 32.into(),
+            }))
+            .into(),
+            translated_sids: EntriesBuffer::new(vec![TranslatedSidEx2 {
+                name_use: NdrEnum16::from(SidNameUse::User).into(),
+                sid: rpc_sid("S-1-5-21-1-2-3-1000"),
+                domain_index: 0.into(),
+                flags: 0.into(),
+            }]),
+            mapped_count: 1.into(),
+            status: 0.into(),
+        } => "0000020000000000 0100000000000000 0000020000000000 2000000000000000
+              0100000000000000 0200020000000000 0000020000000000 0000020000000000
+              0100000000000000 0000000000000000 0100000000000000 4400000000000000
+              0400000000000000 010400000000000515000000010000000200000003000000
+              0100000000000000 0000020000000000 0100000000000000
+              0100000000000000 0000020000000000 00000000 00000000
+              0500000000000000 010500000000000515000000010000000200000003000000e8030000
+              01000000 00000000"
+    }
+}
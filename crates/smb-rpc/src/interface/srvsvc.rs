@@ -1,20 +1,26 @@
 #![allow(unused_parens)]
 
 use crate::{interface::*, pdu::DceRpcSyntaxId};
-use smb_dtyp::make_guid;
+use smb_dtyp::{SecurityDescriptor, make_guid};
 
+use crate::ndr20::Ndr20;
 use crate::ndr64::*;
+use crate::syntax::{NdrSyntax, TransferSyntax};
 use binrw::prelude::*;
 use maybe_async::maybe_async;
 use modular_bitfield::prelude::*;
 /// [SHARE_ENUM_STRUCT](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-srvs/79ee052e-e16b-4ec5-b4b7-e99777c26eca>)
 #[binrw::binrw]
 #[derive(Debug, PartialEq, Eq)]
-struct ShareEnumStruct {
+struct ShareEnumStruct<S: NdrSyntax> {
     #[bw(calc = share_info.level().into())]
-    level: NdrAlign<ShareInfoLevel>,
+    level: NdrAlign<ShareInfoLevel, NDR_SIZE_ALIGNMENT, S>,
+    // The discriminant of the (non-encapsulated) union, aligned as the union.
+    #[bw(calc = share_info.level().into())]
+    #[br(temp, assert(*switch_value == *level))]
+    switch_value: NdrAlign<ShareInfoLevel, NDR_SIZE_ALIGNMENT, S>,
     #[br(args(*level))]
-    share_info: NdrAlign<ShareEnumUnion>,
+    share_info: ShareEnumUnion<S>,
 }
 
 #[binrw::binrw]
@@ -33,16 +39,14 @@ pub enum ShareInfoLevel {
 #[binrw::binrw]
 #[derive(Debug, PartialEq, Eq)]
 #[br(import(level: ShareInfoLevel))]
-enum ShareEnumUnion {
-    #[brw(magic = 0u64)]
+enum ShareEnumUnion<S: NdrSyntax> {
     #[br(pre_assert(level == ShareInfoLevel::Info0))]
-    Info0(NdrPtr<ShareInfoContainer<ShareInfo0>>),
-    #[brw(magic = 1u64)]
+    Info0(NdrPtr<InfoContainer<ShareInfo0<S>, S>, S>),
     #[br(pre_assert(level == ShareInfoLevel::Info1))]
-    Info1(NdrPtr<ShareInfoContainer<ShareInfo1>>),
+    Info1(NdrPtr<InfoContainer<ShareInfo1<S>, S>, S>),
 }
 
-impl ShareEnumUnion {
+impl<S: NdrSyntax> ShareEnumUnion<S> {
     /// Returns the level of the share info contained in this union.
     pub fn level(&self) -> ShareInfoLevel {
         match self {
@@ -52,20 +56,22 @@ impl ShareEnumUnion {
     }
 }
 
+/// A container of enumerated entries, such as
 /// [`SHARE_INFO_1_CONTAINER`](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-srvs/919abd5d-87d9-4ffa-b4b1-632a66053bc6>)
 #[binrw::binrw]
 #[derive(Debug, PartialEq, Eq)]
-struct ShareInfoContainer<T>
+struct InfoContainer<T, S = Ndr64>
 where
-    T: ShareInfo,
+    T: InfoEntry,
+    S: NdrSyntax,
 {
     #[bw(calc = (buffer.as_ref().map_or(0, |x| x.len() as u32)).into())]
-    entries_read: NdrAlign<u32>,
+    entries_read: NdrAlign<u32, NDR_SIZE_ALIGNMENT, S>,
     #[br(args(None, NdrPtrReadMode::NoArraySupport, (*entries_read as u64,)))]
-    buffer: NdrPtr<NdrArray<T>>,
+    buffer: NdrPtr<NdrArray<T, S>, S>,
 }
 
-trait ShareInfo:
+trait InfoEntry:
     for<'a> BinRead<Args<'a> = (Option<&'a Self>,)>
     + for<'a> BinWrite<Args<'a> = (NdrPtrWriteStage,)>
     + Clone
@@ -80,16 +86,27 @@ trait ShareInfo:
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[bw(import(stage: NdrPtrWriteStage))]
 #[br(import(prev: Option<&Self>))]
-pub struct ShareInfo1 {
+pub struct ShareInfo1<S: NdrSyntax = Ndr64> {
     #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
     #[br(args(prev.map(|x| &x.netname), NdrPtrReadMode::WithArraySupport, ()))]
-    pub netname: NdrPtr<NdrString<u16>>,
+    pub netname: NdrPtr<NdrString<u16, 0, S>, S>,
     #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
     #[br(args(prev.map(|x| &**x.share_type)))]
-    pub share_type: NdrArrayStructureElement<ShareType>,
+    pub share_type: NdrArrayStructureElement<ShareType, 4, S>,
     #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
     #[br(args(prev.map(|x| &x.remark), NdrPtrReadMode::WithArraySupport, ()))]
-    pub remark: NdrPtr<NdrString<u16>>,
+    pub remark: NdrPtr<NdrString<u16, 0, S>, S>,
+}
+
+impl<S: NdrSyntax> ShareInfo1<S> {
+    /// Converts the share info into a different syntax.
+    pub fn into_syntax<S2: NdrSyntax>(self) -> ShareInfo1<S2> {
+        ShareInfo1 {
+            netname: self.netname.map(NdrString::into_syntax),
+            share_type: self.share_type.value.into(),
+            remark: self.remark.map(NdrString::into_syntax),
+        }
+    }
 }
 
 /// [`SHARE_INFO_0`](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-srvs/73a25288-8086-4975-91a3-5cbee5b590cc>)
@@ -97,14 +114,228 @@ pub struct ShareInfo1 {
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[bw(import(stage: NdrPtrWriteStage))]
 #[br(import(prev: Option<&Self>))]
-pub struct ShareInfo0 {
+pub struct ShareInfo0<S: NdrSyntax = Ndr64> {
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.netname), NdrPtrReadMode::WithArraySupport, ()))]
+    netname: NdrPtr<NdrString<u16, 0, S>, S>,
+}
+
+/// `SHARE_INFO_2` (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+pub struct ShareInfo2<S: NdrSyntax = Ndr64> {
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.netname), NdrPtrReadMode::WithArraySupport, ()))]
+    pub netname: NdrPtr<NdrString<u16, 0, S>, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.share_type)))]
+    pub share_type: NdrArrayStructureElement<ShareType, 4, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.remark), NdrPtrReadMode::WithArraySupport, ()))]
+    pub remark: NdrPtr<NdrString<u16, 0, S>, S>,
+    /// Ignored by servers running in user-level security mode.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.permissions)))]
+    pub permissions: NdrArrayStructureElement<u32, 4, S>,
+    /// The maximum number of concurrent connections, or [`ShareInfo2::UNLIMITED_USES`].
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.max_uses)))]
+    pub max_uses: NdrArrayStructureElement<u32, 4, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.current_uses)))]
+    pub current_uses: NdrArrayStructureElement<u32, 4, S>,
+    /// The local path of the share on the server.
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.path), NdrPtrReadMode::WithArraySupport, ()))]
+    pub path: NdrPtr<NdrString<u16, 0, S>, S>,
+    /// Ignored by servers running in user-level security mode.
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.passwd), NdrPtrReadMode::WithArraySupport, ()))]
+    pub passwd: NdrPtr<NdrString<u16, 0, S>, S>,
+}
+
+impl<S: NdrSyntax> ShareInfo2<S> {
+    /// A value of [`ShareInfo2::max_uses`], for an unlimited number of connections.
+    pub const UNLIMITED_USES: u32 = u32::MAX;
+
+    /// Converts the share info into a different syntax.
+    pub fn into_syntax<S2: NdrSyntax>(self) -> ShareInfo2<S2> {
+        ShareInfo2 {
+            netname: self.netname.map(NdrString::into_syntax),
+            share_type: self.share_type.value.into(),
+            remark: self.remark.map(NdrString::into_syntax),
+            permissions: self.permissions.value.into(),
+            max_uses: self.max_uses.value.into(),
+            current_uses: self.current_uses.value.into(),
+            path: self.path.map(NdrString::into_syntax),
+            passwd: self.passwd.map(NdrString::into_syntax),
+        }
+    }
+}
+
+/// `SHARE_INFO_502_I` (MS-SRVS)
+///
+/// [`ShareInfo2`], along with the security descriptor of the share.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+pub struct ShareInfo502<S: NdrSyntax = Ndr64> {
     #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
     #[br(args(prev.map(|x| &x.netname), NdrPtrReadMode::WithArraySupport, ()))]
-    netname: NdrPtr<NdrString<u16>>,
+    pub netname: NdrPtr<NdrString<u16, 0, S>, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.share_type)))]
+    pub share_type: NdrArrayStructureElement<ShareType, 4, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.remark), NdrPtrReadMode::WithArraySupport, ()))]
+    pub remark: NdrPtr<NdrString<u16, 0, S>, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.permissions)))]
+    pub permissions: NdrArrayStructureElement<u32, 4, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.max_uses)))]
+    pub max_uses: NdrArrayStructureElement<u32, 4, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.current_uses)))]
+    pub current_uses: NdrArrayStructureElement<u32, 4, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.path), NdrPtrReadMode::WithArraySupport, ()))]
+    pub path: NdrPtr<NdrString<u16, 0, S>, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.passwd), NdrPtrReadMode::WithArraySupport, ()))]
+    pub passwd: NdrPtr<NdrString<u16, 0, S>, S>,
+    // The size of the security descriptor.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[bw(calc = NdrArrayStructureElement::from(
+        security_descriptor.as_ref().map_or(0, |sd| sd.len() as u32)
+    ))]
+    #[br(temp, args(prev.map(|_| &0)))]
+    reserved: NdrArrayStructureElement<u32, 4, S>,
+    /// The self-relative security descriptor of the share, see [`ShareInfo502::read_security_descriptor`].
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.security_descriptor), NdrPtrReadMode::WithArraySupport, ()))]
+    pub security_descriptor: NdrPtr<NdrConformantArray<u8, S>, S>,
+}
+
+impl<S: NdrSyntax> ShareInfo502<S> {
+    /// Converts the share info into a different syntax.
+    pub fn into_syntax<S2: NdrSyntax>(self) -> ShareInfo502<S2> {
+        ShareInfo502 {
+            netname: self.netname.map(NdrString::into_syntax),
+            share_type: self.share_type.value.into(),
+            remark: self.remark.map(NdrString::into_syntax),
+            permissions: self.permissions.value.into(),
+            max_uses: self.max_uses.value.into(),
+            current_uses: self.current_uses.value.into(),
+            path: self.path.map(NdrString::into_syntax),
+            passwd: self.passwd.map(NdrString::into_syntax),
+            security_descriptor: self
+                .security_descriptor
+                .map(NdrConformantArray::into_syntax),
+        }
+    }
+
+    /// Parses the security descriptor of the share, if present.
+    pub fn read_security_descriptor(&self) -> crate::Result<Option<SecurityDescriptor>> {
+        self.security_descriptor
+            .as_ref()
+            .map(|sd| {
+                SecurityDescriptor::read_le(&mut std::io::Cursor::new(&sd.data))
+                    .map_err(crate::SmbRpcError::FailedToParseRpcResponse)
+            })
+            .transpose()
+    }
+}
+
+/// Share information, at one of the supported levels of the `SHARE_INFO` union (MS-SRVS).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ShareInfo<S: NdrSyntax = Ndr64> {
+    Info1(ShareInfo1<S>),
+    Info2(ShareInfo2<S>),
+    Info502(ShareInfo502<S>),
+}
+
+impl<S: NdrSyntax> ShareInfo<S> {
+    pub fn level(&self) -> ShareInfoLevel {
+        match self {
+            ShareInfo::Info1(_) => ShareInfoLevel::Info1,
+            ShareInfo::Info2(_) => ShareInfoLevel::Info2,
+            ShareInfo::Info502(_) => ShareInfoLevel::Info502,
+        }
+    }
+
+    /// Converts the share info into a different syntax.
+    pub fn into_syntax<S2: NdrSyntax>(self) -> ShareInfo<S2> {
+        match self {
+            ShareInfo::Info1(info) => ShareInfo::Info1(info.into_syntax()),
+            ShareInfo::Info2(info) => ShareInfo::Info2(info.into_syntax()),
+            ShareInfo::Info502(info) => ShareInfo::Info502(info.into_syntax()),
+        }
+    }
+}
+
+/// The wire representation of [`ShareInfo`], a non-encapsulated union of pointers to the info structures.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[br(import(level: ShareInfoLevel))]
+enum ShareInfoUnion<S: NdrSyntax> {
+    #[br(pre_assert(level == ShareInfoLevel::Info1))]
+    Info1(NdrPtr<NdrStructure<ShareInfo1<S>, S>, S>),
+    #[br(pre_assert(level == ShareInfoLevel::Info2))]
+    Info2(NdrPtr<NdrStructure<ShareInfo2<S>, S>, S>),
+    #[br(pre_assert(level == ShareInfoLevel::Info502))]
+    Info502(NdrPtr<NdrStructure<ShareInfo502<S>, S>, S>),
+}
+
+impl<S: NdrSyntax> From<ShareInfo<S>> for ShareInfoUnion<S> {
+    fn from(value: ShareInfo<S>) -> Self {
+        match value {
+            ShareInfo::Info1(info) => ShareInfoUnion::Info1(NdrStructure::from(info).into()),
+            ShareInfo::Info2(info) => ShareInfoUnion::Info2(NdrStructure::from(info).into()),
+            ShareInfo::Info502(info) => ShareInfoUnion::Info502(NdrStructure::from(info).into()),
+        }
+    }
+}
+
+impl<S: NdrSyntax> ShareInfoUnion<S> {
+    fn level(&self) -> ShareInfoLevel {
+        match self {
+            ShareInfoUnion::Info1(_) => ShareInfoLevel::Info1,
+            ShareInfoUnion::Info2(_) => ShareInfoLevel::Info2,
+            ShareInfoUnion::Info502(_) => ShareInfoLevel::Info502,
+        }
+    }
+
+    /// Returns the share info pointed to, if not null.
+    fn into_share_info(self) -> Option<ShareInfo<S>> {
+        match self {
+            ShareInfoUnion::Info1(ptr) => ptr.into_value().map(|x| ShareInfo::Info1(x.value)),
+            ShareInfoUnion::Info2(ptr) => ptr.into_value().map(|x| ShareInfo::Info2(x.value)),
+            ShareInfoUnion::Info502(ptr) => ptr.into_value().map(|x| ShareInfo::Info502(x.value)),
+        }
+    }
+}
+
+/// A [`ShareInfoUnion`] as a parameter, preceded by its discriminant.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct ShareInfoStruct<S: NdrSyntax> {
+    #[bw(calc = share_info.level().into())]
+    level: NdrAlign<ShareInfoLevel, NDR_SIZE_ALIGNMENT, S>,
+    #[br(args(*level))]
+    share_info: ShareInfoUnion<S>,
 }
 
-impl ShareInfo for ShareInfo0 {}
-impl ShareInfo for ShareInfo1 {}
+impl<S: NdrSyntax> InfoEntry for ShareInfo0<S> {}
+impl<S: NdrSyntax> InfoEntry for ShareInfo1<S> {}
+impl<S: NdrSyntax> InfoEntry for ShareInfo2<S> {}
+impl<S: NdrSyntax> InfoEntry for ShareInfo502<S> {}
+impl<S: NdrSyntax> InfoEntry for SessionInfo1<S> {}
+impl<S: NdrSyntax> InfoEntry for FileInfo3<S> {}
+impl<S: NdrSyntax> InfoEntry for ServerInfo101<S> {}
 
 #[derive(BitfieldSpecifier, Debug, Clone, Copy, PartialEq, Eq)]
 #[bits = 2]
@@ -139,32 +370,538 @@ impl ShareType {
     }
 }
 
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[brw(repr(u32))]
+pub enum SessionInfoLevel {
+    Info0 = 0,
+    Info1 = 1,
+    Info2 = 2,
+    Info10 = 10,
+    Info502 = 502,
+}
+
+/// `SESSION_ENUM_STRUCT` (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct SessionEnumStruct<S: NdrSyntax> {
+    #[bw(calc = session_info.level().into())]
+    level: NdrAlign<SessionInfoLevel, NDR_SIZE_ALIGNMENT, S>,
+    #[bw(calc = session_info.level().into())]
+    #[br(temp, assert(*switch_value == *level))]
+    switch_value: NdrAlign<SessionInfoLevel, NDR_SIZE_ALIGNMENT, S>,
+    #[br(args(*level))]
+    session_info: SessionEnumUnion<S>,
+}
+
+/// `SESSION_ENUM_UNION` (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[br(import(level: SessionInfoLevel))]
+enum SessionEnumUnion<S: NdrSyntax> {
+    #[br(pre_assert(level == SessionInfoLevel::Info1))]
+    Info1(NdrPtr<InfoContainer<SessionInfo1<S>, S>, S>),
+}
+
+impl<S: NdrSyntax> SessionEnumUnion<S> {
+    pub fn level(&self) -> SessionInfoLevel {
+        match self {
+            SessionEnumUnion::Info1(_) => SessionInfoLevel::Info1,
+        }
+    }
+}
+
+/// `SESSION_INFO_1` (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+pub struct SessionInfo1<S: NdrSyntax = Ndr64> {
+    /// The computer name, or address, of the client.
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.cname), NdrPtrReadMode::WithArraySupport, ()))]
+    pub cname: NdrPtr<NdrString<u16, 0, S>, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.username), NdrPtrReadMode::WithArraySupport, ()))]
+    pub username: NdrPtr<NdrString<u16, 0, S>, S>,
+    /// The number of files, devices and pipes opened during the session.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.num_opens)))]
+    pub num_opens: NdrArrayStructureElement<u32, 4, S>,
+    /// The number of seconds the session has been active.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.time)))]
+    pub time: NdrArrayStructureElement<u32, 4, S>,
+    /// The number of seconds the session has been idle.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.idle_time)))]
+    pub idle_time: NdrArrayStructureElement<u32, 4, S>,
+    /// `SESS_GUEST` (0x1) and `SESS_NOENCRYPTION` (0x2) flags.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.user_flags)))]
+    pub user_flags: NdrArrayStructureElement<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> SessionInfo1<S> {
+    /// Converts the session info into a different syntax.
+    pub fn into_syntax<S2: NdrSyntax>(self) -> SessionInfo1<S2> {
+        SessionInfo1 {
+            cname: self.cname.map(NdrString::into_syntax),
+            username: self.username.map(NdrString::into_syntax),
+            num_opens: self.num_opens.value.into(),
+            time: self.time.value.into(),
+            idle_time: self.idle_time.value.into(),
+            user_flags: self.user_flags.value.into(),
+        }
+    }
+}
+
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[brw(repr(u32))]
+pub enum FileInfoLevel {
+    Info2 = 2,
+    Info3 = 3,
+}
+
+/// `FILE_ENUM_STRUCT` (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct FileEnumStruct<S: NdrSyntax> {
+    #[bw(calc = file_info.level().into())]
+    level: NdrAlign<FileInfoLevel, NDR_SIZE_ALIGNMENT, S>,
+    #[bw(calc = file_info.level().into())]
+    #[br(temp, assert(*switch_value == *level))]
+    switch_value: NdrAlign<FileInfoLevel, NDR_SIZE_ALIGNMENT, S>,
+    #[br(args(*level))]
+    file_info: FileEnumUnion<S>,
+}
+
+/// `FILE_ENUM_UNION` (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[br(import(level: FileInfoLevel))]
+enum FileEnumUnion<S: NdrSyntax> {
+    #[br(pre_assert(level == FileInfoLevel::Info3))]
+    Info3(NdrPtr<InfoContainer<FileInfo3<S>, S>, S>),
+}
+
+impl<S: NdrSyntax> FileEnumUnion<S> {
+    pub fn level(&self) -> FileInfoLevel {
+        match self {
+            FileEnumUnion::Info3(_) => FileInfoLevel::Info3,
+        }
+    }
+}
+
+/// `FILE_INFO_3` (MS-SRVS), describing an open of a file, device or pipe.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+pub struct FileInfo3<S: NdrSyntax = Ndr64> {
+    /// The identifier of the open, to be used with [`SrvSvc::netr_file_close`].
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.id)))]
+    pub id: NdrArrayStructureElement<u32, 4, S>,
+    /// `PERM_FILE_READ` (0x1), `PERM_FILE_WRITE` (0x2) and `PERM_FILE_CREATE` (0x4) flags.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.permissions)))]
+    pub permissions: NdrArrayStructureElement<u32, 4, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.num_locks)))]
+    pub num_locks: NdrArrayStructureElement<u32, 4, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.pathname), NdrPtrReadMode::WithArraySupport, ()))]
+    pub pathname: NdrPtr<NdrString<u16, 0, S>, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.username), NdrPtrReadMode::WithArraySupport, ()))]
+    pub username: NdrPtr<NdrString<u16, 0, S>, S>,
+}
+
+impl<S: NdrSyntax> FileInfo3<S> {
+    /// Converts the file info into a different syntax.
+    pub fn into_syntax<S2: NdrSyntax>(self) -> FileInfo3<S2> {
+        FileInfo3 {
+            id: self.id.value.into(),
+            permissions: self.permissions.value.into(),
+            num_locks: self.num_locks.value.into(),
+            pathname: self.pathname.map(NdrString::into_syntax),
+            username: self.username.map(NdrString::into_syntax),
+        }
+    }
+}
+
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[brw(repr(u32))]
+pub enum ServerInfoLevel {
+    Info100 = 100,
+    Info101 = 101,
+    Info102 = 102,
+}
+
+/// `SERVER_INFO` (MS-SRVS), as a parameter, preceded by its discriminant.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct ServerInfoStruct<S: NdrSyntax> {
+    #[bw(calc = server_info.level().into())]
+    level: NdrAlign<ServerInfoLevel, NDR_SIZE_ALIGNMENT, S>,
+    #[br(args(*level))]
+    server_info: ServerInfoUnion<S>,
+}
+
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+#[br(import(level: ServerInfoLevel))]
+enum ServerInfoUnion<S: NdrSyntax> {
+    #[br(pre_assert(level == ServerInfoLevel::Info101))]
+    Info101(NdrPtr<NdrStructure<ServerInfo101<S>, S>, S>),
+}
+
+impl<S: NdrSyntax> ServerInfoUnion<S> {
+    pub fn level(&self) -> ServerInfoLevel {
+        match self {
+            ServerInfoUnion::Info101(_) => ServerInfoLevel::Info101,
+        }
+    }
+}
+
+/// `SERVER_INFO_101` (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[bw(import(stage: NdrPtrWriteStage))]
+#[br(import(prev: Option<&Self>))]
+pub struct ServerInfo101<S: NdrSyntax = Ndr64> {
+    /// `PLATFORM_ID_NT` (500) for Windows NT based servers.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.platform_id)))]
+    pub platform_id: NdrArrayStructureElement<u32, 4, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.name), NdrPtrReadMode::WithArraySupport, ()))]
+    pub name: NdrPtr<NdrString<u16, 0, S>, S>,
+    /// The major version of the operating system of the server, e.g. 10 for Windows Server 2016 and later.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.version_major)))]
+    pub version_major: NdrArrayStructureElement<u32, 4, S>,
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.version_minor)))]
+    pub version_minor: NdrArrayStructureElement<u32, 4, S>,
+    /// `SV_TYPE_*` flags, describing the roles of the server.
+    #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+    #[br(args(prev.map(|x| &**x.server_type)))]
+    pub server_type: NdrArrayStructureElement<u32, 4, S>,
+    #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+    #[br(args(prev.map(|x| &x.comment), NdrPtrReadMode::WithArraySupport, ()))]
+    pub comment: NdrPtr<NdrString<u16, 0, S>, S>,
+}
+
+impl<S: NdrSyntax> ServerInfo101<S> {
+    /// Converts the server info into a different syntax.
+    pub fn into_syntax<S2: NdrSyntax>(self) -> ServerInfo101<S2> {
+        ServerInfo101 {
+            platform_id: self.platform_id.value.into(),
+            name: self.name.map(NdrString::into_syntax),
+            version_major: self.version_major.value.into(),
+            version_minor: self.version_minor.value.into(),
+            server_type: self.server_type.value.into(),
+            comment: self.comment.map(NdrString::into_syntax),
+        }
+    }
+}
+
+/// `TIME_OF_DAY_INFO` (MS-SRVS), the time of day on a server.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct TimeOfDayInfo {
+    /// Seconds since 00:00:00, January 1, 1970, UTC.
+    pub elapsedt: u32,
+    /// Milliseconds since the system was started.
+    pub msecs: u32,
+    pub hours: u32,
+    pub mins: u32,
+    pub secs: u32,
+    pub hunds: u32,
+    /// The time zone of the server, in minutes from UTC, or -1 if undefined.
+    pub timezone: i32,
+    /// The interval of the clock ticks, in ten-thousandths of a second.
+    pub tinterval: u32,
+    pub day: u32,
+    pub month: u32,
+    pub year: u32,
+    /// The day of the week, from 0 (Sunday).
+    pub weekday: u32,
+}
+
+impl TimeOfDayInfo {
+    /// Returns the time on the server, in UTC.
+    pub fn date_time(&self) -> time::OffsetDateTime {
+        time::OffsetDateTime::UNIX_EPOCH
+            + time::Duration::seconds(self.elapsedt as i64)
+            + time::Duration::milliseconds(self.hunds as i64 * 10)
+    }
+}
+
+impl NdrAligned for TimeOfDayInfo {}
+
 // FYI: RPC top-level stub data is aligned to min(8, arg_size0, arg_size1, ...) bytes.
 // DCE/RPC Chap. 12.3: RPC PDU Encodings/Alignment.
 
 /// Input arguments for [NetrShareEnum](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-srvs/c4a98e7b-d416-439c-97bd-4d9f52f8ba52>)
 #[binrw::binrw]
 #[derive(Debug, PartialEq, Eq)]
-struct NetrShareEnumIn {
-    server_name: NdrAlign<NdrPtr<NdrString<u16>>, 4>,
-    info_struct: NdrAlign<ShareEnumStruct, 4>,
-    prefered_maximum_length: NdrAlign<u32, 4>,
-    resume_handle: NdrAlign<NdrPtr<u32>, 4>,
+struct NetrShareEnumIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    info_struct: NdrAlign<ShareEnumStruct<S>, 4, S>,
+    prefered_maximum_length: NdrAlign<u32, 4, S>,
+    resume_handle: NdrAlign<NdrPtr<u32, S>, 4, S>,
 }
 
 /// Return value and out params of [NetrShareEnum](<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-srvs/c4a98e7b-d416-439c-97bd-4d9f52f8ba52>)
 #[binrw::binrw]
 #[derive(Debug, PartialEq, Eq)]
-struct NetrShareEnumOut {
-    info_struct: NdrAlign<ShareEnumStruct, 4>,
-    total_entries: NdrAlign<u32, 4>,
-    resume_handle: NdrAlign<NdrPtr<u32>, 4>,
+struct NetrShareEnumOut<S: NdrSyntax = Ndr64> {
+    info_struct: NdrAlign<ShareEnumStruct<S>, 4, S>,
+    total_entries: NdrAlign<u32, 4, S>,
+    resume_handle: NdrAlign<NdrPtr<u32, S>, 4, S>,
 }
 
-impl RpcCall for NetrShareEnumIn {
+impl<S: NdrSyntax> RpcCall for NetrShareEnumIn<S> {
     const OPNUM: u16 = 0xf;
 
-    type ResponseType = NetrShareEnumOut;
+    type ResponseType = NetrShareEnumOut<S>;
+}
+
+/// Input arguments for NetrShareGetInfo (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrShareGetInfoIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    net_name: NdrAlign<NdrString<u16, 0, S>, 4, S>,
+    level: NdrAlign<ShareInfoLevel, 4, S>,
+}
+
+/// Return value and out params of NetrShareGetInfo (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrShareGetInfoOut<S: NdrSyntax = Ndr64> {
+    info_struct: NdrAlign<ShareInfoStruct<S>, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for NetrShareGetInfoIn<S> {
+    const OPNUM: u16 = 0x10;
+
+    type ResponseType = NetrShareGetInfoOut<S>;
+}
+
+/// Input arguments for NetrShareAdd (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrShareAddIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    #[bw(calc = info_struct.share_info.level().into())]
+    #[br(temp)]
+    level: NdrAlign<ShareInfoLevel, 4, S>,
+    info_struct: NdrAlign<ShareInfoStruct<S>, 4, S>,
+    parm_err: NdrAlign<NdrPtr<u32, S>, 4, S>,
+}
+
+/// Return value and out params of NetrShareAdd (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrShareAddOut<S: NdrSyntax = Ndr64> {
+    parm_err: NdrAlign<NdrPtr<u32, S>, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for NetrShareAddIn<S> {
+    const OPNUM: u16 = 0xe;
+
+    type ResponseType = NetrShareAddOut<S>;
+}
+
+/// Input arguments for NetrShareSetInfo (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrShareSetInfoIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    net_name: NdrAlign<NdrString<u16, 0, S>, 4, S>,
+    #[bw(calc = info_struct.share_info.level().into())]
+    #[br(temp)]
+    level: NdrAlign<ShareInfoLevel, 4, S>,
+    info_struct: NdrAlign<ShareInfoStruct<S>, 4, S>,
+    parm_err: NdrAlign<NdrPtr<u32, S>, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for NetrShareSetInfoIn<S> {
+    const OPNUM: u16 = 0x11;
+
+    type ResponseType = NetrShareAddOut<S>;
+}
+
+/// Input arguments for NetrShareDel (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrShareDelIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    net_name: NdrAlign<NdrString<u16, 0, S>, 4, S>,
+    #[bw(calc = 0.into())]
+    #[br(temp)]
+    reserved: NdrAlign<u32, 4, S>,
+}
+
+/// Return value of calls with no out params.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetApiStatusOut<S: NdrSyntax = Ndr64> {
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for NetrShareDelIn<S> {
+    const OPNUM: u16 = 0x12;
+
+    type ResponseType = NetApiStatusOut<S>;
+}
+
+/// Input arguments for NetrSessionEnum (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrSessionEnumIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    client_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    user_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    info_struct: NdrAlign<SessionEnumStruct<S>, 4, S>,
+    prefered_maximum_length: NdrAlign<u32, 4, S>,
+    resume_handle: NdrAlign<NdrPtr<u32, S>, 4, S>,
+}
+
+/// Return value and out params of NetrSessionEnum (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrSessionEnumOut<S: NdrSyntax = Ndr64> {
+    info_struct: NdrAlign<SessionEnumStruct<S>, 4, S>,
+    total_entries: NdrAlign<u32, 4, S>,
+    resume_handle: NdrAlign<NdrPtr<u32, S>, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for NetrSessionEnumIn<S> {
+    const OPNUM: u16 = 0xc;
+
+    type ResponseType = NetrSessionEnumOut<S>;
+}
+
+/// Input arguments for NetrFileEnum (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrFileEnumIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    base_path: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    user_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    info_struct: NdrAlign<FileEnumStruct<S>, 4, S>,
+    prefered_maximum_length: NdrAlign<u32, 4, S>,
+    resume_handle: NdrAlign<NdrPtr<u32, S>, 4, S>,
+}
+
+/// Return value and out params of NetrFileEnum (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrFileEnumOut<S: NdrSyntax = Ndr64> {
+    info_struct: NdrAlign<FileEnumStruct<S>, 4, S>,
+    total_entries: NdrAlign<u32, 4, S>,
+    resume_handle: NdrAlign<NdrPtr<u32, S>, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for NetrFileEnumIn<S> {
+    const OPNUM: u16 = 0x9;
+
+    type ResponseType = NetrFileEnumOut<S>;
+}
+
+/// Input arguments for NetrFileClose (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrFileCloseIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    file_id: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for NetrFileCloseIn<S> {
+    const OPNUM: u16 = 0xb;
+
+    type ResponseType = NetApiStatusOut<S>;
+}
+
+/// Input arguments for NetrServerGetInfo (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrServerGetInfoIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+    level: NdrAlign<ServerInfoLevel, 4, S>,
+}
+
+/// Return value and out params of NetrServerGetInfo (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrServerGetInfoOut<S: NdrSyntax = Ndr64> {
+    info_struct: NdrAlign<ServerInfoStruct<S>, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for NetrServerGetInfoIn<S> {
+    const OPNUM: u16 = 0x15;
+
+    type ResponseType = NetrServerGetInfoOut<S>;
+}
+
+/// Input arguments for NetrRemoteTOD (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrRemoteTodIn<S: NdrSyntax = Ndr64> {
+    server_name: NdrAlign<NdrPtr<NdrString<u16, 0, S>, S>, 4, S>,
+}
+
+/// Return value and out params of NetrRemoteTOD (MS-SRVS)
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+struct NetrRemoteTodOut<S: NdrSyntax = Ndr64> {
+    buffer: NdrAlign<NdrPtr<TimeOfDayInfo, S>, 4, S>,
+    status: NdrAlign<u32, 4, S>,
+}
+
+impl<S: NdrSyntax> RpcCall for NetrRemoteTodIn<S> {
+    const OPNUM: u16 = 0x1c;
+
+    type ResponseType = NetrRemoteTodOut<S>;
+}
+
+/// Fails on a non-zero `NET_API_STATUS`, returned by a call.
+fn check_status(status: u32) -> crate::Result<()> {
+    match status {
+        0 => Ok(()),
+        status => Err(crate::SmbRpcError::CallFailed(status)),
+    }
+}
+
+fn ndr_string<S: NdrSyntax>(value: &str) -> NdrString<u16, 0, S> {
+    value.parse().unwrap()
+}
+
+fn ndr_string_ptr<S: NdrSyntax>(value: Option<&str>) -> NdrPtr<NdrString<u16, 0, S>, S> {
+    value.map(ndr_string).into()
+}
+
+/// Returns the entries of an enumeration container; a null container or buffer has no entries.
+fn container_entries<T: InfoEntry, S: NdrSyntax>(
+    container: NdrPtr<InfoContainer<T, S>, S>,
+) -> Vec<T> {
+    container
+        .into_value()
+        .and_then(|container| container.buffer.into_value())
+        .map(|entries| entries.data.into_iter().map(|x| x.value).collect())
+        .unwrap_or_default()
 }
 
 pub struct SrvSvc<T>
@@ -180,21 +917,38 @@ where
 {
     #[maybe_async]
     pub async fn netr_share_enum(&mut self, server_name: &str) -> crate::Result<Vec<ShareInfo1>> {
-        let input_struct = NetrShareEnumIn {
-            server_name: NdrPtr::from(server_name.parse::<NdrString<u16>>().unwrap()).into(),
+        match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => self.netr_share_enum_with::<Ndr64>(server_name).await,
+            TransferSyntax::Ndr20 => Ok(self
+                .netr_share_enum_with::<Ndr20>(server_name)
+                .await?
+                .into_iter()
+                .map(ShareInfo1::into_syntax)
+                .collect()),
+        }
+    }
+
+    #[maybe_async]
+    async fn netr_share_enum_with<S: NdrSyntax>(
+        &mut self,
+        server_name: &str,
+    ) -> crate::Result<Vec<ShareInfo1<S>>> {
+        let input_struct = NetrShareEnumIn::<S> {
+            server_name: NdrPtr::from(server_name.parse::<NdrString<u16, 0, S>>().unwrap()).into(),
             info_struct: ShareEnumStruct {
-                share_info: ShareEnumUnion::Info1(NdrPtr::from(ShareInfoContainer::<ShareInfo1> {
-                    buffer: NdrPtr::from(None),
-                }))
-                .into(),
+                share_info: ShareEnumUnion::Info1(NdrPtr::from(
+                    InfoContainer::<ShareInfo1<S>, S> {
+                        buffer: NdrPtr::from(None),
+                    },
+                )),
             }
             .into(),
             prefered_maximum_length: u32::MAX.into(),
-            resume_handle: NdrPtr::<u32>::from(None).into(),
+            resume_handle: NdrPtr::<u32, S>::from(None).into(),
         };
         let enum_result = self.bound_pipe.send_receive(input_struct).await?;
-        let mut result: Vec<ShareInfo1> = vec![];
-        if let ShareEnumUnion::Info1(container) = &*enum_result.info_struct.share_info {
+        let mut result: Vec<ShareInfo1<S>> = vec![];
+        if let ShareEnumUnion::Info1(container) = &enum_result.info_struct.share_info {
             match &**container {
                 None => {
                     return Err(crate::SmbRpcError::InvalidResponseData(
@@ -218,6 +972,369 @@ where
         }
         Ok(result)
     }
+
+    /// Returns information about a share (NetrShareGetInfo).
+    ///
+    /// Supported levels are 1, 2 and 502; levels 2 and 502 require administrative privileges.
+    #[maybe_async]
+    pub async fn netr_share_get_info(
+        &mut self,
+        server_name: &str,
+        net_name: &str,
+        level: ShareInfoLevel,
+    ) -> crate::Result<ShareInfo> {
+        if !matches!(
+            level,
+            ShareInfoLevel::Info1 | ShareInfoLevel::Info2 | ShareInfoLevel::Info502
+        ) {
+            return Err(crate::SmbRpcError::InvalidArgument(format!(
+                "Unsupported share info level: {level:?}"
+            )));
+        }
+        match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                self.netr_share_get_info_with::<Ndr64>(server_name, net_name, level)
+                    .await
+            }
+            TransferSyntax::Ndr20 => Ok(self
+                .netr_share_get_info_with::<Ndr20>(server_name, net_name, level)
+                .await?
+                .into_syntax()),
+        }
+    }
+
+    #[maybe_async]
+    async fn netr_share_get_info_with<S: NdrSyntax>(
+        &mut self,
+        server_name: &str,
+        net_name: &str,
+        level: ShareInfoLevel,
+    ) -> crate::Result<ShareInfo<S>> {
+        let result = self
+            .bound_pipe
+            .send_receive(NetrShareGetInfoIn::<S> {
+                server_name: ndr_string_ptr(Some(server_name)).into(),
+                net_name: ndr_string(net_name).into(),
+                level: level.into(),
+            })
+            .await?;
+        check_status(*result.status)?;
+        result.info_struct.value.share_info.into_share_info().ok_or(
+            crate::SmbRpcError::InvalidResponseData("NetrShareGetInfo returned no data"),
+        )
+    }
+
+    /// Creates a share (NetrShareAdd), described at level 2 or 502.
+    #[maybe_async]
+    pub async fn netr_share_add(
+        &mut self,
+        server_name: &str,
+        info: ShareInfo,
+    ) -> crate::Result<()> {
+        if info.level() == ShareInfoLevel::Info1 {
+            return Err(crate::SmbRpcError::InvalidArgument(
+                "Shares are added with level 2 or 502 share info".to_string(),
+            ));
+        }
+        match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => self.netr_share_add_with::<Ndr64>(server_name, info).await,
+            TransferSyntax::Ndr20 => self.netr_share_add_with::<Ndr20>(server_name, info).await,
+        }
+    }
+
+    #[maybe_async]
+    async fn netr_share_add_with<S: NdrSyntax>(
+        &mut self,
+        server_name: &str,
+        info: ShareInfo,
+    ) -> crate::Result<()> {
+        let result = self
+            .bound_pipe
+            .send_receive(NetrShareAddIn::<S> {
+                server_name: ndr_string_ptr(Some(server_name)).into(),
+                info_struct: ShareInfoStruct {
+                    share_info: info.into_syntax().into(),
+                }
+                .into(),
+                parm_err: NdrPtr::<u32, S>::from(None).into(),
+            })
+            .await?;
+        check_status(*result.status)
+    }
+
+    /// Sets the parameters of a share (NetrShareSetInfo), at level 1, 2 or 502.
+    ///
+    /// The name and the type of the share cannot be changed.
+    #[maybe_async]
+    pub async fn netr_share_set_info(
+        &mut self,
+        server_name: &str,
+        net_name: &str,
+        info: ShareInfo,
+    ) -> crate::Result<()> {
+        match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                self.netr_share_set_info_with::<Ndr64>(server_name, net_name, info)
+                    .await
+            }
+            TransferSyntax::Ndr20 => {
+                self.netr_share_set_info_with::<Ndr20>(server_name, net_name, info)
+                    .await
+            }
+        }
+    }
+
+    #[maybe_async]
+    async fn netr_share_set_info_with<S: NdrSyntax>(
+        &mut self,
+        server_name: &str,
+        net_name: &str,
+        info: ShareInfo,
+    ) -> crate::Result<()> {
+        let result = self
+            .bound_pipe
+            .send_receive(NetrShareSetInfoIn::<S> {
+                server_name: ndr_string_ptr(Some(server_name)).into(),
+                net_name: ndr_string(net_name).into(),
+                info_struct: ShareInfoStruct {
+                    share_info: info.into_syntax().into(),
+                }
+                .into(),
+                parm_err: NdrPtr::<u32, S>::from(None).into(),
+            })
+            .await?;
+        check_status(*result.status)
+    }
+
+    /// Deletes a share (NetrShareDel), disconnecting its current connections.
+    #[maybe_async]
+    pub async fn netr_share_del(&mut self, server_name: &str, net_name: &str) -> crate::Result<()> {
+        let status = match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                *self
+                    .bound_pipe
+                    .send_receive(NetrShareDelIn::<Ndr64> {
+                        server_name: ndr_string_ptr(Some(server_name)).into(),
+                        net_name: ndr_string(net_name).into(),
+                    })
+                    .await?
+                    .status
+            }
+            TransferSyntax::Ndr20 => {
+                *self
+                    .bound_pipe
+                    .send_receive(NetrShareDelIn::<Ndr20> {
+                        server_name: ndr_string_ptr(Some(server_name)).into(),
+                        net_name: ndr_string(net_name).into(),
+                    })
+                    .await?
+                    .status
+            }
+        };
+        check_status(status)
+    }
+
+    /// Lists the sessions established on the server (NetrSessionEnum),
+    /// optionally filtered by the name of the client computer, and by the user name.
+    ///
+    /// Requires administrative privileges.
+    #[maybe_async]
+    pub async fn netr_session_enum(
+        &mut self,
+        server_name: &str,
+        client_name: Option<&str>,
+        user_name: Option<&str>,
+    ) -> crate::Result<Vec<SessionInfo1>> {
+        match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                self.netr_session_enum_with::<Ndr64>(server_name, client_name, user_name)
+                    .await
+            }
+            TransferSyntax::Ndr20 => Ok(self
+                .netr_session_enum_with::<Ndr20>(server_name, client_name, user_name)
+                .await?
+                .into_iter()
+                .map(SessionInfo1::into_syntax)
+                .collect()),
+        }
+    }
+
+    #[maybe_async]
+    async fn netr_session_enum_with<S: NdrSyntax>(
+        &mut self,
+        server_name: &str,
+        client_name: Option<&str>,
+        user_name: Option<&str>,
+    ) -> crate::Result<Vec<SessionInfo1<S>>> {
+        let result = self
+            .bound_pipe
+            .send_receive(NetrSessionEnumIn::<S> {
+                server_name: ndr_string_ptr(Some(server_name)).into(),
+                client_name: ndr_string_ptr(client_name).into(),
+                user_name: ndr_string_ptr(user_name).into(),
+                info_struct: SessionEnumStruct {
+                    session_info: SessionEnumUnion::Info1(NdrPtr::from(InfoContainer {
+                        buffer: NdrPtr::from(None),
+                    })),
+                }
+                .into(),
+                prefered_maximum_length: u32::MAX.into(),
+                resume_handle: NdrPtr::<u32, S>::from(None).into(),
+            })
+            .await?;
+        check_status(*result.status)?;
+        let SessionEnumUnion::Info1(container) = result.info_struct.value.session_info;
+        Ok(container_entries(container))
+    }
+
+    /// Lists the open files, devices and pipes on the server (NetrFileEnum),
+    /// optionally filtered by a path prefix, and by the user name.
+    ///
+    /// Requires administrative privileges.
+    #[maybe_async]
+    pub async fn netr_file_enum(
+        &mut self,
+        server_name: &str,
+        base_path: Option<&str>,
+        user_name: Option<&str>,
+    ) -> crate::Result<Vec<FileInfo3>> {
+        match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                self.netr_file_enum_with::<Ndr64>(server_name, base_path, user_name)
+                    .await
+            }
+            TransferSyntax::Ndr20 => Ok(self
+                .netr_file_enum_with::<Ndr20>(server_name, base_path, user_name)
+                .await?
+                .into_iter()
+                .map(FileInfo3::into_syntax)
+                .collect()),
+        }
+    }
+
+    #[maybe_async]
+    async fn netr_file_enum_with<S: NdrSyntax>(
+        &mut self,
+        server_name: &str,
+        base_path: Option<&str>,
+        user_name: Option<&str>,
+    ) -> crate::Result<Vec<FileInfo3<S>>> {
+        let result = self
+            .bound_pipe
+            .send_receive(NetrFileEnumIn::<S> {
+                server_name: ndr_string_ptr(Some(server_name)).into(),
+                base_path: ndr_string_ptr(base_path).into(),
+                user_name: ndr_string_ptr(user_name).into(),
+                info_struct: FileEnumStruct {
+                    file_info: FileEnumUnion::Info3(NdrPtr::from(InfoContainer {
+                        buffer: NdrPtr::from(None),
+                    })),
+                }
+                .into(),
+                prefered_maximum_length: u32::MAX.into(),
+                resume_handle: NdrPtr::<u32, S>::from(None).into(),
+            })
+            .await?;
+        check_status(*result.status)?;
+        let FileEnumUnion::Info3(container) = result.info_struct.value.file_info;
+        Ok(container_entries(container))
+    }
+
+    /// Forces an open file, device or pipe to close (NetrFileClose), releasing its locks.
+    ///
+    /// `file_id` is the [`FileInfo3::id`] of the open, as returned by [`SrvSvc::netr_file_enum`].
+    #[maybe_async]
+    pub async fn netr_file_close(&mut self, server_name: &str, file_id: u32) -> crate::Result<()> {
+        let status = match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                *self
+                    .bound_pipe
+                    .send_receive(NetrFileCloseIn::<Ndr64> {
+                        server_name: ndr_string_ptr(Some(server_name)).into(),
+                        file_id: file_id.into(),
+                    })
+                    .await?
+                    .status
+            }
+            TransferSyntax::Ndr20 => {
+                *self
+                    .bound_pipe
+                    .send_receive(NetrFileCloseIn::<Ndr20> {
+                        server_name: ndr_string_ptr(Some(server_name)).into(),
+                        file_id: file_id.into(),
+                    })
+                    .await?
+                    .status
+            }
+        };
+        check_status(status)
+    }
+
+    /// Returns the name, operating system version and type of the server (NetrServerGetInfo).
+    #[maybe_async]
+    pub async fn netr_server_get_info(
+        &mut self,
+        server_name: &str,
+    ) -> crate::Result<ServerInfo101> {
+        match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => self.netr_server_get_info_with::<Ndr64>(server_name).await,
+            TransferSyntax::Ndr20 => Ok(self
+                .netr_server_get_info_with::<Ndr20>(server_name)
+                .await?
+                .into_syntax()),
+        }
+    }
+
+    #[maybe_async]
+    async fn netr_server_get_info_with<S: NdrSyntax>(
+        &mut self,
+        server_name: &str,
+    ) -> crate::Result<ServerInfo101<S>> {
+        let result = self
+            .bound_pipe
+            .send_receive(NetrServerGetInfoIn::<S> {
+                server_name: ndr_string_ptr(Some(server_name)).into(),
+                level: ServerInfoLevel::Info101.into(),
+            })
+            .await?;
+        check_status(*result.status)?;
+        let ServerInfoUnion::Info101(info) = result.info_struct.value.server_info;
+        info.into_value()
+            .map(|info| info.value)
+            .ok_or(crate::SmbRpcError::InvalidResponseData(
+                "NetrServerGetInfo returned no data",
+            ))
+    }
+
+    /// Returns the time of day on the server (NetrRemoteTOD).
+    #[maybe_async]
+    pub async fn netr_remote_tod(&mut self, server_name: &str) -> crate::Result<TimeOfDayInfo> {
+        let result = match self.bound_pipe.transfer_syntax() {
+            TransferSyntax::Ndr64 => {
+                let result = self
+                    .bound_pipe
+                    .send_receive(NetrRemoteTodIn::<Ndr64> {
+                        server_name: ndr_string_ptr(Some(server_name)).into(),
+                    })
+                    .await?;
+                (*result.status, result.buffer.value.into_value())
+            }
+            TransferSyntax::Ndr20 => {
+                let result = self
+                    .bound_pipe
+                    .send_receive(NetrRemoteTodIn::<Ndr20> {
+                        server_name: ndr_string_ptr(Some(server_name)).into(),
+                    })
+                    .await?;
+                (*result.status, result.buffer.value.into_value())
+            }
+        };
+        let (status, time_of_day) = result;
+        check_status(status)?;
+        time_of_day.ok_or(crate::SmbRpcError::InvalidResponseData(
+            "NetrRemoteTOD returned no data",
+        ))
+    }
 }
 
 impl<T> super::base::RpcInterface<T> for SrvSvc<T>
@@ -232,6 +1349,10 @@ where
     fn new(bound_pipe: T) -> Self {
         SrvSvc { bound_pipe }
     }
+
+    fn into_inner(self) -> T {
+        self.bound_pipe
+    }
 }
 
 #[cfg(test)]
@@ -244,7 +1365,7 @@ mod test {
         struct NetrShareEnumOut {
                 info_struct: ShareEnumStruct {
                     share_info: ShareEnumUnion::Info1(
-                        ShareInfoContainer::<ShareInfo1> {
+                        InfoContainer::<ShareInfo1> {
                             buffer: Into::<NdrArray<ShareInfo1>>::into(vec![
                                 ShareInfo1 {
                                     netname: "ADMIN$".parse::<NdrString<u16>>().unwrap().into(),
@@ -296,7 +1417,6 @@ mod test {
                         }
                         .into()
                     )
-                    .into()
                 }
                 .into(),
                 total_entries: 6.into(),
@@ -309,14 +1429,146 @@ mod test {
             server_name: Into::<NdrPtr<_>>::into(r"\\localhost".parse::<NdrString<u16>>().unwrap())
                 .into(),
             info_struct: ShareEnumStruct {
-                share_info: ShareEnumUnion::Info1(NdrPtr::from(ShareInfoContainer::<ShareInfo1> {
+                share_info: ShareEnumUnion::Info1(NdrPtr::from(InfoContainer::<ShareInfo1> {
                     buffer: NdrPtr::from(None),
-                }))
-                .into(),
+                })),
             }
             .into(),
             prefered_maximum_length: 4294967295.into(),
             resume_handle: NdrPtr::<u32>::from(None).into(),
         } => "00000200000000000c0000000000000000000000000000000c000000000000005c005c006c006f00630061006c0068006f0073007400000001000000000000000100000000000000000002000000000000000000000000000000000000000000ffffffff000000000000000000000000"
     }
+
+    smb_tests::test_binrw_write! {
+        struct NetrShareEnumIn => ndr20 {
+            server_name: Into::<NdrPtr<_, Ndr20>>::into(
+                r"\\localhost".parse::<NdrString<u16, 0, Ndr20>>().unwrap()
+            )
+            .into(),
+            info_struct: ShareEnumStruct {
+                share_info: ShareEnumUnion::Info1(NdrPtr::from(InfoContainer::<
+                    ShareInfo1<Ndr20>,
+                    Ndr20,
+                > {
+                    buffer: NdrPtr::from(None),
+                })),
+            }
+            .into(),
+            prefered_maximum_length: 4294967295.into(),
+            resume_handle: NdrPtr::<u32, Ndr20>::from(None).into(),
+        } => "000002000c000000000000000c0000005c005c006c006f00630061006c0068006f007300740000000100000001000000000002000000000000000000ffffffff00000000"
+    }
+
+    type NetrShareEnumOutNdr20 = NetrShareEnumOut<Ndr20>;
+
+    test_binrw! {
+        NetrShareEnumOutNdr20: NetrShareEnumOut {
+            info_struct: ShareEnumStruct {
+                share_info: ShareEnumUnion::Info1(
+                    InfoContainer::<ShareInfo1<Ndr20>, Ndr20> {
+                        buffer: Into::<NdrArray<ShareInfo1<Ndr20>, Ndr20>>::into(vec![ShareInfo1 {
+                            netname: "A".parse::<NdrString<u16, 0, Ndr20>>().unwrap().into(),
+                            share_type: ShareType::new().into(),
+                            remark: "".parse::<NdrString<u16, 0, Ndr20>>().unwrap().into(),
+                        }])
+                        .into(),
+                    }
+                    .into(),
+                ),
+            }
+            .into(),
+            total_entries: 1.into(),
+            resume_handle: NdrPtr::<u32, Ndr20>::from(None).into(),
+        } => "01000000010000000000020001000000000002000100000000000200000000000000020002000000000000000200000041000000010000000000000001000000000000000100000000000000"
+    }
+
+    type NetrShareGetInfoOutNdr20 = NetrShareGetInfoOut<Ndr20>;
+
+    test_binrw! {
+        NetrShareGetInfoOutNdr20: NetrShareGetInfoOut {
+            info_struct: ShareInfoStruct {
+                share_info: ShareInfo::Info2(ShareInfo2 {
+                    netname: "A".parse::<NdrString<u16, 0, Ndr20>>().unwrap().into(),
+                    share_type: ShareType::new().into(),
+                    remark: "".parse::<NdrString<u16, 0, Ndr20>>().unwrap().into(),
+                    permissions: 0.into(),
+                    max_uses: ShareInfo2::<Ndr20>::UNLIMITED_USES.into(),
+                    current_uses: 1.into(),
+                    path: r"C:\A".parse::<NdrString<u16, 0, Ndr20>>().unwrap().into(),
+                    passwd: NdrPtr::from(None),
+                })
+                .into(),
+            }
+            .into(),
+            status: 0.into(),
+        } => "020000000000020000000200000000000000020000000000ffffffff010000000000020000000000020000000000000002000000410000000100000000000000010000000000000005000000000000000500000043003a005c0041000000000000000000"
+    }
+
+    test_binrw! {
+        struct NetrShareGetInfoOut {
+            info_struct: ShareInfoStruct {
+                share_info: ShareInfo::Info502(ShareInfo502 {
+                    netname: "A".parse::<NdrString<u16>>().unwrap().into(),
+                    share_type: ShareType::new().into(),
+                    remark: "".parse::<NdrString<u16>>().unwrap().into(),
+                    permissions: 0.into(),
+                    max_uses: ShareInfo2::<Ndr64>::UNLIMITED_USES.into(),
+                    current_uses: 0.into(),
+                    path: r"C:\A".parse::<NdrString<u16>>().unwrap().into(),
+                    passwd: NdrPtr::from(None),
+                    security_descriptor: NdrConformantArray::from(vec![1u8, 0, 4, 0x80]).into(),
+                })
+                .into(),
+            }
+            .into(),
+            status: 0.into(),
+        } => "f601000000000000000002000000000000000200000000000000000000000000000002000000000000000000ffffffff000000000000000000000200000000000000000000000000040000000000000000000200000000000200000000000000000000000000000002000000000000004100000000000000010000000000000000000000000000000100000000000000000000000000000005000000000000000000000000000000050000000000000043003a005c004100000000000000000004000000000000000100048000000000"
+    }
+
+    type NetrFileEnumOutNdr20 = NetrFileEnumOut<Ndr20>;
+
+    test_binrw! {
+        NetrFileEnumOutNdr20: NetrFileEnumOut {
+            info_struct: FileEnumStruct {
+                file_info: FileEnumUnion::Info3(NdrPtr::from(
+                    InfoContainer::<FileInfo3<Ndr20>, Ndr20> {
+                        buffer: NdrPtr::from(NdrArray::from(vec![FileInfo3 {
+                            id: 0x11.into(),
+                            permissions: 1.into(),
+                            num_locks: 2.into(),
+                            pathname: r"C:\A".parse::<NdrString<u16, 0, Ndr20>>().unwrap().into(),
+                            username: "U".parse::<NdrString<u16, 0, Ndr20>>().unwrap().into(),
+                        }])),
+                    },
+                )),
+            }
+            .into(),
+            total_entries: 1.into(),
+            resume_handle: NdrPtr::<u32, Ndr20>::from(None).into(),
+            status: 0.into(),
+        } => "030000000300000000000200010000000000020001000000110000000100000002000000000002000000020005000000000000000500000043003a005c0041000000000002000000000000000200000055000000010000000000000000000000"
+    }
+
+    type NetrRemoteTodOutNdr20 = NetrRemoteTodOut<Ndr20>;
+
+    test_binrw! {
+        NetrRemoteTodOutNdr20: NetrRemoteTodOut {
+            buffer: NdrPtr::<_, Ndr20>::from(TimeOfDayInfo {
+                hours: 12,
+                day: 17,
+                month: 5,
+                year: 2024,
+                ..Default::default()
+            })
+            .into(),
+            status: 0.into(),
+        } => "0000020000000000000000000c00000000000000000000000000000000000000000000001100000005000000e80700000000000000000000"
+    }
+
+    smb_tests::test_binrw_write! {
+        struct NetrShareDelIn => ndr20 {
+            server_name: ndr_string_ptr::<Ndr20>(Some("S")).into(),
+            net_name: ndr_string::<Ndr20>("A").into(),
+        } => "00000200020000000000000002000000530000000200000000000000020000004100000000000000"
+    }
 }
@@ -4,8 +4,10 @@
 //! from IDLs.
 
 pub mod interface;
+pub mod ndr20;
 pub mod ndr64;
 pub mod pdu;
+pub mod syntax;
 
 #[derive(thiserror::Error, Debug)]
 pub enum SmbRpcError {
@@ -17,6 +19,17 @@ pub enum SmbRpcError {
 
     #[error("Failed to parse response data: {0}")]
     FailedToParseRpcResponse(binrw::Error),
+
+    /// The server failed the call with a fault PDU, see [`pdu::DcRpcCoPktFault`].
+    #[error("RPC call failed with fault status {0:#x}")]
+    Fault(u32),
+
+    /// The call returned a failure status, such as a Win32 error code.
+    #[error("RPC call failed with status {0:#x}")]
+    CallFailed(u32),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
 }
 
 type Result<T> = std::result::Result<T, SmbRpcError>;
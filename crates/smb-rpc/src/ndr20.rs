@@ -0,0 +1,83 @@
+//! Data structures for NDR 2.0.
+//!
+//! These are the [NDR64](crate::ndr64) data structures, bound to the [`Ndr20`] syntax:
+//! pointer referent IDs, counts and offsets are 32-bit, and aligned to 4 bytes.
+use crate::ndr64;
+
+pub mod consts;
+pub use consts::*;
+
+pub use ndr64::{
+    NULL_PTR_REF_ID, NdrAligned, NdrPtrReadMode, NdrPtrWriteArgs, NdrPtrWriteStage,
+    REF_ID_UNIQUE_DEFAULT,
+};
+
+/// See [`ndr64::NdrAlign`].
+pub type NdrAlign<T, const TO: usize = NDR20_ALIGNMENT> = ndr64::NdrAlign<T, TO, Ndr20>;
+/// See [`ndr64::NdrPtr`].
+pub type NdrPtr<T> = ndr64::NdrPtr<T, Ndr20>;
+/// See [`ndr64::NdrString`].
+pub type NdrString<E, const SIZE: u32 = 0> = ndr64::NdrString<E, SIZE, Ndr20>;
+/// See [`ndr64::NdrArray`].
+pub type NdrArray<E> = ndr64::NdrArray<E, Ndr20>;
+/// See [`ndr64::NdrConformantArray`].
+pub type NdrConformantArray<E> = ndr64::NdrConformantArray<E, Ndr20>;
+/// See [`ndr64::NdrArrayStructureElement`].
+pub type NdrArrayStructureElement<T, const TO: usize = NDR20_ALIGNMENT> =
+    ndr64::NdrArrayStructureElement<T, TO, Ndr20>;
+/// See [`ndr64::NdrStructure`].
+pub type NdrStructure<E> = ndr64::NdrStructure<E, Ndr20>;
+/// See [`ndr64::NdrEnum16`].
+pub type NdrEnum16<T> = ndr64::NdrEnum16<T, Ndr20>;
+
+#[cfg(test)]
+mod tests {
+    use binrw::prelude::*;
+    use smb_tests::*;
+
+    use super::*;
+
+    #[binrw::binrw]
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestNdrStringPtr {
+        string: NdrPtr<NdrString<u16>>,
+    }
+
+    test_binrw! {
+        struct TestNdrStringPtr {
+            string: r"\\localhostt".parse::<NdrString<u16>>().unwrap().into(),
+        } => "000002000d000000000000000d0000005c005c006c006f00630061006c0068006f007300740074000000"
+    }
+
+    #[binrw::binrw]
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestNdrU32Ptr {
+        unalign: u8,
+        ptr: NdrPtr<u32>,
+        other: u32,
+    }
+
+    test_binrw! {
+        struct TestNdrU32Ptr {
+            unalign: 0x1,
+            ptr: Some(0xdeadbeef).into(),
+            other: 0x12345678,
+        } => "01000000 00000200 efbeadde 78563412" // unaligned byte; padding; reference ID; value; other
+    }
+
+    #[binrw::binrw]
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestNdrHyperPtr {
+        unalign: u8,
+        ptr: NdrPtr<NdrAlign<u64, 8>>,
+        hyper: NdrAlign<u64, 8>,
+    }
+
+    test_binrw! {
+        struct TestNdrHyperPtr {
+            unalign: 0x1,
+            ptr: Some(0x1122334455667788.into()).into(),
+            hyper: 0xdeadbeef.into(),
+        } => "01000000 00000200 8877665544332211 efbeadde00000000" // unaligned byte; padding; reference ID; hypers (aligned to 8)
+    }
+}
@@ -0,0 +1,22 @@
+use smb_dtyp::make_guid;
+
+use crate::{
+    pdu::DceRpcSyntaxId,
+    syntax::{NdrSyntax, TransferSyntax},
+};
+
+pub const NDR20_SYNTAX_ID: DceRpcSyntaxId = DceRpcSyntaxId {
+    uuid: make_guid!("8a885d04-1ceb-11c9-9fe8-08002b104860"),
+    version: 2,
+};
+
+pub const NDR20_ALIGNMENT: usize = 4;
+
+/// The NDR 2.0 transfer syntax, for NDR data structures.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Ndr20;
+
+impl NdrSyntax for Ndr20 {
+    const TRANSFER_SYNTAX: TransferSyntax = TransferSyntax::Ndr20;
+    type Size = u32;
+}
@@ -9,6 +9,10 @@ pub mod string;
 pub use string::*;
 pub mod ptr;
 pub use ptr::*;
+pub mod structure;
+pub use structure::*;
+pub mod enums;
+pub use enums::*;
 pub mod consts;
 pub use consts::*;
 
@@ -1,8 +1,21 @@
 use binrw::prelude::*;
-use std::ops::{Deref, DerefMut};
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use super::Ndr64;
+use crate::syntax::NdrSyntax;
 
 pub const NDR64_ALIGNMENT: usize = 8;
 
+/// When used as an alignment parameter (`TO`), stands for the alignment of size values
+/// (pointer referent IDs, counts and offsets) and of the structures and unions containing them,
+/// which depends on the syntax: 8 bytes under NDR64, and 4 bytes under NDR 2.0.
+///
+/// See [`NdrSyntax::alignment`].
+pub const NDR_SIZE_ALIGNMENT: usize = 0;
+
 /// Asserts that the writer is aligned to NDR64 alignment.
 pub fn debug_assert_aligned<W: std::io::Seek>(stream: &mut W) -> binrw::BinResult<()> {
     let pos = stream.stream_position()?;
@@ -13,26 +26,77 @@ pub fn debug_assert_aligned<W: std::io::Seek>(stream: &mut W) -> binrw::BinResul
     Ok(())
 }
 
+/// Advances the reader to the next multiple of `to`, skipping the padding.
+pub fn align_reader<R: std::io::Seek>(reader: &mut R, to: usize) -> binrw::BinResult<()> {
+    let pos = reader.stream_position()?;
+    let padding = (to as u64 - pos % to as u64) % to as u64;
+    reader.seek(std::io::SeekFrom::Current(padding as i64))?;
+    Ok(())
+}
+
+/// Writes zero padding up to the next multiple of `to`.
+pub fn align_writer<W: std::io::Write + std::io::Seek>(
+    writer: &mut W,
+    to: usize,
+) -> binrw::BinResult<()> {
+    let pos = writer.stream_position()?;
+    let padding = (to as u64 - pos % to as u64) % to as u64;
+    writer.write_all(&vec![0; padding as usize])?;
+    Ok(())
+}
+
 /// A trait for types that are aligned according to NDR64 rules.
 pub trait NdrAligned {}
 /// Writes the inner value, and aligns the writer to
 /// the NDR alignment BEFORE writing the value.
 ///
+/// `TO` is the alignment of the value, or [`NDR_SIZE_ALIGNMENT`] for values aligned like size values,
+/// whose alignment depends on the syntax `S` (see [`NdrSyntax::alignment`]).
+///
 /// *Note:* NDR-encoded data can be of an unaligned length!
-#[binrw::binrw]
 #[derive(Debug, PartialEq, Eq)]
-#[br(import_raw(args: <T as BinRead>::Args<'_>))]
-#[bw(import_raw(args: <T as BinWrite>::Args<'_>))]
-pub struct NdrAlign<T, const TO: usize = NDR64_ALIGNMENT>
+pub struct NdrAlign<T, const TO: usize = NDR_SIZE_ALIGNMENT, S: NdrSyntax = Ndr64>
 where
     T: BinRead + BinWrite,
 {
-    #[brw(align_before = TO)]
-    #[brw(args_raw(args))]
     pub value: T,
+    _syntax: PhantomData<S>,
+}
+
+impl<T, const TO: usize, S: NdrSyntax> BinRead for NdrAlign<T, TO, S>
+where
+    T: BinRead + BinWrite,
+{
+    type Args<'a> = <T as BinRead>::Args<'a>;
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: binrw::Endian,
+        args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self> {
+        align_reader(reader, S::alignment(TO))?;
+        Ok(T::read_options(reader, endian, args)?.into())
+    }
+}
+
+impl<T, const TO: usize, S: NdrSyntax> BinWrite for NdrAlign<T, TO, S>
+where
+    T: BinRead + BinWrite,
+{
+    type Args<'a> = <T as BinWrite>::Args<'a>;
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: binrw::Endian,
+        args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        align_writer(writer, S::alignment(TO))?;
+        self.value.write_options(writer, endian, args)
+    }
 }
 
-impl<T, const TO: usize> Deref for NdrAlign<T, TO>
+impl<T, const TO: usize, S: NdrSyntax> Deref for NdrAlign<T, TO, S>
 where
     T: BinRead + BinWrite,
 {
@@ -43,7 +107,7 @@ where
     }
 }
 
-impl<T, const TO: usize> DerefMut for NdrAlign<T, TO>
+impl<T, const TO: usize, S: NdrSyntax> DerefMut for NdrAlign<T, TO, S>
 where
     T: BinRead + BinWrite,
 {
@@ -52,18 +116,21 @@ where
     }
 }
 
-impl<T, const TO: usize> NdrAligned for NdrAlign<T, TO> where T: BinRead + BinWrite {}
+impl<T, const TO: usize, S: NdrSyntax> NdrAligned for NdrAlign<T, TO, S> where T: BinRead + BinWrite {}
 
-impl<T, const TO: usize> From<T> for NdrAlign<T, TO>
+impl<T, const TO: usize, S: NdrSyntax> From<T> for NdrAlign<T, TO, S>
 where
     T: BinRead + BinWrite,
 {
     fn from(value: T) -> Self {
-        Self { value }
+        Self {
+            value,
+            _syntax: PhantomData,
+        }
     }
 }
 
-impl<T, const TO: usize> Default for NdrAlign<T, TO>
+impl<T, const TO: usize, S: NdrSyntax> Default for NdrAlign<T, TO, S>
 where
     T: BinRead + BinWrite + Default,
 {
@@ -72,14 +139,12 @@ where
     }
 }
 
-impl<T, const TO: usize> Clone for NdrAlign<T, TO>
+impl<T, const TO: usize, S: NdrSyntax> Clone for NdrAlign<T, TO, S>
 where
     T: BinRead + BinWrite + Clone,
 {
     fn clone(&self) -> Self {
-        Self {
-            value: self.value.clone(),
-        }
+        self.value.clone().into()
     }
 }
 
@@ -103,9 +168,7 @@ mod tests {
         struct TestNdrAlign {
             unalign: 0,
             unalign2: 0,
-            should_align: NdrAlign {
-                value: 0x12345678,
-            },
+            should_align: 0x12345678.into(),
         } => "
                 00
                 00 00
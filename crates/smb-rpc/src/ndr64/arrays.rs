@@ -1,23 +1,26 @@
+use std::marker::PhantomData;
 use std::ops::Deref;
 use std::ops::DerefMut;
 
+use super::Ndr64;
 use super::align::*;
 use super::ptr::*;
+use crate::syntax::NdrSyntax;
 use binrw::prelude::*;
 
 /// Array NDR structure.
 ///
 /// Each item in the array is assured to be aligned properly in the NDR buffer.
 #[derive(Debug, PartialEq, Eq)]
-pub struct NdrArray<E>
+pub struct NdrArray<E, S: NdrSyntax = Ndr64>
 where
     for<'a> E:
         BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
 {
-    pub data: Vec<NdrAlign<E>>,
+    pub data: Vec<NdrAlign<E, NDR_SIZE_ALIGNMENT, S>>,
 }
 
-impl<E> BinRead for NdrArray<E>
+impl<E, S: NdrSyntax> BinRead for NdrArray<E, S>
 where
     for<'a> E:
         BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
@@ -30,7 +33,7 @@ where
         args: Self::Args<'_>,
     ) -> binrw::BinResult<Self> {
         // Begin by reading the count of elements in the array.
-        let max_count = *NdrAlign::<u64>::read_options(reader, endian, ())?;
+        let max_count = S::read_size(reader, endian)?;
         // First read: direct data (ptr refs & actual data)
         let count = args.0;
         // TODO: Test if that's real, and generally --should we just use `max_count`?
@@ -44,19 +47,27 @@ where
         }
         let mut data = Vec::with_capacity(count as usize);
         for _ in 0..count {
-            data.push(NdrAlign::<E>::read_options(reader, endian, (None,))?);
+            data.push(NdrAlign::<E, NDR_SIZE_ALIGNMENT, S>::read_options(
+                reader,
+                endian,
+                (None,),
+            )?);
         }
         // Second read: ptr values
         let mut resolved = Vec::with_capacity(count as usize);
         for refs_only in &data {
-            let ptr_value = NdrAlign::<E>::read_options(reader, endian, (Some(refs_only),))?;
+            let ptr_value = NdrAlign::<E, NDR_SIZE_ALIGNMENT, S>::read_options(
+                reader,
+                endian,
+                (Some(refs_only),),
+            )?;
             resolved.push(ptr_value);
         }
         Ok(Self { data: resolved })
     }
 }
 
-impl<E> BinWrite for NdrArray<E>
+impl<E, S: NdrSyntax> BinWrite for NdrArray<E, S>
 where
     for<'a> E:
         BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
@@ -71,7 +82,7 @@ where
     ) -> binrw::BinResult<()> {
         // Max count:
         let max_count = self.data.len() as u64;
-        Ndr64Align::from(max_count).write_options(writer, endian, ())?;
+        S::write_size(max_count, writer, endian)?;
         // First write: direct data (ptr refs)
         for item in &self.data {
             item.write_options(writer, endian, (NdrPtrWriteStage::ArraySupportWriteRefId,))?;
@@ -84,37 +95,37 @@ where
     }
 }
 
-impl<E> NdrAligned for NdrArray<E> where
+impl<E, S: NdrSyntax> NdrAligned for NdrArray<E, S> where
     for<'a> E:
         BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static
 {
 }
 
-impl<E> From<Vec<E>> for NdrArray<E>
+impl<E, S: NdrSyntax> From<Vec<E>> for NdrArray<E, S>
 where
     for<'a> E:
         BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
 {
     fn from(val: Vec<E>) -> Self {
-        NdrArray {
+        Self {
             data: val.into_iter().map(NdrAlign::from).collect(),
         }
     }
 }
 
-impl<E> Deref for NdrArray<E>
+impl<E, S: NdrSyntax> Deref for NdrArray<E, S>
 where
     for<'a> E:
         BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
 {
-    type Target = [NdrAlign<E>];
+    type Target = [NdrAlign<E, NDR_SIZE_ALIGNMENT, S>];
 
     fn deref(&self) -> &Self::Target {
         &self.data
     }
 }
 
-impl<E> DerefMut for NdrArray<E>
+impl<E, S: NdrSyntax> DerefMut for NdrArray<E, S>
 where
     for<'a> E:
         BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
@@ -124,19 +135,103 @@ where
     }
 }
 
+/// A conformant array of scalar elements, preceded by its count,
+/// such as the referent of a `[size_is(n)] unsigned char*` pointer.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct NdrConformantArray<E, S: NdrSyntax = Ndr64>
+where
+    for<'a> E: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()> + 'static,
+{
+    pub data: Vec<E>,
+    _syntax: PhantomData<S>,
+}
+
+impl<E, S: NdrSyntax> NdrConformantArray<E, S>
+where
+    for<'a> E: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()> + 'static,
+{
+    /// Converts the array into a different syntax.
+    pub fn into_syntax<S2: NdrSyntax>(self) -> NdrConformantArray<E, S2> {
+        self.data.into()
+    }
+}
+
+impl<E, S: NdrSyntax> BinRead for NdrConformantArray<E, S>
+where
+    for<'a> E: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()> + 'static,
+{
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: binrw::endian::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self> {
+        let max_count = S::read_size(reader, endian)?;
+        let data = binrw::helpers::count(max_count as usize)(reader, endian, ())?;
+        Ok(Self {
+            data,
+            _syntax: PhantomData,
+        })
+    }
+}
+
+impl<E, S: NdrSyntax> BinWrite for NdrConformantArray<E, S>
+where
+    for<'a> E: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()> + 'static,
+{
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: binrw::endian::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        S::write_size(self.data.len() as u64, writer, endian)?;
+        self.data.write_options(writer, endian, ())
+    }
+}
+
+impl<E, S: NdrSyntax> From<Vec<E>> for NdrConformantArray<E, S>
+where
+    for<'a> E: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()> + 'static,
+{
+    fn from(data: Vec<E>) -> Self {
+        Self {
+            data,
+            _syntax: PhantomData,
+        }
+    }
+}
+
+impl<E, S: NdrSyntax> Deref for NdrConformantArray<E, S>
+where
+    for<'a> E: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()> + 'static,
+{
+    type Target = [E];
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
 /// A helper for wrapping in-structure NDR elements, that may be used
 /// for arrays of structures.
 ///
+/// The element is aligned to `TO`, like [`NdrAlign`];
+/// use the natural alignment of `T` for consecutive scalars, e.g. 4 for `u32`.
+///
 /// See example usage in the tests below.
 #[derive(Debug, PartialEq, Eq)]
-pub struct NdrArrayStructureElement<T>
+pub struct NdrArrayStructureElement<T, const TO: usize = NDR_SIZE_ALIGNMENT, S: NdrSyntax = Ndr64>
 where
     T: BinRead + BinWrite + 'static,
 {
-    val: NdrAlign<T>,
+    val: NdrAlign<T, TO, S>,
 }
 
-impl<T> BinRead for NdrArrayStructureElement<T>
+impl<T, const TO: usize, S: NdrSyntax> BinRead for NdrArrayStructureElement<T, TO, S>
 where
     T: BinRead<Args<'static> = ()> + BinWrite + Clone + 'static,
 {
@@ -152,14 +247,14 @@ where
                 val: (*prev).clone().into(),
             }),
             None => {
-                let val = NdrAlign::<T>::read_options(reader, endian, ())?;
+                let val = NdrAlign::<T, TO, S>::read_options(reader, endian, ())?;
                 Ok(Self { val })
             }
         }
     }
 }
 
-impl<T> BinWrite for NdrArrayStructureElement<T>
+impl<T, const TO: usize, S: NdrSyntax> BinWrite for NdrArrayStructureElement<T, TO, S>
 where
     for<'a> T: BinWrite<Args<'a> = ()> + BinRead + Clone + 'static,
 {
@@ -175,30 +270,33 @@ where
     }
 }
 
-impl<T> From<T> for NdrArrayStructureElement<T>
+impl<T, const TO: usize, S: NdrSyntax> From<T> for NdrArrayStructureElement<T, TO, S>
 where
     T: BinRead + BinWrite + Clone + 'static,
 {
     fn from(value: T) -> Self {
         Self {
-            val: NdrAlign::from(value),
+            val: From::from(value),
         }
     }
 }
-impl<T> NdrAligned for NdrArrayStructureElement<T> where T: BinRead + BinWrite + Clone + 'static {}
+impl<T, const TO: usize, S: NdrSyntax> NdrAligned for NdrArrayStructureElement<T, TO, S> where
+    T: BinRead + BinWrite + Clone + 'static
+{
+}
 
-impl<T> Deref for NdrArrayStructureElement<T>
+impl<T, const TO: usize, S: NdrSyntax> Deref for NdrArrayStructureElement<T, TO, S>
 where
     T: BinRead + BinWrite + Clone + 'static,
 {
-    type Target = NdrAlign<T>;
+    type Target = NdrAlign<T, TO, S>;
 
     fn deref(&self) -> &Self::Target {
         &self.val
     }
 }
 
-impl<T> DerefMut for NdrArrayStructureElement<T>
+impl<T, const TO: usize, S: NdrSyntax> DerefMut for NdrArrayStructureElement<T, TO, S>
 where
     T: BinRead + BinWrite + Clone + 'static,
 {
@@ -207,18 +305,18 @@ where
     }
 }
 
-impl<T> Default for NdrArrayStructureElement<T>
+impl<T, const TO: usize, S: NdrSyntax> Default for NdrArrayStructureElement<T, TO, S>
 where
     T: BinRead + BinWrite + Clone + Default + 'static,
 {
     fn default() -> Self {
         Self {
-            val: NdrAlign::from(T::default()),
+            val: From::from(T::default()),
         }
     }
 }
 
-impl<T> Clone for NdrArrayStructureElement<T>
+impl<T, const TO: usize, S: NdrSyntax> Clone for NdrArrayStructureElement<T, TO, S>
 where
     T: BinRead + BinWrite + Clone + 'static,
 {
@@ -262,6 +360,20 @@ mod tests {
         array: NdrArray<InArrayElement>,
     }
 
+    #[binrw::binrw]
+    #[derive(Debug, PartialEq, Eq)]
+    struct WithConformantArray {
+        unalign: u8,
+        bytes: NdrPtr<NdrConformantArray<u8>>,
+    }
+
+    test_binrw! {
+        struct WithConformantArray {
+            unalign: 0x1,
+            bytes: NdrConformantArray::from(vec![0xa, 0xb, 0xc]).into(),
+        } => "0100000000000000 0000020000000000 0300000000000000 0a0b0c"
+    }
+
     test_binrw! {
         struct WithArray {
             array: vec![
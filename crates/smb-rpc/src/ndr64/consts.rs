@@ -1,8 +1,20 @@
 use smb_dtyp::make_guid;
 
-use crate::pdu::DceRpcSyntaxId;
+use crate::{
+    pdu::DceRpcSyntaxId,
+    syntax::{NdrSyntax, TransferSyntax},
+};
 
 pub const NDR64_SYNTAX_ID: DceRpcSyntaxId = DceRpcSyntaxId {
     uuid: make_guid!("71710533-beba-4937-8319-b5dbef9ccc36"),
     version: 1,
 };
+
+/// The NDR64 transfer syntax, for NDR data structures.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Ndr64;
+
+impl NdrSyntax for Ndr64 {
+    const TRANSFER_SYNTAX: TransferSyntax = TransferSyntax::Ndr64;
+    type Size = u64;
+}
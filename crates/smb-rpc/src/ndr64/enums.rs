@@ -0,0 +1,147 @@
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use super::{Ndr64, align::*};
+use crate::syntax::{NdrSyntax, TransferSyntax};
+use binrw::prelude::*;
+
+/// An `enum` value, that is transmitted as 16 bits under NDR 2.0,
+/// but as 32 bits under NDR64 (MS-RPCE).
+///
+/// `T` is expected to be a 16-bit value, such as a `#[brw(repr(u16))]` enum.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NdrEnum16<T, S: NdrSyntax = Ndr64>
+where
+    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
+{
+    pub value: T,
+    _syntax: PhantomData<S>,
+}
+
+impl<T, S: NdrSyntax> NdrEnum16<T, S>
+where
+    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
+{
+    /// The size (and alignment) of the value on the wire.
+    fn wire_size() -> usize {
+        match S::TRANSFER_SYNTAX {
+            TransferSyntax::Ndr20 => size_of::<u16>(),
+            TransferSyntax::Ndr64 => size_of::<u32>(),
+        }
+    }
+}
+
+impl<T, S: NdrSyntax> BinRead for NdrEnum16<T, S>
+where
+    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
+{
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self> {
+        align_reader(reader, Self::wire_size())?;
+        let value = T::read_options(reader, endian, ())?;
+        // Under NDR64, the upper half of the (little-endian) value is zero.
+        let upper = (Self::wire_size() - size_of::<u16>()) as i64;
+        reader.seek(std::io::SeekFrom::Current(upper))?;
+        Ok(value.into())
+    }
+}
+
+impl<T, S: NdrSyntax> BinWrite for NdrEnum16<T, S>
+where
+    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
+{
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        align_writer(writer, Self::wire_size())?;
+        self.value.write_options(writer, endian, ())?;
+        let upper = Self::wire_size() - size_of::<u16>();
+        writer.write_all(&vec![0; upper])?;
+        Ok(())
+    }
+}
+
+impl<T, S: NdrSyntax> NdrAligned for NdrEnum16<T, S> where
+    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>
+{
+}
+
+impl<T, S: NdrSyntax> From<T> for NdrEnum16<T, S>
+where
+    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
+{
+    fn from(value: T) -> Self {
+        Self {
+            value,
+            _syntax: PhantomData,
+        }
+    }
+}
+
+impl<T, S: NdrSyntax> Deref for NdrEnum16<T, S>
+where
+    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T, S: NdrSyntax> DerefMut for NdrEnum16<T, S>
+where
+    for<'a> T: BinRead<Args<'a> = ()> + BinWrite<Args<'a> = ()>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smb_tests::*;
+
+    use super::*;
+    use crate::ndr20::Ndr20;
+
+    #[binrw::binrw]
+    #[derive(Debug, PartialEq, Eq)]
+    struct WithEnum16<S: NdrSyntax> {
+        unalign: u8,
+        value: NdrEnum16<u16, S>,
+        other: u16,
+    }
+
+    type WithEnum16Ndr64 = WithEnum16<Ndr64>;
+
+    test_binrw! {
+        WithEnum16Ndr64: WithEnum16::<Ndr64> {
+            unalign: 1,
+            value: 0x1234.into(),
+            other: 0x5678,
+        } => "01000000 34120000 7856"
+    }
+
+    type WithEnum16Ndr20 = WithEnum16<Ndr20>;
+
+    test_binrw! {
+        WithEnum16Ndr20: WithEnum16::<Ndr20> {
+            unalign: 1,
+            value: 0x1234.into(),
+            other: 0x5678,
+        } => "0100 3412 7856"
+    }
+}
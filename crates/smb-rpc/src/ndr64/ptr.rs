@@ -1,6 +1,7 @@
 use std::ops::{Deref, DerefMut};
 
-use super::align::*;
+use super::{Ndr64, align::*};
+use crate::syntax::NdrSyntax;
 use binrw::{endian, prelude::*};
 
 pub const REF_ID_UNIQUE_DEFAULT: u64 = 0x20000;
@@ -28,7 +29,7 @@ pub enum NdrPtrWriteStage {
 ///
 /// *Note*: This is only aligned if the type argument `T` is aligned!
 #[derive(Debug, Default, PartialEq, Eq)]
-pub enum NdrPtr<T>
+pub enum NdrPtr<T, S: NdrSyntax = Ndr64>
 where
     for<'a, 'b> T: BinRead + BinWrite,
 {
@@ -38,10 +39,37 @@ where
     // read started, with a reference ID read.
     RefIdRead(u64),
     // read done, with a value resolved.
-    Resolved(Option<NdrAlign<T>>),
+    Resolved(Option<NdrAlign<T, NDR_SIZE_ALIGNMENT, S>>),
 }
 
-impl<T> BinRead for NdrPtr<T>
+impl<T, S: NdrSyntax> NdrPtr<T, S>
+where
+    T: BinRead + BinWrite,
+{
+    /// Returns the value pointed to, or `None` for a null pointer.
+    ///
+    /// *Note:* Panics if the pointer is not resolved, just like dereferencing it.
+    pub fn into_value(self) -> Option<T> {
+        match self {
+            Self::Resolved(value) => value.map(|x| x.value),
+            _ => panic!("Cannot take the value of an unresolved pointer"),
+        }
+    }
+
+    /// Maps the value pointed to, possibly into a different syntax.
+    pub fn map<U, S2: NdrSyntax>(self, f: impl FnOnce(T) -> U) -> NdrPtr<U, S2>
+    where
+        U: BinRead + BinWrite,
+    {
+        match self {
+            Self::Uninit => NdrPtr::Uninit,
+            Self::RefIdRead(ref_id) => NdrPtr::RefIdRead(ref_id),
+            Self::Resolved(value) => NdrPtr::Resolved(value.map(|x| f(x.value).into())),
+        }
+    }
+}
+
+impl<T, S: NdrSyntax> BinRead for NdrPtr<T, S>
 where
     T: BinRead + BinWrite + 'static,
 {
@@ -63,13 +91,15 @@ where
                     parent.is_none(),
                     "NdrPtrReadMode::NoArraySupport does not support parent pointers"
                 );
-                let ref_id = NdrAlign::<u64>::read_options(reader, endian, ())?;
-                let value = if *ref_id != NULL_PTR_REF_ID {
+                let ref_id = S::read_size(reader, endian)?;
+                let value = if ref_id != NULL_PTR_REF_ID {
                     debug_assert!(
-                        *ref_id == REF_ID_UNIQUE_DEFAULT,
+                        ref_id == REF_ID_UNIQUE_DEFAULT,
                         "Reference ID must be unique when read_mode is NoArraySupport"
                     );
-                    Some(NdrAlign::<T>::read_options(reader, endian, align_args)?)
+                    Some(NdrAlign::<T, NDR_SIZE_ALIGNMENT, S>::read_options(
+                        reader, endian, align_args,
+                    )?)
                 } else {
                     None
                 };
@@ -93,7 +123,9 @@ where
                             ref_id == REF_ID_UNIQUE_DEFAULT,
                             "Reference ID must be unique when read_mode is NoArraySupport"
                         );
-                        Some(NdrAlign::<T>::read_options(reader, endian, align_args)?)
+                        Some(NdrAlign::<T, NDR_SIZE_ALIGNMENT, S>::read_options(
+                            reader, endian, align_args,
+                        )?)
                     } else {
                         None
                     };
@@ -102,8 +134,8 @@ where
                 }
                 None => {
                     // Read reference ID and assign into the state.
-                    let ref_id = NdrAlign::<u64>::read_options(reader, endian, ())?;
-                    Ok(Self::RefIdRead(*ref_id))
+                    let ref_id = S::read_size(reader, endian)?;
+                    Ok(Self::RefIdRead(ref_id))
                 }
             },
         }
@@ -127,7 +159,7 @@ where
     }
 }
 
-impl<T> BinWrite for NdrPtr<T>
+impl<T, S: NdrSyntax> BinWrite for NdrPtr<T, S>
 where
     T: BinRead + BinWrite + 'static,
 {
@@ -165,7 +197,7 @@ where
                 Some(_) => REF_ID_UNIQUE_DEFAULT,
                 None => NULL_PTR_REF_ID,
             };
-            Ndr64Align::from(ref_id).write_options(writer, endian, ())?;
+            S::write_size(ref_id, writer, endian)?;
         }
 
         if write_data {
@@ -177,13 +209,13 @@ where
     }
 }
 
-impl<T> NdrAligned for NdrPtr<T> where T: BinRead + BinWrite + NdrAligned {}
+impl<T, S: NdrSyntax> NdrAligned for NdrPtr<T, S> where T: BinRead + BinWrite + NdrAligned {}
 
-impl<T> Deref for NdrPtr<T>
+impl<T, S: NdrSyntax> Deref for NdrPtr<T, S>
 where
     T: BinRead + BinWrite,
 {
-    type Target = Option<NdrAlign<T>>;
+    type Target = Option<NdrAlign<T, NDR_SIZE_ALIGNMENT, S>>;
 
     fn deref(&self) -> &Self::Target {
         match self {
@@ -194,7 +226,7 @@ where
     }
 }
 
-impl<T> DerefMut for NdrPtr<T>
+impl<T, S: NdrSyntax> DerefMut for NdrPtr<T, S>
 where
     T: BinRead + BinWrite,
 {
@@ -206,7 +238,7 @@ where
     }
 }
 
-impl<T> From<T> for NdrPtr<T>
+impl<T, S: NdrSyntax> From<T> for NdrPtr<T, S>
 where
     T: BinRead + BinWrite,
 {
@@ -215,7 +247,7 @@ where
     }
 }
 
-impl<T> From<Option<T>> for NdrPtr<T>
+impl<T, S: NdrSyntax> From<Option<T>> for NdrPtr<T, S>
 where
     T: BinRead + BinWrite,
 {
@@ -224,7 +256,7 @@ where
     }
 }
 
-impl<T> Clone for NdrPtr<T>
+impl<T, S: NdrSyntax> Clone for NdrPtr<T, S>
 where
     T: BinRead + BinWrite + Clone,
 {
@@ -1,13 +1,14 @@
 use std::{fmt::Display, str::FromStr};
 
-use super::align::*;
+use super::{Ndr64, align::*};
+use crate::syntax::{NdrSize, NdrSyntax};
 use binrw::prelude::*;
 
 #[binrw::binrw]
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[br(import_raw(args: <E as BinRead>::Args<'_>))]
 #[bw(import_raw(args: <E as BinWrite>::Args<'_>))]
-pub struct NdrString<E, const SIZE: u32 = 0>
+pub struct NdrString<E, const SIZE: u32 = 0, S: NdrSyntax = Ndr64>
 where
     E: BinRead + BinWrite + Clone + 'static,
     for<'a> <E as BinRead>::Args<'a>: Clone,
@@ -18,21 +19,35 @@ where
     // for conformant strings, const SIZE is non-zero!
     #[bw(if(SIZE == 0), calc = Some((data.len() as u64).into()))]
     #[br(if(SIZE == 0))]
-    alloc_length: Option<NdrAlign<u64>>,
+    alloc_length: Option<NdrSize<S>>,
 
     #[bw(calc = 0.into())]
     #[br(assert(*offset == 0))] // TODO: Support non-zero offsets!
-    offset: NdrAlign<u64>,
+    offset: NdrSize<S>,
     #[bw(calc = (data.len() as u64).into())]
     #[br(assert((SIZE == 0 || *actual_count < SIZE as u64) || *actual_count < { *(alloc_length.unwrap()) }
     ))]
-    actual_count: NdrAlign<u64>,
+    actual_count: NdrSize<S>,
     #[br(count = *actual_count, args { inner: args })]
     #[bw(args_raw(args))]
-    pub data: NdrAlign<Vec<E>>,
+    pub data: NdrAlign<Vec<E>, NDR_SIZE_ALIGNMENT, S>,
 }
 
-impl<E> NdrAligned for NdrString<E>
+impl<E, const SIZE: u32, S: NdrSyntax> NdrString<E, SIZE, S>
+where
+    E: BinRead + BinWrite + Clone + 'static,
+    for<'a> <E as BinRead>::Args<'a>: Clone,
+    for<'a> <E as BinWrite>::Args<'a>: Clone,
+{
+    /// Converts the string into a different syntax.
+    pub fn into_syntax<S2: NdrSyntax>(self) -> NdrString<E, SIZE, S2> {
+        NdrString {
+            data: self.data.value.into(),
+        }
+    }
+}
+
+impl<E, S: NdrSyntax> NdrAligned for NdrString<E, 0, S>
 where
     E: BinRead + BinWrite + Clone + 'static,
     for<'a> <E as BinRead>::Args<'a>: Clone,
@@ -41,7 +56,7 @@ where
 }
 
 // String to NdrString<u16> conversion:
-impl FromStr for NdrString<u16, 0> {
+impl<S: NdrSyntax> FromStr for NdrString<u16, 0, S> {
     type Err = std::string::FromUtf16Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -51,13 +66,11 @@ impl FromStr for NdrString<u16, 0> {
                 std::iter::once(0), // Null terminator
             )
             .collect();
-        Ok(Self {
-            data: NdrAlign { value: data },
-        })
+        Ok(Self { data: data.into() })
     }
 }
 
-impl<const SIZE: u32> Display for NdrString<u16, SIZE> {
+impl<const SIZE: u32, S: NdrSyntax> Display for NdrString<u16, SIZE, S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s: String = self.data.value.iter().map(|&c| c as u8 as char).collect();
         write!(f, "{s}")
@@ -0,0 +1,143 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{Ndr64, align::*, ptr::*};
+use crate::syntax::NdrSyntax;
+use binrw::prelude::*;
+
+/// A structure with embedded pointers, that is not an element of an [`NdrArray`](super::NdrArray),
+/// such as the referent of a pointer to a structure.
+///
+/// Just like array elements, the structure is read and written in two stages:
+/// first the structure itself, with the reference IDs of its embedded pointers,
+/// and then the deferred referents of those pointers.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct NdrStructure<E, S: NdrSyntax = Ndr64>
+where
+    for<'a> E:
+        BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
+{
+    pub value: E,
+    _syntax: std::marker::PhantomData<S>,
+}
+
+impl<E, S: NdrSyntax> BinRead for NdrStructure<E, S>
+where
+    for<'a> E:
+        BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
+{
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: binrw::endian::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self> {
+        let refs_only =
+            NdrAlign::<E, NDR_SIZE_ALIGNMENT, S>::read_options(reader, endian, (None,))?;
+        let value = E::read_options(reader, endian, (Some(&refs_only),))?;
+        Ok(value.into())
+    }
+}
+
+impl<E, S: NdrSyntax> BinWrite for NdrStructure<E, S>
+where
+    for<'a> E:
+        BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
+{
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: binrw::endian::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        align_writer(writer, S::alignment(NDR_SIZE_ALIGNMENT))?;
+        self.value
+            .write_options(writer, endian, (NdrPtrWriteStage::ArraySupportWriteRefId,))?;
+        self.value
+            .write_options(writer, endian, (NdrPtrWriteStage::ArraySupportWriteData,))
+    }
+}
+
+impl<E, S: NdrSyntax> NdrAligned for NdrStructure<E, S> where
+    for<'a> E:
+        BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static
+{
+}
+
+impl<E, S: NdrSyntax> From<E> for NdrStructure<E, S>
+where
+    for<'a> E:
+        BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
+{
+    fn from(value: E) -> Self {
+        Self {
+            value,
+            _syntax: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<E, S: NdrSyntax> Deref for NdrStructure<E, S>
+where
+    for<'a> E:
+        BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
+{
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<E, S: NdrSyntax> DerefMut for NdrStructure<E, S>
+where
+    for<'a> E:
+        BinRead<Args<'a> = (Option<&'a E>,)> + BinWrite<Args<'a> = (NdrPtrWriteStage,)> + 'static,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smb_tests::*;
+
+    use super::*;
+    use crate::ndr64::{NdrArrayStructureElement, NdrString};
+
+    #[binrw::binrw]
+    #[derive(Debug, PartialEq, Eq)]
+    #[bw(import(stage: NdrPtrWriteStage))]
+    #[br(import(prev: Option<&Self>))]
+    struct WithPointers {
+        #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+        #[br(args(prev.map(|x| &x.first), NdrPtrReadMode::WithArraySupport, ()))]
+        first: NdrPtr<NdrString<u16>>,
+        #[bw(if(stage == NdrPtrWriteStage::ArraySupportWriteRefId))]
+        #[br(args(prev.map(|x| &**x.value)))]
+        value: NdrArrayStructureElement<u32, 4>,
+        #[bw(args_raw(NdrPtrWriteArgs(stage, ())))]
+        #[br(args(prev.map(|x| &x.second), NdrPtrReadMode::WithArraySupport, ()))]
+        second: NdrPtr<NdrString<u16>>,
+    }
+
+    #[binrw::binrw]
+    #[derive(Debug, PartialEq, Eq)]
+    struct PtrToStructure {
+        ptr: NdrPtr<NdrStructure<WithPointers>>,
+    }
+
+    test_binrw! {
+        struct PtrToStructure {
+            ptr: NdrStructure::from(WithPointers {
+                first: "A".parse::<NdrString<u16>>().unwrap().into(),
+                value: 0x1234.into(),
+                second: None.into(),
+            })
+            .into(),
+        } => "0000020000000000 0000020000000000 3412000000000000 0000000000000000 0200000000000000 0000000000000000 0200000000000000 41000000"
+    }
+}
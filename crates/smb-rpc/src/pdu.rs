@@ -41,13 +41,21 @@ pub struct [<DceRpcCo $name Pkt>] {
     #[bw(calc = PosMarker::default())]
     #[br(temp)]
     _frag_length: PosMarker<u16>,
-    #[br(assert(auth_length == 0))]
-    #[bw(calc = 0)]
-    auth_length: u16, // auth currently disabled.
+    #[bw(try_calc = auth.as_ref().map_or(Ok(0), |auth| auth.auth_value.len().try_into()))]
+    auth_length: u16,
     call_id: u32,
-    #[br(args(ptype), map_stream = |s| s.take_seek(_frag_length.value as u64))]
+    #[br(args(ptype), map_stream = |s| s.take_seek(Self::content_size(_frag_length.value, auth_length)))]
     content: [<DcRpcCoPkt $name Content>],
 
+    // The auth verifier is at the end of the PDU, following the padding of the content.
+    #[br(if(auth_length > 0), args(auth_length))]
+    #[br(seek_before = std::io::SeekFrom::Start(
+        _save_pdu_start.pos.get().copied().unwrap_or_default()
+            + Self::content_size(_frag_length.value, auth_length)
+            + Self::COMMON_SIZE_BYTES as u64
+    ))]
+    auth: Option<DceRpcAuthVerifier>,
+
     #[bw(write_with = PosMarker::write_roff_b, args(&_frag_length, &_save_pdu_start))]
     _write_pdu_size: ()
 }
@@ -61,10 +69,35 @@ impl [<DceRpcCo $name Pkt>] {
             packed_drep,
             call_id,
             content,
+            auth: None,
             _write_pdu_size: (),
         }
     }
 
+    /// Attaches an auth verifier to the PDU.
+    ///
+    /// The content must be padded to a 4-byte boundary, as indicated by [`DceRpcAuthVerifier::auth_pad_length`].
+    pub fn with_auth(mut self, auth: DceRpcAuthVerifier) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn auth(&self) -> Option<&DceRpcAuthVerifier> {
+        self.auth.as_ref()
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the size of the content of a PDU, which precedes the auth verifier, if any.
+    fn content_size(frag_length: u16, auth_length: u16) -> u64 {
+        let auth_size = if auth_length > 0 {
+            DceRpcAuthVerifier::SEC_TRAILER_SIZE + auth_length as usize
+        } else {
+            0
+        };
+        (frag_length as usize).saturating_sub(Self::COMMON_SIZE_BYTES + auth_size) as u64
+    }
+
     pub fn content(&self) -> &[<DcRpcCoPkt $name Content>] {
         &self.content
     }
@@ -73,6 +106,10 @@ impl [<DceRpcCo $name Pkt>] {
         self.content
     }
 
+    pub fn into_parts(self) -> ([<DcRpcCoPkt $name Content>], Option<DceRpcAuthVerifier>) {
+        (self.content, self.auth)
+    }
+
     pub fn call_id(&self) -> u32 {
         self.call_id
     }
@@ -151,16 +188,17 @@ rpc_pkts! {
     Request {
         Request = 0,
         Bind = 11,
-        // AlterContext = 14,
+        AlterContext = 14,
+        Auth3 = 16,
         // Cancel = 18,
         // Orphaned = 19,
     },
     Response {
         Response = 2,
-        // Fault = 3,
+        Fault = 3,
         BindAck = 12,
         BindNak = 13,
-        // AlterContextResp = 15,
+        AlterContextResp = 15,
         // Shutdown = 17,
     }
 }
@@ -359,6 +397,105 @@ pub struct DcRpcCoPktResponse {
     pub stub_data: Vec<u8>,
 }
 
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+pub struct DcRpcCoPktFault {
+    pub alloc_hint: u32,
+    pub context_id: u16,
+    pub cancel_count: u8,
+    #[bw(calc = 0)]
+    #[br(temp)]
+    _reserved: u8,
+    /// The status of the fault, such as `nca_s_fault_access_denied` (5).
+    pub status: u32,
+    #[bw(calc = 0)]
+    #[br(temp)]
+    _reserved2: u32,
+
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub stub_data: Vec<u8>,
+}
+
+/// An alter_context PDU, which has the format of a bind PDU.
+///
+/// It is used to add presentation contexts to an association, or for additional legs of authentication.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+pub struct DcRpcCoPktAlterContext(pub DcRpcCoPktBind);
+
+/// An alter_context_resp PDU, which has the format of a bind_ack PDU.
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq)]
+pub struct DcRpcCoPktAlterContextResp(pub DcRpcCoPktBindAck);
+
+/// An rpc_auth_3 PDU, carrying the last leg of a three-leg authentication.
+/// The server does not respond to it.
+#[binrw::binrw]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DcRpcCoPktAuth3 {
+    #[bw(calc = 0)]
+    #[br(temp)]
+    _pad: u32,
+}
+
+/// Authentication services (MS-RPCE 2.2.1.1.7).
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[brw(repr(u8))]
+pub enum DceRpcAuthType {
+    None = 0x00,
+    /// SPNEGO
+    GssNegotiate = 0x09,
+    /// NTLM
+    WinNt = 0x0a,
+    GssSchannel = 0x0e,
+    GssKerberos = 0x10,
+    Netlogon = 0x44,
+    Default = 0xff,
+}
+
+/// Authentication levels (MS-RPCE 2.2.1.1.8).
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, PartialOrd, Ord)]
+#[brw(repr(u8))]
+pub enum DceRpcAuthLevel {
+    Default = 0,
+    None = 1,
+    /// Authenticate on bind only.
+    Connect = 2,
+    Call = 3,
+    Packet = 4,
+    /// Sign every PDU.
+    PacketIntegrity = 5,
+    /// Sign and encrypt every PDU.
+    PacketPrivacy = 6,
+}
+
+/// The sec_trailer and the auth_value following it, at the end of an authenticated PDU.
+///
+/// Reference: MS-RPCE 2.2.2.11
+#[binrw::binrw]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[br(import(auth_length: u16))]
+pub struct DceRpcAuthVerifier {
+    pub auth_type: DceRpcAuthType,
+    pub auth_level: DceRpcAuthLevel,
+    /// The number of padding bytes at the end of the content, aligning the sec_trailer.
+    pub auth_pad_length: u8,
+    #[bw(calc = 0)]
+    #[br(temp)]
+    _auth_reserved: u8,
+    pub auth_context_id: u32,
+    /// The authentication token, or the signature of the PDU.
+    #[br(count = auth_length)]
+    pub auth_value: Vec<u8>,
+}
+
+impl DceRpcAuthVerifier {
+    /// The size of the sec_trailer, preceding the auth_value.
+    pub const SEC_TRAILER_SIZE: usize = 8;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -592,8 +729,101 @@ mod tests {
                     ]
                     .into()
                 }),
+                auth: None,
                 _write_pdu_size: ()
             }
         );
     }
+
+    #[test]
+    fn test_request_with_auth() {
+        let auth = DceRpcAuthVerifier {
+            auth_type: DceRpcAuthType::WinNt,
+            auth_level: DceRpcAuthLevel::PacketIntegrity,
+            auth_pad_length: 3,
+            auth_context_id: 0,
+            auth_value: vec![0x11; 16],
+        };
+        let request = DceRpcCoRequestPkt::new(
+            DcRpcCoPktRequest {
+                alloc_hint: 5,
+                context_id: 0,
+                opnum: 2,
+                stub_data: vec![1, 2, 3, 4, 5, 0, 0, 0],
+            }
+            .into(),
+            4,
+            DceRpcCoPktFlags::new()
+                .with_first_frag(true)
+                .with_last_frag(true),
+            0x00000010,
+        )
+        .with_auth(auth.clone());
+
+        let data: Vec<u8> = request.try_into().unwrap();
+        assert_eq!(
+            data[..32],
+            [
+                0x5, 0x0, 0x0, 0x3, 0x10, 0x0, 0x0, 0x0, 0x38, 0x0, 0x10, 0x0, 0x4, 0x0, 0x0, 0x0,
+                0x5, 0x0, 0x0, 0x0, 0x0, 0x0, 0x2, 0x0, 0x1, 0x2, 0x3, 0x4, 0x5, 0x0, 0x0, 0x0,
+            ]
+        );
+        assert_eq!(data[32..40], [0xa, 0x5, 0x3, 0x0, 0x0, 0x0, 0x0, 0x0]);
+        assert_eq!(data[40..], [0x11; 16]);
+
+        let parsed = DceRpcCoRequestPkt::try_from(data.as_slice()).unwrap();
+        assert_eq!(parsed.auth(), Some(&auth));
+        let DcRpcCoPktRequestContent::Request(parsed) = parsed.content() else {
+            panic!("Expected a request");
+        };
+        assert_eq!(parsed.stub_data, [1, 2, 3, 4, 5, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_auth3_write() {
+        let auth3 = DceRpcCoRequestPkt::new(
+            DcRpcCoPktAuth3::default().into(),
+            2,
+            DceRpcCoPktFlags::new()
+                .with_first_frag(true)
+                .with_last_frag(true),
+            0x00000010,
+        )
+        .with_auth(DceRpcAuthVerifier {
+            auth_type: DceRpcAuthType::WinNt,
+            auth_level: DceRpcAuthLevel::PacketPrivacy,
+            auth_pad_length: 0,
+            auth_context_id: 0,
+            auth_value: vec![0x22; 4],
+        });
+        let data: Vec<u8> = auth3.try_into().unwrap();
+        assert_eq!(
+            data,
+            [
+                0x5, 0x0, 0x10, 0x3, 0x10, 0x0, 0x0, 0x0, 0x20, 0x0, 0x4, 0x0, 0x2, 0x0, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0xa, 0x6, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x22, 0x22, 0x22, 0x22,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fault_parses() {
+        let data = [
+            0x5, 0x0, 0x3, 0x3, 0x10, 0x0, 0x0, 0x0, 0x20, 0x0, 0x0, 0x0, 0x7, 0x0, 0x0, 0x0, 0x20,
+            0x0, 0x0, 0x0, 0x1, 0x0, 0x0, 0x0, 0x5, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+        ];
+        let fault = DceRpcCoResponsePkt::try_from(data.as_slice()).unwrap();
+        assert_eq!(fault.call_id(), 7);
+        assert_eq!(
+            fault.into_content(),
+            DcRpcCoPktFault {
+                alloc_hint: 0x20,
+                context_id: 1,
+                cancel_count: 0,
+                status: 5,
+                stub_data: vec![],
+            }
+            .into()
+        );
+    }
 }
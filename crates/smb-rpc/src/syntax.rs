@@ -0,0 +1,152 @@
+//! Transfer syntaxes for NDR-encoded stub data.
+//!
+//! The NDR data structures (see [`crate::ndr64`] and [`crate::ndr20`]) are generic over
+//! an [`NdrSyntax`], which determines the size of pointers and counts, and the alignment of the data.
+//! An interface definition that is generic over the syntax may be serialized under either one,
+//! according to the [`TransferSyntax`] negotiated on bind.
+
+use std::{fmt::Debug, marker::PhantomData, ops::Deref};
+
+use binrw::{Endian, prelude::*};
+
+use crate::{
+    ndr20::NDR20_SYNTAX_ID,
+    ndr64::{NDR_SIZE_ALIGNMENT, NDR64_SYNTAX_ID, align_reader, align_writer},
+    pdu::DceRpcSyntaxId,
+};
+
+/// A transfer syntax of stub data, as negotiated on bind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferSyntax {
+    /// NDR 2.0 (DCE/RPC chap. 14).
+    Ndr20,
+    /// NDR64 (MS-RPCE 2.2.5).
+    Ndr64,
+}
+
+impl TransferSyntax {
+    /// All the supported transfer syntaxes, by order of preference.
+    pub const ALL: [TransferSyntax; 2] = [TransferSyntax::Ndr64, TransferSyntax::Ndr20];
+
+    /// Returns the syntax ID identifying the transfer syntax in bind requests.
+    pub fn syntax_id(&self) -> DceRpcSyntaxId {
+        match self {
+            TransferSyntax::Ndr20 => NDR20_SYNTAX_ID,
+            TransferSyntax::Ndr64 => NDR64_SYNTAX_ID,
+        }
+    }
+}
+
+impl TryFrom<&DceRpcSyntaxId> for TransferSyntax {
+    type Error = crate::SmbRpcError;
+
+    fn try_from(value: &DceRpcSyntaxId) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|syntax| &syntax.syntax_id() == value)
+            .ok_or(crate::SmbRpcError::InvalidResponseData(
+                "Unsupported transfer syntax",
+            ))
+    }
+}
+
+/// Type-level selection of the transfer syntax of NDR data structures.
+pub trait NdrSyntax: Debug + Default + Clone + Copy + PartialEq + Eq + 'static {
+    const TRANSFER_SYNTAX: TransferSyntax;
+
+    /// The type of pointer referent IDs, and of array and string counts and offsets.
+    type Size: for<'a> BinRead<Args<'a> = ()>
+        + for<'a> BinWrite<Args<'a> = ()>
+        + TryFrom<u64>
+        + Into<u64>;
+
+    /// Resolves an alignment parameter to the alignment of data under the syntax.
+    ///
+    /// Size values (pointer referent IDs, counts and offsets), and structures containing them,
+    /// are aligned to their size - 8 bytes under NDR64, and 4 bytes under NDR 2.0,
+    /// which is requested by [`NDR_SIZE_ALIGNMENT`]. Any other alignment is the same under both syntaxes,
+    /// e.g. 8 bytes for hyper values.
+    fn alignment(to: usize) -> usize {
+        match to {
+            NDR_SIZE_ALIGNMENT => size_of::<Self::Size>(),
+            to => to,
+        }
+    }
+
+    /// Reads an aligned size value (count, offset or referent ID).
+    fn read_size<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: Endian,
+    ) -> BinResult<u64> {
+        align_reader(reader, size_of::<Self::Size>())?;
+        Ok(Self::Size::read_options(reader, endian, ())?.into())
+    }
+
+    /// Writes an aligned size value (count, offset or referent ID).
+    fn write_size<W: std::io::Write + std::io::Seek>(
+        value: u64,
+        writer: &mut W,
+        endian: Endian,
+    ) -> BinResult<()> {
+        align_writer(writer, size_of::<Self::Size>())?;
+        let pos = writer.stream_position()?;
+        let value = Self::Size::try_from(value).map_err(|_| binrw::Error::AssertFail {
+            pos,
+            message: format!(
+                "Size value {value} exceeds the limits of {:?}",
+                Self::TRANSFER_SYNTAX
+            ),
+        })?;
+        value.write_options(writer, endian, ())
+    }
+}
+
+/// A size value - a count, an offset or a pointer referent ID,
+/// that is sized and aligned according to the syntax `S`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NdrSize<S: NdrSyntax> {
+    pub value: u64,
+    _syntax: PhantomData<S>,
+}
+
+impl<S: NdrSyntax> BinRead for NdrSize<S> {
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<Self> {
+        Ok(S::read_size(reader, endian)?.into())
+    }
+}
+
+impl<S: NdrSyntax> BinWrite for NdrSize<S> {
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        S::write_size(self.value, writer, endian)
+    }
+}
+
+impl<S: NdrSyntax> From<u64> for NdrSize<S> {
+    fn from(value: u64) -> Self {
+        Self {
+            value,
+            _syntax: PhantomData,
+        }
+    }
+}
+
+impl<S: NdrSyntax> Deref for NdrSize<S> {
+    type Target = u64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
@@ -95,6 +95,11 @@ impl IoVec {
         self.insert_shared(self.0.len(), buf)
     }
 
+    /// Moves all the buffers of `other` to the end of the IoVec.
+    pub fn append(&mut self, mut other: IoVec) {
+        self.0.append(&mut other.0);
+    }
+
     /// Consolidates all buffers into a single owned buffer,
     /// and puts it in the IoVec, replacing all previous buffers.
     pub fn consolidate(&mut self) -> &mut Vec<u8> {
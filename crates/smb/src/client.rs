@@ -4,6 +4,6 @@ mod config;
 mod smb_client;
 mod unc_path;
 
-pub use config::ClientConfig;
+pub use config::{ClientConfig, ReconnectPolicy};
 pub use smb_client::Client;
 pub use unc_path::UncPath;
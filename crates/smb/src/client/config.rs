@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use smb_dtyp::Guid;
 
 use crate::ConnectionConfig;
@@ -18,6 +20,10 @@ pub struct ClientConfig {
 
     pub client_guid: Guid,
 
+    /// Configuration for re-establishing lost connections.
+    /// See [`ReconnectPolicy`] for more details.
+    pub reconnect: ReconnectPolicy,
+
     #[cfg(feature = "rdma")]
     pub rdma_type: Option<crate::transport::RdmaType>,
 }
@@ -28,8 +34,77 @@ impl Default for ClientConfig {
             dfs: true,
             connection: ConnectionConfig::default(),
             client_guid: Guid::generate(),
+            reconnect: ReconnectPolicy::default(),
             #[cfg(feature = "rdma")]
             rdma_type: None,
         }
     }
 }
+
+/// Specifies how the client re-establishes connections that were lost.
+///
+/// See [`Client::reconnect`][crate::Client::reconnect] for more details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// The maximum number of attempts to re-establish a lost connection.
+    /// Setting this to zero disables reconnection.
+    pub max_attempts: u32,
+    /// The delay before retrying after the first failed attempt.
+    /// The delay is doubled after each further failed attempt.
+    pub initial_backoff: Duration,
+    /// The maximum delay between attempts.
+    pub max_backoff: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Returns a policy that never re-establishes lost connections.
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Returns whether lost connections are re-established.
+    pub fn is_enabled(&self) -> bool {
+        self.max_attempts > 0
+    }
+
+    /// Returns the delay to wait after the specified failed attempt (starting at 1).
+    pub fn backoff(&self, failed_attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failed_attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let policy = ReconnectPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(3),
+        };
+        let backoffs: Vec<_> = (1..=5).map(|a| policy.backoff(a)).collect();
+        assert_eq!(
+            backoffs,
+            [500, 1000, 2000, 3000, 3000].map(Duration::from_millis)
+        );
+        assert!(!ReconnectPolicy::disabled().is_enabled());
+    }
+}
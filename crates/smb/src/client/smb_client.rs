@@ -1,14 +1,24 @@
 use crate::ConnectionConfig;
-use crate::{Connection, Error, FileCreateArgs, Pipe, Resource, Session, Tree, sync_helpers::*};
+use crate::{
+    Connection, Error, FileCreateArgs, Pipe, PipeRpcConnection, Resource, Session, Tree,
+    sync_helpers::*,
+};
 use maybe_async::maybe_async;
+use smb_dtyp::SID;
 use smb_msg::{NetworkInterfaceInfo, ReferralEntry, ReferralEntryValue, Status};
-use smb_rpc::interface::{ShareInfo1, SrvSvc};
+use smb_rpc::interface::{
+    FileInfo3, LsaAccount, LsaRpc, PolicyHandle, RpcInterface, ServerInfo101, SessionInfo1,
+    ShareInfo, ShareInfo1, ShareInfoLevel, SrvSvc, TimeOfDayInfo,
+};
 use smb_transport::TransportConfig;
 use smb_transport::utils::TransportUtils;
 use sspi::{AuthIdentity, Secret};
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 use super::{config::ClientConfig, unc_path::UncPath};
 
@@ -36,6 +46,16 @@ use super::{config::ClientConfig, unc_path::UncPath};
 /// To force a closure of all connections and their managed resources,
 /// use the [`Client::close`] method.
 ///
+/// ## Reconnection
+/// When a connection to a server is lost, requests over it fail with [`Error::ConnectionStopped`],
+/// and requests over files, directories and pipes opened on it fail with [`Error::ReconnectRequired`].
+/// Reconnection is not triggered by such requests: the client re-establishes the connection
+/// before the next access to any of the server's shares through the client (e.g. [`Client::get_tree`]
+/// or [`Client::create_file`]), or when [`Client::reconnect`] is called, according to [`ClientConfig::reconnect`].
+/// Durable opens are then reclaimed, and the same handles may be used again,
+/// while any other open on the server fails with [`Error::HandleLost`].
+/// Requests that failed while the connection was lost are not retried.
+///
 /// ## Example
 ///
 /// ```no_run
@@ -67,6 +87,10 @@ pub struct Client {
     connections: RwLock<HashMap<IpAddr, ClientConnectionInfo>>,
     /// shares (trees) that are currently connected.
     share_connects: Mutex<HashMap<UncPath, ClientConectedTree>>,
+    /// Serializes re-establishment of lost connections.
+    reconnect_lock: Mutex<()>,
+    /// Server Name => accounts translated by the server's LSA.
+    account_cache: Mutex<HashMap<String, AccountCache>>,
 }
 
 /// (Internal)
@@ -91,6 +115,16 @@ struct ClientConectedTree {
     credentials: Option<AuthIdentity>,
 }
 
+/// (Internal)
+///
+/// Accounts translated by the LSA of a server, by SID and by lower-case name.
+/// SIDs and names that could not be translated are not cached, so they are looked up again.
+#[derive(Default)]
+struct AccountCache {
+    by_sid: HashMap<SID, LsaAccount>,
+    by_name: HashMap<String, LsaAccount>,
+}
+
 #[derive(Clone)]
 pub struct AltChannelInfo {
     connection: Arc<Connection>,
@@ -104,6 +138,8 @@ impl Client {
             config,
             connections: Default::default(),
             share_connects: Default::default(),
+            reconnect_lock: Default::default(),
+            account_cache: Default::default(),
         }
     }
 
@@ -149,15 +185,240 @@ impl Client {
         Ok(())
     }
 
-    /// Lists all shares on the specified server.
-    pub async fn list_shares(&self, server: &str) -> crate::Result<Vec<ShareInfo1>> {
+    /// Opens the server service pipe on the specified server, and binds to it.
+    async fn srvsvc(&self, server: &str) -> crate::Result<SrvSvc<PipeRpcConnection>> {
         let srvsvc_pipe_name: &str = "srvsvc";
         let srvsvc_pipe = self.open_pipe(server, srvsvc_pipe_name).await?;
 
-        let mut srvsvc_pipe: SrvSvc<_> = srvsvc_pipe.bind().await?;
-        let shares = srvsvc_pipe.netr_share_enum(server).await?;
+        srvsvc_pipe.bind().await
+    }
+
+    /// Lists all shares on the specified server.
+    pub async fn list_shares(&self, server: &str) -> crate::Result<Vec<ShareInfo1>> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let shares = srvsvc.netr_share_enum(server).await;
+        Self::close_rpc(srvsvc, shares).await
+    }
+
+    /// Returns information about a share on the specified server.
+    ///
+    /// `level` is one of [`ShareInfoLevel::Info1`], [`ShareInfoLevel::Info2`] (adds the local path and usage)
+    /// or [`ShareInfoLevel::Info502`] (adds the security descriptor). Levels 2 and 502 require administrative privileges.
+    pub async fn get_share_info(
+        &self,
+        server: &str,
+        share: &str,
+        level: ShareInfoLevel,
+    ) -> crate::Result<ShareInfo> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let info = srvsvc.netr_share_get_info(server, share, level).await;
+        Self::close_rpc(srvsvc, info).await
+    }
+
+    /// Creates a new share on the specified server, described by level 2 or 502 share info.
+    pub async fn add_share(&self, server: &str, info: ShareInfo) -> crate::Result<()> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let result = srvsvc.netr_share_add(server, info).await;
+        Self::close_rpc(srvsvc, result).await
+    }
+
+    /// Deletes a share from the specified server.
+    pub async fn delete_share(&self, server: &str, share: &str) -> crate::Result<()> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let result = srvsvc.netr_share_del(server, share).await;
+        Self::close_rpc(srvsvc, result).await
+    }
+
+    /// Updates the remark, permissions, usage limit, or security descriptor of a share on the specified server.
+    pub async fn set_share_info(
+        &self,
+        server: &str,
+        share: &str,
+        info: ShareInfo,
+    ) -> crate::Result<()> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let result = srvsvc.netr_share_set_info(server, share, info).await;
+        Self::close_rpc(srvsvc, result).await
+    }
+
+    /// Lists the sessions established on the specified server.
+    pub async fn list_sessions(&self, server: &str) -> crate::Result<Vec<SessionInfo1>> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let sessions = srvsvc.netr_session_enum(server, None, None).await;
+        Self::close_rpc(srvsvc, sessions).await
+    }
+
+    /// Lists the files opened on the specified server, optionally only those under `base_path`.
+    ///
+    /// Use [`Client::close_open_file`] to force one of them to close, releasing its locks.
+    pub async fn list_open_files(
+        &self,
+        server: &str,
+        base_path: Option<&str>,
+    ) -> crate::Result<Vec<FileInfo3>> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let files = srvsvc.netr_file_enum(server, base_path, None).await;
+        Self::close_rpc(srvsvc, files).await
+    }
+
+    /// Forces an open file on the specified server to close, by its [`FileInfo3::id`].
+    pub async fn close_open_file(&self, server: &str, file_id: u32) -> crate::Result<()> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let result = srvsvc.netr_file_close(server, file_id).await;
+        Self::close_rpc(srvsvc, result).await
+    }
+
+    /// Returns the name, operating system version and type of the specified server.
+    pub async fn server_info(&self, server: &str) -> crate::Result<ServerInfo101> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let info = srvsvc.netr_server_get_info(server).await;
+        Self::close_rpc(srvsvc, info).await
+    }
+
+    /// Returns the current time of day on the specified server.
+    pub async fn remote_time(&self, server: &str) -> crate::Result<TimeOfDayInfo> {
+        let mut srvsvc = self.srvsvc(server).await?;
+        let time_of_day = srvsvc.netr_remote_tod(server).await;
+        Self::close_rpc(srvsvc, time_of_day).await
+    }
+
+    /// Closes the pipe of an RPC interface opened by the client, and returns the result of the call made over it.
+    async fn close_rpc<I, T, E>(interface: I, result: Result<T, E>) -> crate::Result<T>
+    where
+        I: RpcInterface<PipeRpcConnection>,
+        Error: From<E>,
+    {
+        let closed = interface.into_inner().close().await;
+        let result = result?;
+        closed?;
+        Ok(result)
+    }
+
+    /// Opens the LSA pipe on the specified server, binds to it, and opens the server's policy.
+    async fn lsarpc(
+        &self,
+        server: &str,
+    ) -> crate::Result<(LsaRpc<PipeRpcConnection>, PolicyHandle)> {
+        let lsarpc_pipe_name: &str = "lsarpc";
+        let lsarpc_pipe = self.open_pipe(server, lsarpc_pipe_name).await?;
+
+        let mut lsarpc: LsaRpc<_> = lsarpc_pipe.bind().await?;
+        match lsarpc.lsar_open_policy2(server).await {
+            Ok(policy_handle) => Ok((lsarpc, policy_handle)),
+            Err(e) => Self::close_rpc(lsarpc, Err(e)).await,
+        }
+    }
+
+    /// Translates SIDs, such as the ones in a security descriptor, to their accounts
+    /// (displayed as `DOMAIN\user`), using the LSA of the specified server.
+    ///
+    /// Returns the account of each SID, by order, or `None` for SIDs the server could not translate.
+    /// Translations are cached by the client, per server, so each translated SID is only sent to the server once.
+    /// SIDs that could not be translated are sent again on the next lookup.
+    /// Use [`Client::clear_account_cache`] to drop translations that may have changed, e.g. renamed accounts.
+    pub async fn lookup_sids(
+        &self,
+        server: &str,
+        sids: &[SID],
+    ) -> crate::Result<Vec<Option<LsaAccount>>> {
+        let server_key = server.to_lowercase();
+        let mut missing: Vec<SID> = vec![];
+        {
+            let cache = self.account_cache.lock().await?;
+            let server_cache = cache.get(&server_key);
+            let mut seen = HashSet::new();
+            for sid in sids {
+                let cached = server_cache.is_some_and(|c| c.by_sid.contains_key(sid));
+                if !cached && seen.insert(sid) {
+                    missing.push(sid.clone());
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let (mut lsarpc, policy_handle) = self.lsarpc(server).await?;
+            let accounts = lsarpc.lsar_lookup_sids2(&policy_handle, &missing).await;
+            lsarpc.lsar_close(policy_handle).await.ok();
+            let accounts = Self::close_rpc(lsarpc, accounts).await?;
+
+            let mut cache = self.account_cache.lock().await?;
+            let server_cache = cache.entry(server_key.clone()).or_default();
+            for (sid, account) in missing.into_iter().zip(accounts) {
+                if let Some(account) = account {
+                    server_cache
+                        .by_name
+                        .insert(account.to_string().to_lowercase(), account.clone());
+                    server_cache.by_sid.insert(sid, account);
+                }
+            }
+        }
+
+        let cache = self.account_cache.lock().await?;
+        let server_cache = cache.get(&server_key);
+        Ok(sids
+            .iter()
+            .map(|sid| server_cache.and_then(|c| c.by_sid.get(sid).cloned()))
+            .collect())
+    }
+
+    /// Translates account names, such as `DOMAIN\user` or `user`, to their accounts (and SIDs),
+    /// using the LSA of the specified server.
+    ///
+    /// Returns the account of each name, by order, or `None` for names the server could not translate.
+    /// Translations are cached by the client, per server, like in [`Client::lookup_sids`],
+    /// so the SIDs of translated names are not sent to the server again.
+    pub async fn lookup_names(
+        &self,
+        server: &str,
+        names: &[&str],
+    ) -> crate::Result<Vec<Option<LsaAccount>>> {
+        let server_key = server.to_lowercase();
+        let mut missing: Vec<&str> = vec![];
+        {
+            let cache = self.account_cache.lock().await?;
+            let server_cache = cache.get(&server_key);
+            let mut seen = HashSet::new();
+            for name in names {
+                let key = name.to_lowercase();
+                let cached = server_cache.is_some_and(|c| c.by_name.contains_key(&key));
+                if !cached && seen.insert(key) {
+                    missing.push(name);
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let (mut lsarpc, policy_handle) = self.lsarpc(server).await?;
+            let accounts = lsarpc.lsar_lookup_names3(&policy_handle, &missing).await;
+            lsarpc.lsar_close(policy_handle).await.ok();
+            let accounts = Self::close_rpc(lsarpc, accounts).await?;
+
+            let mut cache = self.account_cache.lock().await?;
+            let server_cache = cache.entry(server_key.clone()).or_default();
+            for (name, account) in missing.into_iter().zip(accounts) {
+                if let Some(account) = account {
+                    server_cache
+                        .by_sid
+                        .entry(account.sid.clone())
+                        .or_insert_with(|| account.clone());
+                    server_cache.by_name.insert(name.to_lowercase(), account);
+                }
+            }
+        }
+
+        let cache = self.account_cache.lock().await?;
+        let server_cache = cache.get(&server_key);
+        Ok(names
+            .iter()
+            .map(|name| server_cache.and_then(|c| c.by_name.get(&name.to_lowercase()).cloned()))
+            .collect())
+    }
 
-        Ok(shares)
+    /// Drops the accounts translated by [`Client::lookup_sids`] and [`Client::lookup_names`],
+    /// so they are looked up again on the server.
+    pub async fn clear_account_cache(&self) -> crate::Result<()> {
+        self.account_cache.lock().await?.clear();
+        Ok(())
     }
 
     /// Connects to a share on the specified server.
@@ -293,7 +554,8 @@ impl Client {
 
         let tree = session.tree_connect(&target).await?;
 
-        let credentials = if tree.is_dfs_root()? {
+        // Credentials are kept for resolving DFS paths, and for re-authenticating after reconnection.
+        let credentials = if tree.is_dfs_root()? || self.config.reconnect.is_enabled() {
             Some(identity.to_owned())
         } else {
             None
@@ -469,6 +731,7 @@ impl Client {
     }
 
     pub async fn get_session(&self, path: &UncPath) -> crate::Result<Arc<Session>> {
+        self._ensure_connected(path).await?;
         self._with_tree(path, |tree| Ok(tree.session.clone())).await
     }
 
@@ -518,9 +781,210 @@ impl Client {
     /// Returns the underlying [`Tree`] for the specified UNC path,
     /// after a successful call to [`Client::share_connect`].
     pub async fn get_tree(&self, path: &UncPath) -> crate::Result<Arc<Tree>> {
+        self._ensure_connected(path).await?;
         self._with_tree(path, |tree| Ok(tree.tree.clone())).await
     }
 
+    /// Re-establishes the connection to the specified server, if it was lost.
+    ///
+    /// A new connection is made, and each of the lost sessions is authenticated again, using
+    /// the credentials it was originally set up with. Then, all the shares connected through
+    /// [`Client::share_connect`] are connected again, and durable opens on them
+    /// (see [`Durability`][crate::Durability]) are reclaimed, and may be used just as before.
+    /// Any other open on the server fails with [`Error::HandleLost`].
+    ///
+    /// Attempts are made according to [`ClientConfig::reconnect`]. If all of them fail,
+    /// the last error is returned, and the next call will try again.
+    ///
+    /// This method is called automatically before any share on the server is accessed through the client.
+    /// Requests over opens, sessions and trees that were obtained before the connection was lost
+    /// do not call it, and keep failing with [`Error::ConnectionStopped`] (or [`Error::ReconnectRequired`],
+    /// for opens) until it is called.
+    ///
+    /// ## Notes
+    /// * [`Connection`], [`Session`] and [`Tree`] instances obtained before the connection was lost
+    ///   are not revived, and should be obtained from the client again.
+    /// * Alternate channels of lost sessions are closed, and are not re-established.
+    pub async fn reconnect(&self, server: &str) -> crate::Result<()> {
+        let policy = &self.config.reconnect;
+        if !policy.is_enabled() {
+            return Err(Error::InvalidConfiguration(
+                "Reconnection is disabled in client configuration.".to_string(),
+            ));
+        }
+
+        let _reconnecting = self.reconnect_lock.lock().await?;
+        let address = TransportUtils::parse_socket_address(server)?;
+
+        let lost_connection = {
+            let mut connections = self.connections.write().await?;
+            match connections.get(&address.ip()) {
+                Some(c) if Self::is_lost(&c.connection) => connections.remove(&address.ip()),
+                _ => None,
+            }
+        };
+        let Some(lost_connection) = lost_connection else {
+            // Still connected, or already re-established by another caller.
+            return Ok(());
+        };
+        log::info!("Connection to {server} was lost, reconnecting");
+
+        for session in lost_connection.sessions.values() {
+            for alt_channel in session.session_alt_channels.iter().flat_map(|c| c.values()) {
+                alt_channel.connection.close().await.ok();
+                if let Some(info) = alt_channel.connection.conn_info() {
+                    let alt_ip = info.server_address.ip();
+                    self.connections.write().await?.remove(&alt_ip);
+                }
+            }
+        }
+
+        let lost_trees: Vec<_> = {
+            let mut share_connects = self.share_connects.lock().await?;
+            let paths: Vec<_> = share_connects
+                .keys()
+                .filter(|path| {
+                    TransportUtils::parse_socket_address(path.server())
+                        .is_ok_and(|a| a.ip() == address.ip())
+                })
+                .cloned()
+                .collect();
+            paths
+                .into_iter()
+                .filter_map(|path| share_connects.remove_entry(&path))
+                .collect()
+        };
+
+        let mut attempt = 0;
+        let result = loop {
+            attempt += 1;
+            let result = self._reestablish(server, address, &lost_trees).await;
+            if result.is_err() {
+                // Drop the partially re-established connection, if any.
+                if let Some(c) = self.connections.write().await?.remove(&address.ip()) {
+                    c.connection.close().await.ok();
+                }
+            }
+            match result {
+                Err(e) if attempt < policy.max_attempts => {
+                    let backoff = policy.backoff(attempt);
+                    log::warn!(
+                        "Reconnect attempt {attempt} to {server} failed: {e}. Retrying in {backoff:?}"
+                    );
+                    sleep(backoff).await;
+                }
+                result => break result,
+            }
+        };
+
+        match result {
+            Ok(trees) => {
+                self.share_connects.lock().await?.extend(trees);
+                log::info!("Successfully reconnected to {server}");
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("Failed to reconnect to {server} after {attempt} attempts: {e}");
+                // Keep the lost state, so the next call may try again.
+                self.connections
+                    .write()
+                    .await?
+                    .entry(address.ip())
+                    .or_insert(lost_connection);
+                self.share_connects.lock().await?.extend(lost_trees);
+                Err(e)
+            }
+        }
+    }
+
+    /// (Internal)
+    ///
+    /// Performs a single attempt of re-establishing the connection to the server,
+    /// and the specified lost trees on top of it.
+    ///
+    /// Durable opens are reclaimed only after all the trees are connected,
+    /// so a failed attempt leaves them intact for the next one.
+    #[maybe_async]
+    async fn _reestablish(
+        &self,
+        server: &str,
+        address: SocketAddr,
+        lost_trees: &[(UncPath, ClientConectedTree)],
+    ) -> crate::Result<Vec<(UncPath, ClientConectedTree)>> {
+        let connection = self
+            ._connect_transport_to_address(server, address, None)
+            .await?;
+
+        // Previous session ID => re-authenticated session
+        let mut sessions: HashMap<u64, Arc<Session>> = HashMap::new();
+        let mut trees = Vec::with_capacity(lost_trees.len());
+        for (path, lost) in lost_trees {
+            let credentials = lost.credentials.clone().ok_or_else(|| {
+                Error::InvalidState(format!("No credentials found for share {path}"))
+            })?;
+
+            let previous_session_id = lost.session.session_id();
+            let session = match sessions.get(&previous_session_id) {
+                Some(session) => session.clone(),
+                None => {
+                    let session = connection
+                        .reauthenticate(credentials.clone(), previous_session_id)
+                        .await?;
+                    let session = Arc::new(session);
+                    self._with_connection(address.ip(), |c| {
+                        c.sessions.insert(
+                            session.session_id(),
+                            ClientSessionInfo {
+                                session: session.clone(),
+                                session_alt_channels: None,
+                            },
+                        );
+                        Ok(())
+                    })
+                    .await?;
+                    sessions.insert(previous_session_id, session.clone());
+                    session
+                }
+            };
+
+            let tree = session.tree_connect(path).await?;
+            trees.push((
+                path.clone(),
+                ClientConectedTree {
+                    session,
+                    tree: Arc::new(tree),
+                    credentials: Some(credentials),
+                },
+            ));
+        }
+
+        for ((_, lost), (_, connected)) in lost_trees.iter().zip(trees.iter()) {
+            connected.tree.reclaim_opens(&lost.tree).await?;
+        }
+
+        Ok(trees)
+    }
+
+    /// (Internal)
+    ///
+    /// Re-establishes the connection to the server of the specified path,
+    /// if it was lost and reconnection is enabled.
+    #[maybe_async]
+    async fn _ensure_connected(&self, path: &UncPath) -> crate::Result<()> {
+        if !self.config.reconnect.is_enabled() {
+            return Ok(());
+        }
+        match self.get_connection(path.server()).await {
+            Ok(connection) if Self::is_lost(&connection) => self.reconnect(path.server()).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns whether the connection was established, but then lost.
+    fn is_lost(connection: &Connection) -> bool {
+        connection.conn_info().is_some() && !connection.is_connected()
+    }
+
     #[maybe_async]
     async fn _with_connection<F, R>(&self, ip: IpAddr, f: F) -> crate::Result<R>
     where
@@ -584,6 +1048,76 @@ impl Client {
         Ok(resource)
     }
 
+    /// Submits a print job to a printer share.
+    ///
+    /// ## Arguments
+    /// * `path` - The UNC path of the print file, on a printer share the client is connected to.
+    ///   The file name is usually displayed by the server as the job's document name.
+    /// * `data` - The data to print, passed to the printer as-is.
+    ///
+    /// See [`Tree::print`] for more information.
+    pub async fn print(&self, path: &UncPath, data: &[u8]) -> crate::Result<()> {
+        let tree = self.get_tree(path).await?;
+        tree.print(path.path().unwrap_or_default(), data).await
+    }
+
+    /// Renames (moves) a file or directory. Both paths must be on the same share.
+    ///
+    /// See [`Tree::rename`] for more information.
+    pub async fn rename(&self, from: &UncPath, to: &UncPath, replace: bool) -> crate::Result<()> {
+        let to = Self::same_share_path(from, to)?;
+        let tree = self.get_tree(from).await?;
+        tree.rename(from.path().unwrap_or_default(), to, replace)
+            .await
+    }
+
+    /// Creates a new hard link to an existing file. Both paths must be on the same share.
+    ///
+    /// See [`Tree::hard_link`] for more information.
+    pub async fn hard_link(&self, original: &UncPath, link: &UncPath) -> crate::Result<()> {
+        let link = Self::same_share_path(original, link)?;
+        let tree = self.get_tree(original).await?;
+        tree.hard_link(original.path().unwrap_or_default(), link)
+            .await
+    }
+
+    /// Removes a file. See [`Tree::remove_file`] for more information.
+    pub async fn remove_file(&self, path: &UncPath) -> crate::Result<()> {
+        let tree = self.get_tree(path).await?;
+        tree.remove_file(path.path().unwrap_or_default()).await
+    }
+
+    /// Removes an empty directory. See [`Tree::remove_dir`] for more information.
+    pub async fn remove_dir(&self, path: &UncPath) -> crate::Result<()> {
+        let tree = self.get_tree(path).await?;
+        tree.remove_dir(path.path().unwrap_or_default()).await
+    }
+
+    /// Creates a directory and all of its missing parents. See [`Tree::create_dir_all`] for more information.
+    pub async fn create_dir_all(&self, path: &UncPath) -> crate::Result<()> {
+        let tree = self.get_tree(path).await?;
+        tree.create_dir_all(path.path().unwrap_or_default()).await
+    }
+
+    /// Removes a directory, after removing all of its contents. See [`Tree::remove_dir_all`] for more information.
+    pub async fn remove_dir_all(&self, path: &UncPath) -> crate::Result<()> {
+        let tree = self.get_tree(path).await?;
+        tree.remove_dir_all(path.path().unwrap_or_default()).await
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the path of `other` in the share, making sure it is on the same share as `path`.
+    fn same_share_path<'a>(path: &UncPath, other: &'a UncPath) -> crate::Result<&'a str> {
+        let share = path.clone().with_no_path();
+        if other.clone().with_no_path() != share {
+            return Err(Error::InvalidArgument(format!(
+                "{other} must be on the same share as {path}"
+            )));
+        }
+        Ok(other.path().unwrap_or_default())
+    }
+
     /// Similar [`Client::share_connect`], but connects to the SMB pipes share (IPC$).
     ///
     /// After calling this method, the [`Client::open_pipe`] method can be used to open named pipes.
@@ -635,6 +1169,31 @@ impl Client {
         }
     }
 
+    /// Waits until an instance of a named pipe on the specified server is available for connection,
+    /// before opening it with [`Client::open_pipe`].
+    ///
+    /// ## Arguments
+    /// * `server` - The name of the server hosting the pipe.
+    /// * `pipe_name` - The name of the pipe to wait for.
+    /// * `timeout` - The maximum time to wait, or `None` to wait indefinitely.
+    ///
+    /// ## Returns
+    /// Whether an instance of the pipe is available. `false` is returned if the timeout elapsed first.
+    ///
+    /// ## Notes
+    /// Like [`Client::open_pipe`], this method requires calling [`Client::ipc_connect`] first.
+    pub async fn wait_named_pipe(
+        &self,
+        server: &str,
+        pipe_name: &str,
+        timeout: Option<std::time::Duration>,
+    ) -> crate::Result<bool> {
+        let tree = self.get_tree(&UncPath::ipc_share(server)?).await?;
+        tree.as_ipc_tree()?
+            .wait_named_pipe(pipe_name, timeout)
+            .await
+    }
+
     /// If multi-channel is enabled in the client configuration, and the server supports it,
     /// this method will attempt to establish an additional channel to the server,
     /// using a different network interface, if available.
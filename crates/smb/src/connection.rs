@@ -351,6 +351,7 @@ impl Connection {
             preauth_hash,
             client_guid: self.handler.client_guid,
             server_address,
+            oplock_breaks: Default::default(),
         })
     }
 
@@ -432,7 +433,7 @@ impl Connection {
                 .with_leasing(true)
                 .with_large_mtu(true)
                 .with_multi_channel(self.config.multichannel.is_enabled())
-                .with_persistent_handles(false)
+                .with_persistent_handles(true)
                 .with_directory_leasing(true);
 
             if has_encryption {
@@ -490,8 +491,9 @@ impl Connection {
             .negotaite_complete(&info)
             .await;
 
+        // Oplock & lease breaks are sent regardless of the notifications capability.
         #[cfg(not(feature = "single_threaded"))]
-        if !self.config.disable_notifications && info.negotiation.caps.notifications() {
+        if !self.config.disable_notifications {
             log::debug!("Starting Notification job.");
             self.handler.handler.start_notify().await?;
             log::debug!("Notification job started.");
@@ -516,10 +518,36 @@ impl Connection {
     /// ## Notes:
     /// * Use the [`ConnectionConfig`] to configure authentication options.
     pub async fn authenticate(&self, identity: sspi::AuthIdentity) -> crate::Result<Session> {
+        self._authenticate(identity, None).await
+    }
+
+    /// Starts a new session for the current connection, replacing a session that was
+    /// lost along with a previous connection to the server.
+    ///
+    /// The server uses the previous session ID to release any state held for the lost session,
+    /// except for durable opens, which may then be reclaimed over the new session.
+    ///
+    /// Reference: MS-SMB2 3.2.4.2.3
+    pub async fn reauthenticate(
+        &self,
+        identity: sspi::AuthIdentity,
+        previous_session_id: u64,
+    ) -> crate::Result<Session> {
+        self._authenticate(identity, Some(previous_session_id))
+            .await
+    }
+
+    #[maybe_async]
+    async fn _authenticate(
+        &self,
+        identity: sspi::AuthIdentity,
+        previous_session_id: Option<u64>,
+    ) -> crate::Result<Session> {
         let session = Session::create(
             identity,
             &self.handler,
             self.handler.conn_info.get().unwrap(),
+            previous_session_id,
         )
         .await?;
         let session_handler = session.handler.weak();
@@ -531,6 +559,12 @@ impl Connection {
         Ok(session)
     }
 
+    /// Returns whether the connection is established, and was not stopped -
+    /// either using [`Connection::close`], or since the transport was lost.
+    pub fn is_connected(&self) -> bool {
+        self.handler.worker().is_some_and(|w| !w.is_stopped())
+    }
+
     /// Returns the connection information, if the connection has been negotiated.
     /// Otherwise, returns `None`.
     pub fn conn_info(&self) -> Option<&Arc<ConnectionInfo>> {
@@ -576,7 +610,7 @@ impl ConnectionMessageHandler {
             client_guid,
             worker: OnceCell::new(),
             conn_info: OnceCell::new(),
-            credits_backlog: credits_backlog.unwrap_or(128),
+            credits_backlog: credits_backlog.unwrap_or(Self::DEFAULT_CREDITS_BACKLOG),
             curr_credits: Semaphore::new(1),
             curr_msg_id: AtomicU64::new(0),
             credit_pool: AtomicU16::new(1),
@@ -597,41 +631,64 @@ impl ConnectionMessageHandler {
         Command::QueryDirectory,
     ];
 
-    const CREDIT_CALC_RATIO: u32 = 65536;
+    /// The number of payload bytes each credit is charged for, when large MTU is enabled.
+    pub(crate) const CREDIT_CALC_RATIO: u32 = 65536;
+    /// The default value of [`ConnectionConfig::credits_backlog`].
+    pub(crate) const DEFAULT_CREDITS_BACKLOG: u16 = 128;
     const CREDITS_PER_MSG_NO_LARGE_MTU: u32 = 1;
 
+    /// Returns the credit charge of a request, according to its payload size.
+    fn credit_charge(message: &PlainRequest) -> u16 {
+        if Self::SET_CREDIT_CHARGE_CMDS.contains(&message.header.command) {
+            let send_payload_size = message.content.req_payload_size();
+            let expected_response_payload_size = message.content.expected_resp_size();
+            (1 + (max(send_payload_size, expected_response_payload_size) - 1)
+                / Self::CREDIT_CALC_RATIO)
+                .try_into()
+                .unwrap()
+        } else {
+            1
+        }
+    }
+
     #[maybe_async]
     async fn process_sequence_outgoing(&self, msg: &mut OutgoingMessage) -> crate::Result<()> {
         if let Some(neg) = self.conn_info.get() {
             if neg.negotiation.caps.large_mtu() {
-                // Calculate the cost of the message (charge).
-                let cost = if Self::SET_CREDIT_CHARGE_CMDS.contains(&msg.message.header.command) {
-                    let send_payload_size = msg.message.content.req_payload_size();
-                    let expected_response_payload_size = msg.message.content.expected_resp_size();
-                    (1 + (max(send_payload_size, expected_response_payload_size) - 1)
-                        / Self::CREDIT_CALC_RATIO)
-                        .try_into()
-                        .unwrap()
-                } else {
-                    1
-                };
+                // Calculate the cost of each message (charge).
+                let costs = std::iter::once(&msg.message)
+                    .chain(msg.compound.iter())
+                    .map(Self::credit_charge)
+                    .collect::<Vec<_>>();
+                let total_cost: u16 = costs.iter().sum();
 
                 // First, acquire credits from the semaphore, and forget them.
                 // They may be returned via the response message, at `process_sequence_incoming` below.
-                self.curr_credits.acquire_many(cost as u32).await?.forget();
+                self.curr_credits
+                    .acquire_many(total_cost as u32)
+                    .await?
+                    .forget();
+
+                // Compounded messages take consecutive message IDs, each charged separately.
+                let mut message_id = self
+                    .curr_msg_id
+                    .fetch_add(total_cost as u64, Ordering::SeqCst);
+                for (message, cost) in std::iter::once(&mut msg.message)
+                    .chain(msg.compound.iter_mut())
+                    .zip(costs)
+                {
+                    message.header.credit_charge = cost;
+                    message.header.credit_request = cost;
+                    message.header.message_id = message_id;
+                    message_id += cost as u64;
+                }
 
-                let mut request = cost;
                 // Request additional credits if required: if balance < extra, add to request the diff:
                 let current_pool_size = self.credit_pool.load(Ordering::SeqCst);
                 if current_pool_size < self.credits_backlog {
-                    request += self.credits_backlog - current_pool_size;
+                    msg.message.header.credit_request += self.credits_backlog - current_pool_size;
                 }
 
-                msg.message.header.credit_charge = cost;
-                msg.message.header.credit_request = request;
-                msg.message.header.message_id =
-                    self.curr_msg_id.fetch_add(cost as u64, Ordering::SeqCst);
-
                 return Ok(());
             } else {
                 debug_assert_eq!(msg.message.header.credit_request, 0);
@@ -639,6 +696,12 @@ impl ConnectionMessageHandler {
             }
         }
 
+        if !msg.compound.is_empty() {
+            return Err(Error::UnsupportedOperation(
+                "Compounded requests require the large MTU capability".into(),
+            ));
+        }
+
         // Default case: logically waiting for single credit per message,
         // which will make the client wait for next response before allowing next request.
         self.curr_credits
@@ -826,6 +889,17 @@ impl MessageHandler for ConnectionMessageHandler {
 
     #[maybe_async]
     async fn notify(&self, msg: IncomingMessage) -> crate::Result<()> {
+        // Breaks are routed by file ID/lease key, since lease breaks carry no session ID.
+        if matches!(
+            msg.message.content,
+            ResponseContent::OplockBreakNotify(_) | ResponseContent::LeaseBreakNotify(_)
+        ) {
+            let conn_info = self.conn_info.get().ok_or_else(|| {
+                Error::InvalidState("Received oplock break before negotiation".to_string())
+            })?;
+            return conn_info.oplock_breaks.dispatch(msg).await;
+        }
+
         if msg.message.header.session_id == 0 {
             log::warn!("Received notification without session ID: {msg:?}");
             return Ok(());
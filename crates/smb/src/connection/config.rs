@@ -127,7 +127,8 @@ pub struct ConnectionConfig {
     pub client_name: Option<String>,
 
     /// Specifies whether to disable support for Server-to-client notifications.
-    /// If set to true, the client will NOT support notifications.
+    /// If set to true, the client will NOT support notifications,
+    /// and oplocks or leases may not be requested.
     pub disable_notifications: bool,
 
     /// Whether to avoid multi-protocol negotiation,
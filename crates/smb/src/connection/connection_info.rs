@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use crate::{connection::preauth_hash::PreauthHashState, dialects::DialectImpl};
+use crate::{
+    connection::preauth_hash::PreauthHashState, dialects::DialectImpl, resource::OplockBreakRouter,
+};
 use binrw::prelude::*;
 use smb_dtyp::Guid;
 use smb_msg::*;
@@ -65,4 +67,6 @@ pub struct ConnectionInfo {
     pub preauth_hash: PreauthHashState,
     /// The client GUID used for the connection.
     pub client_guid: Guid,
+    /// Routes oplock & lease breaks to the opens of the connection.
+    pub(crate) oplock_breaks: OplockBreakRouter,
 }
@@ -156,60 +156,87 @@ impl Transformer {
     }
 
     /// Transforms an outgoing message to a raw SMB message.
+    ///
+    /// Requests compounded to the message are written after it, each padded to 8 bytes and
+    /// signed separately. The whole chain is then encrypted as a single message, if required.
     pub async fn transform_outgoing(&self, mut msg: OutgoingMessage) -> crate::Result<IoVec> {
         let should_encrypt = msg.encrypt;
         let should_sign = msg.message.header.flags.signed();
         let session_id = msg.message.header.session_id;
-
-        let mut outgoing_data = IoVec::default();
-        // Plain header + content
-        {
-            let buffer = outgoing_data.add_owned(Vec::with_capacity(Header::STRUCT_SIZE));
-            msg.message.write(&mut Cursor::new(buffer))?;
-        }
-        // Additional data, if any
-        if msg.additional_data.as_ref().is_some_and(|d| !d.is_empty()) {
-            outgoing_data.add_shared(msg.additional_data.unwrap().clone());
-        }
-
-        // 1. Sign
-        if should_sign {
-            debug_assert!(
-                !should_encrypt,
-                "Should not sign and encrypt at the same time!"
-            );
-
-            let mut signer = self
-                ._with_channel(session_id, |session| {
-                    let channel_info =
-                        session
-                            .channel
-                            .as_ref()
-                            .ok_or(crate::Error::TranformFailed(TransformError {
+        let message_id = msg.message.header.message_id;
+
+        let mut signer =
+            if should_sign {
+                debug_assert!(
+                    !should_encrypt,
+                    "Should not sign and encrypt at the same time!"
+                );
+
+                let signer = self
+                    ._with_channel(session_id, |session| {
+                        let channel_info = session.channel.as_ref().ok_or(
+                            crate::Error::TranformFailed(TransformError {
                                 outgoing: true,
                                 phase: TransformPhase::SignVerify,
                                 session_id: Some(session_id),
                                 why: "Message is required to be signed, but no channel is set up!",
-                                msg_id: Some(msg.message.header.message_id),
-                            }))?;
+                                msg_id: Some(message_id),
+                            }),
+                        )?;
+
+                        Ok(channel_info.signer()?.clone())
+                    })
+                    .await?;
+                Some(signer)
+            } else {
+                None
+            };
 
-                    Ok(channel_info.signer()?.clone())
-                })
-                .await?;
+        let compound = std::mem::take(&mut msg.compound);
+        let is_compound = !compound.is_empty();
+        let message_count = compound.len() + 1;
+        let first_header = msg.message.header.clone();
+        let mut additional_data = msg.additional_data.take();
 
-            signer.sign_message(&mut msg.message.header, &mut outgoing_data)?;
+        let mut outgoing_data = IoVec::default();
+        let messages = std::iter::once(msg.message).chain(compound);
+        for (index, mut message) in messages.enumerate() {
+            if index > 0 {
+                // Compounded requests are sent on behalf of the same session & tree as the first one.
+                message.header.session_id = first_header.session_id;
+                if !message.header.flags.async_command() {
+                    message.header.tree_id = first_header.tree_id;
+                }
+                message.header.flags = message
+                    .header
+                    .flags
+                    .with_signed(first_header.flags.signed())
+                    .with_priority_mask(first_header.flags.priority_mask());
+            }
 
-            log::debug!(
-                "Message #{} signed (signature={}).",
-                msg.message.header.message_id,
-                msg.message.header.signature
-            );
-        };
+            let has_next = index + 1 < message_count;
+            let mut message_data =
+                Self::write_plain(&mut message, additional_data.take(), has_next)?;
+
+            // 1. Sign
+            if let Some(signer) = signer.as_mut() {
+                signer.sign_message(&mut message.header, &mut message_data)?;
+
+                log::debug!(
+                    "Message #{} signed (signature={}).",
+                    message.header.message_id,
+                    message.header.signature
+                );
+            }
+
+            outgoing_data.append(message_data);
+        }
 
         // 2. Compress
         const COMPRESSION_THRESHOLD: usize = 1024;
         outgoing_data = {
-            if msg.compress && outgoing_data.total_size() > COMPRESSION_THRESHOLD {
+            // Compounded messages are not compressed, since chained compression is not supported.
+            if msg.compress && !is_compound && outgoing_data.total_size() > COMPRESSION_THRESHOLD {
                 let rconfig = self.config.read().await?;
                 if let Some(compress) = &rconfig.compress {
                     // Build a vector of the entire data. In the future, this may be optimized to avoid copying.
@@ -240,14 +267,14 @@ impl Transformer {
                             phase: TransformPhase::EncryptDecrypt,
                             session_id: Some(session_id),
                             why: "Message is required to be encrypted, but no encryptor is set up!",
-                            msg_id: Some(msg.message.header.message_id),
+                            msg_id: Some(message_id),
                         },
                     ))?;
                     Ok(encryptor.clone())
                 })
                 .await?;
 
-            debug_assert!(should_encrypt && !should_sign);
+            debug_assert!(should_encrypt && signer.is_none());
 
             let encrypted_header = encryptor.encrypt_message(&mut outgoing_data, session_id)?;
 
@@ -260,8 +287,49 @@ impl Transformer {
         Ok(outgoing_data)
     }
 
-    /// Transforms an incoming message buffer to an [`IncomingMessage`].
-    pub async fn transform_incoming(&self, data: Vec<u8>) -> crate::Result<IncomingMessage> {
+    /// (Internal)
+    ///
+    /// Writes a plain message, followed by its additional data, if any.
+    ///
+    /// If another message is compounded after this one, the message is padded to an 8-byte boundary,
+    /// and the next command offset of the header is set accordingly.
+    fn write_plain(
+        message: &mut PlainRequest,
+        additional_data: Option<Arc<[u8]>>,
+        has_next: bool,
+    ) -> crate::Result<IoVec> {
+        let mut message_data = IoVec::default();
+        // Plain header + content
+        {
+            let buffer = message_data.add_owned(Vec::with_capacity(Header::STRUCT_SIZE));
+            message.write(&mut Cursor::new(buffer))?;
+        }
+        // Additional data, if any
+        if let Some(additional_data) = additional_data.filter(|d| !d.is_empty()) {
+            message_data.add_shared(additional_data);
+        }
+
+        if has_next {
+            let size = message_data.total_size();
+            let padded_size = size.next_multiple_of(8);
+            if padded_size > size {
+                message_data.add_owned(vec![0; padded_size - size]);
+            }
+
+            message.header.next_command = padded_size as u32;
+            let header_buffer = message_data.first_mut().unwrap();
+            message
+                .header
+                .write(&mut Cursor::new(&mut header_buffer[..Header::STRUCT_SIZE]))?;
+        }
+
+        Ok(message_data)
+    }
+
+    /// Transforms an incoming message buffer to [`IncomingMessage`]s.
+    ///
+    /// A buffer contains more than one message if the server compounded its responses.
+    pub async fn transform_incoming(&self, data: Vec<u8>) -> crate::Result<Vec<IncomingMessage>> {
         let message = Response::try_from(data.as_ref())?;
 
         let mut form = MessageForm::default();
@@ -316,27 +384,70 @@ impl Transformer {
             _ => panic!("Unexpected message type"),
         };
 
-        let iovec = IoVec::from(raw);
-        // If fails, return TranformFailed, with message id.
-        // this allows to notify the error to the task that was waiting for this message.
-        match self
-            .verify_plain_incoming(&mut message, &iovec, &mut form)
+        // Single message - no need to split the buffer.
+        if message.header.next_command == 0 {
+            let iovec = IoVec::from(raw);
+            self.verify_incoming(&mut message, &iovec, &mut form)
+                .await?;
+            return Ok(vec![IncomingMessage::new(message, iovec, form)]);
+        }
+
+        // Compounded responses: each message is verified over its own part of the buffer.
+        let mut messages = vec![];
+        let mut offset = 0;
+        loop {
+            let next_command = message.header.next_command as usize;
+            let end = if next_command == 0 {
+                raw.len()
+            } else {
+                offset + next_command
+            };
+            if next_command % 8 != 0 || end > raw.len() {
+                return Err(crate::Error::InvalidMessage(format!(
+                    "Invalid next command offset {next_command} in compounded message #{}",
+                    message.header.message_id
+                )));
+            }
+
+            let iovec = IoVec::from(raw[offset..end].to_vec());
+            let mut message_form = form.clone();
+            self.verify_incoming(&mut message, &iovec, &mut message_form)
+                .await?;
+            messages.push(IncomingMessage::new(message, iovec, message_form));
+
+            if next_command == 0 {
+                break;
+            }
+            offset = end;
+            message = PlainResponse::read(&mut Cursor::new(&raw[offset..]))?;
+        }
+
+        Ok(messages)
+    }
+
+    /// (Internal)
+    ///
+    /// Verifies an incoming message. If fails, returns [`TranformFailed`][crate::Error::TranformFailed],
+    /// with the message id. This allows to notify the error to the task that was waiting for this message.
+    #[maybe_async]
+    async fn verify_incoming(
+        &self,
+        message: &mut PlainResponse,
+        raw: &IoVec,
+        form: &mut MessageForm,
+    ) -> crate::Result<()> {
+        self.verify_plain_incoming(message, raw, form)
             .await
-        {
-            Ok(_) => {}
-            Err(e) => {
+            .map_err(|e| {
                 log::error!("Failed to verify incoming message: {e:?}",);
-                return Err(crate::Error::TranformFailed(TransformError {
+                crate::Error::TranformFailed(TransformError {
                     outgoing: false,
                     phase: TransformPhase::SignVerify,
                     session_id: Some(message.header.session_id),
                     why: "Failed to verify incoming message!",
                     msg_id: Some(message.header.message_id),
-                }));
-            }
-        };
-
-        Ok(IncomingMessage::new(message, iovec, form))
+                })
+            })
     }
 
     /// (Internal)
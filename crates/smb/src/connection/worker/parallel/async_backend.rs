@@ -33,6 +33,7 @@ impl AsyncBackend {
                 Ok(_) => {}
                 Err(Error::TransportError(TransportError::NotConnected)) => {
                     log::error!("Connection was force-closed by the server.");
+                    worker.transport_lost();
                     self_ref.token.cancel();
                     break;
                 }
@@ -70,6 +71,7 @@ impl AsyncBackend {
                 Ok(_) => {}
                 Err(Error::TransportError(TransportError::NotConnected)) => {
                     log::error!("Connection was force-closed by the server.");
+                    worker.transport_lost();
                     self_ref.token.cancel();
                     break;
                 }
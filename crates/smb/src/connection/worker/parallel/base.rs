@@ -90,6 +90,13 @@ where
         self.stopped.load(std::sync::atomic::Ordering::SeqCst)
     }
 
+    /// Marks the worker as stopped, after the transport was lost,
+    /// so further requests fail with [`Error::ConnectionStopped`].
+    pub(crate) fn transport_lost(&self) {
+        self.stopped
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
     /// This is a function that should be used by multi worker implementations (async/mtd),
     /// after gettting a messages from the server, this function processes it and
     /// notifies the awaiting tasks.
@@ -101,24 +108,37 @@ where
         let message = message?;
 
         // Tranform the message and verify it.
-        let msg = self.transformer.transform_incoming(message).await;
-        let (msg, msg_id) = match msg {
-            // Good flow, message is OK.
-            Ok(msg) => {
-                let msg_id = msg.message.header.message_id;
-                (Ok(msg), msg_id)
+        let msgs = self.transformer.transform_incoming(message).await;
+        match msgs {
+            // Good flow, messages are OK.
+            Ok(msgs) => {
+                for msg in msgs {
+                    let msg_id = msg.message.header.message_id;
+                    self.dispatch_incoming(Ok(msg), msg_id).await?;
+                }
+                Ok(())
             }
             // If we have a message ID to notify the error, use it.
             Err(crate::Error::TranformFailed(e)) => match e.msg_id {
-                Some(msg_id) => (Err(crate::Error::TranformFailed(e)), msg_id),
-                None => return Err(Error::TranformFailed(e)),
+                Some(msg_id) => {
+                    self.dispatch_incoming(Err(crate::Error::TranformFailed(e)), msg_id)
+                        .await
+                }
+                None => Err(Error::TranformFailed(e)),
             },
             Err(e) => {
                 log::error!("Failed to transform message: {e:?}");
-                return Err(e);
+                Err(e)
             }
-        };
+        }
+    }
 
+    /// Notifies the task awaiting an incoming message, or stores the message until awaited.
+    async fn dispatch_incoming(
+        self: &Arc<Self>,
+        msg: crate::Result<IncomingMessage>,
+        msg_id: u64,
+    ) -> crate::Result<()> {
         // Message ID (-1) is used and valid for notifications -
         // OPLOCK_BREAK or SERVER_TO_CLIENT_NOTIFICATION only.
         if msg_id == u64::MAX {
@@ -131,6 +151,7 @@ where
             if !matches!(
                 msg.message.content,
                 ResponseContent::OplockBreakNotify(_)
+                    | ResponseContent::LeaseBreakNotify(_)
                     | ResponseContent::ServerToClientNotification(_)
             ) {
                 return Err(Error::MessageProcessingError(
@@ -252,9 +273,13 @@ where
 
     async fn send(&self, msg: OutgoingMessage) -> crate::Result<SendMessageResult> {
         log::trace!("ParallelWorker::send({msg:?}) called");
+        if self.stopped() {
+            return Err(Error::ConnectionStopped);
+        }
         let return_raw_data = msg.return_raw_data;
 
         let id = msg.message.header.message_id;
+        let compound_msg_ids = msg.compound.iter().map(|m| m.header.message_id).collect();
         let message = { self.transformer.transform_outgoing(msg).await? };
 
         log::trace!("Message with ID {id} is passed to the worker for sending",);
@@ -271,7 +296,7 @@ where
             Error::MessageProcessingError("Failed to send message to worker!".to_string())
         })?;
 
-        Ok(SendMessageResult::new(id, raw_message_copy))
+        Ok(SendMessageResult::new(id, raw_message_copy).with_compound_msg_ids(compound_msg_ids))
     }
 
     async fn receive_next(&self, options: &ReceiveOptions<'_>) -> crate::Result<IncomingMessage> {
@@ -312,6 +337,10 @@ where
         Ok(result)
     }
 
+    fn is_stopped(&self) -> bool {
+        self.stopped()
+    }
+
     fn transformer(&self) -> &Transformer {
         &self.transformer
     }
@@ -39,6 +39,7 @@ impl ThreadingBackend {
                 Ok(_) => {}
                 Err(Error::TransportError(TransportError::NotConnected)) => {
                     log::error!("Connection closed.");
+                    self.worker.transport_lost();
                     self.stopped
                         .store(true, std::sync::atomic::Ordering::SeqCst);
                     break;
@@ -69,6 +70,7 @@ impl ThreadingBackend {
                 Ok(_) => {}
                 Err(Error::TransportError(TransportError::NotConnected)) => {
                     log::error!("Connection closed.");
+                    self.worker.transport_lost();
                     self.stopped
                         .store(true, std::sync::atomic::Ordering::SeqCst);
                     break;
@@ -148,8 +150,8 @@ impl MultiWorkerBackend for ThreadingBackend {
             .take()
             .ok_or(Error::ConnectionStopped)?;
 
-        // wake up the sender to stop the loop.
-        self.worker.sender.send(None).unwrap();
+        // wake up the sender to stop the loop. It might have already stopped if the transport was lost.
+        self.worker.sender.send(None).ok();
 
         // Join the threads.
         handles
@@ -6,6 +6,7 @@ use crate::{
 use smb_transport::{SmbTransport, TransportError};
 use std::sync::OnceLock;
 use std::{
+    collections::VecDeque,
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -20,6 +21,8 @@ pub struct SingleWorker {
     transport: Mutex<OnceLock<Box<dyn SmbTransport>>>,
     transformer: Transformer,
     timeout: Mutex<Option<Duration>>,
+    /// Compounded responses, received along with a previous message, in order.
+    pending: Mutex<VecDeque<IncomingMessage>>,
 }
 
 impl Worker for SingleWorker {
@@ -29,6 +32,7 @@ impl Worker for SingleWorker {
             transport: Mutex::new(OnceLock::from(transport)),
             transformer: Transformer::default(),
             timeout: Mutex::new(Some(timeout)),
+            pending: Default::default(),
         }))
     }
 
@@ -42,14 +46,21 @@ impl Worker for SingleWorker {
 
     fn send(&self, msg: OutgoingMessage) -> crate::Result<SendMessageResult> {
         let msg_id = msg.message.header.message_id;
+        let compound_msg_ids = msg.compound.iter().map(|m| m.header.message_id).collect();
         let return_raw_data = msg.return_raw_data;
 
         let msg_to_send = self.transformer.transform_outgoing(msg)?;
 
         let mut t = self.transport.lock()?;
-        t.get_mut()
+        let sent = t
+            .get_mut()
             .ok_or(crate::Error::ConnectionStopped)?
-            .send(&msg_to_send)?;
+            .send(&msg_to_send);
+        if let Err(TransportError::NotConnected) = sent {
+            // The transport is lost for good, stop the worker.
+            t.take();
+        }
+        sent?;
 
         let raw_msg = if return_raw_data {
             Some(msg_to_send)
@@ -57,10 +68,15 @@ impl Worker for SingleWorker {
             None
         };
 
-        Ok(SendMessageResult::new(msg_id, raw_msg))
+        Ok(SendMessageResult::new(msg_id, raw_msg).with_compound_msg_ids(compound_msg_ids))
     }
 
     fn receive_next(&self, options: &ReceiveOptions<'_>) -> crate::Result<IncomingMessage> {
+        // Compounded responses are received before any other message.
+        if let Some(im) = self.pending.lock()?.pop_front() {
+            return Self::check_msg_id(im, options);
+        }
+
         // Receive next message
         let mut self_mut = self.transport.lock()?;
         let transport = self_mut.get_mut().ok_or(crate::Error::ConnectionStopped)?;
@@ -70,7 +86,12 @@ impl Worker for SingleWorker {
             unimplemented!("Receive with timeout is not supported in SingleWorker");
         }
 
-        let msg = transport.receive().map_err(|e| match e {
+        let msg = transport.receive();
+        if let Err(TransportError::NotConnected) = msg {
+            // The transport is lost for good, stop the worker.
+            self_mut.take();
+        }
+        let msg = msg.map_err(|e| match e {
             TransportError::IoError(ioe) => {
                 if ioe.kind() == std::io::ErrorKind::WouldBlock {
                     Error::OperationTimeout(
@@ -87,8 +108,32 @@ impl Worker for SingleWorker {
             _ => e.into(),
         })?;
         // Transform the message
-        let im = self.transformer.transform_incoming(msg)?;
-        // Make sure this is our message.
+        let mut messages = VecDeque::from(self.transformer.transform_incoming(msg)?);
+        let im = messages.pop_front().ok_or_else(|| {
+            crate::Error::InvalidMessage("Received a frame with no messages".to_string())
+        })?;
+        self.pending.lock()?.extend(messages);
+        Self::check_msg_id(im, options)
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.transport
+            .lock()
+            .map(|t| t.get().is_none())
+            .unwrap_or(true)
+    }
+
+    fn transformer(&self) -> &Transformer {
+        &self.transformer
+    }
+}
+
+impl SingleWorker {
+    /// Makes sure this is our message.
+    fn check_msg_id(
+        im: IncomingMessage,
+        options: &ReceiveOptions<'_>,
+    ) -> crate::Result<IncomingMessage> {
         // In async clients, this is no issue, but here, we can't deal with unordered/unexpected message IDs.
         if im.message.header.message_id != options.msg_id {
             return Err(crate::Error::UnexpectedMessageId(
@@ -98,10 +143,6 @@ impl Worker for SingleWorker {
         }
         Ok(im)
     }
-
-    fn transformer(&self) -> &Transformer {
-        &self.transformer
-    }
 }
 
 impl std::fmt::Debug for SingleWorker {
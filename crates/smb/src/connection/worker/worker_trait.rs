@@ -160,6 +160,10 @@ pub trait Worker: Sized + std::fmt::Debug {
         }
     }
 
+    /// Returns whether the worker is stopped - either using [`Worker::stop`],
+    /// or since the underlying transport was lost.
+    fn is_stopped(&self) -> bool;
+
     /// Get the transformer for this worker.
     fn transformer(&self) -> &Transformer;
 
@@ -28,6 +28,18 @@ pub enum Error {
     #[error("Client connection is stopped")]
     ConnectionStopped,
 
+    /// Indicates the connection was re-established after it was lost,
+    /// but the open of the specified resource could not be reclaimed.
+    /// The resource must be opened again.
+    #[error("Connection was re-established, but the handle to {0} was lost")]
+    HandleLost(String),
+
+    /// Indicates the connection the specified resource was opened on is stopped.
+    /// Call [`Client::reconnect`][crate::Client::reconnect] (or access the server's shares through the client),
+    /// and then use the resource again, or open it again if it fails with [`Error::HandleLost`].
+    #[error("Connection of {0} is stopped, and must be re-established")]
+    ReconnectRequired(String),
+
     #[error("Operation cancelled: {0}")]
     Cancelled(&'static str),
 
@@ -126,6 +138,62 @@ pub enum Error {
     Other(&'static str),
 }
 
+impl Error {
+    /// Returns the status the server responded with, if the error is an error response from the server.
+    pub fn status(&self) -> Option<Status> {
+        match self {
+            Error::ReceivedErrorMessage(status, _) | Error::UnexpectedMessageStatus(status) => {
+                Status::try_from(*status).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the [`std::io::ErrorKind`] matching the error, similar to the errors
+    /// returned by the [`std::fs`] functions.
+    pub fn kind(&self) -> std::io::ErrorKind {
+        use std::io::ErrorKind;
+        match self {
+            Error::IoError(e) => return e.kind(),
+            Error::NotFound(_) => return ErrorKind::NotFound,
+            Error::InvalidArgument(_) => return ErrorKind::InvalidInput,
+            Error::UnsupportedOperation(_) => return ErrorKind::Unsupported,
+            Error::MissingPermissions(_) => return ErrorKind::PermissionDenied,
+            Error::OperationTimeout(..) => return ErrorKind::TimedOut,
+            Error::ConnectionStopped | Error::ReconnectRequired(_) => {
+                return ErrorKind::NotConnected;
+            }
+            _ => {}
+        }
+        match self.status() {
+            Some(
+                Status::ObjectNameNotFound | Status::ObjectPathNotFound | Status::NoSuchDevice,
+            ) => ErrorKind::NotFound,
+            Some(Status::ObjectNameCollision) => ErrorKind::AlreadyExists,
+            Some(Status::AccessDenied | Status::CannotDelete | Status::DeletePending) => {
+                ErrorKind::PermissionDenied
+            }
+            Some(Status::DirectoryNotEmpty) => ErrorKind::DirectoryNotEmpty,
+            Some(Status::FileIsADirectory) => ErrorKind::IsADirectory,
+            Some(Status::NotADirectory) => ErrorKind::NotADirectory,
+            Some(Status::SharingViolation | Status::FileLockConflict) => ErrorKind::ResourceBusy,
+            Some(Status::ObjectNameInvalid | Status::InvalidParameter) => ErrorKind::InvalidInput,
+            Some(Status::NotSupported | Status::NotImplemented) => ErrorKind::Unsupported,
+            Some(Status::IoTimeout) => ErrorKind::TimedOut,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<Error> for std::io::Error {
+    fn from(value: Error) -> Self {
+        match value {
+            Error::IoError(e) => e,
+            e => std::io::Error::new(e.kind(), e),
+        }
+    }
+}
+
 impl<T> From<PoisonError<T>> for Error {
     fn from(_: PoisonError<T>) -> Self {
         Error::LockError
@@ -0,0 +1,310 @@
+//! One-shot file system helpers, modelled after the [`std::fs`] functions.
+//!
+//! Each function opens the resource at the specified [`UncPath`], performs the operation and closes it,
+//! so simple tasks do not require building [`FileCreateArgs`] and managing opens.
+//! The share must be connected first, using [`Client::share_connect`].
+//!
+//! For operations that do not transfer data, such as [`Client::rename`] or [`Client::remove_dir_all`],
+//! see the methods of [`Client`] and [`Tree`][crate::Tree].
+
+use maybe_async::*;
+use smb_fscc::{
+    DirAccessMask, FileAccessMask, FileAllInformation, FileAttributes, FileDirectoryInformation,
+};
+use smb_msg::{CreateDisposition, CreateOptions};
+use time::PrimitiveDateTime;
+
+use crate::{Client, Directory, Error, File, FileCreateArgs, GetLen, UncPath};
+
+/// Metadata of a file or directory, as returned by [`metadata`] and [`read_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// The attributes of the file or directory.
+    pub attributes: FileAttributes,
+    /// The size of the file, in bytes.
+    pub len: u64,
+    /// The number of bytes allocated for the file.
+    pub allocation_size: u64,
+    /// The time the file was created.
+    pub created: PrimitiveDateTime,
+    /// The time the file was last accessed.
+    pub accessed: PrimitiveDateTime,
+    /// The time data was last written to the file.
+    pub modified: PrimitiveDateTime,
+    /// The time the file (data or metadata) was last changed.
+    pub changed: PrimitiveDateTime,
+}
+
+impl Metadata {
+    /// Whether the metadata is of a directory.
+    pub fn is_dir(&self) -> bool {
+        self.attributes.directory()
+    }
+
+    /// Whether the metadata is of a regular file.
+    pub fn is_file(&self) -> bool {
+        !self.is_dir()
+    }
+
+    /// Whether the metadata is of a reparse point, such as a symbolic link.
+    ///
+    /// Metadata returned by [`metadata`] is of the target of links the server follows,
+    /// so this is mostly useful for entries returned by [`read_dir`].
+    pub fn is_reparse_point(&self) -> bool {
+        self.attributes.reparse_point()
+    }
+}
+
+impl From<FileAllInformation> for Metadata {
+    fn from(info: FileAllInformation) -> Self {
+        Self {
+            attributes: info.basic.file_attributes,
+            len: info.standard.end_of_file,
+            allocation_size: info.standard.allocation_size,
+            created: info.basic.creation_time.into(),
+            accessed: info.basic.last_access_time.into(),
+            modified: info.basic.last_write_time.into(),
+            changed: info.basic.change_time.into(),
+        }
+    }
+}
+
+impl From<&FileDirectoryInformation> for Metadata {
+    fn from(info: &FileDirectoryInformation) -> Self {
+        Self {
+            attributes: info.file_attributes,
+            len: info.end_of_file,
+            allocation_size: info.allocation_size,
+            created: info.creation_time.into(),
+            accessed: info.last_access_time.into(),
+            modified: info.last_write_time.into(),
+            changed: info.change_time.into(),
+        }
+    }
+}
+
+/// An entry of a directory, as returned by [`read_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// The name of the entry, without the path of the directory.
+    pub name: String,
+    /// The full path of the entry.
+    pub path: UncPath,
+    /// The metadata of the entry.
+    pub metadata: Metadata,
+}
+
+/// Returns the metadata of a file or directory.
+#[maybe_async]
+pub async fn metadata(client: &Client, path: &UncPath) -> crate::Result<Metadata> {
+    let resource = client
+        .create_file(
+            path,
+            &FileCreateArgs::make_open_existing(
+                FileAccessMask::new().with_file_read_attributes(true),
+            ),
+        )
+        .await?;
+    let handle = resource.handle();
+    let info = handle.query_info::<FileAllInformation>().await;
+    handle.close().await?;
+    Ok(info?.into())
+}
+
+/// Returns whether a file or directory exists at the path.
+///
+/// Errors other than the path not existing, such as access being denied, are returned as errors.
+#[maybe_async]
+pub async fn exists(client: &Client, path: &UncPath) -> crate::Result<bool> {
+    match metadata(client, path).await {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Reads the entire contents of a file.
+#[maybe_async]
+pub async fn read(client: &Client, path: &UncPath) -> crate::Result<Vec<u8>> {
+    let file = open_file(
+        client,
+        path,
+        &FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true)),
+    )
+    .await?;
+    let result = read_all(&file).await;
+    file.close().await?;
+    result
+}
+
+/// Reads the entire contents of a file into a string. The file must be valid UTF-8.
+#[maybe_async]
+pub async fn read_to_string(client: &Client, path: &UncPath) -> crate::Result<String> {
+    let data = read(client, path).await?;
+    String::from_utf8(data)
+        .map_err(|e| Error::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+}
+
+/// Writes data to a file, creating it if it does not exist, and replacing its contents if it does.
+#[maybe_async]
+pub async fn write(client: &Client, path: &UncPath, data: impl AsRef<[u8]>) -> crate::Result<()> {
+    let file = open_file(
+        client,
+        path,
+        &FileCreateArgs {
+            desired_access: FileAccessMask::new().with_generic_write(true),
+            ..FileCreateArgs::make_overwrite(FileAttributes::new(), CreateOptions::new())
+        },
+    )
+    .await?;
+    let result = write_all(&file, data.as_ref(), 0).await;
+    file.close().await?;
+    result
+}
+
+/// Appends data to the end of a file, creating it if it does not exist.
+#[maybe_async]
+pub async fn append(client: &Client, path: &UncPath, data: impl AsRef<[u8]>) -> crate::Result<()> {
+    let file = open_file(
+        client,
+        path,
+        &FileCreateArgs {
+            disposition: CreateDisposition::OpenIf,
+            options: CreateOptions::new().with_non_directory_file(true),
+            ..FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_write(true))
+        },
+    )
+    .await?;
+    let result = match file.get_len().await {
+        Ok(end_of_file) => write_all(&file, data.as_ref(), end_of_file).await,
+        Err(e) => Err(e),
+    };
+    file.close().await?;
+    result
+}
+
+/// Copies the contents of a file to another file, replacing the destination if it exists.
+///
+/// The data is copied by the server where possible, falling back to a copy through the client
+/// (see [`File::copy_range`]).
+///
+/// Returns the number of bytes copied, like [`std::fs::copy`].
+#[maybe_async]
+pub async fn copy(client: &Client, from: &UncPath, to: &UncPath) -> crate::Result<u64> {
+    let source = open_file(
+        client,
+        from,
+        &FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true)),
+    )
+    .await?;
+    let destination = match open_file(
+        client,
+        to,
+        &FileCreateArgs {
+            desired_access: FileAccessMask::new()
+                .with_generic_read(true)
+                .with_generic_write(true),
+            ..FileCreateArgs::make_overwrite(FileAttributes::new(), CreateOptions::new())
+        },
+    )
+    .await
+    {
+        Ok(destination) => destination,
+        Err(e) => {
+            source.close().await?;
+            return Err(e);
+        }
+    };
+
+    let result = destination.copy_from(&source).await;
+    source.close().await?;
+    destination.close().await?;
+    result
+}
+
+/// Returns the entries of a directory, excluding the `.` and `..` entries.
+#[maybe_async]
+pub async fn read_dir(client: &Client, path: &UncPath) -> crate::Result<Vec<DirEntry>> {
+    let dir: Directory = client
+        .create_file(
+            path,
+            &FileCreateArgs {
+                options: CreateOptions::new().with_directory_file(true),
+                ..FileCreateArgs::make_open_existing(
+                    DirAccessMask::new().with_list_directory(true).into(),
+                )
+            },
+        )
+        .await?
+        .try_into()
+        .map_err(|(e, _)| e)?;
+    let entries = dir.query_all::<FileDirectoryInformation>("*").await;
+    dir.close().await?;
+
+    Ok(entries?
+        .iter()
+        .filter_map(|entry| {
+            let name = entry.file_name.to_string();
+            if name == "." || name == ".." {
+                return None;
+            }
+            Some(DirEntry {
+                path: path.clone().with_add_path(&name),
+                metadata: entry.into(),
+                name,
+            })
+        })
+        .collect())
+}
+
+/// (Internal)
+///
+/// Opens the file at the path.
+#[maybe_async]
+async fn open_file(client: &Client, path: &UncPath, args: &FileCreateArgs) -> crate::Result<File> {
+    client
+        .create_file(path, args)
+        .await?
+        .try_into()
+        .map_err(|(e, _)| e)
+}
+
+/// (Internal)
+///
+/// Reads the entire contents of an open file.
+#[maybe_async]
+async fn read_all(file: &File) -> crate::Result<Vec<u8>> {
+    let len = file.get_len().await?;
+    let mut data = vec![0u8; len as usize];
+    let mut offset = 0;
+    while offset < data.len() {
+        let bytes_read = file
+            .read_block(&mut data[offset..], offset as u64, None, false)
+            .await?;
+        if bytes_read == 0 {
+            // The file was truncated since it was opened.
+            data.truncate(offset);
+            break;
+        }
+        offset += bytes_read;
+    }
+    Ok(data)
+}
+
+/// (Internal)
+///
+/// Writes all the data to an open file, starting at the specified offset.
+#[maybe_async]
+async fn write_all(file: &File, data: &[u8], offset: u64) -> crate::Result<()> {
+    let mut written = 0;
+    while written < data.len() {
+        let bytes_written = file
+            .write_block(&data[written..], offset + written as u64, None)
+            .await?;
+        if bytes_written == 0 {
+            return Err(Error::IoError(std::io::ErrorKind::WriteZero.into()));
+        }
+        written += bytes_written;
+    }
+    Ok(())
+}
@@ -25,20 +25,25 @@ pub mod crypto;
 pub mod dialects;
 pub mod docs;
 pub mod error;
+pub mod fs;
 pub mod msg_handler;
 pub mod resource;
 pub mod session;
 pub mod tree;
 
-pub use client::{Client, ClientConfig, UncPath};
+pub use client::{Client, ClientConfig, ReconnectPolicy, UncPath};
 pub use connection::{Connection, ConnectionConfig};
 pub use error::Error;
+#[cfg(feature = "async")]
+pub use resource::{AsyncFile, AsyncIo, AsyncPipe};
 pub use resource::{
-    Directory, File, FileCreateArgs, GetLen, Pipe, PipeRpcConnection, ReadAt, ReadAtChannel,
-    Resource, ResourceHandle, WriteAt, WriteAtChannel,
+    BreakAction, Directory, Durability, DurableHandle, File, FileCreateArgs, FileLock, GetLen,
+    GrantedOplock, LeaseRequest, LockMode, OplockBreak, OplockBreakCallback, OplockRequest, Pipe,
+    PipeRpcConnection, Printer, ReadAt, ReadAtChannel, Resource, ResourceHandle, Snapshot,
+    StreamInfo, WriteAt, WriteAtChannel,
 };
 pub use session::Session;
-pub use tree::{DfsRootTreeRef, Tree};
+pub use tree::{Compound, CompoundResponses, CompoundSlot, DfsRootTreeRef, Tree};
 
 pub use smb_dtyp::*;
 pub use smb_fscc::*;
@@ -27,6 +27,11 @@ pub struct OutgoingMessage {
 
     /// Channel ID to use for this message, if any.
     pub channel_id: Option<u32>,
+
+    /// Requests to compound after this message, sent in the same transport frame.
+    ///
+    /// Headers of compounded requests inherit the session, tree and signing of the first request.
+    pub compound: Vec<PlainRequest>,
 }
 
 impl OutgoingMessage {
@@ -39,6 +44,7 @@ impl OutgoingMessage {
             has_response: true,
             additional_data: None,
             channel_id: None,
+            compound: vec![],
         }
     }
 
@@ -61,6 +67,11 @@ impl OutgoingMessage {
         self.channel_id = channel_id;
         self
     }
+
+    pub fn with_compound(mut self, compound: Vec<PlainRequest>) -> Self {
+        self.compound = compound;
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -69,11 +80,22 @@ pub struct SendMessageResult {
     pub msg_id: u64,
     // If finalized, this is set.
     pub raw: Option<IoVec>,
+    // The message IDs of the requests compounded after the first one, in order.
+    pub compound_msg_ids: Vec<u64>,
 }
 
 impl SendMessageResult {
     pub fn new(msg_id: u64, raw: Option<IoVec>) -> SendMessageResult {
-        SendMessageResult { msg_id, raw }
+        SendMessageResult {
+            msg_id,
+            raw,
+            compound_msg_ids: vec![],
+        }
+    }
+
+    pub fn with_compound_msg_ids(mut self, compound_msg_ids: Vec<u64>) -> Self {
+        self.compound_msg_ids = compound_msg_ids;
+        self
     }
 }
 
@@ -100,7 +122,7 @@ impl IncomingMessage {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct MessageForm {
     pub compressed: bool,
     pub encrypted: bool,
@@ -4,7 +4,7 @@ use std::sync::{
 };
 
 use maybe_async::*;
-use smb_dtyp::SecurityDescriptor;
+use smb_dtyp::{Guid, SecurityDescriptor};
 use smb_fscc::*;
 use smb_msg::*;
 use time::PrimitiveDateTime;
@@ -16,18 +16,40 @@ use crate::{
         AsyncMessageIds, HandlerReference, IncomingMessage, MessageHandler, OutgoingMessage,
         ReceiveOptions, SendMessageResult,
     },
-    tree::TreeMessageHandler,
+    tree::{TreeConnectInfo, TreeMessageHandler},
 };
 
+#[cfg(feature = "async")]
+pub mod async_io;
+pub mod branch_cache;
 pub mod directory;
+pub mod durable;
+pub mod ea;
 pub mod file;
 pub mod file_util;
+pub mod lock;
+pub mod oplock;
 pub mod pipe;
+pub mod printer;
+pub mod reparse;
+pub mod snapshot;
+pub mod sparse;
+pub mod srv_copy;
+pub mod stream;
 
+#[cfg(feature = "async")]
+pub use async_io::*;
+pub use branch_cache::*;
 pub use directory::*;
+pub use durable::*;
 pub use file::*;
 pub use file_util::*;
+pub use lock::*;
+pub use oplock::*;
 pub use pipe::*;
+pub use printer::*;
+pub use snapshot::*;
+pub use stream::*;
 
 type Upstream = HandlerReference<TreeMessageHandler>;
 
@@ -37,6 +59,32 @@ pub struct FileCreateArgs {
     pub attributes: FileAttributes,
     pub options: CreateOptions,
     pub desired_access: FileAccessMask,
+    /// The oplock or lease to request for the open. See [`OplockRequest`].
+    pub oplock: OplockRequest,
+    /// Invoked when the oplock or lease of the open is broken by the server.
+    /// If not set, breaks are acknowledged automatically.
+    pub on_oplock_break: Option<OplockBreakCallback>,
+    /// The durability to request for the open. See [`Durability`].
+    pub durability: Durability,
+    /// The sharing mode of the open, restricting the access of other opens to the resource.
+    ///
+    /// If not set, resources on disk shares are shared for read, write and delete,
+    /// and other resources are not shared. Set to [`ShareAccessFlags::new`] for an exclusive open.
+    pub share_access: Option<ShareAccessFlags>,
+    /// The impersonation level the server should use for the open.
+    pub impersonation_level: ImpersonationLevel,
+    /// Additional create contexts to send with the request,
+    /// such as [`AllocationSize`], [`TimewarpToken`], [`AppInstanceId`] or a [`SecurityDescriptor`].
+    ///
+    /// The contexts of the response are available through [`ResourceHandle::create_contexts`].
+    pub contexts: Vec<CreateContextRequest>,
+    /// Opens the resource as it was in the specified snapshot (previous version),
+    /// using a [`TimewarpToken`] create context. See [`ResourceHandle::enumerate_snapshots`].
+    pub snapshot: Option<Snapshot>,
+    /// Whether to follow relative symbolic links in the path, when the server stops on them.
+    ///
+    /// See [`Tree::create`][crate::Tree::create] for more information.
+    pub follow_symlinks: bool,
 }
 
 impl FileCreateArgs {
@@ -46,6 +94,7 @@ impl FileCreateArgs {
             attributes: FileAttributes::new(),
             options: CreateOptions::new(),
             desired_access: access,
+            ..Default::default()
         }
     }
 
@@ -57,6 +106,7 @@ impl FileCreateArgs {
             attributes,
             options,
             desired_access: FileAccessMask::new().with_generic_all(true),
+            ..Default::default()
         }
     }
 
@@ -69,6 +119,43 @@ impl FileCreateArgs {
             attributes,
             options,
             desired_access: FileAccessMask::new().with_generic_all(true),
+            ..Default::default()
+        }
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the create contexts to send for the open: the maximal access and on-disk ID queries,
+    /// unless overridden by the caller, followed by the caller's contexts and the snapshot token, if set.
+    pub(crate) fn request_contexts(&self) -> Vec<CreateContextRequest> {
+        let defaults: [CreateContextRequest; 2] = [
+            QueryMaximalAccessRequest::default().into(),
+            QueryOnDiskIdReq.into(),
+        ];
+        defaults
+            .into_iter()
+            .filter(|d| !self.contexts.iter().any(|c| c.name == d.name))
+            .chain(self.contexts.iter().cloned())
+            .chain(self.snapshot.map(|s| TimewarpToken::from(s).into()))
+            .collect()
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the share access to request for the open, on a share of the specified type.
+    pub(crate) fn share_access_for(&self, share_type: ShareType) -> ShareAccessFlags {
+        self.share_access
+            .unwrap_or_else(|| Resource::default_share_access(share_type))
+    }
+
+    /// Returns arguments for creating a print file, spooling a new print job.
+    pub fn make_print_job() -> FileCreateArgs {
+        FileCreateArgs {
+            disposition: CreateDisposition::Create,
+            attributes: Default::default(),
+            options: Default::default(),
+            desired_access: FileAccessMask::new().with_generic_write(true),
+            ..Default::default()
         }
     }
 
@@ -81,6 +168,7 @@ impl FileCreateArgs {
             desired_access: FileAccessMask::new()
                 .with_generic_read(true)
                 .with_generic_write(true),
+            ..Default::default()
         }
     }
 }
@@ -90,6 +178,7 @@ pub enum Resource {
     File(File),
     Directory(Directory),
     Pipe(Pipe),
+    Printer(Printer),
 }
 
 impl Resource {
@@ -99,17 +188,10 @@ impl Resource {
         upstream: &Upstream,
         create_args: &FileCreateArgs,
         conn_info: &Arc<ConnectionInfo>,
-        share_type: ShareType,
-        is_dfs: bool,
+        tree_info: &TreeConnectInfo,
     ) -> crate::Result<Resource> {
-        let share_access = if share_type == ShareType::Disk {
-            ShareAccessFlags::new()
-                .with_read(true)
-                .with_write(true)
-                .with_delete(true)
-        } else {
-            ShareAccessFlags::new()
-        };
+        let share_type = tree_info.share_type;
+        let share_access = create_args.share_access_for(share_type);
 
         if share_type == ShareType::Print && create_args.disposition != CreateDisposition::Create {
             return Err(Error::InvalidArgument(
@@ -122,25 +204,162 @@ impl Resource {
                 "Resource name cannot start with a backslash.".to_string(),
             ));
         }
+        if share_type == ShareType::Disk {
+            stream::validate_stream_path(name)?;
+        }
 
-        let mut msg = OutgoingMessage::new(
+        let mut contexts = create_args.request_contexts();
+        if create_args.oplock != OplockRequest::None {
+            Self::check_oplock_request(&create_args.oplock, conn_info)?;
+        }
+        if let OplockRequest::Lease(lease) = create_args.oplock {
+            contexts.push(lease.to_context(conn_info.negotiation.dialect_rev));
+        }
+        Self::check_durability(&create_args.durability, conn_info, tree_info)?;
+        let create_guid = Guid::generate();
+        contexts.extend(create_args.durability.to_context(create_guid));
+
+        let response = Self::send_create(
+            name,
+            upstream,
             CreateRequest {
-                requested_oplock_level: OplockLevel::None,
-                impersonation_level: ImpersonationLevel::Impersonation,
+                requested_oplock_level: create_args.oplock.level(),
+                impersonation_level: create_args.impersonation_level,
                 desired_access: create_args.desired_access,
                 file_attributes: create_args.attributes,
                 share_access,
                 create_disposition: create_args.disposition,
                 create_options: create_args.options,
                 name: name.into(),
-                contexts: vec![
-                    QueryMaximalAccessRequest::default().into(),
-                    QueryOnDiskIdReq.into(),
-                ]
-                .into(),
-            }
-            .into(),
+                contexts: contexts.into(),
+            },
+            tree_info.share_flags.dfs(),
+        )
+        .await?;
+
+        let durable =
+            DurableHandle::from_response(name, create_args, share_access, &response, create_guid);
+        if create_args.durability != Durability::None && durable.is_none() {
+            log::debug!("Durability was not granted for file '{name}'.");
+        }
+
+        Self::from_create_response(
+            name,
+            upstream,
+            response,
+            conn_info,
+            share_type,
+            create_args.on_oplock_break.clone(),
+            durable,
+        )
+        .await
+    }
+
+    /// (Internal)
+    ///
+    /// Reclaims a durable or persistent open, using the reconnect create contexts.
+    ///
+    /// Reference: MS-SMB2 3.2.4.4
+    #[maybe_async]
+    pub(crate) async fn reopen(
+        durable: &DurableHandle,
+        upstream: &Upstream,
+        conn_info: &Arc<ConnectionInfo>,
+        tree_info: &TreeConnectInfo,
+    ) -> crate::Result<Resource> {
+        let response = Self::send_reopen(durable, upstream, conn_info, tree_info).await?;
+
+        // The open keeps its durability, but may be assigned a new file ID.
+        let reopened = DurableHandle {
+            file_id: response.file_id,
+            ..durable.clone()
+        };
+        Self::from_create_response(
+            &durable.name,
+            upstream,
+            response,
+            conn_info,
+            tree_info.share_type,
+            durable.on_oplock_break.clone(),
+            Some(reopened),
+        )
+        .await
+    }
+
+    /// (Internal)
+    ///
+    /// Sends the create request that reclaims a durable open, and returns the server's response.
+    #[maybe_async]
+    async fn send_reopen(
+        durable: &DurableHandle,
+        upstream: &Upstream,
+        conn_info: &Arc<ConnectionInfo>,
+        tree_info: &TreeConnectInfo,
+    ) -> crate::Result<CreateResponse> {
+        if durable.create_guid.is_some() && !conn_info.negotiation.dialect_rev.is_smb3() {
+            return Err(Error::UnsupportedOperation(
+                "Durable handles (v2) require the SMB 3.x dialect family.".to_string(),
+            ));
+        }
+
+        let mut contexts: Vec<CreateContextRequest> = vec![durable.reconnect_context()];
+        if durable.oplock != OplockRequest::None {
+            Self::check_oplock_request(&durable.oplock, conn_info)?;
+        }
+        if let OplockRequest::Lease(lease) = durable.oplock {
+            contexts.push(lease.to_context(conn_info.negotiation.dialect_rev));
+        }
+
+        let response = Self::send_create(
+            &durable.name,
+            upstream,
+            CreateRequest {
+                requested_oplock_level: durable.oplock.level(),
+                impersonation_level: durable.impersonation_level,
+                desired_access: durable.desired_access,
+                file_attributes: durable.attributes,
+                share_access: durable.share_access,
+                create_disposition: CreateDisposition::Open,
+                create_options: durable.options,
+                name: durable.name.as_str().into(),
+                contexts: contexts.into(),
+            },
+            tree_info.share_flags.dfs(),
+        )
+        .await?;
+        log::debug!(
+            "Reclaimed durable open of '{}' ({:?})",
+            durable.name,
+            response.file_id
         );
+        Ok(response)
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the default share access to request for opens on a share of the specified type.
+    fn default_share_access(share_type: ShareType) -> ShareAccessFlags {
+        if share_type == ShareType::Disk {
+            ShareAccessFlags::new()
+                .with_read(true)
+                .with_write(true)
+                .with_delete(true)
+        } else {
+            ShareAccessFlags::new()
+        }
+    }
+
+    /// (Internal)
+    ///
+    /// Sends a create request, and returns the server's response.
+    #[maybe_async]
+    async fn send_create(
+        name: &str,
+        upstream: &Upstream,
+        request: CreateRequest,
+        is_dfs: bool,
+    ) -> crate::Result<CreateResponse> {
+        let mut msg = OutgoingMessage::new(request.into());
         // Make sure to set DFS if required.
         msg.message.header.flags.set_dfs_operation(is_dfs);
 
@@ -150,12 +369,28 @@ impl Resource {
 
         let response = response.message.content.to_create()?;
         log::debug!("Created file '{}', ({:?})", name, response.file_id);
+        Ok(response)
+    }
 
+    /// (Internal)
+    ///
+    /// Builds the resource from the server's create response.
+    #[maybe_async]
+    async fn from_create_response(
+        name: &str,
+        upstream: &Upstream,
+        response: CreateResponse,
+        conn_info: &Arc<ConnectionInfo>,
+        share_type: ShareType,
+        on_oplock_break: Option<OplockBreakCallback>,
+        mut durable: Option<DurableHandle>,
+    ) -> crate::Result<Resource> {
         let is_dir = response.file_attributes.directory();
 
-        // Get maximal access
+        // Get maximal access. Reconnected opens keep the access of the original open.
         let access = CreateContextResponseData::first_mxac(&response.create_contexts)
             .and_then(|r| r.maximal_access())
+            .or_else(|| durable.as_ref().and_then(|d| d.maximal_access))
             .unwrap_or_else(|| {
                     log::debug!(
                         "No maximal access context found for file '{name}', using default (full access)."
@@ -163,18 +398,37 @@ impl Resource {
                     FileAccessMask::from_bytes(u32::MAX.to_be_bytes())
                 }
             );
+        if let Some(durable) = durable.as_mut() {
+            durable.maximal_access = Some(access);
+        }
+
+        let oplock = GrantedOplock::from_response(&response)?;
+        let handler = ResourceMessageHandle::new(
+            name,
+            ResourceBinding {
+                upstream: upstream.clone(),
+                file_id: response.file_id,
+                conn_info: conn_info.clone(),
+            },
+            oplock,
+            on_oplock_break,
+            durable,
+        );
+        conn_info
+            .oplock_breaks
+            .register(&oplock, response.file_id, handler.weak())
+            .await?;
+        upstream.track_open(&handler.handler)?;
 
         // Common information is held in the handle object.
         let handle = ResourceHandle {
             name: name.to_string(),
-            handler: ResourceMessageHandle::new(upstream),
-            open: AtomicBool::new(true),
-            _file_id: response.file_id,
+            handler,
             created: response.creation_time.date_time(),
             modified: response.last_write_time.date_time(),
             access,
             share_type,
-            conn_info: conn_info.clone(),
+            create_contexts: response.create_contexts.into(),
         };
 
         // Construct specific resource and return it.
@@ -185,12 +439,70 @@ impl Resource {
             match share_type {
                 ShareType::Disk => Resource::File(File::new(handle, response.endof_file)),
                 ShareType::Pipe => Resource::Pipe(Pipe::new(handle)),
-                ShareType::Print => unimplemented!("Printer resources are not yet implemented"),
+                ShareType::Print => Resource::Printer(Printer::new(handle)),
             }
         };
         Ok(resource)
     }
 
+    /// (Internal)
+    ///
+    /// Makes sure the requested durability is supported by the connection and the share.
+    fn check_durability(
+        durability: &Durability,
+        conn_info: &Arc<ConnectionInfo>,
+        tree_info: &TreeConnectInfo,
+    ) -> crate::Result<()> {
+        if durability.is_v2() && !conn_info.negotiation.dialect_rev.is_smb3() {
+            return Err(Error::UnsupportedOperation(
+                "Durable handles (v2) require the SMB 3.x dialect family.".to_string(),
+            ));
+        }
+        if let Durability::Persistent { .. } = durability {
+            if !conn_info.negotiation.caps.persistent_handles() {
+                return Err(Error::UnsupportedOperation(
+                    "Server does not support persistent handles.".to_string(),
+                ));
+            }
+            if !tree_info.capabilities.continuous_availability() {
+                return Err(Error::UnsupportedOperation(
+                    "Persistent handles require a continuously available share.".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// (Internal)
+    ///
+    /// Makes sure the requested oplock or lease may be requested over the connection.
+    fn check_oplock_request(
+        request: &OplockRequest,
+        conn_info: &Arc<ConnectionInfo>,
+    ) -> crate::Result<()> {
+        if cfg!(feature = "single_threaded") {
+            return Err(Error::UnsupportedOperation(
+                "Oplocks and leases require notifications, which are not supported in single-threaded mode."
+                    .to_string(),
+            ));
+        }
+        if conn_info.config.disable_notifications {
+            return Err(Error::InvalidConfiguration(
+                "Oplocks and leases require notifications to be enabled.".to_string(),
+            ));
+        }
+        if let OplockRequest::Lease(_) = request {
+            if conn_info.negotiation.dialect_rev < Dialect::Smb021
+                || !conn_info.negotiation.caps.leasing()
+            {
+                return Err(Error::UnsupportedOperation(
+                    "Server does not support leasing.".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn as_file(&self) -> Option<&File> {
         match self {
             Resource::File(f) => Some(f),
@@ -213,6 +525,16 @@ impl Resource {
         self.as_dir().is_some()
     }
 
+    /// Returns the handle of the resource, regardless of its type.
+    pub fn handle(&self) -> &ResourceHandle {
+        match self {
+            Resource::File(f) => f,
+            Resource::Directory(d) => d,
+            Resource::Pipe(p) => p,
+            Resource::Printer(p) => p,
+        }
+    }
+
     pub fn unwrap_file(self) -> File {
         match self {
             Resource::File(f) => f,
@@ -249,27 +571,21 @@ impl TryInto<$t> for Resource {
     };
 }
 
-make_resource_try_into!(File, Directory, Pipe,);
+make_resource_try_into!(File, Directory, Pipe, Printer,);
 
 /// Holds the common information for an opened SMB resource.
 pub struct ResourceHandle {
     name: String,
+    // Holds the file ID of the open; use the `file_id()` getter,
+    // that makes sure the resource is still open.
     handler: HandlerReference<ResourceMessageHandle>,
 
-    // Whether the resource is open or not.
-    // TODO: Consider using RwLock here on FileId instead of AtomicBool+FileId.
-    open: AtomicBool,
-
-    // Avoid accessing directly; use the `file_id()` getter,
-    // that makes sure the resource is still open.
-    _file_id: FileId,
     created: PrimitiveDateTime,
     modified: PrimitiveDateTime,
     share_type: ShareType,
 
     access: FileAccessMask,
-
-    conn_info: Arc<ConnectionInfo>,
+    create_contexts: Vec<CreateContextResponse>,
 }
 
 #[maybe_async(AFIT)]
@@ -294,6 +610,12 @@ impl ResourceHandle {
         self.share_type
     }
 
+    /// Returns the create contexts the server responded with when the resource was opened.
+    /// See [`FileCreateArgs::contexts`] for requesting additional contexts.
+    pub fn create_contexts(&self) -> &[CreateContextResponse] {
+        &self.create_contexts
+    }
+
     /// Returns the handle of the resource.
     // This is implemented to be "inhrited" by Deref impl of resources impls, to avoid boilerplate code.
     pub fn handle(&self) -> &ResourceHandle {
@@ -304,13 +626,14 @@ impl ResourceHandle {
     ///
     /// Returns the file ID of the resource, ensuring the resource is still open.
     fn file_id(&self) -> crate::Result<FileId> {
-        // The current design here allows the race condition over a close after this validation occurs.
-        // therefore, this atomic load can be relaxed, and actual atomic compare and exchange are used
-        // to avoid double close somehow.
-        if !self.open.load(std::sync::atomic::Ordering::Relaxed) {
-            return Err(Error::InvalidState("Resource is closed".into()));
-        }
-        Ok(self._file_id)
+        self.handler.file_id()
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the information of the connection the resource is currently accessed through.
+    fn conn_info(&self) -> Arc<ConnectionInfo> {
+        self.handler.binding().conn_info
     }
 
     /// (Internal)
@@ -321,7 +644,7 @@ impl ResourceHandle {
     ///
     /// Prints a warning if the requested size exceeds the max transaction size.
     fn calc_transact_size(&self, requested: Option<usize>) -> u32 {
-        let max_transact_size = self.conn_info.negotiation.max_transact_size;
+        let max_transact_size = self.conn_info().negotiation.max_transact_size;
         match requested {
             Some(requested_length) if requested_length > max_transact_size as usize => {
                 log::warn!(
@@ -330,7 +653,7 @@ impl ResourceHandle {
                 max_transact_size
             }
             Some(len) => len as u32,
-            None => max_transact_size.min(self.conn_info.config.default_transaction_size()),
+            None => max_transact_size.min(self.conn_info().config.default_transaction_size()),
         }
     }
 
@@ -615,6 +938,45 @@ impl ResourceHandle {
         Ok(ioctl_result)
     }
 
+    /// (Internal)
+    ///
+    /// Sends an FSCTL message, accepting any of the specified statuses as a valid response,
+    /// since some FSCTLs return meaningful output along with warning or error statuses.
+    /// # Returns
+    /// The status of the response, and the parsed response.
+    pub(crate) async fn fsctl_with_status<T: FsctlRequest>(
+        &self,
+        request: T,
+        max_output_response: u32,
+        status: &[Status],
+    ) -> crate::Result<(Status, T::Response)> {
+        let response = self
+            .send_recvo(
+                RequestContent::Ioctl(IoctlRequest {
+                    ctl_code: T::FSCTL_CODE as u32,
+                    file_id: self.file_id()?,
+                    max_input_response: 0,
+                    max_output_response,
+                    flags: IoctlRequestFlags::new().with_is_fsctl(true),
+                    buffer: request.into(),
+                }),
+                ReceiveOptions::new()
+                    .with_status(status)
+                    .with_allow_async(true),
+            )
+            .await?;
+        let response_status = response.message.header.status;
+        match response.message.content {
+            ResponseContent::Error(error) => {
+                Err(Error::ReceivedErrorMessage(response_status, error))
+            }
+            content => Ok((
+                response_status.try_into()?,
+                content.to_ioctl()?.parse_fsctl::<T::Response>()?,
+            )),
+        }
+    }
+
     /// Sends an IOCTL message for the current resource (file).
     /// # Arguments
     /// * `ctl_code` - The control code for the IOCTL request.
@@ -797,12 +1159,18 @@ impl ResourceHandle {
     /// # Returns
     /// A `Result` indicating success or failure.
     pub async fn close(&self) -> crate::Result<()> {
-        if !self.open.swap(false, std::sync::atomic::Ordering::Relaxed) {
+        if !self.handler.take_open() {
             return Err(Error::InvalidState("Resource is already closed".into()));
         }
 
-        log::debug!("Closing handle for {} ({:?})", self.name, self._file_id);
-        Self::send_close(self._file_id, &self.handler).await?;
+        let binding = self.handler.bound()?;
+        log::debug!("Closing handle for {} ({:?})", self.name, binding.file_id);
+        binding
+            .conn_info
+            .oplock_breaks
+            .unregister(&self.handler.handler)
+            .await?;
+        Self::send_close(binding.file_id, &self.handler).await?;
 
         log::debug!("Closed file {}.", self.name);
 
@@ -840,6 +1208,21 @@ impl ResourceHandle {
         self.handler.sendo_recvo(msg, options).await
     }
 
+    /// (Internal)
+    ///
+    /// Sends a request without waiting for its response, which is received using [`ResourceHandle::recvo`].
+    #[maybe_async]
+    #[inline]
+    async fn sendo(&self, msg: OutgoingMessage) -> crate::Result<SendMessageResult> {
+        self.handler.sendo(msg).await
+    }
+
+    #[maybe_async]
+    #[inline]
+    async fn recvo(&self, options: ReceiveOptions<'_>) -> crate::Result<IncomingMessage> {
+        self.handler.recvo(options).await
+    }
+
     #[maybe_async]
     #[inline]
     pub async fn send_cancel(&self, msg_ids: &AsyncMessageIds) -> crate::Result<SendMessageResult> {
@@ -861,22 +1244,355 @@ impl ResourceHandle {
     ///   share connections, this will return false!
     pub fn same_tree(&self, other: &Self) -> bool {
         Arc::ptr_eq(
-            &self.handler.upstream.handler,
-            &other.handler.upstream.handler,
+            &self.handler.binding().upstream.handler,
+            &other.handler.binding().upstream.handler,
         )
     }
+
+    /// Returns the oplock or lease currently granted for the resource.
+    ///
+    /// The returned value is updated when breaks are received from the server.
+    pub fn oplock(&self) -> crate::Result<GrantedOplock> {
+        self.handler.oplock()
+    }
+
+    /// Returns the state required to reclaim the open after the connection is lost,
+    /// if the server granted durability for it. See [`Tree::reopen`][crate::Tree::reopen].
+    pub fn durable_handle(&self) -> crate::Result<Option<DurableHandle>> {
+        self.file_id()?;
+        self.handler.durable_handle()
+    }
+
+    /// Acknowledges a break of the oplock or lease of the resource,
+    /// accepting the state proposed by the server.
+    ///
+    /// This should only be called for breaks deferred by an [`OplockBreakCallback`],
+    /// that returned [`BreakAction::Defer`].
+    pub async fn acknowledge_break(&self, brk: &OplockBreak) -> crate::Result<()> {
+        if !brk.ack_required() {
+            return Err(Error::InvalidArgument(
+                "Break does not require an acknowledgment".to_string(),
+            ));
+        }
+        self.file_id()?;
+        self.handler.acknowledge(brk).await
+    }
 }
 
-struct ResourceMessageHandle {
+/// (Internal)
+///
+/// The tree & file ID an open is currently accessed through.
+/// Replaced when a durable open is reclaimed over a new connection.
+#[derive(Clone)]
+pub(crate) struct ResourceBinding {
     upstream: Upstream,
+    file_id: FileId,
+    conn_info: Arc<ConnectionInfo>,
+}
+
+pub(crate) struct ResourceMessageHandle {
+    name: String,
+    binding: std::sync::RwLock<ResourceBinding>,
+
+    // Whether the resource is open or not.
+    open: AtomicBool,
+    /// Set when the connection was re-established, but the open could not be reclaimed.
+    lost: AtomicBool,
+
+    oplock: std::sync::Mutex<GrantedOplock>,
+    on_oplock_break: Option<OplockBreakCallback>,
+    durable: std::sync::Mutex<Option<DurableHandle>>,
+    lock_sequence: std::sync::Mutex<LockSequenceBuckets>,
 }
 
+#[maybe_async(AFIT)]
 impl ResourceMessageHandle {
-    fn new(upstream: &Upstream) -> HandlerReference<ResourceMessageHandle> {
+    fn new(
+        name: &str,
+        binding: ResourceBinding,
+        oplock: GrantedOplock,
+        on_oplock_break: Option<OplockBreakCallback>,
+        durable: Option<DurableHandle>,
+    ) -> HandlerReference<ResourceMessageHandle> {
         HandlerReference::new(ResourceMessageHandle {
-            upstream: upstream.clone(),
+            name: name.to_string(),
+            binding: std::sync::RwLock::new(binding),
+            open: AtomicBool::new(true),
+            lost: AtomicBool::new(false),
+            oplock: std::sync::Mutex::new(oplock),
+            on_oplock_break,
+            durable: std::sync::Mutex::new(durable),
+            lock_sequence: Default::default(),
         })
     }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the current binding of the open, even if it is closed or lost.
+    pub(crate) fn binding(&self) -> ResourceBinding {
+        // The binding is only ever replaced as a whole, so a poisoned lock still holds a valid value.
+        self.binding
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the current binding of the open, making sure it was not lost on reconnect.
+    fn bound(&self) -> crate::Result<ResourceBinding> {
+        if self.lost.load(Ordering::SeqCst) {
+            return Err(Error::HandleLost(self.name.clone()));
+        }
+        Ok(self.binding())
+    }
+
+    /// (Internal)
+    ///
+    /// Maps a request failing over a stopped connection to [`Error::ReconnectRequired`],
+    /// so callers may tell it apart from other errors, and reconnect.
+    fn map_stopped(&self, e: Error) -> Error {
+        match e {
+            Error::ConnectionStopped => Error::ReconnectRequired(self.name.clone()),
+            e => e,
+        }
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the file ID of the open, ensuring the open is still usable.
+    pub(crate) fn file_id(&self) -> crate::Result<FileId> {
+        // The current design here allows the race condition over a close after this validation occurs.
+        // therefore, this atomic load can be relaxed, and actual atomic compare and exchange are used
+        // to avoid double close somehow.
+        if !self.open.load(Ordering::Relaxed) {
+            return Err(Error::InvalidState("Resource is closed".into()));
+        }
+        Ok(self.bound()?.file_id)
+    }
+
+    /// (Internal)
+    ///
+    /// Marks the open as closed, returning whether it was open before.
+    fn take_open(&self) -> bool {
+        self.open.swap(false, Ordering::Relaxed)
+    }
+
+    pub(crate) fn oplock(&self) -> crate::Result<GrantedOplock> {
+        Ok(*self.oplock.lock()?)
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the durable state of the open, if durability was granted.
+    fn durable_handle(&self) -> crate::Result<Option<DurableHandle>> {
+        let mut durable = match self.durable.lock()?.as_ref() {
+            Some(durable) => durable.clone(),
+            None => return Ok(None),
+        };
+        // A reclaimed lease must be requested with the state currently held.
+        if let (OplockRequest::Lease(lease), GrantedOplock::Lease { lease_state, .. }) =
+            (&mut durable.oplock, self.oplock()?)
+        {
+            lease.lease_state = lease_state;
+        }
+        Ok(Some(durable))
+    }
+
+    /// (Internal)
+    ///
+    /// Reclaims the open over a new tree, after the connection it was opened on was lost,
+    /// and binds it to the new tree.
+    ///
+    /// Opens that are not durable, or that fail to be reclaimed, are marked as lost;
+    /// any further use of them fails with [`Error::HandleLost`].
+    pub(crate) async fn reclaim(
+        self: &Arc<Self>,
+        upstream: &Upstream,
+        conn_info: &Arc<ConnectionInfo>,
+        tree_info: &TreeConnectInfo,
+    ) -> crate::Result<()> {
+        let result = self._reclaim(upstream, conn_info, tree_info).await;
+        if result.is_err() {
+            self.lost.store(true, Ordering::SeqCst);
+        }
+        result
+    }
+
+    async fn _reclaim(
+        self: &Arc<Self>,
+        upstream: &Upstream,
+        conn_info: &Arc<ConnectionInfo>,
+        tree_info: &TreeConnectInfo,
+    ) -> crate::Result<()> {
+        let durable = self
+            .durable_handle()?
+            .ok_or_else(|| Error::HandleLost(self.name.clone()))?;
+        let response = Resource::send_reopen(&durable, upstream, conn_info, tree_info).await?;
+
+        let oplock = GrantedOplock::from_response(&response)?;
+        *self.oplock.lock()? = oplock;
+        if let Some(durable) = self.durable.lock()?.as_mut() {
+            durable.file_id = response.file_id;
+        }
+        *self.binding.write()? = ResourceBinding {
+            upstream: upstream.clone(),
+            file_id: response.file_id,
+            conn_info: conn_info.clone(),
+        };
+
+        conn_info
+            .oplock_breaks
+            .register(&oplock, response.file_id, Arc::downgrade(self))
+            .await?;
+        upstream.track_open(self)?;
+        Ok(())
+    }
+
+    /// (Internal)
+    ///
+    /// Sends a lock request with a single element.
+    ///
+    /// Lock sequences are sent for durable opens over the SMB 3.x dialect family,
+    /// so the server may detect replays of the request.
+    ///
+    /// Reference: MS-SMB2 3.2.4.19
+    pub(crate) async fn send_lock(
+        &self,
+        element: LockElement,
+        mut options: ReceiveOptions<'_>,
+    ) -> crate::Result<IncomingMessage> {
+        let file_id = self.file_id()?;
+        let sequenced = self.durable.lock()?.is_some()
+            && self.binding().conn_info.negotiation.dialect_rev >= Dialect::Smb030;
+        let lock_sequence = if sequenced {
+            // All buckets are in use only with 64 concurrent lock requests; Send those unsequenced.
+            self.lock_sequence.lock()?.acquire()
+        } else {
+            None
+        };
+
+        options.cmd = Some(Command::Lock);
+        let result = self
+            .send_recvo(
+                LockRequest {
+                    lock_sequence: lock_sequence.unwrap_or(LockSequence::new()),
+                    file_id,
+                    locks: vec![element],
+                }
+                .into(),
+                options,
+            )
+            .await;
+
+        if let Some(lock_sequence) = lock_sequence {
+            self.lock_sequence.lock()?.release(lock_sequence);
+        }
+        result
+    }
+
+    /// (Internal)
+    ///
+    /// Updates the granted caching according to a received break,
+    /// and returns the break to pass to the user.
+    fn on_break(&self, content: &ResponseContent) -> crate::Result<OplockBreak> {
+        let mut oplock = self.oplock.lock()?;
+        let brk = match content {
+            ResponseContent::OplockBreakNotify(notify) => {
+                // Breaking a level II oplock is never acknowledged.
+                let ack_required = matches!(
+                    *oplock,
+                    GrantedOplock::Oplock(OplockLevel::Exclusive | OplockLevel::Batch)
+                );
+                *oplock = GrantedOplock::Oplock(notify.oplock_level);
+                OplockBreak::Oplock {
+                    new_level: notify.oplock_level,
+                    ack_required,
+                }
+            }
+            ResponseContent::LeaseBreakNotify(notify) => {
+                let lease_key = notify.lease_key.as_u128();
+                *oplock = GrantedOplock::Lease {
+                    lease_key,
+                    lease_state: notify.new_lease_state,
+                    epoch: notify.new_epoch,
+                };
+                OplockBreak::Lease {
+                    lease_key,
+                    current_state: notify.current_lease_state,
+                    new_state: notify.new_lease_state,
+                    new_epoch: notify.new_epoch,
+                    ack_required: notify.ack_required != 0,
+                }
+            }
+            _ => {
+                return Err(Error::InvalidMessage(format!(
+                    "Expected an oplock or lease break, got {}",
+                    content.content_name()
+                )));
+            }
+        };
+        Ok(brk)
+    }
+
+    /// (Internal)
+    ///
+    /// Updates the granted caching according to a received break, and passes it to the callback of the open,
+    /// returning the break and the action the callback decided on.
+    pub(crate) fn break_received(
+        &self,
+        content: &ResponseContent,
+    ) -> crate::Result<(OplockBreak, BreakAction)> {
+        let brk = self.on_break(content)?;
+        log::debug!("Received break for '{}': {brk:?}", self.name);
+
+        let action = match &self.on_oplock_break {
+            Some(callback) => callback(&brk),
+            None => BreakAction::Acknowledge,
+        };
+        Ok((brk, action))
+    }
+
+    /// (Internal)
+    ///
+    /// Acknowledges a break, accepting the state proposed by the server.
+    pub(crate) async fn acknowledge(&self, brk: &OplockBreak) -> crate::Result<()> {
+        match *brk {
+            OplockBreak::Oplock { new_level, .. } => {
+                let response = self
+                    .send_recv(RequestContent::OplockBreakAck(OplockBreakAck {
+                        oplock_level: new_level,
+                        file_id: self.binding().file_id,
+                    }))
+                    .await?;
+                // The response is identical to the notification in structure.
+                let response = response.message.content.to_oplockbreaknotify()?;
+                *self.oplock.lock()? = GrantedOplock::Oplock(response.oplock_level);
+            }
+            OplockBreak::Lease {
+                lease_key,
+                new_state,
+                ..
+            } => {
+                let response = self
+                    .send_recv(RequestContent::LeaseBreakAck(LeaseBreakAck {
+                        lease_key: lease_key_guid(lease_key),
+                        lease_state: new_state,
+                    }))
+                    .await?;
+                let response = response.message.content.to_leasebreak()?;
+                if let GrantedOplock::Lease { lease_state, .. } = &mut *self.oplock.lock()? {
+                    *lease_state = response.lease_state;
+                }
+            }
+        }
+        log::debug!("Acknowledged break for '{}': {brk:?}", self.name);
+        Ok(())
+    }
 }
 
 impl MessageHandler for ResourceMessageHandle {
@@ -886,7 +1602,11 @@ impl MessageHandler for ResourceMessageHandle {
         &self,
         msg: crate::msg_handler::OutgoingMessage,
     ) -> crate::Result<crate::msg_handler::SendMessageResult> {
-        self.upstream.sendo(msg).await
+        self.bound()?
+            .upstream
+            .sendo(msg)
+            .await
+            .map_err(|e| self.map_stopped(e))
     }
 
     #[maybe_async]
@@ -895,7 +1615,20 @@ impl MessageHandler for ResourceMessageHandle {
         &self,
         options: crate::msg_handler::ReceiveOptions<'_>,
     ) -> crate::Result<crate::msg_handler::IncomingMessage> {
-        self.upstream.recvo(options).await
+        self.bound()?
+            .upstream
+            .recvo(options)
+            .await
+            .map_err(|e| self.map_stopped(e))
+    }
+
+    #[maybe_async]
+    async fn notify(&self, msg: IncomingMessage) -> crate::Result<()> {
+        let (brk, action) = self.break_received(&msg.message.content)?;
+        if brk.ack_required() && action == BreakAction::Acknowledge {
+            self.acknowledge(&brk).await?;
+        }
+        Ok(())
     }
 }
 
@@ -910,7 +1643,7 @@ impl Drop for ResourceHandle {
         log::warn!(
             "ResourceHandle for '{}' ({}) is being dropped without closing it properly. This may lead to resource leaks.",
             self.name,
-            self._file_id
+            file_id.unwrap()
         );
     }
 }
@@ -918,15 +1651,24 @@ impl Drop for ResourceHandle {
 #[cfg(feature = "async")]
 impl Drop for ResourceHandle {
     fn drop(&mut self) {
-        if !self.open.swap(false, std::sync::atomic::Ordering::Relaxed) {
+        if !self.handler.take_open() {
             // already closed, no problem
             return;
         }
 
-        let file_id = self._file_id;
+        // Lost opens were already released by the server.
+        let ResourceBinding {
+            file_id, conn_info, ..
+        } = match self.handler.bound() {
+            Ok(binding) => binding,
+            Err(_) => return,
+        };
         let handler = self.handler.clone();
         log::debug!("Spawning task to close file with ID: {file_id:?}");
         tokio::task::spawn(async move {
+            if let Err(e) = conn_info.oplock_breaks.unregister(&handler.handler).await {
+                log::error!("Error unregistering file from oplock breaks: {e}");
+            }
             if file_id != FileId::EMPTY {
                 if let Err(e) = Self::send_close(file_id, &handler).await {
                     log::error!("Error closing file: {e}");
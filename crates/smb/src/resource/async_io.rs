@@ -0,0 +1,343 @@
+//! [`tokio::io`] adapters for files and pipes.
+//!
+//! The I/O methods of [`File`] and [`Pipe`] borrow the resource, so the resources themselves
+//! cannot keep the in-flight requests that the poll-based [`AsyncRead`] and [`AsyncWrite`] traits require.
+//! Instead, [`AsyncFile`] and [`AsyncPipe`] share the resource with their in-flight requests,
+//! and implement the traits on top of it:
+//!
+//! * [`AsyncFile`] implements [`AsyncRead`], [`AsyncWrite`] and [`AsyncSeek`], keeping a position in the file.
+//! * [`AsyncPipe`] implements [`AsyncRead`] and [`AsyncWrite`], for byte-mode pipes.
+//!
+//! For the [`futures_io`](https://docs.rs/futures-io) equivalents of these traits,
+//! wrap the adapters with the `compat` module of [`tokio_util`].
+
+use std::{
+    future::Future,
+    io::SeekFrom,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+
+use smb_fscc::FileStandardInformation;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+use super::{File, Pipe, ResourceHandle};
+
+type IoFuture<T> = Pin<Box<dyn Future<Output = std::io::Result<T>> + Send>>;
+
+/// A resource that [`AsyncIo`] can perform I/O against.
+///
+/// This trait is implemented for [`File`] and [`Pipe`], and may not be implemented outside of this crate.
+pub trait AsyncIoResource: private::Sealed + Send + Sync + 'static {}
+
+impl AsyncIoResource for File {}
+impl AsyncIoResource for Pipe {}
+
+mod private {
+    use super::*;
+
+    /// Starts the I/O requests of an [`AsyncIo`] adapter.
+    pub trait Sealed {
+        /// Returns the maximum number of bytes to read or write in a single request.
+        fn max_io_size(&self, write: bool) -> usize;
+        fn read_at(self: Arc<Self>, length: usize, pos: u64) -> IoFuture<Vec<u8>>;
+        fn write_at(self: Arc<Self>, data: Arc<[u8]>, pos: u64) -> IoFuture<usize>;
+        fn flush(self: Arc<Self>) -> IoFuture<()>;
+    }
+
+    impl Sealed for File {
+        fn max_io_size(&self, write: bool) -> usize {
+            max_io_size(self, write)
+        }
+
+        fn read_at(self: Arc<Self>, length: usize, pos: u64) -> IoFuture<Vec<u8>> {
+            Box::pin(async move {
+                let mut data = vec![0u8; length];
+                let read = self.read_block(&mut data, pos, None, false).await?;
+                data.truncate(read);
+                Ok(data)
+            })
+        }
+
+        fn write_at(self: Arc<Self>, data: Arc<[u8]>, pos: u64) -> IoFuture<usize> {
+            Box::pin(async move { self.write_block_zc(data, pos, None).await })
+        }
+
+        fn flush(self: Arc<Self>) -> IoFuture<()> {
+            Box::pin(async move { File::flush(&self).await })
+        }
+    }
+
+    impl Sealed for Pipe {
+        fn max_io_size(&self, write: bool) -> usize {
+            max_io_size(self, write)
+        }
+
+        fn read_at(self: Arc<Self>, length: usize, _pos: u64) -> IoFuture<Vec<u8>> {
+            Box::pin(async move {
+                let mut data = vec![0u8; length];
+                let read = self.read(&mut data).await?;
+                data.truncate(read);
+                Ok(data)
+            })
+        }
+
+        fn write_at(self: Arc<Self>, data: Arc<[u8]>, _pos: u64) -> IoFuture<usize> {
+            Box::pin(async move { Ok(self.write(&data).await?) })
+        }
+
+        fn flush(self: Arc<Self>) -> IoFuture<()> {
+            // Pipe writes are not buffered.
+            Box::pin(async { Ok(()) })
+        }
+    }
+}
+
+/// (Internal)
+///
+/// Returns the maximum read or write size negotiated on the connection the resource is opened on.
+fn max_io_size(handle: &ResourceHandle, write: bool) -> usize {
+    let negotiation = &handle.conn_info().negotiation;
+    if write {
+        negotiation.max_write_size as usize
+    } else {
+        negotiation.max_read_size as usize
+    }
+}
+
+/// The request an [`AsyncIo`] adapter is waiting for.
+enum State {
+    Idle,
+    Reading(IoFuture<Vec<u8>>),
+    Writing(IoFuture<usize>),
+    Flushing(IoFuture<()>),
+    Seeking(IoFuture<u64>),
+}
+
+/// An adapter implementing the [`tokio::io`] traits for an SMB resource.
+/// See the [module documentation][self] for more information.
+///
+/// The resource is shared with in-flight requests, so it is held in an [`Arc`].
+/// Once the adapter is no longer needed, close the resource using [`AsyncIo::get_ref`].
+pub struct AsyncIo<T: AsyncIoResource> {
+    resource: Arc<T>,
+    pos: u64,
+    state: State,
+    /// Data read from the resource that did not fit in the caller's buffer.
+    leftover: Vec<u8>,
+}
+
+/// A [`File`] adapted to [`AsyncRead`], [`AsyncWrite`] and [`AsyncSeek`].
+pub type AsyncFile = AsyncIo<File>;
+/// A byte-mode [`Pipe`] adapted to [`AsyncRead`] and [`AsyncWrite`].
+pub type AsyncPipe = AsyncIo<Pipe>;
+
+impl<T: AsyncIoResource> AsyncIo<T> {
+    /// Creates a new adapter for the resource, starting at position 0.
+    pub fn new(resource: impl Into<Arc<T>>) -> Self {
+        Self {
+            resource: resource.into(),
+            pos: 0,
+            state: State::Idle,
+            leftover: vec![],
+        }
+    }
+
+    /// Returns the adapted resource.
+    pub fn get_ref(&self) -> &T {
+        &self.resource
+    }
+
+    /// Returns the adapted resource, which may still be shared with an in-flight request.
+    pub fn into_inner(self) -> Arc<T> {
+        self.resource
+    }
+
+    /// Returns the current position of the adapter.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// (Internal)
+    ///
+    /// Returns an error if another kind of request is in flight.
+    fn busy_error() -> std::io::Error {
+        std::io::Error::other("Another operation is in progress")
+    }
+
+    /// (Internal)
+    ///
+    /// Waits for the write in flight, if any, to complete, returning its error.
+    /// The position was already advanced when the write was started.
+    fn poll_pending_write(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        if let State::Writing(future) = &mut self.state {
+            let result = ready!(future.as_mut().poll(cx));
+            self.state = State::Idle;
+            result?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncIoResource> AsyncRead for AsyncIo<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if !this.leftover.is_empty() {
+            let length = this.leftover.len().min(buf.remaining());
+            buf.put_slice(&this.leftover[..length]);
+            this.leftover.drain(..length);
+            this.pos += length as u64;
+            return Poll::Ready(Ok(()));
+        }
+
+        loop {
+            match &mut this.state {
+                State::Idle => {
+                    if buf.remaining() == 0 {
+                        return Poll::Ready(Ok(()));
+                    }
+                    let length = buf.remaining().min(this.resource.max_io_size(false));
+                    this.state = State::Reading(this.resource.clone().read_at(length, this.pos));
+                }
+                State::Reading(future) => {
+                    let result = ready!(future.as_mut().poll(cx));
+                    this.state = State::Idle;
+                    let data = result?;
+                    let length = data.len().min(buf.remaining());
+                    buf.put_slice(&data[..length]);
+                    this.leftover.extend_from_slice(&data[length..]);
+                    this.pos += length as u64;
+                    return Poll::Ready(Ok(()));
+                }
+                State::Writing(_) => ready!(this.poll_pending_write(cx))?,
+                _ => return Poll::Ready(Err(Self::busy_error())),
+            }
+        }
+    }
+}
+
+impl<T: AsyncIoResource> AsyncWrite for AsyncIo<T> {
+    /// Like [`tokio::fs::File`], the data is written in the background: the call returns as soon as
+    /// the write is started, and an error of the write is returned by the next operation.
+    /// Flush the adapter to make sure all the data was written.
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                State::Idle => {
+                    if buf.is_empty() {
+                        return Poll::Ready(Ok(0));
+                    }
+                    // Data read ahead is stale once the resource is written to.
+                    this.leftover.clear();
+                    let length = buf.len().min(this.resource.max_io_size(true));
+                    let write = this
+                        .resource
+                        .clone()
+                        .write_at(Arc::from(&buf[..length]), this.pos);
+                    // The write is spawned, so it makes progress even if the adapter is not polled again.
+                    let write = tokio::spawn(async move {
+                        let written = write.await?;
+                        if written < length {
+                            return Err(std::io::ErrorKind::WriteZero.into());
+                        }
+                        Ok(written)
+                    });
+                    this.state = State::Writing(Box::pin(async move {
+                        write.await.map_err(std::io::Error::other)?
+                    }));
+                    this.pos += length as u64;
+                    return Poll::Ready(Ok(length));
+                }
+                State::Writing(_) => ready!(this.poll_pending_write(cx))?,
+                _ => return Poll::Ready(Err(Self::busy_error())),
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                State::Idle => this.state = State::Flushing(this.resource.clone().flush()),
+                State::Writing(_) => ready!(this.poll_pending_write(cx))?,
+                State::Flushing(future) => {
+                    let result = ready!(future.as_mut().poll(cx));
+                    this.state = State::Idle;
+                    return Poll::Ready(result);
+                }
+                _ => return Poll::Ready(Err(Self::busy_error())),
+            }
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl AsyncSeek for AsyncIo<File> {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+        let this = self.get_mut();
+        if !matches!(this.state, State::Idle) {
+            return Err(Self::busy_error());
+        }
+
+        let pos = this.pos;
+        let offset = |base: u64, offset: i64| {
+            base.checked_add_signed(offset).ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid seek position")
+            })
+        };
+        this.state = match position {
+            SeekFrom::Start(start) => State::Seeking(Box::pin(async move { Ok(start) })),
+            SeekFrom::Current(delta) => {
+                let target = offset(pos, delta)?;
+                State::Seeking(Box::pin(async move { Ok(target) }))
+            }
+            SeekFrom::End(delta) => {
+                // The size of the file may have changed since it was opened.
+                let file = this.resource.clone();
+                State::Seeking(Box::pin(async move {
+                    let info = file
+                        .query_info::<FileStandardInformation>()
+                        .await
+                        .map_err(std::io::Error::from)?;
+                    offset(info.end_of_file, delta)
+                }))
+            }
+        };
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<u64>> {
+        let this = self.get_mut();
+        match &mut this.state {
+            State::Idle => Poll::Ready(Ok(this.pos)),
+            State::Writing(_) => {
+                ready!(this.poll_pending_write(cx))?;
+                Poll::Ready(Ok(this.pos))
+            }
+            State::Seeking(future) => {
+                let result = ready!(future.as_mut().poll(cx));
+                this.state = State::Idle;
+                let pos = result?;
+                if pos != this.pos {
+                    this.leftover.clear();
+                }
+                this.pos = pos;
+                Poll::Ready(Ok(pos))
+            }
+            _ => Poll::Ready(Err(Self::busy_error())),
+        }
+    }
+}
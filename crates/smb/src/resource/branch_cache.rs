@@ -0,0 +1,193 @@
+//! BranchCache content information.
+//!
+//! Servers that support BranchCache generate content information for files:
+//! the hashes of their segments, and in version 1, of the blocks of each segment (see [`ContentInformation`]).
+//! Comparing the hashes with those of locally cached data detects unchanged ranges without reading them.
+
+use std::ops::Range;
+
+use binrw::{BinRead, io::Cursor};
+use maybe_async::*;
+use smb_msg::{
+    ContentInformation, SrvHashRetrievalType, SrvHashRetrieveFileBased, SrvHashRetrieveHashBased,
+    SrvReadHashReq, SrvReadHashRes,
+};
+
+use super::{File, GetLen};
+use crate::Error;
+
+/// Content information of a range of a file, as returned by [`File::read_hash`].
+#[derive(Debug)]
+pub struct FileContentInformation {
+    /// The range of the file covered by the content information.
+    /// Segment offsets of the content information are relative to its start.
+    pub range: Range<u64>,
+    /// The header of the Content Information File, if it was retrieved.
+    pub header: Option<SrvReadHashRes>,
+    pub content: ContentInformation,
+}
+
+#[maybe_async(AFIT)]
+impl File {
+    /// Retrieves the BranchCache content information of the file.
+    /// # Arguments
+    /// * `range` - The range of the file to retrieve the content information for.
+    /// * `hash_version` - The version of the content information: 1, or 2 for SMB 3.x dialects.
+    /// * `retrieval_type` - How the content information is retrieved:
+    ///   * [`SrvHashRetrievalType::HashBased`] retrieves the entire Content Information File,
+    ///     which covers the whole file, regardless of `range`.
+    ///   * [`SrvHashRetrievalType::FileBased`] retrieves the content information of `range` (SMB 3.x only).
+    ///     The server may cover a different range, according to its segment boundaries,
+    ///     or return less than requested, in which case the call may be repeated for the rest of the range.
+    /// # Notes
+    /// * The server returns [`smb_msg::Status::HashNotPresent`] if the content information was not generated yet,
+    ///   in which case it is usually generated in the background, and the call may be retried later.
+    /// * The content information is only meaningful while [`SrvReadHashRes::dirty`] is zero.
+    pub async fn read_hash(
+        &self,
+        range: Range<u64>,
+        hash_version: u32,
+        retrieval_type: SrvHashRetrievalType,
+    ) -> crate::Result<FileContentInformation> {
+        if range.start > range.end {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid range to read hashes for: {range:?}"
+            )));
+        }
+        if !(1..=2).contains(&hash_version) {
+            return Err(Error::InvalidArgument(format!(
+                "Unsupported hash version: {hash_version}"
+            )));
+        }
+
+        match retrieval_type {
+            SrvHashRetrievalType::HashBased => self.read_hash_based(hash_version).await,
+            SrvHashRetrievalType::FileBased => self.read_file_based(range, hash_version).await,
+        }
+    }
+
+    /// (Internal)
+    ///
+    /// Retrieves the whole Content Information File, and parses it.
+    async fn read_hash_based(&self, hash_version: u32) -> crate::Result<FileContentInformation> {
+        // The response carries 16 bytes before the retrieved data.
+        let max_output = self.calc_transact_size(None);
+        let chunk_length = max_output - 16;
+
+        let mut data: Vec<u8> = vec![];
+        let mut expected_length = None;
+        while expected_length.is_none_or(|length| data.len() < length) {
+            let response: SrvHashRetrieveHashBased = Self::parse_hash_response(
+                &self
+                    .fsctl_with_options(
+                        SrvReadHashReq {
+                            hash_version,
+                            hash_retrieval_type: SrvHashRetrievalType::HashBased,
+                            length: chunk_length,
+                            offset: data.len() as u64,
+                        },
+                        max_output,
+                    )
+                    .await?,
+            )?;
+            let complete = response.blob.len() < chunk_length as usize;
+            data.extend_from_slice(&response.blob);
+            if expected_length.is_none() {
+                // The header locates the hash blob, determining the length of the file.
+                expected_length = Self::parse_hash_header(&data).map(|header| {
+                    header.hash_blob_offset as usize + header.hash_blob_length as usize
+                });
+            }
+            if complete || response.blob.is_empty() {
+                break;
+            }
+        }
+
+        let (header, content) = Self::parse_content_information(&data)?;
+        let file_length = match &header {
+            Some(header) => header.source_file_size,
+            None => self.get_len().await?,
+        };
+        Ok(FileContentInformation {
+            range: 0..file_length,
+            header,
+            content,
+        })
+    }
+
+    /// (Internal)
+    ///
+    /// Retrieves the content information of a range of the file, and parses it.
+    async fn read_file_based(
+        &self,
+        range: Range<u64>,
+        hash_version: u32,
+    ) -> crate::Result<FileContentInformation> {
+        let max_output = self.calc_transact_size(None);
+        let response: SrvHashRetrieveFileBased = Self::parse_hash_response(
+            &self
+                .fsctl_with_options(
+                    SrvReadHashReq {
+                        hash_version,
+                        hash_retrieval_type: SrvHashRetrievalType::FileBased,
+                        length: (range.end - range.start).min(u32::MAX as u64) as u32,
+                        offset: range.start,
+                    },
+                    max_output,
+                )
+                .await?,
+        )?;
+
+        let (header, content) = Self::parse_content_information(&response.buffer)?;
+        Ok(FileContentInformation {
+            range: response.file_data_offset..response.file_data_offset + response.file_data_length,
+            header,
+            content,
+        })
+    }
+
+    /// (Internal)
+    ///
+    /// Parses the output of FSCTL_SRV_READ_HASH, whose format depends on the retrieval type of the request.
+    fn parse_hash_response<T>(data: &[u8]) -> crate::Result<T>
+    where
+        for<'a> T: BinRead<Args<'a> = ()>,
+    {
+        Ok(T::read_le(&mut Cursor::new(data))?)
+    }
+
+    /// (Internal)
+    ///
+    /// Parses the header of a Content Information File, if the data starts with one.
+    fn parse_hash_header(data: &[u8]) -> Option<SrvReadHashRes> {
+        // Content information starts with its version, while the header starts with the hash type (1).
+        if !data.starts_with(&1u32.to_le_bytes()) {
+            return None;
+        }
+        SrvReadHashRes::read_le(&mut Cursor::new(data)).ok()
+    }
+
+    /// (Internal)
+    ///
+    /// Parses content information, which may be preceded by the header of the Content Information File.
+    fn parse_content_information(
+        data: &[u8],
+    ) -> crate::Result<(Option<SrvReadHashRes>, ContentInformation)> {
+        let header = Self::parse_hash_header(data);
+        let blob = match &header {
+            Some(header) => {
+                let start = header.hash_blob_offset as usize;
+                let end = start + header.hash_blob_length as usize;
+                data.get(start..end).ok_or_else(|| {
+                    Error::InvalidMessage(format!(
+                        "Hash blob {start}..{end} exceeds the {} bytes of content information",
+                        data.len()
+                    ))
+                })?
+            }
+            None => data,
+        };
+        let content = ContentInformation::read_le(&mut Cursor::new(blob))?;
+        Ok((header, content))
+    }
+}
@@ -52,11 +52,12 @@ impl Directory {
             return Err(Error::MissingPermissions("file_list_directory".to_string()));
         }
 
-        debug_assert!(buffer_size <= self.conn_info.negotiation.max_transact_size);
-        if buffer_size > self.conn_info.negotiation.max_transact_size {
+        debug_assert!(buffer_size <= self.conn_info().negotiation.max_transact_size);
+        if buffer_size > self.conn_info().negotiation.max_transact_size {
             return Err(Error::InvalidArgument(format!(
                 "Buffer size {} exceeds maximum transact size {}",
-                buffer_size, self.conn_info.negotiation.max_transact_size
+                buffer_size,
+                self.conn_info().negotiation.max_transact_size
             )));
         }
 
@@ -156,7 +157,7 @@ impl Directory {
     where
         T: QueryDirectoryInfoValue + for<'b> binrw::prelude::BinWrite<Args<'b> = ()> + Send,
     {
-        let max_allowed_buffer_size = this.conn_info.negotiation.max_transact_size;
+        let max_allowed_buffer_size = this.conn_info().negotiation.max_transact_size;
         if buffer_size > max_allowed_buffer_size {
             log::warn!(
                 "Buffer size {} is larger than max transact size {}. Using minimum.",
@@ -210,6 +211,31 @@ impl Directory {
         iter_sync::QueryDirectoryIterator::new(self, pattern.to_string(), buffer_size)
     }
 
+    /// Returns all the directory contents matching the pattern, querying the server until no more entries are returned.
+    /// # Arguments
+    /// * `pattern` - The pattern to match against the file names in the directory. Use wildcards like `*` and `?` to match multiple files.
+    /// # Notes
+    /// * Like [`Directory::query`], this method blocks other queries on this instance until it returns.
+    ///   Prefer [`Directory::query`] for large directories, to avoid holding all entries in memory.
+    pub async fn query_all<T>(&self, pattern: &str) -> crate::Result<Vec<T>>
+    where
+        T: QueryDirectoryInfoValue + for<'a> binrw::prelude::BinWrite<Args<'a> = ()>,
+    {
+        let buffer_size = Self::QUERY_DIRECTORY_DEFAULT_BUFFER_SIZE
+            .min(self.conn_info().negotiation.max_transact_size);
+        let _lock_guard = self.query_lock.lock().await?;
+        let mut entries = vec![];
+        loop {
+            let batch = self
+                .send_query::<T>(pattern, entries.is_empty(), buffer_size)
+                .await?;
+            if batch.is_empty() {
+                return Ok(entries);
+            }
+            entries.extend(batch);
+        }
+    }
+
     /// Watches the directory for changes.
     /// # Arguments
     /// * `filter` - The filter to use for the changes. This is a bitmask of the changes to watch for.
@@ -0,0 +1,195 @@
+//! Durable & persistent handles.
+//!
+//! Durability is requested using [`FileCreateArgs::durability`][crate::FileCreateArgs::durability].
+//! When granted, the state required to reclaim the open after a network failure is available
+//! through [`ResourceHandle::durable_handle`][crate::ResourceHandle::durable_handle], and
+//! may be passed to [`Tree::reopen`][crate::Tree::reopen] once a tree is connected again.
+
+use std::time::Duration;
+
+use smb_dtyp::Guid;
+use smb_fscc::*;
+use smb_msg::*;
+
+use super::{FileCreateArgs, OplockBreakCallback, OplockRequest};
+
+/// The durability to request for an open.
+///
+/// Reference: MS-SMB2 2.2.13.2.3, 2.2.13.2.11
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Durability {
+    /// The open is lost when the connection is lost.
+    #[default]
+    None,
+    /// A durable handle (version 1).
+    ///
+    /// Granted by the server only together with a batch oplock,
+    /// or a lease with handle caching.
+    Durable,
+    /// A durable handle (version 2). Requires the SMB 3.x dialect family.
+    ///
+    /// As with [`Durability::Durable`], a batch oplock or a lease with handle caching is required.
+    /// A zero timeout lets the server choose the timeout.
+    DurableV2 { timeout: Duration },
+    /// A persistent handle. Requires the SMB 3.x dialect family,
+    /// and a continuously available share.
+    ///
+    /// A zero timeout lets the server choose the timeout.
+    Persistent { timeout: Duration },
+}
+
+impl Durability {
+    /// Returns whether a version 2 durable or persistent handle is requested.
+    pub fn is_v2(&self) -> bool {
+        matches!(
+            self,
+            Durability::DurableV2 { .. } | Durability::Persistent { .. }
+        )
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the create context to send for this request, if any,
+    /// using the specified create GUID for version 2 requests.
+    pub(crate) fn to_context(self, create_guid: Guid) -> Option<CreateContextRequest> {
+        let (timeout, persistent) = match self {
+            Durability::None => return None,
+            Durability::Durable => return Some(DurableHandleRequest {}.into()),
+            Durability::DurableV2 { timeout } => (timeout, false),
+            Durability::Persistent { timeout } => (timeout, true),
+        };
+        Some(
+            DurableHandleRequestV2 {
+                timeout: timeout.as_millis().try_into().unwrap_or(u32::MAX),
+                flags: DurableHandleV2Flags::new().with_persistent(persistent),
+                create_guid,
+            }
+            .into(),
+        )
+    }
+}
+
+/// The state of a durable or persistent open, as granted by the server.
+///
+/// Holds everything needed to reclaim the open using [`Tree::reopen`][crate::Tree::reopen].
+#[derive(Clone)]
+pub struct DurableHandle {
+    pub(crate) name: String,
+    pub(crate) file_id: FileId,
+    /// Set for version 2 durable and persistent handles only.
+    pub(crate) create_guid: Option<Guid>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) persistent: bool,
+
+    pub(crate) desired_access: FileAccessMask,
+    pub(crate) attributes: FileAttributes,
+    pub(crate) options: CreateOptions,
+    pub(crate) share_access: ShareAccessFlags,
+    pub(crate) impersonation_level: ImpersonationLevel,
+    pub(crate) oplock: OplockRequest,
+    pub(crate) on_oplock_break: Option<OplockBreakCallback>,
+    pub(crate) maximal_access: Option<FileAccessMask>,
+}
+
+impl DurableHandle {
+    /// (Internal)
+    ///
+    /// Builds the durable state of a new open from the server's create response,
+    /// if durability was granted.
+    pub(crate) fn from_response(
+        name: &str,
+        create_args: &FileCreateArgs,
+        share_access: ShareAccessFlags,
+        response: &CreateResponse,
+        create_guid: Guid,
+    ) -> Option<Self> {
+        let contexts = &response.create_contexts;
+        let (create_guid, timeout, persistent) =
+            if let Some(dh2q) = CreateContextResponseData::first_dh2q(contexts) {
+                (
+                    Some(create_guid),
+                    Some(Duration::from_millis(dh2q.timeout.into())),
+                    dh2q.flags.persistent(),
+                )
+            } else if CreateContextResponseData::first_dhnq(contexts).is_some() {
+                (None, None, false)
+            } else {
+                return None;
+            };
+
+        Some(Self {
+            name: name.to_string(),
+            file_id: response.file_id,
+            create_guid,
+            timeout,
+            persistent,
+            desired_access: create_args.desired_access,
+            attributes: create_args.attributes,
+            options: create_args.options,
+            share_access,
+            impersonation_level: create_args.impersonation_level,
+            oplock: create_args.oplock,
+            on_oplock_break: create_args.on_oplock_break.clone(),
+            maximal_access: None,
+        })
+    }
+
+    /// Returns the name of the resource, relative to the share.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the file ID of the open, as known to the server.
+    pub fn file_id(&self) -> FileId {
+        self.file_id
+    }
+
+    /// Returns the create GUID of the open. Set for version 2 durable and persistent handles only.
+    pub fn create_guid(&self) -> Option<Guid> {
+        self.create_guid
+    }
+
+    /// Returns the time the server keeps the open after the connection is lost.
+    /// Set for version 2 durable and persistent handles only.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns whether the open is persistent.
+    pub fn is_persistent(&self) -> bool {
+        self.persistent
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the create context that reclaims the open.
+    ///
+    /// Reference: MS-SMB2 2.2.13.2.4, 2.2.13.2.12
+    pub(crate) fn reconnect_context(&self) -> CreateContextRequest {
+        match self.create_guid {
+            Some(create_guid) => DurableHandleReconnectV2 {
+                file_id: self.file_id,
+                create_guid,
+                flags: DurableHandleV2Flags::new().with_persistent(self.persistent),
+            }
+            .into(),
+            None => DurableHandleReconnect {
+                durable_request: self.file_id,
+            }
+            .into(),
+        }
+    }
+}
+
+impl std::fmt::Debug for DurableHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DurableHandle")
+            .field("name", &self.name)
+            .field("file_id", &self.file_id)
+            .field("create_guid", &self.create_guid)
+            .field("timeout", &self.timeout)
+            .field("persistent", &self.persistent)
+            .field("oplock", &self.oplock)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,111 @@
+//! Extended attributes (EAs).
+//!
+//! Extended attributes are name-value pairs attached to files and directories.
+//! Samba exposes `user.*` extended attributes of the underlying file system as EAs,
+//! so they can be read and written from SMB clients as well.
+
+use maybe_async::*;
+use smb_fscc::{EaFlags, FileEaInformation, FileFullEaInformation, FileFullEaInformationInner};
+use smb_msg::Status;
+
+use super::ResourceHandle;
+use crate::Error;
+
+#[maybe_async(AFIT)]
+impl ResourceHandle {
+    /// Returns all the extended attributes of the resource.
+    ///
+    /// The response buffer is grown as needed, up to the maximum transaction size of the connection.
+    pub async fn eas(&self) -> crate::Result<impl Iterator<Item = FileFullEaInformationInner>> {
+        Ok(self.query_eas(&[]).await?.into_iter())
+    }
+
+    /// Returns the extended attribute with the specified name, or `None` if the resource has no such attribute.
+    ///
+    /// EA names are case-insensitive.
+    pub async fn get_ea(&self, name: &str) -> crate::Result<Option<FileFullEaInformationInner>> {
+        // Servers return requested attributes that do not exist with an empty value.
+        Ok(self.query_eas(&[name]).await?.into_iter().find(|ea| {
+            ea.ea_name.to_string().eq_ignore_ascii_case(name) && !ea.ea_value.is_empty()
+        }))
+    }
+
+    /// Sets the value of an extended attribute, creating it if it does not exist.
+    /// # Arguments
+    /// * `name` - The name of the attribute, of up to 255 ASCII characters.
+    /// * `value` - The value of the attribute. An empty value removes the attribute.
+    /// * `flags` - The flags of the attribute. Set [`EaFlags::file_need_ea`] for attributes that
+    ///   applications must understand in order to interpret the file.
+    pub async fn set_ea(&self, name: &str, value: &[u8], flags: EaFlags) -> crate::Result<()> {
+        if name.is_empty() || name.len() > u8::MAX as usize || !name.is_ascii() {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid extended attribute name: {name}"
+            )));
+        }
+        if value.len() > u16::MAX as usize {
+            return Err(Error::InvalidArgument(format!(
+                "Value of extended attribute {name} is too long ({} bytes)",
+                value.len()
+            )));
+        }
+
+        self.set_info(FileFullEaInformation::from(vec![
+            FileFullEaInformationInner {
+                flags,
+                ea_name: name.into(),
+                ea_value: value.to_vec(),
+            },
+        ]))
+        .await
+    }
+
+    /// Removes an extended attribute from the resource.
+    ///
+    /// Removing an attribute that does not exist succeeds.
+    pub async fn remove_ea(&self, name: &str) -> crate::Result<()> {
+        self.set_ea(name, &[], EaFlags::new()).await
+    }
+
+    /// (Internal)
+    ///
+    /// Queries the extended attributes with the specified names, or all of them if no names are specified.
+    /// Retries with a larger buffer as long as the server reports that the attributes do not fit.
+    async fn query_eas(&self, names: &[&str]) -> crate::Result<FileFullEaInformation> {
+        // The EA size is a hint only, since it is calculated for a more compact format.
+        let ea_size = match self.query_info::<FileEaInformation>().await {
+            Ok(info) if info.ea_size == 0 => return Ok(Default::default()),
+            Ok(info) => info.ea_size as usize,
+            Err(e) => {
+                log::debug!("Failed to query EA size, using the default buffer size: {e}");
+                0
+            }
+        };
+
+        let max_transact_size = self.conn_info().negotiation.max_transact_size as usize;
+        let mut buffer_length = ea_size
+            .max(self.calc_transact_size(None) as usize)
+            .min(max_transact_size);
+        loop {
+            match self
+                .query_full_ea_info_with_options(names.to_vec(), Some(buffer_length))
+                .await
+            {
+                Err(Error::BufferTooSmall { required, .. })
+                    if buffer_length < max_transact_size =>
+                {
+                    buffer_length = required
+                        .filter(|&required| required > buffer_length)
+                        .unwrap_or(buffer_length * 2)
+                        .min(max_transact_size);
+                    log::debug!(
+                        "EAs do not fit in the buffer, retrying with {buffer_length} bytes"
+                    );
+                }
+                Err(Error::ReceivedErrorMessage(Status::U32_NO_EAS_ON_FILE, _)) => {
+                    return Ok(Default::default());
+                }
+                result => return result,
+            }
+        }
+    }
+}
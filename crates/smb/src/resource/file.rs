@@ -2,7 +2,10 @@ use super::file_util::*;
 use super::*;
 #[cfg(not(feature = "async"))]
 use std::io::prelude::*;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::connection::ConnectionMessageHandler;
 
 /// An opened file on the server.
 ///
@@ -14,6 +17,7 @@ use std::ops::{Deref, DerefMut};
 /// This allows you to seek to a specific position in the file, combined with the [Read][std::io::Read] and [Write][std::io::Write] traits.
 /// Using any of the implemented [std::io] traits mentioned above should have no effect on calling the other, non-blocking methods.
 /// Since we would NOT like to call a tokio task from a blocking context, these traits are **NOT** implemented in the async context!
+/// Instead, wrap the file in an [AsyncFile][crate::resource::AsyncFile], which implements the [tokio::io] equivalents.
 ///
 /// You may not directly create this struct. Instead, use the [Tree::create][crate::tree::Tree::create] method to gain
 /// a proper handle against the server in the shape of a [Resource], that can be then converted to a [File].
@@ -25,7 +29,9 @@ pub struct File {
     #[cfg(not(feature = "async"))]
     dirty: bool,
 
-    end_of_file: u64,
+    /// The size of the file, as known to this open.
+    /// Updated by writes through this open, and by [`SetLen::set_len`].
+    end_of_file: AtomicU64,
 }
 
 #[maybe_async(AFIT)]
@@ -33,7 +39,7 @@ impl File {
     pub fn new(handle: ResourceHandle, end_of_file: u64) -> Self {
         File {
             handle,
-            end_of_file,
+            end_of_file: AtomicU64::new(end_of_file),
             #[cfg(not(feature = "async"))]
             pos: 0,
             #[cfg(not(feature = "async"))]
@@ -54,6 +60,9 @@ impl File {
     /// * `unbuffered` - Whether to try using unbuffered I/O (if supported by the server).
     /// # Returns
     /// The number of bytes read, up to `buf.len()`.
+    /// # Notes
+    /// * Reads larger than the negotiated maximum read size are split into multiple requests,
+    ///   which are sent concurrently, as far as the connection's credits allow.
     pub async fn read_block(
         &self,
         buf: &mut [u8],
@@ -73,7 +82,7 @@ impl File {
         }
 
         // EOF
-        if pos >= self.end_of_file {
+        if pos >= self.end_of_file.load(Ordering::SeqCst) {
             return Ok(0);
         }
 
@@ -85,47 +94,93 @@ impl File {
         );
 
         let mut flags = ReadFlags::new();
-        if self.handle.conn_info.config.compression_enabled
-            && self.handle.conn_info.dialect.supports_compression()
+        if self.handle.conn_info().config.compression_enabled
+            && self.handle.conn_info().dialect.supports_compression()
         {
             flags.set_read_compressed(true);
         }
 
-        if unbuffered && self.handle.conn_info.negotiation.dialect_rev >= Dialect::Smb0302 {
+        if unbuffered && self.handle.conn_info().negotiation.dialect_rev >= Dialect::Smb0302 {
             flags.set_read_unbuffered(true);
         }
 
+        let (chunk_size, in_flight) = self.io_chunking(false);
+        let actual_read_length = run_chunks(
+            buf.chunks_mut(chunk_size),
+            chunk_size,
+            in_flight,
+            |offset, chunk| self.send_read_chunk(chunk.len(), pos + offset, channel, flags),
+            |_, msg_id, chunk| self.receive_read_chunk(msg_id, chunk, channel),
+        )
+        .await?;
+        log::debug!(
+            "Read {} bytes from {}.",
+            actual_read_length,
+            self.handle.name()
+        );
+        Ok(actual_read_length)
+    }
+
+    /// (Internal)
+    ///
+    /// Sends a single read request, of up to the negotiated maximum read size,
+    /// returning its message ID. The response is received using [`File::receive_read_chunk`].
+    async fn send_read_chunk(
+        &self,
+        length: usize,
+        pos: u64,
+        channel: Option<u32>,
+        flags: ReadFlags,
+    ) -> std::io::Result<u64> {
         let request = OutgoingMessage::new(
             ReadRequest {
                 flags,
-                length: buf.len() as u32,
+                length: length as u32,
                 offset: pos,
-                file_id: self.handle.file_id().map_err(std::io::Error::other)?,
+                file_id: self.handle.file_id().map_err(std::io::Error::from)?,
                 minimum_count: 1,
             }
             .into(),
         )
         .with_channel_id(channel);
 
-        let response = self
+        let sent = self
             .handle
-            .sendo_recvo(request, ReceiveOptions::new())
+            .sendo(request)
             .await
-            .map_err(|e| std::io::Error::other(e.to_string()))?;
+            .map_err(std::io::Error::from)?;
+        Ok(sent.msg_id)
+    }
+
+    /// (Internal)
+    ///
+    /// Receives the response of a read request, into the buffer.
+    /// Reading at or beyond the end of the file returns 0.
+    async fn receive_read_chunk(
+        &self,
+        msg_id: u64,
+        buf: &mut [u8],
+        channel: Option<u32>,
+    ) -> std::io::Result<usize> {
+        let options = ReceiveOptions {
+            channel_id: channel,
+            ..ReceiveOptions::new()
+                .with_cmd(Some(Command::Read))
+                .with_msg_id_filter(msg_id)
+        };
+        let response = match self.handle.recvo(options).await {
+            Ok(response) => response,
+            // The file may have been truncated by another open.
+            Err(e) if e.status() == Some(Status::EndOfFile) => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
         let content = response
             .message
             .content
             .to_read()
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-        let actual_read_length = content.buffer.len();
-        log::debug!(
-            "Read {} bytes from {}.",
-            actual_read_length,
-            self.handle.name()
-        );
-
-        buf[..actual_read_length].copy_from_slice(&content.buffer);
-
+        let actual_read_length = content.buffer.len().min(buf.len());
+        buf[..actual_read_length].copy_from_slice(&content.buffer[..actual_read_length]);
         Ok(actual_read_length)
     }
 
@@ -156,6 +211,10 @@ impl File {
     /// * `pos` - The offset in the file to write to.
     /// # Returns
     /// The number of bytes written.
+    /// # Notes
+    /// * Writes larger than the negotiated maximum write size are split into multiple requests,
+    ///   which are sent concurrently, as far as the connection's credits allow.
+    ///   The data of each request is copied from `buf` in this case.
     pub async fn write_block_zc(
         &self,
         buf: Arc<[u8]>,
@@ -180,25 +239,81 @@ impl File {
             self.handle.name()
         );
 
+        let (chunk_size, in_flight) = self.io_chunking(true);
+        let actual_written_length = if buf.len() <= chunk_size {
+            let msg_id = self.send_write_chunk(buf, pos, channel).await?;
+            self.receive_write_chunk(msg_id, pos, channel).await?
+        } else {
+            run_chunks(
+                buf.chunks(chunk_size),
+                chunk_size,
+                in_flight,
+                |offset, chunk| self.send_write_chunk((*chunk).into(), pos + offset, channel),
+                |offset, msg_id, _| self.receive_write_chunk(msg_id, pos + offset, channel),
+            )
+            .await?
+        };
+        log::debug!(
+            "Wrote {} bytes to {}.",
+            actual_written_length,
+            self.handle.name()
+        );
+        Ok(actual_written_length)
+    }
+
+    /// (Internal)
+    ///
+    /// Sends a single write request, of up to the negotiated maximum write size,
+    /// returning its message ID. The response is received using [`File::receive_write_chunk`].
+    async fn send_write_chunk(
+        &self,
+        buf: Arc<[u8]>,
+        pos: u64,
+        channel: Option<u32>,
+    ) -> std::io::Result<u64> {
         // Arc is accepted to provide safety regarding the buffer's lifetime,
         // without forcing an actual copy of the data.
         let outgoing = OutgoingMessage::new(
             WriteRequest::new(
                 pos,
-                self.handle.file_id().map_err(std::io::Error::other)?,
+                self.handle.file_id().map_err(std::io::Error::from)?,
                 WriteFlags::new(),
                 buf.len() as u32,
             )
             .into(),
         )
-        .with_additional_data(Arc::clone(&buf))
+        .with_additional_data(buf)
         .with_channel_id(channel);
 
+        let sent = self
+            .handle
+            .sendo(outgoing)
+            .await
+            .map_err(std::io::Error::from)?;
+        Ok(sent.msg_id)
+    }
+
+    /// (Internal)
+    ///
+    /// Receives the response of a write request to the specified position.
+    async fn receive_write_chunk(
+        &self,
+        msg_id: u64,
+        pos: u64,
+        channel: Option<u32>,
+    ) -> std::io::Result<usize> {
+        let options = ReceiveOptions {
+            channel_id: channel,
+            ..ReceiveOptions::new()
+                .with_cmd(Some(Command::Write))
+                .with_msg_id_filter(msg_id)
+                .with_allow_async(true)
+        };
         let response = self
             .handle
-            .sendo_recvo(outgoing, ReceiveOptions::new().with_allow_async(true))
+            .recvo(options)
             .await
-            .map_err(|e| std::io::Error::other(e.to_string()))?;
+            .map_err(std::io::Error::from)?;
 
         let content = response
             .message
@@ -206,89 +321,146 @@ impl File {
             .to_write()
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
         let actual_written_length = content.count as usize;
-        log::debug!(
-            "Wrote {} bytes to {}.",
-            actual_written_length,
-            self.handle.name()
-        );
+        self.end_of_file
+            .fetch_max(pos + actual_written_length as u64, Ordering::SeqCst);
         Ok(actual_written_length)
     }
 
+    /// (Internal)
+    ///
+    /// Returns the size of the chunks to split reads or writes into,
+    /// and the number of chunks that may be in flight at once.
+    fn io_chunking(&self, write: bool) -> (usize, usize) {
+        let conn_info = self.handle.conn_info();
+        let negotiation = &conn_info.negotiation;
+        let max_size = if write {
+            negotiation.max_write_size
+        } else {
+            negotiation.max_read_size
+        } as usize;
+        let credits = conn_info
+            .config
+            .credits_backlog
+            .unwrap_or(ConnectionMessageHandler::DEFAULT_CREDITS_BACKLOG);
+        Self::chunking_for(max_size, negotiation.caps.large_mtu(), credits)
+    }
+
+    /// (Internal)
+    ///
+    /// Splits the credit window of the connection into chunks, so that several of them are in flight at once.
+    ///
+    /// Chunks are at most [`File::MAX_CHUNK_SIZE`], and never cost more credits than the connection requests,
+    /// so that a single request can always be sent once enough credits are returned.
+    fn chunking_for(max_size: usize, large_mtu: bool, credits: u16) -> (usize, usize) {
+        let credit_size = ConnectionMessageHandler::CREDIT_CALC_RATIO as usize;
+        if !large_mtu {
+            // Each request is charged a single credit.
+            return (max_size.clamp(1, credit_size), 1);
+        }
+
+        let credits = credits.max(1) as usize;
+        // Aim for a window of several chunks, without making chunks smaller than a single credit.
+        let window_chunk_size = (credits / Self::TARGET_IN_FLIGHT).max(1) * credit_size;
+        let chunk_size = max_size
+            .min(Self::MAX_CHUNK_SIZE)
+            .min(window_chunk_size)
+            .max(1);
+        let in_flight = credits / chunk_size.div_ceil(credit_size);
+        (chunk_size, in_flight.max(1))
+    }
+
+    /// The maximum size of a single read or write request, when large requests are split.
+    const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+    /// The number of chunks to aim to have in flight, when the credit window is small.
+    const TARGET_IN_FLIGHT: usize = 4;
+
     /// Sends a flush request to the server to flush the file.
     pub async fn flush(&self) -> std::io::Result<()> {
         let _response = self
             .handle
             .send_recvo(
                 FlushRequest {
-                    file_id: self.handle.file_id().map_err(std::io::Error::other)?,
+                    file_id: self.handle.file_id().map_err(std::io::Error::from)?,
                 }
                 .into(),
                 ReceiveOptions::new().with_allow_async(true),
             )
             .await
-            .map_err(|e| std::io::Error::other(e.to_string()))?;
+            .map_err(std::io::Error::from)?;
 
         log::debug!("Flushed {}.", self.handle.name());
         Ok(())
     }
 
-    /// Performs a server-side copy from another file on the same server.
+    /// Locks a byte range of the file.
+    ///
     /// # Arguments
-    /// * `from` - The file to copy from.
-    /// # Notes
-    /// * This copy must be performed against a file from the same share (tree) as this file.
-    pub async fn srv_copy(&self, from: &File) -> crate::Result<()> {
-        if !self.access.file_write_data() {
-            return Err(Error::InvalidState(
-                "No write permission on destination file".to_string(),
-            ));
-        }
-        if !from.access.file_read_data() {
-            return Err(Error::InvalidState(
-                "No read permission on source file".to_string(),
-            ));
-        }
+    /// * `range` - The range to lock. Locking beyond the end of the file is allowed.
+    /// * `mode` - Whether other opens may still read and lock the range. See [`LockMode`].
+    /// * `fail_immediately` - Whether to fail with [`Status::LockNotGranted`] when the range conflicts with
+    ///   an existing lock, instead of waiting until the range becomes available.
+    ///   Use [`File::lock_cancellable`] to be able to cancel the wait.
+    /// # Returns
+    /// A guard, that unlocks the range when dropped.
+    pub async fn lock(
+        &self,
+        range: Range<u64>,
+        mode: LockMode,
+        fail_immediately: bool,
+    ) -> crate::Result<FileLock> {
+        FileLock::acquire(
+            &self.handle.handler.handler,
+            range,
+            mode,
+            fail_immediately,
+            None,
+        )
+        .await
+    }
 
-        // Even if we weren't testing it properly, the remote would have returned
-        // [Status::ObjectNameNotFound] error for unmatching trees.
-        if !self.same_tree(from) {
-            return Err(Error::InvalidArgument(
-                "Source and destination files must be opened from the same share (tree)"
-                    .to_string(),
-            ));
+    /// Attempts to lock a byte range of the file, without waiting.
+    ///
+    /// Returns `None` if the range conflicts with an existing lock.
+    /// See [`File::lock`] for more information.
+    pub async fn try_lock(
+        &self,
+        range: Range<u64>,
+        mode: LockMode,
+    ) -> crate::Result<Option<FileLock>> {
+        match self.lock(range, mode, true).await {
+            Ok(lock) => Ok(Some(lock)),
+            Err(Error::ReceivedErrorMessage(Status::U32_LOCK_NOT_GRANTED, _)) => Ok(None),
+            Err(e) => Err(e),
         }
+    }
 
-        let other_end_of_file = from.get_len().await?;
-        self.set_len(other_end_of_file).await?;
-
-        let resume_key_response = from.fsctl(SrvRequestResumeKeyRequest(())).await?;
-        let resume_key = resume_key_response.resume_key;
-
-        let chunks = (0..other_end_of_file)
-            .step_by(CHUNK_SIZE)
-            .map(|start| {
-                let len_left = other_end_of_file - start;
-                SrvCopychunkItem {
-                    source_offset: start,
-                    target_offset: start,
-                    length: std::cmp::min(CHUNK_SIZE as u32, len_left as u32),
-                }
-            })
-            .collect::<Vec<_>>();
+    /// Locks a byte range of the file, waiting until the range becomes available.
+    ///
+    /// Once the server reports the lock as pending, `async_msg_ids` is set, and may be passed
+    /// to [`ResourceHandle::send_cancel`] to cancel the wait, in which case [`Error::Cancelled`] is returned.
+    /// See [`File::lock`] for more information.
+    pub async fn lock_cancellable(
+        &self,
+        range: Range<u64>,
+        mode: LockMode,
+        async_msg_ids: Arc<AsyncMessageIds>,
+    ) -> crate::Result<FileLock> {
+        FileLock::acquire(
+            &self.handle.handler.handler,
+            range,
+            mode,
+            false,
+            Some(async_msg_ids),
+        )
+        .await
+    }
 
-        const CHUNK_SIZE: usize = 1024 * 1024; // 1 MB
-        let req = SrvCopychunkCopy {
-            source_key: resume_key,
-            chunks,
-        };
-        let copy_response = self.fsctl(req).await?;
-        if copy_response.total_bytes_written as u64 != other_end_of_file {
-            return Err(Error::InvalidArgument(format!(
-                "Expected to write {} bytes, but wrote {} bytes",
-                other_end_of_file, copy_response.total_bytes_written
-            )));
-        }
-        Ok(())
+    /// Unlocks a byte range of the file.
+    ///
+    /// The range must be identical to a previously locked range.
+    /// This is usually not required, since the [`FileLock`] guard unlocks the range when dropped.
+    pub async fn unlock(&self, range: Range<u64>) -> crate::Result<()> {
+        FileLock::release(&self.handle.handler, range).await
     }
 }
 
@@ -300,7 +472,7 @@ impl Seek for File {
         let next_pos = match pos {
             std::io::SeekFrom::Start(pos) => pos,
             std::io::SeekFrom::End(pos) => {
-                let pos = self.end_of_file as i64 + pos;
+                let pos = self.end_of_file.load(Ordering::SeqCst) as i64 + pos;
                 if pos < 0 {
                     return Err(std::io::Error::new(
                         std::io::ErrorKind::InvalidInput,
@@ -324,7 +496,7 @@ impl Seek for File {
                 })?
             }
         };
-        if next_pos > self.end_of_file {
+        if next_pos > self.end_of_file.load(Ordering::SeqCst) {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
                 "Invalid seek position",
@@ -337,8 +509,7 @@ impl Seek for File {
 #[cfg(not(feature = "async"))]
 impl Read for File {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let read_length = File::read_block(self, buf, self.pos, None, false)
-            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        let read_length = File::read_block(self, buf, self.pos, None, false)?;
         self.pos += read_length as u64;
         Ok(read_length)
     }
@@ -392,7 +563,7 @@ impl WriteAtChannel for File {
 impl GetLen for File {
     #[maybe_async]
     async fn get_len(&self) -> crate::Result<u64> {
-        Ok(self.end_of_file)
+        Ok(self.end_of_file.load(Ordering::SeqCst))
     }
 }
 
@@ -400,7 +571,9 @@ impl SetLen for File {
     #[maybe_async]
     async fn set_len(&self, len: u64) -> crate::Result<()> {
         self.set_info(FileEndOfFileInformation { end_of_file: len })
-            .await
+            .await?;
+        self.end_of_file.store(len, Ordering::SeqCst);
+        Ok(())
     }
 }
 
@@ -417,3 +590,214 @@ impl DerefMut for File {
         &mut self.handle
     }
 }
+
+/// (Internal)
+///
+/// Sends a request for each chunk, and receives its response, with up to `in_flight` chunks in progress at once.
+/// Both functions are called with the offset of the chunk relative to the first one;
+/// `send` returns the message ID of the request, which is passed to `receive`.
+///
+/// Returns the total number of bytes transferred, up to (and including) the first short chunk.
+#[cfg(feature = "async")]
+async fn run_chunks<C, S, SFut, R, RFut>(
+    chunks: impl ExactSizeIterator<Item = C>,
+    chunk_size: usize,
+    in_flight: usize,
+    send: S,
+    receive: R,
+) -> std::io::Result<usize>
+where
+    S: Fn(u64, &C) -> SFut,
+    SFut: std::future::Future<Output = std::io::Result<u64>>,
+    R: Fn(u64, u64, C) -> RFut,
+    RFut: std::future::Future<Output = std::io::Result<usize>>,
+{
+    use futures_util::StreamExt;
+
+    // The requests are created up front, and only polled (sent) as the window allows.
+    let (send, receive) = (&send, &receive);
+    let mut requests = Vec::with_capacity(chunks.len());
+    for (index, chunk) in chunks.enumerate() {
+        let offset = (index * chunk_size) as u64;
+        requests.push(async move {
+            let msg_id = send(offset, &chunk).await?;
+            receive(offset, msg_id, chunk).await
+        });
+    }
+    let results = futures_util::stream::iter(requests)
+        .buffered(in_flight)
+        .collect::<Vec<_>>()
+        .await;
+    sum_chunks(results, chunk_size)
+}
+
+/// (Internal)
+///
+/// Sends a request for each chunk, and receives its response, with up to `in_flight` chunks in progress at once.
+/// Both functions are called with the offset of the chunk relative to the first one;
+/// `send` returns the message ID of the request, which is passed to `receive`.
+///
+/// The connection's worker receives responses in the background, so a window of requests is sent
+/// before the oldest response is waited for, without any additional threads.
+///
+/// Returns the total number of bytes transferred, up to (and including) the first short chunk.
+#[cfg(feature = "multi_threaded")]
+fn run_chunks<C>(
+    chunks: impl ExactSizeIterator<Item = C>,
+    chunk_size: usize,
+    in_flight: usize,
+    send: impl Fn(u64, &C) -> std::io::Result<u64>,
+    receive: impl Fn(u64, u64, C) -> std::io::Result<usize>,
+) -> std::io::Result<usize> {
+    let mut results = Vec::with_capacity(chunks.len());
+    let mut window = std::collections::VecDeque::with_capacity(in_flight);
+    let mut send_error = None;
+    for (index, chunk) in chunks.enumerate() {
+        if window.len() >= in_flight {
+            let (offset, msg_id, chunk) = window.pop_front().unwrap();
+            results.push(receive(offset, msg_id, chunk));
+        }
+        let offset = (index * chunk_size) as u64;
+        match send(offset, &chunk) {
+            Ok(msg_id) => window.push_back((offset, msg_id, chunk)),
+            Err(e) => {
+                send_error = Some(e);
+                break;
+            }
+        }
+    }
+    // Responses of requests already sent are always received.
+    for (offset, msg_id, chunk) in window {
+        results.push(receive(offset, msg_id, chunk));
+    }
+    results.extend(send_error.map(Err));
+    sum_chunks(results, chunk_size)
+}
+
+/// (Internal)
+///
+/// Sends a request for each chunk and receives its response, one chunk after the other.
+/// Both functions are called with the offset of the chunk relative to the first one;
+/// `send` returns the message ID of the request, which is passed to `receive`.
+///
+/// Returns the total number of bytes transferred, up to (and including) the first short chunk.
+#[cfg(feature = "single_threaded")]
+fn run_chunks<C>(
+    chunks: impl ExactSizeIterator<Item = C>,
+    chunk_size: usize,
+    _in_flight: usize,
+    send: impl Fn(u64, &C) -> std::io::Result<u64>,
+    receive: impl Fn(u64, u64, C) -> std::io::Result<usize>,
+) -> std::io::Result<usize> {
+    let mut total = 0;
+    for (index, chunk) in chunks.enumerate() {
+        let offset = (index * chunk_size) as u64;
+        let msg_id = send(offset, &chunk)?;
+        let length = receive(offset, msg_id, chunk)?;
+        total += length;
+        if length < chunk_size {
+            break;
+        }
+    }
+    Ok(total)
+}
+
+/// (Internal)
+///
+/// Sums the results of consecutive chunks, stopping at the first short chunk:
+/// data past it was not transferred contiguously, and errors past it are irrelevant.
+#[cfg(not(feature = "single_threaded"))]
+fn sum_chunks(
+    results: impl IntoIterator<Item = std::io::Result<usize>>,
+    chunk_size: usize,
+) -> std::io::Result<usize> {
+    let mut total = 0;
+    for result in results {
+        let length = result?;
+        total += length;
+        if length < chunk_size {
+            break;
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::File;
+
+    #[test]
+    fn test_chunking_for() {
+        const MIB: usize = 1024 * 1024;
+        // Default credit backlog, with the 8 MiB maximum size of Windows and Samba.
+        assert_eq!(File::chunking_for(8 * MIB, true, 128), (MIB, 8));
+        // A small window is still split into several chunks.
+        assert_eq!(File::chunking_for(8 * MIB, true, 16), (4 * 65536, 4));
+        assert_eq!(File::chunking_for(8 * MIB, true, 2), (65536, 2));
+        assert_eq!(File::chunking_for(8 * MIB, true, 0), (65536, 1));
+        // Small maximum sizes are kept.
+        assert_eq!(File::chunking_for(65536, true, 128), (65536, 128));
+        // Without large MTU, each request is charged a single credit, and only one is in flight.
+        assert_eq!(File::chunking_for(8 * MIB, false, 128), (65536, 1));
+    }
+
+    #[test]
+    #[cfg(not(feature = "single_threaded"))]
+    fn test_run_chunks_in_flight() {
+        use super::run_chunks;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const CHUNK_SIZE: usize = 4;
+        const IN_FLIGHT: usize = 3;
+        let data = [0u8; CHUNK_SIZE * 10];
+        let outstanding = AtomicUsize::new(0);
+        let max_outstanding = AtomicUsize::new(0);
+
+        #[maybe_async::maybe_async]
+        async fn send(
+            outstanding: &AtomicUsize,
+            max_outstanding: &AtomicUsize,
+            offset: u64,
+        ) -> std::io::Result<u64> {
+            let current = outstanding.fetch_add(1, Ordering::SeqCst) + 1;
+            max_outstanding.fetch_max(current, Ordering::SeqCst);
+            Ok(offset)
+        }
+
+        // Lets the other chunks be sent before this one is received.
+        #[cfg(feature = "async")]
+        async fn yield_now() {
+            tokio::task::yield_now().await
+        }
+        #[cfg(not(feature = "async"))]
+        fn yield_now() {}
+
+        #[maybe_async::maybe_async]
+        async fn receive(outstanding: &AtomicUsize, chunk: &[u8]) -> std::io::Result<usize> {
+            yield_now().await;
+            outstanding.fetch_sub(1, Ordering::SeqCst);
+            Ok(chunk.len())
+        }
+
+        let run = || {
+            run_chunks(
+                data.chunks(CHUNK_SIZE),
+                CHUNK_SIZE,
+                IN_FLIGHT,
+                |offset, _| send(&outstanding, &max_outstanding, offset),
+                |_, _, chunk| receive(&outstanding, chunk),
+            )
+        };
+        #[cfg(feature = "async")]
+        let total = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(run());
+        #[cfg(not(feature = "async"))]
+        let total = run();
+
+        assert_eq!(total.unwrap(), data.len());
+        assert_eq!(max_outstanding.load(Ordering::SeqCst), IN_FLIGHT);
+        assert_eq!(outstanding.load(Ordering::SeqCst), 0);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::sync_helpers::*;
 use maybe_async::*;
+use std::ops::Range;
 
 /// This trait describes an object that can perform read operations at a specific offset,
 /// optionally using a specific channel ID.
@@ -128,6 +129,59 @@ pub trait SetLen {
     async fn set_len(&self, len: u64) -> crate::Result<()>;
 }
 
+/// This trait describes an object that may contain holes: ranges that read as zeros,
+/// without being allocated. Used by the sparse block copy functions, to skip reading holes.
+#[maybe_async(AFIT)]
+#[allow(async_fn_in_trait)]
+pub trait GetAllocatedRanges {
+    /// Returns the allocated ranges of the object, in ascending order.
+    async fn allocated_ranges(&self) -> crate::Result<Vec<Range<u64>>>;
+}
+
+/// This trait describes an object that can be made sparse, so that ranges that are not written to
+/// remain holes. Used by the sparse block copy functions, to recreate holes.
+#[maybe_async(AFIT)]
+#[allow(async_fn_in_trait)]
+pub trait SetSparse {
+    async fn set_sparse(&self, sparse: bool) -> crate::Result<()>;
+}
+
+/// This trait describes an object that may have named data streams besides its default data,
+/// which can be copied to a destination of type `T`. Used by the block copy functions,
+/// when [`CopyOptions::streams`] is set.
+#[maybe_async(AFIT)]
+#[allow(async_fn_in_trait)]
+pub trait CopyStreams<T> {
+    /// Copies the named data streams to `to`, replacing streams of the same name.
+    async fn copy_streams(&self, to: &T) -> crate::Result<()>;
+}
+
+/// Options of the block copy functions, for copying more than the default data of the source.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CopyOptions {
+    /// Only read the allocated ranges of the source, and make the destination sparse,
+    /// so that holes of the source remain holes in the destination.
+    ///
+    /// The destination is truncated before the copy, so that any previous data in it is not left in the holes.
+    pub sparse: bool,
+    /// Also copy the named data streams of the source, such as `Zone.Identifier`.
+    pub streams: bool,
+}
+
+/// (Internal)
+///
+/// Returns the parts of `range` that intersect the sorted `allocated` ranges.
+fn intersect_ranges(
+    range: Range<u64>,
+    allocated: &[Range<u64>],
+) -> impl Iterator<Item = Range<u64>> + '_ {
+    let first = allocated.partition_point(|allocated| allocated.end <= range.start);
+    allocated[first..]
+        .iter()
+        .take_while(move |allocated| allocated.start < range.end)
+        .map(move |allocated| allocated.start.max(range.start)..allocated.end.min(range.end))
+}
+
 #[cfg(feature = "std-fs-impls")]
 mod impls {
     use super::*;
@@ -208,6 +262,25 @@ mod impls {
             Ok(File::set_len(&file, len).await?)
         }
     }
+
+    /// Holes of local files are not detected: the entire file is reported as allocated.
+    impl GetAllocatedRanges for Mutex<File> {
+        #[maybe_async]
+        async fn allocated_ranges(&self) -> crate::Result<Vec<Range<u64>>> {
+            let len = self.get_len().await?;
+            Ok(std::iter::once(0..len)
+                .filter(|range| !range.is_empty())
+                .collect())
+        }
+    }
+
+    /// Local files are extended with holes on file systems that support them, so this is a no-op.
+    impl SetSparse for Mutex<File> {
+        #[maybe_async]
+        async fn set_sparse(&self, _sparse: bool) -> crate::Result<()> {
+            Ok(())
+        }
+    }
 }
 
 #[cfg(feature = "std-fs-impls")]
@@ -231,6 +304,9 @@ mod copy {
 
         max_chunk_size: u64,
         channel_jobs: HashMap<Option<u32>, usize>,
+
+        /// The allocated ranges of the source, for sparse copies. Other ranges are not copied.
+        allocated: Option<Vec<Range<u64>>>,
     }
 
     impl CopyState {
@@ -397,6 +473,7 @@ mod copy {
                 total_size: 0,
                 max_chunk_size: CHUNK_SIZE,
                 channel_jobs,
+                allocated: None,
             });
         }
 
@@ -406,9 +483,103 @@ mod copy {
             total_size: file_length,
             max_chunk_size: CHUNK_SIZE,
             channel_jobs,
+            allocated: None,
         })
     }
 
+    /// Sparse block copy function.
+    ///
+    /// Like [`block_copy`], but only the allocated ranges of the source are read,
+    /// and the destination is made sparse, so that holes of the source remain holes in the destination.
+    ///
+    /// # Notes
+    /// - The destination is truncated before the copy, so that any previous data in it is not left in the holes.
+    #[maybe_async]
+    pub async fn block_copy_sparse<
+        F: ReadAtChannel + GetLen + GetAllocatedRanges + Send + Sync + 'static,
+        T: WriteAtChannel + SetLen + SetSparse + Send + Sync + 'static,
+    >(
+        from: F,
+        to: T,
+        jobs: usize,
+    ) -> crate::Result<()> {
+        let copy_state =
+            prepare_parallel_copy_sparse(&from, &to, HashMap::from([(None, jobs)])).await?;
+
+        log::debug!("Starting sparse parallel copy: {copy_state:?}",);
+        start_parallel_copy(from, to, Arc::new(copy_state)).await?;
+
+        Ok(())
+    }
+
+    /// Returns a CopyState that can be used to start a sparse parallel copy.
+    ///
+    /// See [`prepare_parallel_copy`] and [`block_copy_sparse`] for more details.
+    #[maybe_async]
+    pub async fn prepare_parallel_copy_sparse<
+        F: ReadAtChannel + GetLen + GetAllocatedRanges + Send + Sync + 'static,
+        T: WriteAtChannel + SetLen + SetSparse + Send + Sync + 'static,
+    >(
+        from: &F,
+        to: &T,
+        channel_jobs: HashMap<Option<u32>, usize>,
+    ) -> crate::Result<CopyState> {
+        let allocated = from.allocated_ranges().await?;
+        to.set_sparse(true).await?;
+        to.set_len(0).await?;
+
+        let mut copy_state = prepare_parallel_copy(from, to, channel_jobs).await?;
+        copy_state.allocated = Some(allocated);
+        Ok(copy_state)
+    }
+
+    /// Block copy function with [`CopyOptions`].
+    ///
+    /// Like [`block_copy`], but the source may also be copied sparsely, or along with its named data streams.
+    #[maybe_async]
+    pub async fn block_copy_with_options<
+        F: ReadAtChannel + GetLen + GetAllocatedRanges + CopyStreams<T> + Send + Sync + 'static,
+        T: WriteAtChannel + SetLen + SetSparse + Send + Sync + 'static,
+    >(
+        from: F,
+        to: T,
+        jobs: usize,
+        options: CopyOptions,
+    ) -> crate::Result<()> {
+        let copy_state =
+            prepare_parallel_copy_with_options(&from, &to, HashMap::from([(None, jobs)]), options)
+                .await?;
+
+        log::debug!("Starting parallel copy with {options:?}: {copy_state:?}",);
+        start_parallel_copy(from, to, Arc::new(copy_state)).await?;
+
+        Ok(())
+    }
+
+    /// Returns a CopyState that can be used to start a parallel copy with [`CopyOptions`].
+    ///
+    /// Named data streams are copied before returning, if requested, since they are usually small.
+    /// See [`prepare_parallel_copy`] and [`block_copy_with_options`] for more details.
+    #[maybe_async]
+    pub async fn prepare_parallel_copy_with_options<
+        F: ReadAtChannel + GetLen + GetAllocatedRanges + CopyStreams<T> + Send + Sync + 'static,
+        T: WriteAtChannel + SetLen + SetSparse + Send + Sync + 'static,
+    >(
+        from: &F,
+        to: &T,
+        channel_jobs: HashMap<Option<u32>, usize>,
+        options: CopyOptions,
+    ) -> crate::Result<CopyState> {
+        if options.streams {
+            from.copy_streams(to).await?;
+        }
+        if options.sparse {
+            prepare_parallel_copy_sparse(from, to, channel_jobs).await
+        } else {
+            prepare_parallel_copy(from, to, channel_jobs).await
+        }
+    }
+
     /// Starts a parallel copy using the provided [`CopyState`].
     ///
     /// See [`prepare_parallel_copy`] for more details.
@@ -511,18 +682,27 @@ mod copy {
                 state.max_chunk_size
             } as usize;
 
-            let offset = current_block * state.max_chunk_size;
-            let bytes_read = from
-                .read_at_channel(&mut curr_chunk[..chunk_size], offset, channel_id)
-                .await?;
-            if bytes_read < chunk_size {
-                log::warn!(
-                    "Task {task_id}@{channel_id:?}: Read less bytes than expected. File might be corrupt. Expected: {chunk_size}: {bytes_read}"
-                );
+            let block_offset = current_block * state.max_chunk_size;
+            let block = block_offset..block_offset + chunk_size as u64;
+            let ranges = match &state.allocated {
+                Some(allocated) => intersect_ranges(block, allocated).collect(),
+                None => vec![block],
+            };
+            for range in ranges {
+                let offset = range.start;
+                let chunk_size = (range.end - range.start) as usize;
+                let bytes_read = from
+                    .read_at_channel(&mut curr_chunk[..chunk_size], offset, channel_id)
+                    .await?;
+                if bytes_read < chunk_size {
+                    log::warn!(
+                        "Task {task_id}@{channel_id:?}: Read less bytes than expected. File might be corrupt. Expected: {chunk_size}: {bytes_read}"
+                    );
+                }
+                let valid_chunk_end = bytes_read;
+                to.write_at_channel(&curr_chunk[..valid_chunk_end], offset, channel_id)
+                    .await?;
             }
-            let valid_chunk_end = bytes_read;
-            to.write_at_channel(&curr_chunk[..valid_chunk_end], offset, channel_id)
-                .await?;
         }
         log::debug!("Copy task {task_id}@{channel_id:?} completed",);
         Ok(())
@@ -569,32 +749,115 @@ mod copy {
     ) -> crate::Result<()> {
         let file_length = from.get_len()?;
         to.set_len(file_length)?;
+        copy_ranges(
+            &from,
+            &to,
+            std::slice::from_ref(&(0..file_length)),
+            file_length,
+            progress_callback,
+            channel,
+        )
+    }
+
+    /// Sparse block copy function.
+    ///
+    /// Like [`block_copy`], but only the allocated ranges of the source are read,
+    /// and the destination is made sparse, so that holes of the source remain holes in the destination.
+    ///
+    /// # Notes
+    /// * The destination is truncated before the copy, so that any previous data in it is not left in the holes.
+    pub fn block_copy_sparse<
+        F: ReadAtChannel + GetLen + GetAllocatedRanges,
+        T: WriteAtChannel + SetLen + SetSparse,
+    >(
+        from: F,
+        to: T,
+    ) -> crate::Result<()> {
+        block_copy_sparse_progress(from, to, None)
+    }
+
+    /// Sparse block copy function with progress callback.
+    ///
+    /// * `progress_callback` - A callback function that will be called with the offset copied up to so far.
+    pub fn block_copy_sparse_progress<
+        F: ReadAtChannel + GetLen + GetAllocatedRanges,
+        T: WriteAtChannel + SetLen + SetSparse,
+    >(
+        from: F,
+        to: T,
+        progress_callback: Option<&dyn Fn(u64)>,
+    ) -> crate::Result<()> {
+        let file_length = from.get_len()?;
+        let allocated = from.allocated_ranges()?;
+        to.set_sparse(true)?;
+        to.set_len(0)?;
+        to.set_len(file_length)?;
+        copy_ranges(&from, &to, &allocated, file_length, progress_callback, None)
+    }
+
+    /// Block copy function with [`CopyOptions`] and progress callback.
+    ///
+    /// Like [`block_copy_progress`], but the source may also be copied sparsely, or along with its named data streams.
+    /// Named data streams are copied before the default data, since they are usually small.
+    pub fn block_copy_with_options<
+        F: ReadAtChannel + GetLen + GetAllocatedRanges + CopyStreams<T>,
+        T: WriteAtChannel + SetLen + SetSparse,
+    >(
+        from: F,
+        to: T,
+        options: CopyOptions,
+        progress_callback: Option<&dyn Fn(u64)>,
+    ) -> crate::Result<()> {
+        if options.streams {
+            from.copy_streams(&to)?;
+        }
+        if options.sparse {
+            block_copy_sparse_progress(from, to, progress_callback)
+        } else {
+            block_copy_progress(from, to, progress_callback)
+        }
+    }
 
+    /// (Internal)
+    ///
+    /// Copies the specified ranges of the source, which are clamped to `file_length`.
+    fn copy_ranges<F: ReadAtChannel, T: WriteAtChannel>(
+        from: &F,
+        to: &T,
+        ranges: &[Range<u64>],
+        file_length: u64,
+        progress_callback: Option<&dyn Fn(u64)>,
+        channel: Option<u32>,
+    ) -> crate::Result<()> {
         if file_length == 0 {
             log::debug!("Source file is empty, nothing to copy.");
             return Ok(());
         }
 
         let mut curr_chunk = vec![0u8; 2u64.pow(16) as usize];
-        let mut offset = 0;
-
-        while offset < file_length {
-            let chunk_size = if offset + curr_chunk.len() as u64 > file_length {
-                (file_length - offset) as usize
-            } else {
-                curr_chunk.len()
-            };
-            let bytes_read =
-                from.read_at_channel(&mut curr_chunk[..chunk_size], offset, channel)?;
-            if bytes_read < chunk_size {
-                log::warn!(
-                    "Read less bytes than expected. File might be corrupt. Expected: {chunk_size}: {bytes_read}"
-                );
-            }
-            to.write_at(&curr_chunk[..bytes_read], offset)?;
-            offset += bytes_read as u64;
-            if let Some(callback) = progress_callback {
-                callback(offset);
+        for range in intersect_ranges(0..file_length, ranges) {
+            let mut offset = range.start;
+            while offset < range.end {
+                let chunk_size = if offset + curr_chunk.len() as u64 > range.end {
+                    (range.end - offset) as usize
+                } else {
+                    curr_chunk.len()
+                };
+                let bytes_read =
+                    from.read_at_channel(&mut curr_chunk[..chunk_size], offset, channel)?;
+                if bytes_read < chunk_size {
+                    log::warn!(
+                        "Read less bytes than expected. File might be corrupt. Expected: {chunk_size}: {bytes_read}"
+                    );
+                }
+                if bytes_read == 0 {
+                    break;
+                }
+                to.write_at_channel(&curr_chunk[..bytes_read], offset, channel)?;
+                offset += bytes_read as u64;
+                if let Some(callback) = progress_callback {
+                    callback(offset);
+                }
             }
         }
         Ok(())
@@ -602,3 +865,21 @@ mod copy {
 }
 
 pub use copy::*;
+
+#[cfg(test)]
+mod tests {
+    use super::intersect_ranges;
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_intersect_ranges() {
+        let allocated = [0..10, 20..30, 40..50];
+        let intersect =
+            |range: std::ops::Range<u64>| intersect_ranges(range, &allocated).collect::<Vec<_>>();
+        assert_eq!(intersect(0..100), allocated);
+        assert_eq!(intersect(5..25), [5..10, 20..25]);
+        assert_eq!(intersect(10..20), []);
+        assert_eq!(intersect(45..60), [45..50]);
+        assert_eq!(intersect(60..70), []);
+    }
+}
@@ -0,0 +1,265 @@
+//! Byte-range locks on opened files.
+//!
+//! Ranges are locked using [`File::lock`][crate::File::lock] and its variants,
+//! which return a [`FileLock`] guard, that unlocks the range when dropped.
+
+use std::{ops::Range, sync::Arc};
+
+use maybe_async::*;
+use smb_msg::*;
+
+use crate::{
+    Error,
+    msg_handler::{AsyncMessageIds, ReceiveOptions},
+};
+
+use super::ResourceMessageHandle;
+
+/// The mode of a byte-range lock.
+///
+/// Reference: MS-SMB2 2.2.26.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// Other opens may read the range, and lock it shared as well, but may not write to it.
+    Shared,
+    /// Other opens may not read, write or lock the range.
+    Exclusive,
+}
+
+impl LockMode {
+    fn flags(&self) -> LockFlag {
+        match self {
+            LockMode::Shared => LockFlag::new().with_shared(true),
+            LockMode::Exclusive => LockFlag::new().with_exclusive(true),
+        }
+    }
+}
+
+/// A locked byte range of a file.
+///
+/// The range is unlocked when the guard is dropped. Use [`FileLock::unlock`]
+/// to unlock the range explicitly, and observe the result.
+///
+/// Closing the file releases all of its locks on the server, so dropping a guard
+/// of a closed file does nothing.
+#[must_use = "the range is unlocked as soon as the guard is dropped"]
+pub struct FileLock {
+    /// Taken when the range is unlocked.
+    handler: Option<Arc<ResourceMessageHandle>>,
+    range: Range<u64>,
+    mode: LockMode,
+}
+
+#[maybe_async(AFIT)]
+impl FileLock {
+    /// (Internal)
+    ///
+    /// Locks the range, waiting for it to become available unless `fail_immediately` is set.
+    pub(crate) async fn acquire(
+        handler: &Arc<ResourceMessageHandle>,
+        range: Range<u64>,
+        mode: LockMode,
+        fail_immediately: bool,
+        async_msg_ids: Option<Arc<AsyncMessageIds>>,
+    ) -> crate::Result<Self> {
+        let element = LockElement {
+            offset: range.start,
+            length: Self::range_length(&range)?,
+            flags: mode.flags().with_fail_immediately(fail_immediately),
+        };
+
+        let mut options = ReceiveOptions::new();
+        if !fail_immediately {
+            // A blocking lock is answered with an interim response,
+            // and completes only when the range becomes available, or the request is cancelled.
+            options = options
+                .with_allow_async(true)
+                .with_status(&[Status::Success, Status::Cancelled]);
+            // Zero stands for no timeout; The single threaded worker only supports the default one.
+            #[cfg(not(feature = "single_threaded"))]
+            {
+                options = options.with_timeout(std::time::Duration::ZERO);
+            }
+            if let Some(async_msg_ids) = async_msg_ids {
+                options = options.with_async_msg_ids(async_msg_ids);
+            }
+        }
+
+        let response = handler.send_lock(element, options).await?;
+        if response.message.header.status == Status::Cancelled as u32 {
+            return Err(Error::Cancelled("lock"));
+        }
+
+        log::debug!("Locked range {range:?} of {} ({mode:?})", handler.name());
+        Ok(Self {
+            handler: Some(handler.clone()),
+            range,
+            mode,
+        })
+    }
+
+    /// (Internal)
+    ///
+    /// Unlocks a range, that must be identical to a previously locked range.
+    pub(crate) async fn release(
+        handler: &ResourceMessageHandle,
+        range: Range<u64>,
+    ) -> crate::Result<()> {
+        let element = LockElement {
+            offset: range.start,
+            length: Self::range_length(&range)?,
+            flags: LockFlag::new().with_unlock(true),
+        };
+        handler.send_lock(element, ReceiveOptions::new()).await?;
+        log::debug!("Unlocked range {range:?} of {}", handler.name());
+        Ok(())
+    }
+
+    fn range_length(range: &Range<u64>) -> crate::Result<u64> {
+        range
+            .end
+            .checked_sub(range.start)
+            .ok_or_else(|| Error::InvalidArgument(format!("Invalid lock range: {range:?}")))
+    }
+
+    /// Returns the locked range.
+    pub fn range(&self) -> Range<u64> {
+        self.range.clone()
+    }
+
+    /// Returns the mode of the lock.
+    pub fn mode(&self) -> LockMode {
+        self.mode
+    }
+
+    /// Unlocks the range.
+    pub async fn unlock(mut self) -> crate::Result<()> {
+        match self.handler.take() {
+            Some(handler) => Self::release(&handler, self.range.clone()).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Debug for FileLock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileLock")
+            .field("range", &self.range)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let Some(handler) = self.handler.take() else {
+            return;
+        };
+        if handler.file_id().is_err() {
+            return;
+        }
+        if let Err(e) = Self::release(&handler, self.range.clone()) {
+            log::warn!("Failed to unlock range {:?} on drop: {e}", self.range);
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let Some(handler) = self.handler.take() else {
+            return;
+        };
+        if handler.file_id().is_err() {
+            return;
+        }
+        let range = self.range.clone();
+        tokio::task::spawn(async move {
+            if let Err(e) = Self::release(&handler, range.clone()).await {
+                log::warn!("Failed to unlock range {range:?} on drop: {e}");
+            }
+        });
+    }
+}
+
+/// (Internal)
+///
+/// The lock sequence state of an open, used by the server to detect replays
+/// of lock requests of durable opens.
+///
+/// Reference: MS-SMB2 3.2.1.6 (Open.OperationBuckets), 3.2.4.19
+#[derive(Debug)]
+pub(crate) struct LockSequenceBuckets {
+    buckets: [LockSequenceBucket; Self::COUNT],
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct LockSequenceBucket {
+    sequence_number: u8,
+    in_use: bool,
+}
+
+impl Default for LockSequenceBuckets {
+    fn default() -> Self {
+        Self {
+            buckets: [LockSequenceBucket::default(); Self::COUNT],
+        }
+    }
+}
+
+impl LockSequenceBuckets {
+    const COUNT: usize = 64;
+
+    /// Takes a free bucket, and returns the sequence to send in a lock request.
+    ///
+    /// Returns `None` if all the buckets are in use.
+    pub fn acquire(&mut self) -> Option<LockSequence> {
+        let (index, bucket) = self
+            .buckets
+            .iter_mut()
+            .enumerate()
+            .find(|(_, b)| !b.in_use)?;
+        bucket.sequence_number = (bucket.sequence_number + 1) % 16;
+        bucket.in_use = true;
+        Some(
+            LockSequence::new()
+                .with_number(bucket.sequence_number)
+                .with_index(index as u32 + 1),
+        )
+    }
+
+    /// Frees the bucket of a completed lock request.
+    pub fn release(&mut self, sequence: LockSequence) {
+        if let Some(bucket) = (sequence.index() as usize)
+            .checked_sub(1)
+            .and_then(|i| self.buckets.get_mut(i))
+        {
+            bucket.in_use = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LockSequenceBuckets;
+
+    #[test]
+    fn test_lock_sequence_buckets() {
+        let mut buckets = LockSequenceBuckets::default();
+        let first = buckets.acquire().unwrap();
+        assert_eq!((first.number(), first.index()), (1, 1));
+        let second = buckets.acquire().unwrap();
+        assert_eq!((second.number(), second.index()), (1, 2));
+
+        // A freed bucket is reused, with the next sequence number.
+        buckets.release(first);
+        let third = buckets.acquire().unwrap();
+        assert_eq!((third.number(), third.index()), (2, 1));
+
+        for _ in 2..LockSequenceBuckets::COUNT {
+            buckets.acquire().unwrap();
+        }
+        assert!(buckets.acquire().is_none());
+    }
+}
@@ -0,0 +1,363 @@
+//! Oplocks & leases for opened resources.
+//!
+//! Caching is requested using [`FileCreateArgs::oplock`][crate::FileCreateArgs::oplock].
+//! When the server breaks an oplock or a lease, the break is routed to the owning
+//! [`ResourceHandle`][crate::ResourceHandle] (or to all the opens sharing the lease),
+//! and acknowledged automatically, unless the [`OplockBreakCallback`] of an open decides otherwise.
+
+use std::collections::HashMap;
+
+use maybe_async::*;
+use smb_dtyp::Guid;
+use smb_msg::*;
+
+use crate::{
+    Error,
+    msg_handler::{IncomingMessage, MessageHandler},
+    sync_helpers::*,
+};
+
+use super::ResourceMessageHandle;
+
+/// The caching requested by the client when opening a resource.
+///
+/// Reference: MS-SMB2 2.2.13, 3.2.4.3.8
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OplockRequest {
+    /// No caching is requested.
+    #[default]
+    None,
+    /// A level II oplock - read caching, shared with other opens.
+    LevelII,
+    /// An exclusive oplock - read & write caching.
+    Exclusive,
+    /// A batch oplock - read, write & handle caching.
+    Batch,
+    /// A lease. Requires the SMB 2.1 dialect or above.
+    Lease(LeaseRequest),
+}
+
+impl OplockRequest {
+    /// Returns the oplock level to send in the create request.
+    pub fn level(&self) -> OplockLevel {
+        match self {
+            OplockRequest::None => OplockLevel::None,
+            OplockRequest::LevelII => OplockLevel::II,
+            OplockRequest::Exclusive => OplockLevel::Exclusive,
+            OplockRequest::Batch => OplockLevel::Batch,
+            OplockRequest::Lease(_) => OplockLevel::Lease,
+        }
+    }
+}
+
+/// A lease to request when opening a resource.
+///
+/// The lease version is selected according to the negotiated dialect:
+/// version 1 for SMB 2.1, and version 2 for the SMB 3.x dialect family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeaseRequest {
+    /// Identifies the lease. Should be unique per open, unless opens of the
+    /// same file are meant to share the lease.
+    pub lease_key: u128,
+    /// The requested caching.
+    pub lease_state: LeaseState,
+    /// The lease key of the parent directory, if any. Ignored for SMB 2.1.
+    pub parent_lease_key: Option<u128>,
+}
+
+impl LeaseRequest {
+    /// Creates a new lease request for the specified state, with a random lease key.
+    pub fn new(lease_state: LeaseState) -> Self {
+        Self {
+            lease_key: Guid::generate().as_u128(),
+            lease_state,
+            parent_lease_key: None,
+        }
+    }
+
+    /// Creates a new lease request for read, write & handle caching, with a random lease key.
+    pub fn rwh() -> Self {
+        Self::new(
+            LeaseState::new()
+                .with_read_caching(true)
+                .with_write_caching(true)
+                .with_handle_caching(true),
+        )
+    }
+
+    /// Returns the create context to send for this request.
+    pub(crate) fn to_context(self, dialect: Dialect) -> CreateContextRequest {
+        let lease = if dialect.is_smb3() {
+            RequestLease::RqLsReqv2(RequestLeaseV2 {
+                lease_key: self.lease_key,
+                lease_state: self.lease_state,
+                lease_flags: LeaseFlags::new()
+                    .with_parent_lease_key_set(self.parent_lease_key.is_some()),
+                parent_lease_key: self.parent_lease_key.unwrap_or_default(),
+                epoch: 0,
+            })
+        } else {
+            RequestLease::RqLsReqv1(RequestLeaseV1 {
+                lease_key: self.lease_key,
+                lease_state: self.lease_state,
+            })
+        };
+        lease.into()
+    }
+}
+
+/// The caching currently granted by the server for an open.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GrantedOplock {
+    /// Nothing is cached.
+    #[default]
+    None,
+    /// An oplock of the specified level is held.
+    Oplock(OplockLevel),
+    /// A lease is held.
+    Lease {
+        lease_key: u128,
+        lease_state: LeaseState,
+        /// Only valid for the SMB 3.x dialect family.
+        epoch: u16,
+    },
+}
+
+impl GrantedOplock {
+    /// (Internal)
+    ///
+    /// Builds the granted caching from the server's create response.
+    pub(crate) fn from_response(response: &CreateResponse) -> crate::Result<Self> {
+        match response.oplock_level {
+            OplockLevel::None => Ok(GrantedOplock::None),
+            OplockLevel::Lease => {
+                let lease = CreateContextResponseData::first_rqls(&response.create_contexts)
+                    .ok_or_else(|| {
+                        Error::InvalidMessage(
+                            "Lease granted, but no lease context in create response".to_string(),
+                        )
+                    })?;
+                Ok(match lease {
+                    RequestLease::RqLsReqv1(v1) => GrantedOplock::Lease {
+                        lease_key: v1.lease_key,
+                        lease_state: v1.lease_state,
+                        epoch: 0,
+                    },
+                    RequestLease::RqLsReqv2(v2) => GrantedOplock::Lease {
+                        lease_key: v2.lease_key,
+                        lease_state: v2.lease_state,
+                        epoch: v2.epoch,
+                    },
+                })
+            }
+            level => Ok(GrantedOplock::Oplock(level)),
+        }
+    }
+
+    /// Returns whether any caching is currently granted.
+    pub fn is_none(&self) -> bool {
+        match self {
+            GrantedOplock::None | GrantedOplock::Oplock(OplockLevel::None) => true,
+            GrantedOplock::Lease { lease_state, .. } => *lease_state == LeaseState::new(),
+            GrantedOplock::Oplock(_) => false,
+        }
+    }
+
+    /// Returns whether reads may be served from a local cache.
+    pub fn can_cache_reads(&self) -> bool {
+        match self {
+            GrantedOplock::Oplock(level) => *level != OplockLevel::None,
+            GrantedOplock::Lease { lease_state, .. } => lease_state.read_caching(),
+            GrantedOplock::None => false,
+        }
+    }
+
+    /// Returns whether writes may be cached locally.
+    pub fn can_cache_writes(&self) -> bool {
+        match self {
+            GrantedOplock::Oplock(level) => {
+                matches!(level, OplockLevel::Exclusive | OplockLevel::Batch)
+            }
+            GrantedOplock::Lease { lease_state, .. } => lease_state.write_caching(),
+            GrantedOplock::None => false,
+        }
+    }
+}
+
+/// A break of an oplock or a lease, as received from the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OplockBreak {
+    /// Reference: MS-SMB2 2.2.23.1
+    Oplock {
+        /// The maximum oplock level the server accepts for the open.
+        new_level: OplockLevel,
+        /// Whether the server waits for an acknowledgment.
+        ack_required: bool,
+    },
+    /// Reference: MS-SMB2 2.2.23.2
+    Lease {
+        lease_key: u128,
+        current_state: LeaseState,
+        new_state: LeaseState,
+        /// Only valid for the SMB 3.x dialect family.
+        new_epoch: u16,
+        /// Whether the server waits for an acknowledgment.
+        ack_required: bool,
+    },
+}
+
+impl OplockBreak {
+    /// Returns whether the server waits for an acknowledgment of this break.
+    pub fn ack_required(&self) -> bool {
+        match self {
+            OplockBreak::Oplock { ack_required, .. } | OplockBreak::Lease { ack_required, .. } => {
+                *ack_required
+            }
+        }
+    }
+}
+
+/// The action to take after an [`OplockBreakCallback`] is invoked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BreakAction {
+    /// Acknowledge the break right away, accepting the state proposed by the server.
+    #[default]
+    Acknowledge,
+    /// Do not acknowledge the break. The acknowledgment must be sent later,
+    /// using [`ResourceHandle::acknowledge_break`][crate::ResourceHandle::acknowledge_break],
+    /// for example, after flushing any cached writes.
+    Defer,
+}
+
+/// A callback invoked when an oplock or a lease of an open is broken by the server.
+///
+/// The callback is invoked from the notification handler of the connection,
+/// so it should return quickly.
+pub type OplockBreakCallback = Arc<dyn Fn(&OplockBreak) -> BreakAction + Send + Sync>;
+
+/// (Internal)
+///
+/// Routes oplock & lease break notifications of a connection to the opens they belong to.
+///
+/// Oplocks are looked up by file ID, and leases by lease key. A lease may be shared by several opens
+/// of the same file, in which case a break of it is passed to all of them, and acknowledged once,
+/// unless the [`OplockBreakCallback`] of any of them defers it.
+#[derive(Debug, Default)]
+pub(crate) struct OplockBreakRouter {
+    oplocks: Mutex<HashMap<FileId, Weak<ResourceMessageHandle>>>,
+    leases: Mutex<HashMap<u128, Vec<Weak<ResourceMessageHandle>>>>,
+}
+
+#[maybe_async(AFIT)]
+impl OplockBreakRouter {
+    /// Registers an open that holds an oplock or a lease, so it receives breaks.
+    pub async fn register(
+        &self,
+        granted: &GrantedOplock,
+        file_id: FileId,
+        handler: Weak<ResourceMessageHandle>,
+    ) -> crate::Result<()> {
+        match granted {
+            GrantedOplock::None => {}
+            GrantedOplock::Oplock(_) => {
+                let mut oplocks = self.oplocks.lock().await?;
+                oplocks.retain(|_, h| h.strong_count() > 0);
+                oplocks.insert(file_id, handler);
+            }
+            GrantedOplock::Lease { lease_key, .. } => {
+                let mut leases = self.leases.lock().await?;
+                for handlers in leases.values_mut() {
+                    handlers.retain(|h| h.strong_count() > 0);
+                }
+                leases.retain(|_, handlers| !handlers.is_empty());
+                let handlers = leases.entry(*lease_key).or_default();
+                if !handlers.iter().any(|h| h.ptr_eq(&handler)) {
+                    handlers.push(handler);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes any registration of the specified open.
+    pub async fn unregister(&self, handler: &Arc<ResourceMessageHandle>) -> crate::Result<()> {
+        let is_handler = |h: &Weak<ResourceMessageHandle>| std::ptr::eq(h.as_ptr(), &**handler);
+        let file_id = handler.binding().file_id;
+        {
+            let mut oplocks = self.oplocks.lock().await?;
+            if oplocks.get(&file_id).is_some_and(is_handler) {
+                oplocks.remove(&file_id);
+            }
+        }
+        if let GrantedOplock::Lease { lease_key, .. } = handler.oplock()? {
+            let mut leases = self.leases.lock().await?;
+            if let Some(handlers) = leases.get_mut(&lease_key) {
+                handlers.retain(|h| !is_handler(h) && h.strong_count() > 0);
+                if handlers.is_empty() {
+                    leases.remove(&lease_key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Passes an oplock or lease break notification to the opens it belongs to.
+    pub async fn dispatch(&self, msg: IncomingMessage) -> crate::Result<()> {
+        // Avoid holding the lock while notifying the opens.
+        let handlers = match &msg.message.content {
+            ResponseContent::OplockBreakNotify(notify) => self
+                .oplocks
+                .lock()
+                .await?
+                .get(&notify.file_id)
+                .and_then(|h| h.upgrade())
+                .into_iter()
+                .collect::<Vec<_>>(),
+            ResponseContent::LeaseBreakNotify(notify) => self
+                .leases
+                .lock()
+                .await?
+                .get(&notify.lease_key.as_u128())
+                .map(|handlers| handlers.iter().filter_map(|h| h.upgrade()).collect())
+                .unwrap_or_default(),
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "Not an oplock or lease break: {}",
+                    msg.message.content.content_name()
+                )));
+            }
+        };
+
+        match handlers.as_slice() {
+            [] => {
+                log::warn!(
+                    "Received break for an unknown or closed open: {:?}",
+                    msg.message.content
+                );
+                Ok(())
+            }
+            [handler] => handler.notify(msg).await,
+            [first, ..] => {
+                // A shared lease is acknowledged once, for all of its opens.
+                let mut acknowledge = true;
+                let mut brk = None;
+                for handler in &handlers {
+                    let (handler_brk, action) = handler.break_received(&msg.message.content)?;
+                    acknowledge &= action == BreakAction::Acknowledge;
+                    brk = Some(handler_brk);
+                }
+                match brk {
+                    Some(brk) if brk.ack_required() && acknowledge => first.acknowledge(&brk).await,
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// (Internal)
+///
+/// Converts a lease key into the GUID form used by lease break messages.
+pub(crate) fn lease_key_guid(lease_key: u128) -> Guid {
+    Guid::from(lease_key.to_le_bytes())
+}
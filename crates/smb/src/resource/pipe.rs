@@ -5,13 +5,28 @@ use std::{
 
 use super::ResourceHandle;
 use crate::msg_handler::{OutgoingMessage, ReceiveOptions};
+use crate::session::authenticator::Authenticator;
 use maybe_async::*;
-use smb_msg::{IoctlBuffer, PipeTransceiveRequest, ReadRequest, WriteRequest};
-use smb_rpc::{SmbRpcError, interface::*, ndr64::NDR64_SYNTAX_ID, pdu::*};
+use smb_fscc::{FilePipeInformation, PipeCompletionMode, PipeReadMode};
+use smb_msg::{
+    IoctlBuffer, PipePeekRequest, PipePeekResponse, PipeTransceiveRequest, ReadRequest, Status,
+    WriteRequest,
+};
+use smb_rpc::{SmbRpcError, interface::*, pdu::*, syntax::TransferSyntax};
+use sspi::{AuthIdentity, NegotiatedProtocol};
 pub struct Pipe {
     handle: ResourceHandle,
 }
 
+/// The result of reading from a pipe, see [`Pipe::read_message`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MessageRead {
+    /// The number of bytes read.
+    pub length: usize,
+    /// Whether the message has more data, to be returned by the next reads.
+    pub more_data: bool,
+}
+
 #[maybe_async(AFIT)]
 impl Pipe {
     pub fn new(handle: ResourceHandle) -> Self {
@@ -24,6 +39,167 @@ impl Pipe {
     {
         PipeRpcConnection::bind::<I>(self).await
     }
+
+    /// Binds to the interface `I`, authenticating the association with `identity`,
+    /// using NTLM or Kerberos, as configured for the connection.
+    ///
+    /// With [`DceRpcAuthLevel::PacketIntegrity`], every request and response PDU is signed,
+    /// and with [`DceRpcAuthLevel::PacketPrivacy`], it is also encrypted.
+    /// [`DceRpcAuthLevel::Connect`] only authenticates the bind. Other levels are not supported.
+    ///
+    /// Note that Kerberos contexts only support [`DceRpcAuthLevel::PacketPrivacy`] for protecting PDUs.
+    pub async fn bind_authenticated<I>(
+        self,
+        identity: AuthIdentity,
+        auth_level: DceRpcAuthLevel,
+    ) -> crate::Result<I>
+    where
+        I: RpcInterface<PipeRpcConnection>,
+    {
+        PipeRpcConnection::bind_authenticated::<I>(self, identity, auth_level).await
+    }
+
+    /// Reads data from the pipe, waiting until some data is available.
+    ///
+    /// In message mode, a single message is read, which may be truncated to `buf.len()`;
+    /// use [`Pipe::read_message`] to learn whether the message has more data.
+    /// # Returns
+    /// The number of bytes read, up to `buf.len()`. Zero is returned once the other end of the pipe is closed.
+    pub async fn read(&self, buf: &mut [u8]) -> crate::Result<usize> {
+        Ok(self.read_message(buf).await?.length)
+    }
+
+    /// Reads a message, or a part of it, from a message-mode pipe, waiting until a message is available.
+    ///
+    /// If the message does not fit in `buf`, the rest of it is returned by the next reads,
+    /// and [`MessageRead::more_data`] is set. Byte-mode pipes never set it.
+    pub async fn read_message(&self, buf: &mut [u8]) -> crate::Result<MessageRead> {
+        if buf.is_empty() {
+            return Ok(MessageRead::default());
+        }
+
+        let response = self
+            .handle
+            .send_recvo(
+                ReadRequest {
+                    flags: Default::default(),
+                    length: buf.len() as u32,
+                    offset: 0,
+                    file_id: self.handle.file_id()?,
+                    minimum_count: 1,
+                }
+                .into(),
+                ReceiveOptions::new()
+                    .with_status(&[Status::Success, Status::BufferOverflow])
+                    .with_allow_async(true),
+            )
+            .await;
+        let response = match response {
+            Ok(response) => response,
+            Err(e) if Self::is_closed_error(&e) => return Ok(MessageRead::default()),
+            Err(e) => return Err(e),
+        };
+        let more_data = response.message.header.status == Status::BufferOverflow as u32;
+        let content = response.message.content.to_read()?;
+        let length = content.buffer.len().min(buf.len());
+        buf[..length].copy_from_slice(&content.buffer[..length]);
+        Ok(MessageRead { length, more_data })
+    }
+
+    /// Reads a whole message from a message-mode pipe, waiting until a message is available.
+    ///
+    /// An empty message is returned once the other end of the pipe is closed.
+    pub async fn read_full_message(&self) -> crate::Result<Vec<u8>> {
+        const READ_SIZE: usize = 0x10000;
+        let read_size = READ_SIZE.min(self.conn_info().negotiation.max_read_size as usize);
+
+        let mut message = vec![];
+        loop {
+            let start = message.len();
+            message.resize(start + read_size, 0);
+            let read = self.read_message(&mut message[start..]).await?;
+            message.truncate(start + read.length);
+            if !read.more_data {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// Returns the data available in the pipe, without removing it, along with the state of the pipe.
+    ///
+    /// The returned data is limited by the default transaction size of the connection;
+    /// [`PipePeekResponse::read_data_available`] is the total size of the available data.
+    pub async fn peek(&self) -> crate::Result<PipePeekResponse> {
+        let (_, response) = self
+            .handle
+            .fsctl_with_status(
+                PipePeekRequest(()),
+                self.handle.calc_transact_size(None),
+                &[Status::Success, Status::BufferOverflow],
+            )
+            .await?;
+        Ok(response)
+    }
+
+    /// Returns the read mode and completion mode of the pipe.
+    pub async fn pipe_mode(&self) -> crate::Result<FilePipeInformation> {
+        self.handle.query_info().await
+    }
+
+    /// Sets the read mode and completion mode of the pipe.
+    ///
+    /// Reading in [`PipeReadMode::Message`] mode requires a pipe created as a message-type pipe by the server.
+    pub async fn set_pipe_mode(
+        &self,
+        read_mode: PipeReadMode,
+        completion_mode: PipeCompletionMode,
+    ) -> crate::Result<()> {
+        self.handle
+            .set_info(FilePipeInformation {
+                read_mode,
+                completion_mode,
+            })
+            .await
+    }
+
+    /// Writes data to the pipe.
+    ///
+    /// In message mode, each call writes a single message.
+    /// # Returns
+    /// The number of bytes written.
+    pub async fn write(&self, buf: &[u8]) -> crate::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let response = self
+            .handle
+            .sendo_recvo(
+                OutgoingMessage::new(
+                    WriteRequest::new(
+                        0,
+                        self.handle.file_id()?,
+                        Default::default(),
+                        buf.len() as u32,
+                    )
+                    .into(),
+                )
+                .with_additional_data(Arc::from(buf)),
+                ReceiveOptions::new().with_allow_async(true),
+            )
+            .await?;
+        Ok(response.message.content.to_write()?.count as usize)
+    }
+
+    /// (Internal)
+    ///
+    /// Whether the error indicates that the other end of the pipe was closed.
+    fn is_closed_error(error: &crate::Error) -> bool {
+        matches!(
+            error.status(),
+            Some(Status::PipeBroken | Status::PipeDisconnected | Status::EndOfFile)
+        )
+    }
 }
 
 pub struct PipeRpcConnection {
@@ -31,59 +207,219 @@ pub struct PipeRpcConnection {
     next_call_id: u32,
     /// Selected, accepted, context ID from binding.
     context_id: u16,
+    /// The transfer syntax of the selected context.
+    transfer_syntax: TransferSyntax,
 
+    /// The maximum size of fragments sent by the server.
     server_max_xmit_frag: u16,
-    _server_max_recv_frag: u16,
+    /// The maximum size of fragments the server accepts.
+    server_max_recv_frag: u16,
+
+    /// RPC-level authentication of the association, if bound with one.
+    auth: Option<PipeRpcAuth>,
 }
 
 #[maybe_async(AFIT)]
 impl PipeRpcConnection {
-    pub async fn bind<I>(mut pipe: Pipe) -> crate::Result<I>
+    pub async fn bind<I>(pipe: Pipe) -> crate::Result<I>
+    where
+        I: RpcInterface<PipeRpcConnection>,
+    {
+        Self::bind_with::<I>(pipe, None).await
+    }
+
+    /// See [`Pipe::bind_authenticated`].
+    pub async fn bind_authenticated<I>(
+        pipe: Pipe,
+        identity: AuthIdentity,
+        auth_level: DceRpcAuthLevel,
+    ) -> crate::Result<I>
     where
         I: RpcInterface<PipeRpcConnection>,
     {
-        let tranfer_syntaxes: [DceRpcSyntaxId; 2] = [NDR64_SYNTAX_ID, BIND_TIME_NEGOTIATION];
+        if !matches!(
+            auth_level,
+            DceRpcAuthLevel::Connect
+                | DceRpcAuthLevel::PacketIntegrity
+                | DceRpcAuthLevel::PacketPrivacy
+        ) {
+            return Err(crate::Error::UnsupportedOperation(format!(
+                "RPC authentication level {auth_level:?} is not supported"
+            )));
+        }
+        let authenticator = Authenticator::build_for_rpc(
+            identity,
+            &pipe.conn_info(),
+            auth_level == DceRpcAuthLevel::PacketPrivacy,
+        )?;
+        Self::bind_with::<I>(pipe, Some(PipeRpcAuth::new(authenticator, auth_level))).await
+    }
+
+    async fn bind_with<I>(mut pipe: Pipe, mut auth: Option<PipeRpcAuth>) -> crate::Result<I>
+    where
+        I: RpcInterface<PipeRpcConnection>,
+    {
+        // Offer all the supported transfer syntaxes, by order of preference,
+        // and let the server accept those it supports.
+        let tranfer_syntaxes: Vec<DceRpcSyntaxId> = TransferSyntax::ALL
+            .iter()
+            .map(TransferSyntax::syntax_id)
+            .chain(std::iter::once(BIND_TIME_NEGOTIATION))
+            .collect();
         let context_elements = Self::make_bind_contexts(I::SYNTAX_ID, &tranfer_syntaxes);
 
         const START_CALL_ID: u32 = 2;
-        const DEFAULT_FRAG_LIMIT: u16 = 4280;
         const NO_ASSOC_GROUP_ID: u32 = 0;
+        let bind_auth = match auth.as_mut() {
+            Some(auth) => {
+                let token = auth.first_token().await?;
+                Some(auth.verifier(token, 0)?)
+            }
+            None => None,
+        };
         let bind_ack = Self::rpc_rw(
             &mut pipe,
             START_CALL_ID,
             DcRpcCoPktBind {
-                max_xmit_frag: DEFAULT_FRAG_LIMIT,
-                max_recv_frag: DEFAULT_FRAG_LIMIT,
+                max_xmit_frag: Self::DEFAULT_FRAG_LIMIT,
+                max_recv_frag: Self::DEFAULT_FRAG_LIMIT,
                 assoc_group_id: NO_ASSOC_GROUP_ID,
                 context_elements,
             }
             .into(),
+            bind_auth,
         )
         .await?;
 
-        let bind_ack = match bind_ack.content() {
-            DcRpcCoPktResponseContent::BindAck(bind_ack) => {
+        let (bind_ack, bind_ack_auth) = match bind_ack.into_parts() {
+            (DcRpcCoPktResponseContent::BindAck(bind_ack), bind_ack_auth) => {
                 log::debug!("Bounded to pipe with port spec {}", bind_ack.port_spec);
-                bind_ack
+                (bind_ack, bind_ack_auth)
             }
-            _ => {
+            (content, _) => {
                 return Err(crate::Error::InvalidMessage(format!(
-                    "Expected BindAck, got: {bind_ack:?}",
+                    "Expected BindAck, got: {content:?}",
                 )));
             }
         };
 
-        let context_id = Self::check_bind_results(bind_ack, &tranfer_syntaxes)?;
+        let (context_id, transfer_syntax) = Self::check_bind_results(&bind_ack, &tranfer_syntaxes)?;
+
+        let mut next_call_id = START_CALL_ID + 1;
+        if let Some(auth) = auth.as_mut() {
+            let context = DcRpcCoPktBindContextElement {
+                context_id,
+                abstract_syntax: I::SYNTAX_ID,
+                transfer_syntaxes: vec![transfer_syntax.syntax_id()],
+            };
+            next_call_id = Self::complete_authentication(
+                &mut pipe,
+                auth,
+                bind_ack_auth,
+                START_CALL_ID,
+                &bind_ack,
+                context,
+            )
+            .await?;
+        }
 
         Ok(I::new(PipeRpcConnection {
             pipe,
-            next_call_id: START_CALL_ID + 1,
+            next_call_id,
             context_id,
+            transfer_syntax,
             server_max_xmit_frag: bind_ack.max_xmit_frag,
-            _server_max_recv_frag: bind_ack.max_recv_frag,
+            server_max_recv_frag: bind_ack.max_recv_frag,
+            auth,
         }))
     }
 
+    /// (Internal)
+    ///
+    /// Completes the authentication of an association, given the auth verifier of the bind_ack.
+    ///
+    /// NTLM authentication ends with an AUTH3 PDU, to which the server does not respond,
+    /// while any other leg is carried by an alter_context PDU (MS-RPCE 3.3.1.5.2.1).
+    /// # Returns
+    /// The next call ID to use on the connection.
+    async fn complete_authentication(
+        pipe: &mut Pipe,
+        auth: &mut PipeRpcAuth,
+        mut server_auth: Option<DceRpcAuthVerifier>,
+        bind_call_id: u32,
+        bind_ack: &DcRpcCoPktBindAck,
+        context: DcRpcCoPktBindContextElement,
+    ) -> crate::Result<u32> {
+        let mut next_call_id = bind_call_id + 1;
+        loop {
+            let server_token = server_auth.take().ok_or_else(|| {
+                crate::Error::InvalidMessage(
+                    "Expected an auth verifier in the bind response".to_string(),
+                )
+            })?;
+            let token = auth.authenticator.next(&server_token.auth_value).await?;
+
+            if auth.authenticator.is_authenticated()? {
+                if token.is_empty() {
+                    return Ok(next_call_id);
+                }
+                if auth.auth_type()? == DceRpcAuthType::WinNt {
+                    let auth3: Vec<u8> = DceRpcCoRequestPkt::new(
+                        DcRpcCoPktAuth3::default().into(),
+                        bind_call_id,
+                        DceRpcCoPktFlags::new()
+                            .with_first_frag(true)
+                            .with_last_frag(true),
+                        Self::PACKED_DREP,
+                    )
+                    .with_auth(auth.verifier(token, 0)?)
+                    .try_into()?;
+                    if pipe.write(&auth3).await? != auth3.len() {
+                        return Err(crate::Error::InvalidMessage(
+                            "Failed to write the full AUTH3 to the pipe".to_string(),
+                        ));
+                    }
+                    return Ok(next_call_id);
+                }
+            }
+
+            let response = Self::rpc_rw(
+                pipe,
+                next_call_id,
+                DcRpcCoPktAlterContext(DcRpcCoPktBind {
+                    max_xmit_frag: Self::DEFAULT_FRAG_LIMIT,
+                    max_recv_frag: Self::DEFAULT_FRAG_LIMIT,
+                    assoc_group_id: bind_ack.assoc_group_id,
+                    context_elements: vec![DcRpcCoPktBindContextElement {
+                        context_id: context.context_id,
+                        abstract_syntax: context.abstract_syntax.clone(),
+                        transfer_syntaxes: context.transfer_syntaxes.clone(),
+                    }],
+                })
+                .into(),
+                Some(auth.verifier(token, 0)?),
+            )
+            .await?;
+            next_call_id += 1;
+
+            match response.into_parts() {
+                (DcRpcCoPktResponseContent::AlterContextResp(_), _)
+                    if auth.authenticator.is_authenticated()? =>
+                {
+                    return Ok(next_call_id);
+                }
+                (DcRpcCoPktResponseContent::AlterContextResp(_), response_auth) => {
+                    server_auth = response_auth;
+                }
+                (content, _) => {
+                    return Err(crate::Error::InvalidMessage(format!(
+                        "Expected AlterContextResp, got: {content:?}",
+                    )));
+                }
+            }
+        }
+    }
+
     fn make_bind_contexts(
         syntax_id: DceRpcSyntaxId,
         transfer_syntaxes: &[DceRpcSyntaxId],
@@ -104,7 +440,7 @@ impl PipeRpcConnection {
     fn check_bind_results(
         bind_ack: &DcRpcCoPktBindAck,
         transfer_syntaxes: &[DceRpcSyntaxId],
-    ) -> crate::Result<u16> {
+    ) -> crate::Result<(u16, TransferSyntax)> {
         if bind_ack.results.len() != transfer_syntaxes.len() {
             return Err(crate::Error::InvalidMessage(format!(
                 "BindAck results length {} does not match transfer syntaxes length {}",
@@ -129,9 +465,10 @@ impl PipeRpcConnection {
                 continue;
             }
             if ack_context.result != DceRpcCoPktBindAckDefResult::Acceptance {
-                return Err(crate::Error::InvalidMessage(format!(
+                log::debug!(
                     "BindAck result for syntax {syntax} was not acceptance: {ack_context:?}"
-                )));
+                );
+                continue;
             }
             if &ack_context.syntax != syntax {
                 return Err(crate::Error::InvalidMessage(format!(
@@ -139,12 +476,15 @@ impl PipeRpcConnection {
                     ack_context.syntax, syntax
                 )));
             }
-            context_id_selected = Some(indx as u16);
+            // Syntaxes are offered by order of preference.
+            if context_id_selected.is_none() {
+                context_id_selected = Some((indx as u16, TransferSyntax::try_from(syntax)?));
+            }
         }
 
-        if let Some(context_id) = context_id_selected {
-            log::debug!("Selected context ID: {context_id}");
-            Ok(context_id)
+        if let Some((context_id, transfer_syntax)) = context_id_selected {
+            log::debug!("Selected context ID: {context_id} ({transfer_syntax:?})");
+            Ok((context_id, transfer_syntax))
         } else {
             Err(crate::Error::InvalidMessage(
                 "No accepted context ID found in BindAck".to_string(),
@@ -153,24 +493,30 @@ impl PipeRpcConnection {
     }
 
     pub const PACKED_DREP: u32 = 0x10;
+    const DEFAULT_FRAG_LIMIT: u16 = 4280;
+
     /// Performs a read+write operation on the pipe, sending a request and receiving it's response.
     #[maybe_async]
     async fn rpc_rw(
         pipe: &mut Pipe,
         call_id: u32,
         to_send: DcRpcCoPktRequestContent,
+        auth: Option<DceRpcAuthVerifier>,
     ) -> crate::Result<DceRpcCoResponsePkt> {
         const READ_WRITE_PIPE_OFFSET: u64 = 0;
         let file_id = pipe.handle.file_id()?;
-        let dcerpc_request_buffer: Vec<u8> = DceRpcCoRequestPkt::new(
+        let mut request = DceRpcCoRequestPkt::new(
             to_send,
             call_id,
             DceRpcCoPktFlags::new()
                 .with_first_frag(true)
                 .with_last_frag(true),
             Self::PACKED_DREP,
-        )
-        .try_into()?;
+        );
+        if let Some(auth) = auth {
+            request = request.with_auth(auth);
+        }
+        let dcerpc_request_buffer: Vec<u8> = request.try_into()?;
         let exp_write_size = dcerpc_request_buffer.len() as u32;
         let write_result = pipe
             .sendo_recvo(
@@ -193,21 +539,9 @@ impl PipeRpcConnection {
             ));
         }
 
-        let read_result = pipe
-            .send_recvo(
-                ReadRequest {
-                    flags: Default::default(),
-                    length: 1024,
-                    offset: READ_WRITE_PIPE_OFFSET,
-                    file_id,
-                    minimum_count: DceRpcCoRequestPkt::COMMON_SIZE_BYTES as u32,
-                }
-                .into(),
-                ReceiveOptions::new().with_allow_async(true),
-            )
-            .await?;
-        let content = read_result.message.content.to_read()?;
-        let response = DceRpcCoResponsePkt::try_from(content.buffer.as_ref())?;
+        // The response may not fit in a single read, see `Pipe::read_message`.
+        let response_buffer = pipe.read_full_message().await?;
+        let response = DceRpcCoResponsePkt::try_from(response_buffer.as_ref())?;
 
         if response.packed_drep() != Self::PACKED_DREP {
             return Err(crate::Error::InvalidMessage(format!(
@@ -228,49 +562,116 @@ impl PipeRpcConnection {
     pub fn pipe(&self) -> &Pipe {
         &self.pipe
     }
+
+    /// Closes the pipe of the connection.
+    pub async fn close(self) -> crate::Result<()> {
+        self.pipe.close().await
+    }
 }
 
-impl BoundRpcConnection for PipeRpcConnection {
-    #[maybe_async]
-    async fn send_receive_raw(
-        &mut self,
+impl PipeRpcConnection {
+    /// The size of the headers of a request PDU, preceding its stub data.
+    const REQUEST_HEADERS_SIZE: usize = DceRpcCoRequestPkt::COMMON_SIZE_BYTES + 8;
+    /// The size of the headers of a response PDU, preceding its stub data.
+    const RESPONSE_HEADERS_SIZE: usize = DceRpcCoResponsePkt::COMMON_SIZE_BYTES + 8;
+
+    /// (Internal)
+    ///
+    /// Splits the stub data of a call into request fragments, none of which exceeds `max_frag` bytes.
+    ///
+    /// If PDUs are protected by `auth`, each fragment is signed or sealed.
+    fn make_request_fragments(
+        context_id: u16,
         opnum: u16,
+        call_id: u32,
         stub_input: &[u8],
-    ) -> Result<Vec<u8>, SmbRpcError> {
-        let req = DcRpcCoPktRequest {
-            alloc_hint: DcRpcCoPktRequest::ALLOC_HINT_NONE,
-            context_id: self.context_id,
-            opnum,
-            stub_data: stub_input.to_vec(),
+        max_frag: u16,
+        auth: Option<&mut PipeRpcAuth>,
+    ) -> Result<Vec<Vec<u8>>, SmbRpcError> {
+        let mut auth = auth.filter(|auth| auth.protects_pdus());
+        let (verifier_size, alignment) = match auth.as_deref_mut() {
+            Some(auth) => (
+                auth.verifier_size().map_err(|e| {
+                    SmbRpcError::SendReceiveError(format!("Failed to query RPC auth sizes: {e}"))
+                })?,
+                PipeRpcAuth::STUB_ALIGNMENT,
+            ),
+            None => (0, 8),
+        };
+
+        // Stub data is split on 8-byte boundaries, keeping the alignment of NDR data in each fragment.
+        let max_stub_size = (max_frag as usize)
+            .saturating_sub(Self::REQUEST_HEADERS_SIZE + verifier_size)
+            & !(alignment - 1);
+        if max_stub_size == 0 {
+            return Err(SmbRpcError::SendReceiveError(format!(
+                "Fragment size {max_frag} is too small for a request"
+            )));
         }
-        .into();
-        let req = DceRpcCoRequestPkt::new(
-            req,
-            self.next_call_id,
-            DceRpcCoPktFlags::new()
-                .with_first_frag(true)
-                .with_last_frag(true),
-            Self::PACKED_DREP, // Packed DREP
-        );
-        self.next_call_id += 1;
 
-        let req_data: Vec<u8> = req.try_into().map_err(|e| {
-            SmbRpcError::SendReceiveError(format!("Failed to serialize RPC request: {e}"))
-        })?;
+        let chunks = stub_input.chunks(max_stub_size).collect::<Vec<_>>();
+        // A call always has at least one fragment, even with no stub data.
+        let chunks = if chunks.is_empty() {
+            vec![&[][..]]
+        } else {
+            chunks
+        };
 
-        let res = self
-            .pipe
-            .handle
-            .fsctl_with_options(
-                PipeTransceiveRequest::from(IoctlBuffer::from(req_data)),
-                self.server_max_xmit_frag as u32,
-            )
-            .await
-            .map_err(|e| {
-                SmbRpcError::SendReceiveError(format!("Failed to send RPC request via FSCTL: {e}"))
-            })?;
+        let mut remaining = stub_input.len();
+        let last_index = chunks.len() - 1;
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mut stub_data = chunk.to_vec();
+                // The sec_trailer of a protected PDU follows the padded stub data.
+                let auth_pad_length = if auth.is_some() {
+                    chunk.len().next_multiple_of(PipeRpcAuth::STUB_ALIGNMENT) - chunk.len()
+                } else {
+                    0
+                };
+                stub_data.resize(chunk.len() + auth_pad_length, 0);
+                let request = DceRpcCoRequestPkt::new(
+                    DcRpcCoPktRequest {
+                        alloc_hint: remaining as u32,
+                        context_id,
+                        opnum,
+                        stub_data,
+                    }
+                    .into(),
+                    call_id,
+                    DceRpcCoPktFlags::new()
+                        .with_first_frag(index == 0)
+                        .with_last_frag(index == last_index),
+                    Self::PACKED_DREP,
+                );
+                remaining -= chunk.len();
+                match auth.as_deref_mut() {
+                    Some(auth) => auth.protect(request, auth_pad_length as u8).map_err(|e| {
+                        SmbRpcError::SendReceiveError(format!("Failed to protect RPC request: {e}"))
+                    }),
+                    None => request.try_into().map_err(|e| {
+                        SmbRpcError::SendReceiveError(format!(
+                            "Failed to serialize RPC request: {e}"
+                        ))
+                    }),
+                }
+            })
+            .collect()
+    }
 
-        let rpc_reply = DceRpcCoResponsePkt::try_from(res.as_ref())
+    /// (Internal)
+    ///
+    /// Parses a response fragment of a call, returning its stub data and whether it is the last fragment.
+    ///
+    /// A protected fragment is verified, and decrypted in place if sealed.
+    fn parse_response_fragment(
+        &mut self,
+        data: &mut [u8],
+        call_id: u32,
+        first: bool,
+    ) -> Result<(Vec<u8>, bool), SmbRpcError> {
+        let rpc_reply = DceRpcCoResponsePkt::try_from(&data[..])
             .map_err(SmbRpcError::FailedToParseRpcResponse)?;
 
         if rpc_reply.packed_drep() != Self::PACKED_DREP {
@@ -279,15 +680,26 @@ impl BoundRpcConnection for PipeRpcConnection {
                 rpc_reply.packed_drep()
             )));
         }
-
-        if !rpc_reply.pfc_flags().first_frag() || !rpc_reply.pfc_flags().last_frag() {
-            return Err(SmbRpcError::SendReceiveError(
-                "Expected first and last fragment flags to be set".to_string(),
-            ));
+        if rpc_reply.call_id() != call_id {
+            return Err(SmbRpcError::SendReceiveError(format!(
+                "Response call ID {} does not match expected {call_id}",
+                rpc_reply.call_id()
+            )));
+        }
+        if rpc_reply.pfc_flags().first_frag() != first {
+            return Err(SmbRpcError::SendReceiveError(format!(
+                "Unexpected first fragment flag in response fragment: {:?}",
+                rpc_reply.pfc_flags()
+            )));
         }
+        let last = rpc_reply.pfc_flags().last_frag();
 
-        let response = match rpc_reply.into_content() {
+        let (content, auth) = rpc_reply.into_parts();
+        let response = match content {
             DcRpcCoPktResponseContent::Response(dc_rpc_co_pkt_response) => dc_rpc_co_pkt_response,
+            DcRpcCoPktResponseContent::Fault(fault) => {
+                return Err(SmbRpcError::Fault(fault.status));
+            }
             content => {
                 return Err(SmbRpcError::SendReceiveError(format!(
                     "Expected DceRpcCoPktResponseContent::Response, got: {content:?}",
@@ -302,7 +714,273 @@ impl BoundRpcConnection for PipeRpcConnection {
             )));
         }
 
-        Ok(response.stub_data)
+        let Some(rpc_auth) = self.auth.as_mut().filter(|auth| auth.protects_pdus()) else {
+            return Ok((response.stub_data, last));
+        };
+        let auth = auth.ok_or(SmbRpcError::InvalidResponseData(
+            "Missing auth verifier in a protected response",
+        ))?;
+        rpc_auth.unprotect(data, &auth).map_err(|e| {
+            SmbRpcError::SendReceiveError(format!("Failed to verify RPC response: {e}"))
+        })?;
+        // Sealed stub data is only readable from the fragment, once decrypted in place.
+        let stub_size = response
+            .stub_data
+            .len()
+            .checked_sub(auth.auth_pad_length as usize)
+            .ok_or(SmbRpcError::InvalidResponseData(
+                "Auth padding exceeds the stub data",
+            ))?;
+        let stub_data =
+            data[Self::RESPONSE_HEADERS_SIZE..Self::RESPONSE_HEADERS_SIZE + stub_size].to_vec();
+        Ok((stub_data, last))
+    }
+}
+
+/// The RPC-level authentication of an association (MS-RPCE 3.3.1.5.2).
+struct PipeRpcAuth {
+    authenticator: Authenticator,
+    auth_level: DceRpcAuthLevel,
+    /// Whether the tokens of the security context are framed in SPNEGO, see [`PipeRpcAuth::auth_type`].
+    spnego: bool,
+    /// Sequence numbers of the protected PDUs, counted separately in each direction.
+    send_sequence: u32,
+    recv_sequence: u32,
+}
+
+impl PipeRpcAuth {
+    /// A single security context is used on the association.
+    const AUTH_CONTEXT_ID: u32 = 0;
+    /// The alignment of the stub data of protected PDUs, including the auth padding.
+    const STUB_ALIGNMENT: usize = 16;
+
+    fn new(authenticator: Authenticator, auth_level: DceRpcAuthLevel) -> Self {
+        Self {
+            authenticator,
+            auth_level,
+            spnego: false,
+            send_sequence: 0,
+            recv_sequence: 0,
+        }
+    }
+
+    /// The SPNEGO mechanism OID (1.3.6.1.5.5.2), DER-encoded.
+    const SPNEGO_OID: [u8; 8] = [0x06, 0x06, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x02];
+
+    /// Returns the first token of the security context, to be sent on bind.
+    #[maybe_async]
+    async fn first_token(&mut self) -> crate::Result<Vec<u8>> {
+        let token = self.authenticator.next(&[]).await?;
+        self.spnego = Self::is_spnego_token(&token);
+        Ok(token)
+    }
+
+    /// Whether the token is a GSS-API initial context token of the SPNEGO mechanism (RFC 4178),
+    /// rather than a raw token of the security package.
+    fn is_spnego_token(token: &[u8]) -> bool {
+        const GSS_INITIAL_CONTEXT_TOKEN_TAG: u8 = 0x60;
+        if token.len() < 2 || token[0] != GSS_INITIAL_CONTEXT_TOKEN_TAG {
+            return false;
+        }
+        // Skip the DER length, in either its short or long form.
+        let oid_start = match token[1] {
+            length if length < 0x80 => 2,
+            length => 2 + (length & 0x7f) as usize,
+        };
+        token
+            .get(oid_start..)
+            .is_some_and(|rest| rest.starts_with(&Self::SPNEGO_OID))
+    }
+
+    /// Returns the auth type of the security context.
+    ///
+    /// Tokens framed in SPNEGO are sent as [`DceRpcAuthType::GssNegotiate`], whatever the negotiated package is -
+    /// e.g. sspi frames Kerberos tokens in SPNEGO, which servers do not accept as [`DceRpcAuthType::GssKerberos`].
+    fn auth_type(&self) -> crate::Result<DceRpcAuthType> {
+        if self.spnego {
+            return Ok(DceRpcAuthType::GssNegotiate);
+        }
+        match self.authenticator.negotiated_protocol() {
+            NegotiatedProtocol::Ntlm(_) => Ok(DceRpcAuthType::WinNt),
+            NegotiatedProtocol::Kerberos(_) => Ok(DceRpcAuthType::GssKerberos),
+            NegotiatedProtocol::Pku2u(_) => Err(crate::Error::UnsupportedAuthenticationMechanism(
+                "PKU2U is not supported for RPC authentication".to_string(),
+            )),
+        }
+    }
+
+    fn verifier(
+        &self,
+        auth_value: Vec<u8>,
+        auth_pad_length: u8,
+    ) -> crate::Result<DceRpcAuthVerifier> {
+        Ok(DceRpcAuthVerifier {
+            auth_type: self.auth_type()?,
+            auth_level: self.auth_level,
+            auth_pad_length,
+            auth_context_id: Self::AUTH_CONTEXT_ID,
+            auth_value,
+        })
+    }
+
+    /// Whether request and response PDUs are signed or sealed, rather than the bind only.
+    fn protects_pdus(&self) -> bool {
+        self.auth_level >= DceRpcAuthLevel::PacketIntegrity
+    }
+
+    fn signature_size(&mut self) -> crate::Result<usize> {
+        Ok(self.authenticator.context_sizes()?.security_trailer as usize)
+    }
+
+    /// Returns the size of the auth verifier of a protected PDU.
+    fn verifier_size(&mut self) -> crate::Result<usize> {
+        Ok(DceRpcAuthVerifier::SEC_TRAILER_SIZE + self.signature_size()?)
+    }
+
+    /// Serializes a request PDU, whose stub data is padded by `auth_pad_length` bytes,
+    /// and signs or seals it.
+    ///
+    /// The signature covers the whole PDU, up to the auth_value (MS-RPCE 3.3.1.5.2.2).
+    fn protect(
+        &mut self,
+        request: DceRpcCoRequestPkt,
+        auth_pad_length: u8,
+    ) -> crate::Result<Vec<u8>> {
+        let signature_size = self.signature_size()?;
+        let verifier = self.verifier(vec![0; signature_size], auth_pad_length)?;
+        let mut pdu: Vec<u8> = request.with_auth(verifier).try_into()?;
+
+        let signed_size = pdu.len() - signature_size;
+        let (signed, signature_dst) = pdu.split_at_mut(signed_size);
+        let signature = if self.auth_level == DceRpcAuthLevel::PacketPrivacy {
+            let (header, rest) = signed.split_at_mut(PipeRpcConnection::REQUEST_HEADERS_SIZE);
+            let (stub, trailer) =
+                rest.split_at_mut(rest.len() - DceRpcAuthVerifier::SEC_TRAILER_SIZE);
+            self.authenticator
+                .seal(header, stub, trailer, self.send_sequence)?
+        } else {
+            self.authenticator.sign(signed, self.send_sequence)?
+        };
+        if signature.len() != signature_size {
+            return Err(crate::Error::InvalidState(format!(
+                "Unexpected RPC signature size {}, expected {signature_size}",
+                signature.len()
+            )));
+        }
+        signature_dst.copy_from_slice(&signature);
+
+        self.send_sequence = self.send_sequence.wrapping_add(1);
+        Ok(pdu)
+    }
+
+    /// Verifies a protected response PDU, whose auth verifier is `auth`, decrypting it in place if sealed.
+    fn unprotect(&mut self, pdu: &mut [u8], auth: &DceRpcAuthVerifier) -> crate::Result<()> {
+        if auth.auth_level != self.auth_level || auth.auth_context_id != Self::AUTH_CONTEXT_ID {
+            return Err(crate::Error::InvalidMessage(format!(
+                "Unexpected auth verifier in response: {auth:?}"
+            )));
+        }
+
+        let signed_size = pdu
+            .len()
+            .checked_sub(auth.auth_value.len())
+            .filter(|&size| {
+                size >= PipeRpcConnection::RESPONSE_HEADERS_SIZE
+                    + DceRpcAuthVerifier::SEC_TRAILER_SIZE
+            })
+            .ok_or_else(|| {
+                crate::Error::InvalidMessage("Protected response is too short".to_string())
+            })?;
+        let (signed, signature) = pdu.split_at_mut(signed_size);
+        if self.auth_level == DceRpcAuthLevel::PacketPrivacy {
+            let (header, rest) = signed.split_at_mut(PipeRpcConnection::RESPONSE_HEADERS_SIZE);
+            let (stub, trailer) =
+                rest.split_at_mut(rest.len() - DceRpcAuthVerifier::SEC_TRAILER_SIZE);
+            self.authenticator
+                .unseal(header, stub, trailer, signature, self.recv_sequence)?;
+        } else {
+            self.authenticator
+                .verify(signed, signature, self.recv_sequence)?;
+        }
+
+        self.recv_sequence = self.recv_sequence.wrapping_add(1);
+        Ok(())
+    }
+}
+
+impl BoundRpcConnection for PipeRpcConnection {
+    fn transfer_syntax(&self) -> TransferSyntax {
+        self.transfer_syntax
+    }
+
+    #[maybe_async]
+    async fn send_receive_raw(
+        &mut self,
+        opnum: u16,
+        stub_input: &[u8],
+    ) -> Result<Vec<u8>, SmbRpcError> {
+        let call_id = self.next_call_id;
+        self.next_call_id += 1;
+
+        let send_error = |e: crate::Error| {
+            SmbRpcError::SendReceiveError(format!("Failed to send RPC request: {e}"))
+        };
+
+        // Both limits are negotiated to the same value in practice; respect the smaller one.
+        let max_frag = self.server_max_recv_frag.min(self.server_max_xmit_frag);
+        let mut fragments = Self::make_request_fragments(
+            self.context_id,
+            opnum,
+            call_id,
+            stub_input,
+            max_frag,
+            self.auth.as_mut(),
+        )?;
+        let last_fragment = fragments.pop().unwrap();
+
+        // All fragments but the last are written to the pipe,
+        // and the last is transacted, to receive the response.
+        for fragment in fragments {
+            let fragment_size = fragment.len();
+            let written = self.pipe.write(&fragment).await.map_err(send_error)?;
+            if written != fragment_size {
+                return Err(SmbRpcError::SendReceiveError(
+                    "Failed to write the full request fragment to the pipe".to_string(),
+                ));
+            }
+        }
+
+        let (status, response) = self
+            .pipe
+            .handle
+            .fsctl_with_status(
+                PipeTransceiveRequest::from(IoctlBuffer::from(last_fragment)),
+                self.server_max_xmit_frag as u32,
+                &[Status::Success, Status::BufferOverflow],
+            )
+            .await
+            .map_err(send_error)?;
+        let mut fragment = response.0.to_vec();
+        if status == Status::BufferOverflow {
+            // The rest of the fragment is read from the pipe.
+            fragment.extend(self.pipe.read_full_message().await.map_err(send_error)?);
+        }
+
+        let (mut stub_output, mut last) =
+            self.parse_response_fragment(&mut fragment, call_id, true)?;
+        while !last {
+            let mut fragment = self.pipe.read_full_message().await.map_err(send_error)?;
+            if fragment.is_empty() {
+                return Err(SmbRpcError::SendReceiveError(
+                    "Pipe closed before the last response fragment".to_string(),
+                ));
+            }
+            let stub;
+            (stub, last) = self.parse_response_fragment(&mut fragment, call_id, false)?;
+            stub_output.extend(stub);
+        }
+
+        Ok(stub_output)
     }
 }
 
@@ -319,3 +997,59 @@ impl DerefMut for Pipe {
         &mut self.handle
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{PipeRpcAuth, PipeRpcConnection};
+    use smb_rpc::pdu::{DcRpcCoPktRequestContent, DceRpcCoRequestPkt};
+
+    #[test]
+    fn test_make_request_fragments() {
+        let stub = (0..100u8).collect::<Vec<_>>();
+        // 24 bytes of headers leave 40 bytes (rounded down to 8 bytes) of stub data per fragment.
+        let fragments =
+            PipeRpcConnection::make_request_fragments(1, 15, 3, &stub, 70, None).unwrap();
+        assert_eq!(fragments.len(), 3);
+
+        let mut reassembled = vec![];
+        for (index, fragment) in fragments.iter().enumerate() {
+            assert!(fragment.len() <= 70);
+            let pkt = DceRpcCoRequestPkt::try_from(fragment.as_slice()).unwrap();
+            assert_eq!(pkt.call_id(), 3);
+            assert_eq!(pkt.pfc_flags().first_frag(), index == 0);
+            assert_eq!(pkt.pfc_flags().last_frag(), index == 2);
+            let DcRpcCoPktRequestContent::Request(request) = pkt.into_content() else {
+                panic!("Expected a request fragment");
+            };
+            assert_eq!(request.alloc_hint as usize, stub.len() - reassembled.len());
+            assert_eq!((request.context_id, request.opnum), (1, 15));
+            reassembled.extend(request.stub_data);
+        }
+        assert_eq!(reassembled, stub);
+
+        let fragments =
+            PipeRpcConnection::make_request_fragments(0, 0, 4, &[], 4280, None).unwrap();
+        assert_eq!(fragments.len(), 1);
+    }
+
+    #[test]
+    fn test_is_spnego_token() {
+        // NegTokenInit, short and long form lengths.
+        let spnego_short = [
+            0x60, 0x10, 0x06, 0x06, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x02, 0xa0,
+        ];
+        let spnego_long = [
+            0x60, 0x82, 0x01, 0x00, 0x06, 0x06, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x02,
+        ];
+        assert!(PipeRpcAuth::is_spnego_token(&spnego_short));
+        assert!(PipeRpcAuth::is_spnego_token(&spnego_long));
+
+        // Raw Kerberos AP-REQ token (krb5 OID 1.2.840.113554.1.2.2) and NTLM negotiate message.
+        let krb5 = [
+            0x60, 0x10, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x12, 0x01, 0x02, 0x02,
+        ];
+        assert!(!PipeRpcAuth::is_spnego_token(&krb5));
+        assert!(!PipeRpcAuth::is_spnego_token(b"NTLMSSP\0\x01\0\0\0"));
+        assert!(!PipeRpcAuth::is_spnego_token(&[0x60]));
+    }
+}
@@ -0,0 +1,111 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use super::ResourceHandle;
+use crate::{
+    Error,
+    msg_handler::{OutgoingMessage, ReceiveOptions},
+};
+use maybe_async::*;
+use smb_msg::{WriteFlags, WriteRequest};
+
+/// A print file, opened on a printer share.
+///
+/// Each print file spools a single print job: the data written to it is passed to the printer as-is,
+/// and the job is submitted when the print file is closed.
+/// The printer must therefore support the format of the data (e.g. PDF, PCL or PostScript).
+///
+/// See [`Tree::print`][crate::Tree::print] and [`Client::print`][crate::Client::print]
+/// for submitting a print job in a single call.
+pub struct Printer {
+    handle: ResourceHandle,
+    /// The offset of the next write; print data is always appended.
+    spooled: AtomicU64,
+}
+
+#[maybe_async(AFIT)]
+impl Printer {
+    pub fn new(handle: ResourceHandle) -> Self {
+        Printer {
+            handle,
+            spooled: AtomicU64::new(0),
+        }
+    }
+
+    /// Spools data to the print job, after the data spooled so far.
+    ///
+    /// Writes larger than the max write size of the connection are split.
+    pub async fn write_all(&self, data: &[u8]) -> crate::Result<()> {
+        if !self.handle.access.file_write_data() {
+            return Err(Error::InvalidState(
+                "Print file was opened without write access".to_string(),
+            ));
+        }
+
+        let max_write_size = self.handle.conn_info().negotiation.max_write_size as usize;
+        let mut written = 0;
+        while written < data.len() {
+            let end = data.len().min(written + max_write_size);
+            let offset = self.spooled.load(Ordering::SeqCst);
+            let written_now = self.write_block(data[written..end].into(), offset).await?;
+            if written_now == 0 {
+                return Err(Error::InvalidMessage(
+                    "Server accepted no data for the print job".to_string(),
+                ));
+            }
+            self.spooled.fetch_add(written_now as u64, Ordering::SeqCst);
+            written += written_now;
+        }
+
+        log::debug!(
+            "Spooled {} bytes to {} ({} in total).",
+            data.len(),
+            self.handle.name(),
+            self.spooled()
+        );
+        Ok(())
+    }
+
+    /// Returns the number of bytes spooled to the print job so far.
+    pub fn spooled(&self) -> u64 {
+        self.spooled.load(Ordering::SeqCst)
+    }
+
+    async fn write_block(&self, data: Arc<[u8]>, offset: u64) -> crate::Result<usize> {
+        let outgoing = OutgoingMessage::new(
+            WriteRequest::new(
+                offset,
+                self.handle.file_id()?,
+                WriteFlags::new(),
+                data.len() as u32,
+            )
+            .into(),
+        )
+        .with_additional_data(data);
+
+        let response = self
+            .handle
+            .sendo_recvo(outgoing, ReceiveOptions::new().with_allow_async(true))
+            .await?;
+        Ok(response.message.content.to_write()?.count as usize)
+    }
+}
+
+impl Deref for Printer {
+    type Target = ResourceHandle;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handle
+    }
+}
+
+impl DerefMut for Printer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.handle
+    }
+}
@@ -0,0 +1,133 @@
+//! Reparse points and symbolic links.
+//!
+//! Reparse points are set and queried on open resources, using [`ResourceHandle::set_reparse_point`]
+//! and [`ResourceHandle::get_reparse_point`]. See [`Tree::create_symlink`][crate::Tree::create_symlink]
+//! and [`Tree::read_link`][crate::Tree::read_link] for working with symbolic links by path.
+
+use maybe_async::*;
+use smb_fscc::ReparseData;
+use smb_msg::{GetReparsePointRequest, SetReparsePointRequest, SymbolicLinkErrorResponse};
+
+use super::ResourceHandle;
+use crate::Error;
+
+#[maybe_async(AFIT)]
+impl ResourceHandle {
+    /// Returns the reparse point data of the resource.
+    ///
+    /// The resource must be opened with [`CreateOptions::open_reparse_point`][smb_msg::CreateOptions::open_reparse_point],
+    /// otherwise the server opens the target of the reparse point instead.
+    pub async fn get_reparse_point(&self) -> crate::Result<ReparseData> {
+        // The maximum size of reparse data (MAXIMUM_REPARSE_DATA_BUFFER_SIZE).
+        const MAX_REPARSE_DATA_SIZE: u32 = 16 * 1024;
+        let response = self
+            .fsctl_with_options(GetReparsePointRequest(()), MAX_REPARSE_DATA_SIZE)
+            .await?;
+        Ok(response.0.data)
+    }
+
+    /// Sets the reparse point data of the resource, making it a symbolic link, mount point or special file.
+    ///
+    /// The resource must be opened with write access, and with
+    /// [`CreateOptions::open_reparse_point`][smb_msg::CreateOptions::open_reparse_point].
+    pub async fn set_reparse_point(&self, data: &ReparseData) -> crate::Result<()> {
+        self.fsctl(SetReparsePointRequest::new(data)?).await?;
+        Ok(())
+    }
+}
+
+/// (Internal)
+///
+/// Returns the path to open in place of `path`, after the server stopped on a symbolic link in it.
+///
+/// Only relative links are followed, since absolute links point at paths local to the server,
+/// or on other shares.
+///
+/// Reference: MS-SMB2 2.2.2.2.1
+pub(crate) fn resolve_symlink(
+    path: &str,
+    error: &SymbolicLinkErrorResponse,
+) -> crate::Result<String> {
+    let link = &error.link;
+    if !link.flags.relative() {
+        return Err(Error::UnsupportedOperation(format!(
+            "Cannot follow absolute symbolic link in {path} to {}",
+            link.print_name
+        )));
+    }
+
+    // The unparsed path length is in bytes of the UTF-16 encoded path.
+    let path_utf16: Vec<u16> = path.encode_utf16().collect();
+    let unparsed_length = error.unparsed_path_length as usize / size_of::<u16>();
+    let parsed_length = path_utf16
+        .len()
+        .checked_sub(unparsed_length)
+        .ok_or_else(|| {
+            Error::InvalidMessage(format!(
+                "Unparsed path length {} exceeds the length of {path}",
+                error.unparsed_path_length
+            ))
+        })?;
+    let parsed = String::from_utf16_lossy(&path_utf16[..parsed_length]);
+    let unparsed = String::from_utf16_lossy(&path_utf16[parsed_length..]);
+
+    // The parsed path ends with the link; its target is relative to the link's parent.
+    let parent = parsed.rsplit_once('\\').map_or("", |(parent, _)| parent);
+    let mut resolved: Vec<&str> = vec![];
+    for component in parent
+        .split('\\')
+        .chain(link.substitute_name.split('\\'))
+        .chain(unparsed.split('\\'))
+    {
+        match component {
+            "" | "." => {}
+            ".." => {
+                resolved.pop().ok_or_else(|| {
+                    Error::UnsupportedOperation(format!(
+                        "Cannot follow symbolic link in {path} to {}, outside of the share",
+                        link.print_name
+                    ))
+                })?;
+            }
+            component => resolved.push(component),
+        }
+    }
+    Ok(resolved.join("\\"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_symlink;
+    use smb_fscc::SymbolicLinkReparseBuffer;
+    use smb_msg::SymbolicLinkErrorResponse;
+
+    fn symlink_error(target: &str, relative: bool, unparsed: &str) -> SymbolicLinkErrorResponse {
+        SymbolicLinkErrorResponse {
+            unparsed_path_length: (unparsed.encode_utf16().count() * 2) as u16,
+            link: SymbolicLinkReparseBuffer::new(target, relative),
+        }
+    }
+
+    #[test]
+    fn test_resolve_symlink() {
+        assert_eq!(
+            resolve_symlink(
+                "dir\\link\\file.txt",
+                &symlink_error("target", true, "\\file.txt")
+            )
+            .unwrap(),
+            "dir\\target\\file.txt"
+        );
+        assert_eq!(
+            resolve_symlink(
+                "dir\\link",
+                &symlink_error("..\\other\\.\\target", true, "")
+            )
+            .unwrap(),
+            "other\\target"
+        );
+        assert!(resolve_symlink("link", &symlink_error("..\\target", true, "")).is_err());
+        assert!(resolve_symlink("link", &symlink_error("\\??\\C:\\target", false, "")).is_err());
+        assert!(resolve_symlink("link", &symlink_error("target", true, "\\too\\long")).is_err());
+    }
+}
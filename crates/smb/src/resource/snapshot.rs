@@ -0,0 +1,176 @@
+//! Previous versions (snapshots) of resources.
+//!
+//! The snapshots of a share are listed using [`ResourceHandle::enumerate_snapshots`],
+//! and resources are opened as of a snapshot using [`FileCreateArgs::snapshot`][crate::FileCreateArgs::snapshot],
+//! or by prefixing their path with the snapshot token, see [`Snapshot::path`].
+
+use std::{fmt::Display, str::FromStr};
+
+use maybe_async::*;
+use smb_msg::{SrvEnumerateSnapshotsRequest, TimewarpToken};
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+use super::ResourceHandle;
+use crate::Error;
+
+/// A snapshot (previous version) of the volume backing a share, identified by its creation time (UTC).
+///
+/// Snapshots are represented by `@GMT-YYYY.MM.DD-HH.MM.SS` tokens, which is also
+/// how they are formatted and parsed.
+///
+/// Reference: MS-SMB2 2.2.13.2.7, 3.3.5.15.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Snapshot {
+    timestamp: PrimitiveDateTime,
+}
+
+impl Snapshot {
+    const TOKEN_PREFIX: &str = "@GMT-";
+    /// The size of a snapshot token on the wire, including its null terminator.
+    const TOKEN_SIZE: u32 = ("@GMT-YYYY.MM.DD-HH.MM.SS".len() as u32 + 1) * 2;
+
+    /// Creates a snapshot identifier from its creation time (UTC).
+    ///
+    /// Snapshot tokens have a precision of seconds, so any fraction of a second is dropped.
+    pub fn new(timestamp: PrimitiveDateTime) -> Self {
+        Self {
+            timestamp: timestamp.replace_nanosecond(0).unwrap_or(timestamp),
+        }
+    }
+
+    /// Returns the creation time of the snapshot (UTC).
+    pub fn timestamp(&self) -> PrimitiveDateTime {
+        self.timestamp
+    }
+
+    /// Returns the path of a resource in the snapshot, by prefixing it with the snapshot token.
+    ///
+    /// The returned path may be opened like any other path on the share, for example using [`Tree::create`][crate::Tree::create].
+    pub fn path(&self, path: &str) -> String {
+        if path.is_empty() {
+            self.to_string()
+        } else {
+            format!("{self}\\{path}")
+        }
+    }
+}
+
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ts = &self.timestamp;
+        write!(
+            f,
+            "{}{:04}.{:02}.{:02}-{:02}.{:02}.{:02}",
+            Self::TOKEN_PREFIX,
+            ts.year(),
+            ts.month() as u8,
+            ts.day(),
+            ts.hour(),
+            ts.minute(),
+            ts.second()
+        )
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidArgument(format!("Invalid snapshot token: {s}"));
+
+        let (date, time) = s
+            .strip_prefix(Self::TOKEN_PREFIX)
+            .and_then(|t| t.split_once('-'))
+            .ok_or_else(invalid)?;
+        let parse_parts = |value: &str| -> crate::Result<[u16; 3]> {
+            let mut parts = value.split('.').map(|p| p.parse::<u16>().ok());
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(Some(a)), Some(Some(b)), Some(Some(c)), None) => Ok([a, b, c]),
+                _ => Err(invalid()),
+            }
+        };
+        let [year, month, day] = parse_parts(date)?;
+        let [hour, minute, second] = parse_parts(time)?;
+
+        let month = Month::try_from(month as u8).map_err(|_| invalid())?;
+        let date =
+            Date::from_calendar_date(year as i32, month, day as u8).map_err(|_| invalid())?;
+        let time = Time::from_hms(hour as u8, minute as u8, second as u8).map_err(|_| invalid())?;
+        Ok(Self::new(PrimitiveDateTime::new(date, time)))
+    }
+}
+
+impl From<Snapshot> for TimewarpToken {
+    fn from(snapshot: Snapshot) -> Self {
+        TimewarpToken {
+            timestamp: snapshot.timestamp.into(),
+        }
+    }
+}
+
+#[maybe_async(AFIT)]
+impl ResourceHandle {
+    /// Lists the snapshots (previous versions) available for the resource.
+    ///
+    /// The server is first asked for the number of snapshots, and then for the snapshots themselves.
+    ///
+    /// Reference: MS-SMB2 3.2.4.20.1
+    pub async fn enumerate_snapshots(&self) -> crate::Result<Vec<Snapshot>> {
+        // Snapshots may be taken between the requests, so the count is re-checked a few times.
+        const MAX_ATTEMPTS: usize = 3;
+        const HEADER_SIZE: u32 = 16;
+        const TERMINATOR_SIZE: u32 = 2;
+
+        let max_transact_size = self.conn_info().negotiation.max_transact_size;
+        let mut output_size = HEADER_SIZE;
+        for _ in 0..MAX_ATTEMPTS {
+            let response = self
+                .fsctl_with_options(SrvEnumerateSnapshotsRequest(()), output_size)
+                .await?;
+            if response.number_of_snap_shots_returned >= response.number_of_snap_shots {
+                return response
+                    .snap_shots
+                    .iter()
+                    .map(|token| token.to_string().parse())
+                    .collect();
+            }
+
+            let required_size = HEADER_SIZE
+                + response.number_of_snap_shots * Snapshot::TOKEN_SIZE
+                + TERMINATOR_SIZE;
+            if required_size > max_transact_size {
+                return Err(Error::BufferTooSmall {
+                    data_type: "SrvEnumerateSnapshotsResponse",
+                    required: Some(required_size as usize),
+                    provided: max_transact_size as usize,
+                });
+            }
+            output_size = required_size;
+        }
+
+        Err(Error::InvalidState(
+            "Snapshots kept changing while being enumerated".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Snapshot;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_snapshot_token() {
+        let snapshot: Snapshot = "@GMT-2025.03.09-18.05.42".parse().unwrap();
+        assert_eq!(snapshot.timestamp(), datetime!(2025-03-09 18:05:42));
+        assert_eq!(snapshot.to_string(), "@GMT-2025.03.09-18.05.42");
+        assert_eq!(
+            snapshot.path("dir\\file.txt"),
+            "@GMT-2025.03.09-18.05.42\\dir\\file.txt"
+        );
+
+        assert!("@GMT-2025.13.09-18.05.42".parse::<Snapshot>().is_err());
+        assert!("2025.03.09-18.05.42".parse::<Snapshot>().is_err());
+        assert!("@GMT-2025.03.09-18.05".parse::<Snapshot>().is_err());
+    }
+}
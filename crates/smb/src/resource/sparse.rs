@@ -0,0 +1,114 @@
+//! Sparse files.
+//!
+//! Ranges of sparse files that were never written, or that were explicitly zeroed,
+//! are not allocated on disk and read as zeros. Such ranges are called holes.
+
+use std::ops::Range;
+
+use maybe_async::*;
+use smb_fscc::FileStandardInformation;
+use smb_msg::{QueryAllocRangesItem, SetSparseRequest, SetZeroDataRequest, Status};
+
+use super::{File, GetAllocatedRanges, SetSparse};
+use crate::Error;
+
+#[maybe_async(AFIT)]
+impl File {
+    /// Sets or clears the sparse attribute of the file.
+    ///
+    /// Clearing the attribute allocates all the holes of the file.
+    pub async fn set_sparse(&self, sparse: bool) -> crate::Result<()> {
+        self.fsctl(SetSparseRequest {
+            set_sparse: sparse.into(),
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Sets the range of the file to zeros, deallocating it where possible if the file is sparse.
+    ///
+    /// The range may extend beyond the end of the file, which does not change the file's size.
+    pub async fn punch_hole(&self, range: Range<u64>) -> crate::Result<()> {
+        if range.start > range.end {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid range to punch: {range:?}"
+            )));
+        }
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        self.fsctl(SetZeroDataRequest {
+            file_offset: range.start,
+            beyond_final_zero: range.end,
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the allocated ranges of the file, in ascending order.
+    ///
+    /// Ranges outside of the returned ones are holes. Files that are not sparse are entirely allocated.
+    pub async fn allocated_ranges(&self) -> crate::Result<Vec<Range<u64>>> {
+        let end_of_file = self
+            .query_info::<FileStandardInformation>()
+            .await?
+            .end_of_file;
+
+        const RANGE_SIZE: usize = size_of::<u64>() * 2;
+        let buffer_size = self.calc_transact_size(None) / RANGE_SIZE as u32 * RANGE_SIZE as u32;
+
+        // When the ranges do not fit in the buffer, the server returns the ones that do;
+        // the query is then repeated for the rest of the file.
+        let mut ranges: Vec<Range<u64>> = vec![];
+        let mut offset = 0;
+        while offset < end_of_file {
+            let (status, result) = self
+                .fsctl_with_status(
+                    QueryAllocRangesItem {
+                        offset,
+                        len: end_of_file - offset,
+                    },
+                    buffer_size,
+                    &[Status::Success, Status::BufferOverflow],
+                )
+                .await?;
+            let complete = status == Status::Success;
+
+            ranges.extend(
+                result
+                    .iter()
+                    .map(|item| item.offset..item.offset + item.len),
+            );
+            if complete {
+                break;
+            }
+            match ranges.last() {
+                Some(last) if last.end > offset => offset = last.end,
+                _ => {
+                    return Err(Error::BufferTooSmall {
+                        data_type: "QueryAllocRangesResult",
+                        required: None,
+                        provided: buffer_size as usize,
+                    });
+                }
+            }
+        }
+
+        Ok(ranges)
+    }
+}
+
+impl GetAllocatedRanges for File {
+    #[maybe_async]
+    async fn allocated_ranges(&self) -> crate::Result<Vec<Range<u64>>> {
+        File::allocated_ranges(self).await
+    }
+}
+
+impl SetSparse for File {
+    #[maybe_async]
+    async fn set_sparse(&self, sparse: bool) -> crate::Result<()> {
+        File::set_sparse(self, sparse).await
+    }
+}
@@ -0,0 +1,549 @@
+//! Copies performed by the server.
+//!
+//! Three mechanisms are supported:
+//! * Block cloning (FSCTL_DUPLICATE_EXTENTS_TO_FILE), where the file system shares the storage
+//!   of the ranges instead of copying the data. See [`File::clone_range`].
+//! * Server-side copy (FSCTL_SRV_COPYCHUNK), for files on the same server.
+//!   The server limits the size of each request, and reports its limits when they are exceeded.
+//! * Offload data transfer (ODX, FSCTL_OFFLOAD_READ and FSCTL_OFFLOAD_WRITE), where the source is
+//!   represented by a token that the storage resolves. This works across shares and servers backed by the same storage.
+//!
+//! [`File::copy_range`] tries the latter two, and falls back to copying through the client.
+
+use maybe_async::*;
+use smb_fscc::FileFsSectorSizeInformation;
+use smb_msg::{
+    DuplicateExtentsDataExFlags, DuplicateExtentsToFileExRequest, DuplicateExtentsToFileRequest,
+    OffloadReadRequest, OffloadWriteRequest, SrvCopyChunkCopyWrite, SrvCopychunkCopy,
+    SrvCopychunkItem, SrvRequestResumeKeyRequest, Status,
+};
+
+use super::{File, GetLen, SetLen};
+use crate::Error;
+
+/// Limits of a server-side copy request, as reported by the server.
+///
+/// MS-SMB2 3.3.3 specifies the defaults used until the server reports otherwise.
+#[derive(Debug, Clone, Copy)]
+struct CopychunkLimits {
+    max_chunks: u32,
+    max_chunk_size: u32,
+    max_data_size: u32,
+}
+
+impl Default for CopychunkLimits {
+    fn default() -> Self {
+        Self {
+            max_chunks: 256,
+            max_chunk_size: 1024 * 1024,
+            max_data_size: 16 * 1024 * 1024,
+        }
+    }
+}
+
+#[maybe_async(AFIT)]
+impl File {
+    /// Performs a server-side copy from another file on the same server.
+    /// # Arguments
+    /// * `from` - The file to copy from.
+    /// # Notes
+    /// * The file is resized to the size of the source file, and its contents are replaced.
+    /// * See [`File::srv_copy_range`] for more information.
+    pub async fn srv_copy(&self, from: &File) -> crate::Result<()> {
+        let other_end_of_file = from.get_len().await?;
+        self.set_len(other_end_of_file).await?;
+
+        let copied = self.srv_copy_range(from, 0, 0, other_end_of_file).await?;
+        if copied != other_end_of_file {
+            return Err(Error::InvalidArgument(format!(
+                "Expected to write {other_end_of_file} bytes, but wrote {copied} bytes",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Performs a server-side copy of a range of another file on the same server.
+    /// # Arguments
+    /// * `from` - The file to copy from. It may be opened on another share of the same server.
+    /// * `source_offset` - The offset in the source file to copy from.
+    /// * `target_offset` - The offset in this file to copy to.
+    /// * `length` - The number of bytes to copy.
+    /// # Returns
+    /// The number of bytes copied, which is smaller than `length` if the end of the source file was reached.
+    /// # Notes
+    /// * The copy is split into requests according to the limits of the server,
+    ///   which are adjusted when the server reports them.
+    /// * If this file was not opened for reading, FSCTL_SRV_COPYCHUNK_WRITE is used,
+    ///   which only requires write access to this file.
+    pub async fn srv_copy_range(
+        &self,
+        from: &File,
+        source_offset: u64,
+        target_offset: u64,
+        length: u64,
+    ) -> crate::Result<u64> {
+        if !self.access().file_write_data() {
+            return Err(Error::InvalidState(
+                "No write permission on destination file".to_string(),
+            ));
+        }
+        if !from.access().file_read_data() {
+            return Err(Error::InvalidState(
+                "No read permission on source file".to_string(),
+            ));
+        }
+        // Servers fail chunks that run past the end of the source file, rather than copying less.
+        let length = length.min(from.get_len().await?.saturating_sub(source_offset));
+        if length == 0 {
+            return Ok(0);
+        }
+
+        let source_key = from.fsctl(SrvRequestResumeKeyRequest(())).await?.resume_key;
+
+        let mut limits = CopychunkLimits::default();
+        let mut copied = 0;
+        while copied < length {
+            let request = SrvCopychunkCopy {
+                source_key,
+                chunks: Self::make_copychunks(
+                    source_offset + copied,
+                    target_offset + copied,
+                    length - copied,
+                    &limits,
+                ),
+            };
+            let requested = request
+                .chunks
+                .iter()
+                .map(|chunk| chunk.length as u64)
+                .sum::<u64>();
+
+            const STATUS: &[Status] = &[Status::Success, Status::InvalidParameter];
+            let (status, response) = if self.access().file_read_data() {
+                self.fsctl_with_status(request, 1024, STATUS).await?
+            } else {
+                self.fsctl_with_status(SrvCopyChunkCopyWrite(request), 1024, STATUS)
+                    .await?
+            };
+
+            if status == Status::InvalidParameter {
+                // The response contains the limits of the server.
+                let reported = CopychunkLimits {
+                    max_chunks: response.chunks_written,
+                    max_chunk_size: response.chunk_bytes_written,
+                    max_data_size: response.total_bytes_written,
+                };
+                log::debug!("Server-side copy limits exceeded, server reported: {reported:?}");
+                let adjusted = CopychunkLimits {
+                    max_chunks: limits.max_chunks.min(reported.max_chunks),
+                    max_chunk_size: limits.max_chunk_size.min(reported.max_chunk_size),
+                    max_data_size: limits.max_data_size.min(reported.max_data_size),
+                };
+                let unchanged = adjusted.max_chunks == limits.max_chunks
+                    && adjusted.max_chunk_size == limits.max_chunk_size
+                    && adjusted.max_data_size == limits.max_data_size;
+                if unchanged || adjusted.max_chunks == 0 || adjusted.max_chunk_size == 0 {
+                    return Err(Error::UnexpectedMessageStatus(status as u32));
+                }
+                limits = adjusted;
+                continue;
+            }
+
+            copied += response.total_bytes_written as u64;
+            if (response.total_bytes_written as u64) < requested {
+                // The end of the source file was reached.
+                break;
+            }
+        }
+        Ok(copied)
+    }
+
+    /// Clones a range of another file into this file, using block cloning.
+    ///
+    /// The cloned ranges share their storage until either of them is modified,
+    /// so cloning is nearly instantaneous regardless of the length of the range.
+    /// # Arguments
+    /// * `from` - The file to clone from, which must be opened on the same share.
+    /// * `source_offset` - The offset in the source file to clone from.
+    /// * `target_offset` - The offset in this file to clone to.
+    /// * `length` - The number of bytes to clone.
+    /// # Returns
+    /// The number of bytes cloned or copied.
+    /// # Notes
+    /// * The range must be within both files, and this file is not extended to fit it.
+    /// * File systems may require the offsets and the length to be aligned to their cluster size,
+    ///   unless the range ends at the end of the source file.
+    /// * If the server does not support block cloning, the range is copied using [`File::srv_copy_range`].
+    pub async fn clone_range(
+        &self,
+        from: &File,
+        source_offset: u64,
+        target_offset: u64,
+        length: u64,
+    ) -> crate::Result<u64> {
+        self._clone_range(from, source_offset, target_offset, length, None)
+            .await
+    }
+
+    /// Clones a range of another file into this file, like [`File::clone_range`],
+    /// using FSCTL_DUPLICATE_EXTENTS_TO_FILE_EX, which supports the specified flags.
+    /// # Arguments
+    /// * `from` - The file to clone from, which must be opened on the same share.
+    /// * `source_offset` - The offset in the source file to clone from.
+    /// * `target_offset` - The offset in this file to clone to.
+    /// * `length` - The number of bytes to clone.
+    /// * `flags` - The flags of the request, e.g. whether the source file is kept unmodified while cloning.
+    /// # Returns
+    /// The number of bytes cloned or copied.
+    pub async fn clone_range_ex(
+        &self,
+        from: &File,
+        source_offset: u64,
+        target_offset: u64,
+        length: u64,
+        flags: DuplicateExtentsDataExFlags,
+    ) -> crate::Result<u64> {
+        self._clone_range(from, source_offset, target_offset, length, Some(flags))
+            .await
+    }
+
+    async fn _clone_range(
+        &self,
+        from: &File,
+        source_offset: u64,
+        target_offset: u64,
+        length: u64,
+        ex_flags: Option<DuplicateExtentsDataExFlags>,
+    ) -> crate::Result<u64> {
+        let result = match ex_flags {
+            Some(flags) => self
+                .fsctl(DuplicateExtentsToFileExRequest {
+                    source_file_id: from.file_id()?,
+                    source_file_offset: source_offset,
+                    target_file_offset: target_offset,
+                    byte_count: length,
+                    flags,
+                })
+                .await
+                .map(|_| ()),
+            None => self
+                .fsctl(DuplicateExtentsToFileRequest {
+                    source_file_id: from.file_id()?,
+                    source_file_offset: source_offset,
+                    target_file_offset: target_offset,
+                    byte_count: length,
+                })
+                .await
+                .map(|_| ()),
+        };
+        match result {
+            Ok(()) => Ok(length),
+            Err(e)
+                if matches!(
+                    e.status(),
+                    Some(Status::NotSupported | Status::InvalidDeviceRequest0)
+                ) =>
+            {
+                log::debug!("Block cloning is not supported, falling back to server-side copy");
+                self.srv_copy_range(from, source_offset, target_offset, length)
+                    .await
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replaces the contents of this file with a clone of another file, using [`File::clone_range`].
+    /// # Arguments
+    /// * `from` - The file to clone, which must be opened on the same share.
+    pub async fn clone_from(&self, from: &File) -> crate::Result<()> {
+        let length = from.get_len().await?;
+        self.set_len(length).await?;
+        if length == 0 {
+            return Ok(());
+        }
+
+        let cloned = self.clone_range(from, 0, 0, length).await?;
+        if cloned != length {
+            return Err(Error::InvalidArgument(format!(
+                "Expected to clone {length} bytes, but cloned {cloned} bytes",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Copies a range of another file using offload data transfer (ODX).
+    ///
+    /// The source data is represented by a token, which the storage of this file resolves,
+    /// so the files may be on different shares or servers, as long as the storage supports it.
+    /// # Arguments
+    /// * `from` - The file to copy from.
+    /// * `source_offset` - The offset in the source file to copy from.
+    /// * `target_offset` - The offset in this file to copy to.
+    /// * `length` - The number of bytes to copy.
+    /// # Returns
+    /// The number of bytes copied, which may be smaller than `length`,
+    /// for example when the end of the source file was reached.
+    /// # Notes
+    /// * Offsets and lengths must be aligned to the logical sector size of the storage.
+    /// * Servers that do not support ODX fail with [`Status::InvalidDeviceRequest0`] or [`Status::NotSupported`].
+    pub async fn offload_copy_range(
+        &self,
+        from: &File,
+        source_offset: u64,
+        target_offset: u64,
+        length: u64,
+    ) -> crate::Result<u64> {
+        let mut copied = 0;
+        while copied < length {
+            let read = from
+                .fsctl(OffloadReadRequest {
+                    flags: 0,
+                    token_time_to_live: 0,
+                    file_offset: source_offset + copied,
+                    copy_length: length - copied,
+                })
+                .await?;
+            if read.transfer_length == 0 {
+                break;
+            }
+
+            // A token may be written with several requests, if the storage writes less than requested.
+            let mut transfer_offset = 0;
+            while transfer_offset < read.transfer_length {
+                let written = self
+                    .fsctl(OffloadWriteRequest {
+                        flags: 0,
+                        file_offset: target_offset + copied + transfer_offset,
+                        copy_length: read.transfer_length - transfer_offset,
+                        transfer_offset,
+                        token: read.token.to_vec(),
+                    })
+                    .await?
+                    .length_written;
+                if written == 0 {
+                    return Ok(copied + transfer_offset);
+                }
+                transfer_offset += written;
+            }
+            copied += read.transfer_length;
+        }
+        Ok(copied)
+    }
+
+    /// Copies a range of another file, without transferring the data through the client where possible.
+    ///
+    /// Offload data transfer (see [`File::offload_copy_range`]) is tried first, for the part of the range
+    /// that is aligned to the logical sectors of both files' volumes,
+    /// then server-side copy (see [`File::srv_copy_range`]) for files on the same connection.
+    /// Any data left is then copied through the client.
+    /// # Returns
+    /// The number of bytes copied, which is smaller than `length` if the end of the source file was reached.
+    pub async fn copy_range(
+        &self,
+        from: &File,
+        source_offset: u64,
+        target_offset: u64,
+        length: u64,
+    ) -> crate::Result<u64> {
+        let mut copied = 0;
+
+        let offload_length = match self.offload_sector_size(from).await {
+            Ok(sector_size) => {
+                Self::sector_aligned_length(source_offset, target_offset, length, sector_size)
+            }
+            Err(e) if e.status().is_some() => {
+                log::debug!("Querying sector sizes failed, not offloading: {e}");
+                0
+            }
+            Err(e) => return Err(e),
+        };
+        if offload_length > 0 {
+            match self
+                .offload_copy_range(from, source_offset, target_offset, offload_length)
+                .await
+            {
+                Ok(offloaded) => copied += offloaded,
+                Err(e) if e.status().is_some() => {
+                    log::debug!("Offload copy failed, falling back: {e}");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if copied < length && self.same_connection(from) {
+            match self
+                .srv_copy_range(
+                    from,
+                    source_offset + copied,
+                    target_offset + copied,
+                    length - copied,
+                )
+                .await
+            {
+                Ok(server_copied) => copied += server_copied,
+                Err(e) if e.status().is_some() => {
+                    log::debug!("Server-side copy failed, falling back: {e}");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if copied < length {
+            copied += self
+                .client_copy_range(
+                    from,
+                    source_offset + copied,
+                    target_offset + copied,
+                    length - copied,
+                )
+                .await?;
+        }
+        Ok(copied)
+    }
+
+    /// Replaces the contents of this file with the contents of another file, using [`File::copy_range`].
+    /// # Returns
+    /// The number of bytes copied.
+    pub async fn copy_from(&self, from: &File) -> crate::Result<u64> {
+        let length = from.get_len().await?;
+        self.set_len(length).await?;
+        self.copy_range(from, 0, 0, length).await
+    }
+
+    /// (Internal)
+    ///
+    /// Copies a range of another file by reading it and writing it through the client.
+    async fn client_copy_range(
+        &self,
+        from: &File,
+        source_offset: u64,
+        target_offset: u64,
+        length: u64,
+    ) -> crate::Result<u64> {
+        const BUFFER_SIZE: u64 = 1024 * 1024;
+        let mut buffer = vec![0u8; BUFFER_SIZE.min(length) as usize];
+        let mut copied = 0;
+        while copied < length {
+            let chunk_size = buffer.len().min((length - copied) as usize);
+            let read = from
+                .read_block(
+                    &mut buffer[..chunk_size],
+                    source_offset + copied,
+                    None,
+                    false,
+                )
+                .await?;
+            if read == 0 {
+                break;
+            }
+            let written = self
+                .write_block(&buffer[..read], target_offset + copied, None)
+                .await?;
+            if written < read {
+                return Err(Error::IoError(std::io::ErrorKind::WriteZero.into()));
+            }
+            copied += read as u64;
+        }
+        Ok(copied)
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the sector size that offloaded ranges must be aligned to:
+    /// the larger logical sector size of the volumes of both files.
+    async fn offload_sector_size(&self, from: &File) -> crate::Result<u64> {
+        let target = self.query_fs_info::<FileFsSectorSizeInformation>().await?;
+        let source = from.query_fs_info::<FileFsSectorSizeInformation>().await?;
+        Ok(target
+            .logical_bytes_per_sector
+            .max(source.logical_bytes_per_sector)
+            .max(1) as u64)
+    }
+
+    /// (Internal)
+    ///
+    /// Returns the length of the longest prefix of a range that may be offloaded, which ends on a sector boundary.
+    /// Ranges that do not start on sector boundaries may not be offloaded at all.
+    fn sector_aligned_length(
+        source_offset: u64,
+        target_offset: u64,
+        length: u64,
+        sector_size: u64,
+    ) -> u64 {
+        if source_offset % sector_size != 0 || target_offset % sector_size != 0 {
+            return 0;
+        }
+        length - length % sector_size
+    }
+
+    /// (Internal)
+    ///
+    /// Returns whether both files are opened on the same connection, as required for server-side copies.
+    fn same_connection(&self, other: &File) -> bool {
+        std::sync::Arc::ptr_eq(&self.conn_info(), &other.conn_info())
+    }
+
+    /// (Internal)
+    ///
+    /// Splits a range into the chunks of a single server-side copy request, according to the limits.
+    fn make_copychunks(
+        source_offset: u64,
+        target_offset: u64,
+        length: u64,
+        limits: &CopychunkLimits,
+    ) -> Vec<SrvCopychunkItem> {
+        let length = length.min(limits.max_data_size.max(1) as u64);
+        let chunk_size = limits.max_chunk_size as u64;
+        (0..length)
+            .step_by(chunk_size as usize)
+            .take(limits.max_chunks as usize)
+            .map(|offset| SrvCopychunkItem {
+                source_offset: source_offset + offset,
+                target_offset: target_offset + offset,
+                length: chunk_size.min(length - offset) as u32,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CopychunkLimits, File};
+
+    #[test]
+    fn test_make_copychunks() {
+        let limits = CopychunkLimits {
+            max_chunks: 3,
+            max_chunk_size: 100,
+            max_data_size: 250,
+        };
+
+        let chunks = File::make_copychunks(1000, 2000, 1000, &limits);
+        let lengths = chunks.iter().map(|c| c.length).collect::<Vec<_>>();
+        assert_eq!(lengths, [100, 100, 50]);
+        assert_eq!(chunks[1].source_offset, 1100);
+        assert_eq!(chunks[1].target_offset, 2100);
+
+        let chunks = File::make_copychunks(0, 0, 120, &limits);
+        let lengths = chunks.iter().map(|c| c.length).collect::<Vec<_>>();
+        assert_eq!(lengths, [100, 20]);
+
+        let limits = CopychunkLimits {
+            max_data_size: 10000,
+            ..limits
+        };
+        let chunks = File::make_copychunks(0, 0, 1000, &limits);
+        assert_eq!(chunks.len(), 3);
+    }
+
+    #[test]
+    fn test_sector_aligned_length() {
+        assert_eq!(
+            File::sector_aligned_length(0, 0, 4096 * 3 + 123, 4096),
+            4096 * 3
+        );
+        assert_eq!(File::sector_aligned_length(4096, 8192, 4096, 4096), 4096);
+        assert_eq!(File::sector_aligned_length(0, 0, 100, 512), 0);
+        assert_eq!(File::sector_aligned_length(512, 100, 4096, 512), 0);
+    }
+}
@@ -0,0 +1,298 @@
+//! Alternate (named) data streams.
+//!
+//! Besides its default, unnamed data stream, a file on a disk share may have named data streams,
+//! addressed as `file.txt:stream`, or `file.txt:stream:$DATA` with the explicit stream type.
+//! Streams are listed using [`ResourceHandle::streams`], opened with [`ResourceHandle::open_stream`],
+//! or by passing their full path to [`Tree::create`][crate::Tree::create].
+//! The block copy functions (e.g. [`block_copy_with_options`][super::block_copy_with_options]) copy them along with the default data when [`CopyOptions::streams`][super::CopyOptions::streams] is set.
+
+use maybe_async::*;
+use smb_fscc::{FileAccessMask, FileDispositionInformation, FileStreamInformation};
+use smb_msg::CreateOptions;
+
+use super::{
+    CopyStreams, File, FileCreateArgs, GetLen, ReadAt, Resource, ResourceHandle, SetLen, WriteAt,
+};
+use crate::Error;
+#[cfg(feature = "std-fs-impls")]
+use crate::sync_helpers::Mutex;
+
+/// A data stream of a file, as listed by [`ResourceHandle::streams`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// The name of the stream, without the leading colon and the `:$DATA` type suffix.
+    ///
+    /// The name of the default data stream is empty.
+    pub name: String,
+    /// The size of the stream, in bytes.
+    pub size: u64,
+    /// The number of bytes allocated for the stream.
+    pub allocation_size: u64,
+}
+
+impl StreamInfo {
+    /// The type of data streams, as appended to stream names.
+    const DATA_TYPE: &str = "$DATA";
+    /// The type of directory index streams; opening one opens the directory itself.
+    const INDEX_ALLOCATION_TYPE: &str = "$INDEX_ALLOCATION";
+
+    /// Whether this is the default (unnamed) data stream of the file.
+    pub fn is_default(&self) -> bool {
+        self.name.is_empty()
+    }
+
+    /// (Internal)
+    ///
+    /// Parses a stream name as returned by the server (`:name:$DATA`),
+    /// returning `None` for streams that are not data streams.
+    fn parse_name(name: &str) -> Option<&str> {
+        let (name, stream_type) = name.strip_prefix(':')?.rsplit_once(':')?;
+        stream_type
+            .eq_ignore_ascii_case(Self::DATA_TYPE)
+            .then_some(name)
+    }
+}
+
+/// (Internal)
+///
+/// Validates the stream part of a path, if it has one: only the last component of the path may
+/// specify a stream, in the form `name:stream[:type]`, where the type is a data or directory index type.
+///
+/// Reference: MS-FSCC 2.1.5.3
+pub(crate) fn validate_stream_path(path: &str) -> crate::Result<()> {
+    let invalid = |reason: &str| {
+        Err(Error::InvalidArgument(format!(
+            "Invalid stream in path {path}: {reason}"
+        )))
+    };
+
+    let (parent, last) = path.rsplit_once('\\').unwrap_or(("", path));
+    if parent.contains(':') {
+        return invalid("only the last component of a path may specify a stream");
+    }
+
+    let mut parts = last.split(':').skip(1);
+    let (Some(stream), stream_type) = (parts.next(), parts.next()) else {
+        return Ok(());
+    };
+    if parts.next().is_some() {
+        return invalid("too many colons");
+    }
+    if stream.contains(['/', '\0']) {
+        return invalid("stream names may not contain '/' or NUL characters");
+    }
+    match stream_type {
+        // The default stream may only be named with its type, e.g. `file.txt::$DATA`.
+        None if stream.is_empty() => invalid("empty stream name"),
+        None => Ok(()),
+        Some(t)
+            if t.eq_ignore_ascii_case(StreamInfo::DATA_TYPE)
+                || t.eq_ignore_ascii_case(StreamInfo::INDEX_ALLOCATION_TYPE) =>
+        {
+            Ok(())
+        }
+        Some(t) => invalid(&format!("unsupported stream type {t}")),
+    }
+}
+
+#[maybe_async(AFIT)]
+impl ResourceHandle {
+    /// Returns the data streams of the resource, including the default data stream of files.
+    ///
+    /// Directories have no default data stream, but may have named streams.
+    pub async fn streams(&self) -> crate::Result<Vec<StreamInfo>> {
+        let streams = self.query_info::<FileStreamInformation>().await?;
+        Ok(streams
+            .iter()
+            .filter_map(|s| {
+                let name = s.stream_name.to_string();
+                StreamInfo::parse_name(&name).map(|name| StreamInfo {
+                    name: name.to_string(),
+                    size: s.stream_size,
+                    allocation_size: s.stream_allocation_size,
+                })
+            })
+            .collect())
+    }
+
+    /// Opens or creates a named data stream of the resource, as a [`File`].
+    /// # Arguments
+    /// * `stream_name` - The name of the stream, without the leading colon.
+    ///   The stream type may be appended, e.g. `Zone.Identifier:$DATA`.
+    /// * `args` - The arguments of the open. Use [`FileCreateArgs::make_overwrite`] to create a stream.
+    /// # Notes
+    /// * The stream is opened by its path, so the resource itself may be closed while the stream is open.
+    pub async fn open_stream(
+        &self,
+        stream_name: &str,
+        args: &FileCreateArgs,
+    ) -> crate::Result<File> {
+        let binding = self.handler.bound()?;
+        let tree_info = binding.upstream.handler.info()?;
+        let path = format!("{}:{stream_name}", self.name);
+        let resource = Resource::create(
+            &path,
+            &binding.upstream,
+            args,
+            &binding.conn_info,
+            tree_info,
+        )
+        .await?;
+        resource.try_into().map_err(|(e, _)| e)
+    }
+
+    /// Deletes a named data stream of the resource.
+    ///
+    /// The default data stream may not be deleted; delete the file itself instead.
+    pub async fn delete_stream(&self, stream_name: &str) -> crate::Result<()> {
+        let stream = self
+            .open_stream(
+                stream_name,
+                &FileCreateArgs::make_open_existing(FileAccessMask::new().with_delete(true)),
+            )
+            .await?;
+        let result = stream
+            .set_info(FileDispositionInformation {
+                delete_pending: true.into(),
+            })
+            .await;
+        stream.close().await?;
+        result
+    }
+}
+
+/// Copies the named data streams of a file to another file, replacing streams of the same name.
+///
+/// Both files must be open while the streams are copied.
+impl CopyStreams<File> for File {
+    #[maybe_async]
+    async fn copy_streams(&self, to: &File) -> crate::Result<()> {
+        for stream in self.streams().await? {
+            if stream.is_default() {
+                continue;
+            }
+
+            log::debug!(
+                "Copying stream {} of {} ({} bytes)",
+                stream.name,
+                self.name(),
+                stream.size
+            );
+            let source = self
+                .open_stream(
+                    &stream.name,
+                    &FileCreateArgs::make_open_existing(
+                        FileAccessMask::new().with_generic_read(true),
+                    ),
+                )
+                .await?;
+            let destination = match to
+                .open_stream(
+                    &stream.name,
+                    &FileCreateArgs {
+                        desired_access: FileAccessMask::new().with_generic_write(true),
+                        ..FileCreateArgs::make_overwrite(Default::default(), CreateOptions::new())
+                    },
+                )
+                .await
+            {
+                Ok(destination) => destination,
+                Err(e) => {
+                    source.close().await?;
+                    return Err(e);
+                }
+            };
+
+            let result = copy_stream_data(&source, &destination).await;
+            source.close().await?;
+            destination.close().await?;
+            result?;
+        }
+        Ok(())
+    }
+}
+
+/// Named data streams of local files are not exposed by the standard library,
+/// so copying to a local file only copies the default data.
+#[cfg(feature = "std-fs-impls")]
+impl<L> CopyStreams<Mutex<L>> for File {
+    #[maybe_async]
+    async fn copy_streams(&self, _to: &Mutex<L>) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+/// Named data streams of local files are not exposed by the standard library,
+/// so copying from a local file only copies the default data.
+#[cfg(feature = "std-fs-impls")]
+impl<L> CopyStreams<File> for Mutex<L> {
+    #[maybe_async]
+    async fn copy_streams(&self, _to: &File) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+/// (Internal)
+///
+/// Sequentially copies the data of an open stream to another; streams are usually small.
+#[maybe_async]
+async fn copy_stream_data(from: &File, to: &File) -> crate::Result<()> {
+    const CHUNK_SIZE: usize = 2usize.pow(16);
+
+    let length = from.get_len().await?;
+    to.set_len(length).await?;
+
+    let mut chunk = vec![0u8; CHUNK_SIZE.min(length as usize)];
+    let mut offset = 0;
+    while offset < length {
+        let bytes_read = from.read_at(&mut chunk, offset).await?;
+        if bytes_read == 0 {
+            return Err(Error::InvalidState(format!(
+                "Unexpected end of stream {} at offset {offset}",
+                from.name()
+            )));
+        }
+        to.write_at(&chunk[..bytes_read], offset).await?;
+        offset += bytes_read as u64;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StreamInfo, validate_stream_path};
+
+    #[test]
+    fn test_parse_stream_name() {
+        assert_eq!(StreamInfo::parse_name("::$DATA"), Some(""));
+        assert_eq!(
+            StreamInfo::parse_name(":Zone.Identifier:$DATA"),
+            Some("Zone.Identifier")
+        );
+        assert_eq!(StreamInfo::parse_name(":$I30:$INDEX_ALLOCATION"), None);
+        assert_eq!(StreamInfo::parse_name("Zone.Identifier"), None);
+    }
+
+    #[test]
+    fn test_validate_stream_path() {
+        for valid in [
+            "file.txt",
+            "dir\\file.txt",
+            "dir\\file.txt:stream",
+            "file.txt:stream:$DATA",
+            "file.txt::$data",
+            "dir:$I30:$INDEX_ALLOCATION",
+        ] {
+            assert!(validate_stream_path(valid).is_ok(), "{valid}");
+        }
+        for invalid in [
+            "dir:stream\\file.txt",
+            "file.txt:",
+            "file.txt:stream:$DATA:more",
+            "file.txt:stream:$EA",
+            "file.txt:str/eam",
+        ] {
+            assert!(validate_stream_path(invalid).is_err(), "{invalid}");
+        }
+    }
+}
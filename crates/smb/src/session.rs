@@ -24,7 +24,7 @@ use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::atomic::{AtomicBool, AtomicU32};
 
-mod authenticator;
+pub(crate) mod authenticator;
 mod channel;
 mod encryptor_decryptor;
 mod setup;
@@ -60,6 +60,7 @@ impl Session {
         identity: sspi::AuthIdentity,
         upstream: &ChannelUpstream,
         conn_info: &Arc<ConnectionInfo>,
+        previous_session_id: Option<u64>,
     ) -> crate::Result<Session> {
         const FIRST_CHANNEL_ID: u32 = 0;
 
@@ -70,7 +71,8 @@ impl Session {
             FIRST_CHANNEL_ID,
             None,
         )
-        .await?;
+        .await?
+        .with_previous_session_id(previous_session_id);
 
         let primary_channel = Self::_common_setup(setup_result).await?;
 
@@ -5,8 +5,9 @@ use crate::connection::AuthMethodsConfig;
 use crate::connection::connection_info::ConnectionInfo;
 use maybe_async::*;
 use sspi::{
-    AcquireCredentialsHandleResult, AuthIdentity, BufferType, ClientRequestFlags, CredentialUse,
-    DataRepresentation, InitializeSecurityContextResult, Negotiate, SecurityBuffer, Sspi,
+    AcquireCredentialsHandleResult, AuthIdentity, BufferType, ClientRequestFlags, ContextSizes,
+    CredentialUse, DataRepresentation, EncryptionFlags, InitializeSecurityContextResult, Negotiate,
+    NegotiatedProtocol, SecurityBuffer, SecurityBufferFlags, SecurityBufferRef, Sspi,
     ntlm::NtlmConfig,
 };
 use sspi::{CredentialsBuffers, NegotiateConfig, SspiImpl, Username};
@@ -15,6 +16,9 @@ use sspi::{CredentialsBuffers, NegotiateConfig, SspiImpl, Username};
 pub struct Authenticator {
     server_hostname: String,
     user_name: Username,
+    /// The service class of the target SPN, e.g. "cifs".
+    target_service: &'static str,
+    context_requirements: ClientRequestFlags,
 
     ssp: Negotiate,
     cred_handle: AcquireCredentialsHandleResult<Option<CredentialsBuffers>>,
@@ -25,6 +29,42 @@ impl Authenticator {
     pub fn build(
         identity: AuthIdentity,
         conn_info: &Arc<ConnectionInfo>,
+    ) -> crate::Result<Authenticator> {
+        Self::build_with(
+            identity,
+            conn_info,
+            "cifs",
+            ClientRequestFlags::DELEGATE
+                | ClientRequestFlags::MUTUAL_AUTH
+                | ClientRequestFlags::INTEGRITY
+                | ClientRequestFlags::FRAGMENT_TO_FIT
+                | ClientRequestFlags::USE_SESSION_KEY,
+        )
+    }
+
+    /// Builds an authenticator for DCE/RPC authentication (MS-RPCE 3.3.1.5.2), over an existing connection.
+    ///
+    /// Tokens are produced DCE-style, as expected in bind PDUs.
+    /// If `confidentiality` is set, the context also supports sealing messages.
+    pub fn build_for_rpc(
+        identity: AuthIdentity,
+        conn_info: &Arc<ConnectionInfo>,
+        confidentiality: bool,
+    ) -> crate::Result<Authenticator> {
+        let mut context_requirements = ClientRequestFlags::MUTUAL_AUTH
+            | ClientRequestFlags::INTEGRITY
+            | ClientRequestFlags::USE_DCE_STYLE;
+        if confidentiality {
+            context_requirements |= ClientRequestFlags::CONFIDENTIALITY;
+        }
+        Self::build_with(identity, conn_info, "host", context_requirements)
+    }
+
+    fn build_with(
+        identity: AuthIdentity,
+        conn_info: &Arc<ConnectionInfo>,
+        target_service: &'static str,
+        context_requirements: ClientRequestFlags,
     ) -> crate::Result<Authenticator> {
         let client_computer_name = conn_info
             .config
@@ -51,6 +91,8 @@ impl Authenticator {
             cred_handle,
             current_state: None,
             user_name,
+            target_service,
+            context_requirements,
         })
     }
 
@@ -72,16 +114,93 @@ impl Authenticator {
         Ok(k.try_into().unwrap())
     }
 
-    fn make_sspi_target_name(server_fqdn: &str) -> String {
-        format!("cifs/{server_fqdn}")
+    /// Returns the security package negotiated for the context.
+    ///
+    /// It is only final once the first token has been produced by [`Authenticator::next`].
+    pub fn negotiated_protocol(&self) -> &NegotiatedProtocol {
+        self.ssp.negotiated_protocol()
     }
 
-    fn get_context_requirements() -> ClientRequestFlags {
-        ClientRequestFlags::DELEGATE
-            | ClientRequestFlags::MUTUAL_AUTH
-            | ClientRequestFlags::INTEGRITY
-            | ClientRequestFlags::FRAGMENT_TO_FIT
-            | ClientRequestFlags::USE_SESSION_KEY
+    /// Returns the sizes of the tokens of the established context,
+    /// e.g. [`ContextSizes::security_trailer`] is the size of a signature or sealing header.
+    pub fn context_sizes(&mut self) -> crate::Result<ContextSizes> {
+        Ok(self.ssp.query_context_sizes()?)
+    }
+
+    /// Signs `data` with the established context, returning the signature.
+    pub fn sign(&mut self, data: &mut [u8], sequence_number: u32) -> crate::Result<Vec<u8>> {
+        let mut signature = vec![0; self.context_sizes()?.security_trailer as usize];
+        let mut message = [
+            SecurityBufferRef::data_buf(data),
+            SecurityBufferRef::token_buf(&mut signature),
+        ];
+        self.ssp.make_signature(0, &mut message, sequence_number)?;
+        Ok(signature)
+    }
+
+    /// Verifies the `signature` of `data` with the established context.
+    pub fn verify(
+        &mut self,
+        data: &mut [u8],
+        signature: &mut [u8],
+        sequence_number: u32,
+    ) -> crate::Result<()> {
+        let mut message = [
+            SecurityBufferRef::data_buf(data),
+            SecurityBufferRef::token_buf(signature),
+        ];
+        self.ssp.verify_signature(&mut message, sequence_number)?;
+        Ok(())
+    }
+
+    /// Encrypts `data` in place with the established context, returning the signature.
+    ///
+    /// The signature also covers the `header` and `trailer`, which are not encrypted.
+    pub fn seal(
+        &mut self,
+        header: &mut [u8],
+        data: &mut [u8],
+        trailer: &mut [u8],
+        sequence_number: u32,
+    ) -> crate::Result<Vec<u8>> {
+        let mut signature = vec![0; self.context_sizes()?.security_trailer as usize];
+        let mut message = [
+            SecurityBufferRef::data_buf(header)
+                .with_flags(SecurityBufferFlags::SECBUFFER_READONLY_WITH_CHECKSUM),
+            SecurityBufferRef::data_buf(data),
+            SecurityBufferRef::data_buf(trailer)
+                .with_flags(SecurityBufferFlags::SECBUFFER_READONLY_WITH_CHECKSUM),
+            SecurityBufferRef::token_buf(&mut signature),
+        ];
+        self.ssp
+            .encrypt_message(EncryptionFlags::empty(), &mut message, sequence_number)?;
+        Ok(signature)
+    }
+
+    /// Decrypts `data` in place with the established context, verifying its `signature`,
+    /// which also covers the `header` and `trailer`.
+    pub fn unseal(
+        &mut self,
+        header: &mut [u8],
+        data: &mut [u8],
+        trailer: &mut [u8],
+        signature: &mut [u8],
+        sequence_number: u32,
+    ) -> crate::Result<()> {
+        let mut message = [
+            SecurityBufferRef::data_buf(header)
+                .with_flags(SecurityBufferFlags::SECBUFFER_READONLY_WITH_CHECKSUM),
+            SecurityBufferRef::data_buf(data),
+            SecurityBufferRef::data_buf(trailer)
+                .with_flags(SecurityBufferFlags::SECBUFFER_READONLY_WITH_CHECKSUM),
+            SecurityBufferRef::token_buf(signature),
+        ];
+        self.ssp.decrypt_message(&mut message, sequence_number)?;
+        Ok(())
+    }
+
+    fn make_sspi_target_name(&self) -> String {
+        format!("{}/{}", self.target_service, self.server_hostname)
     }
 
     const SSPI_REQ_DATA_REPRESENTATION: DataRepresentation = DataRepresentation::Native;
@@ -101,12 +220,12 @@ impl Authenticator {
         }
 
         let mut output_buffer = vec![SecurityBuffer::new(Vec::new(), BufferType::Token)];
-        let target_name = Self::make_sspi_target_name(&self.server_hostname);
+        let target_name = self.make_sspi_target_name();
         let mut builder = self
             .ssp
             .initialize_security_context()
             .with_credentials_handle(&mut self.cred_handle.credentials_handle)
-            .with_context_requirements(Self::get_context_requirements())
+            .with_context_requirements(self.context_requirements)
             .with_target_data_representation(Self::SSPI_REQ_DATA_REPRESENTATION)
             .with_output(&mut output_buffer);
 
@@ -30,6 +30,9 @@ where
     channel: Option<ChannelInfo>,
     new_channel_id: u32,
 
+    /// The session this setup replaces, if re-establishing a session after a lost connection.
+    previous_session_id: Option<u64>,
+
     _phantom: std::marker::PhantomData<T>,
 }
 
@@ -58,6 +61,7 @@ where
             conn_info,
             channel: None,
             new_channel_id,
+            previous_session_id: None,
             _phantom: std::marker::PhantomData,
         };
 
@@ -87,6 +91,12 @@ where
         Ok(result)
     }
 
+    /// Sets the ID of the session to be replaced by the new session.
+    pub fn with_previous_session_id(mut self, previous_session_id: Option<u64>) -> Self {
+        self.previous_session_id = previous_session_id;
+        self
+    }
+
     /// Common session setup logic.
     ///
     /// This function sets up a session against a connection, and it is somewhat abstract.
@@ -333,7 +343,16 @@ pub(crate) trait SessionSetupProperties {
         T: SessionSetupProperties,
     {
         let has_dfs = _setup.conn_info().negotiation.caps.dfs();
-        Ok(Self::_make_default_request(buffer, has_dfs))
+        let mut request = Self::_make_default_request(buffer, has_dfs);
+        if let Some(previous_session_id) = _setup.previous_session_id {
+            request
+                .message
+                .content
+                .as_mut_sessionsetup()
+                .unwrap()
+                .previous_session_id = previous_session_id;
+        }
+        Ok(request)
     }
 
     async fn init_session<T>(
@@ -20,6 +20,11 @@ pub use tokio_util::sync::CancellationToken;
 #[cfg(feature = "async")]
 use std::sync::LockResult;
 
+#[cfg(not(feature = "async"))]
+pub use std::thread::sleep;
+#[cfg(feature = "async")]
+pub use tokio::time::sleep;
+
 // for convenience
 pub use maybe_async::maybe_async;
 pub use std::sync::{Arc, Weak};
@@ -1,14 +1,18 @@
-use std::sync::Arc;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Weak};
 
 use maybe_async::*;
 use smb_msg::{FileId, FsctlRequest, IoctlRequest, IoctlRequestFlags};
 
-use crate::FileCreateArgs;
 use crate::connection::connection_info::ConnectionInfo;
-use smb_fscc::{FileAccessMask, FileAttributes};
+use crate::resource::{ResourceMessageHandle, reparse::resolve_symlink};
+use crate::{DurableHandle, FileCreateArgs, Printer};
+use smb_fscc::{
+    FileAccessMask, FileAttributes, FileDispositionInformation, ReparseData,
+    SymbolicLinkReparseBuffer,
+};
 use smb_msg::{
-    CreateOptions, RequestContent, ShareFlags, ShareType,
+    CreateOptions, ErrorId, RequestContent, ShareFlags, ShareType, Status, TreeCapabilities,
     create::CreateDisposition,
     tree_connect::{TreeConnectRequest, TreeDisconnectRequest},
 };
@@ -18,9 +22,12 @@ use crate::{
     msg_handler::{HandlerReference, MessageHandler},
     session::SessionMessageHandler,
 };
+mod compound;
 mod dfs_tree;
 mod ipc_tree;
+mod path_ops;
 use crate::msg_handler::OutgoingMessage;
+pub use compound::*;
 pub use dfs_tree::*;
 pub use ipc_tree::*;
 
@@ -28,8 +35,9 @@ type Upstream = HandlerReference<SessionMessageHandler>;
 
 #[derive(Debug, Clone)]
 pub struct TreeConnectInfo {
-    share_type: ShareType,
-    share_flags: ShareFlags,
+    pub(crate) share_type: ShareType,
+    pub(crate) share_flags: ShareFlags,
+    pub(crate) capabilities: TreeCapabilities,
 }
 
 /// Represents an SMB share.
@@ -97,6 +105,7 @@ impl Tree {
         let tree_connect_info = TreeConnectInfo {
             share_type: content.share_type,
             share_flags: content.share_flags,
+            capabilities: content.capabilities,
         };
 
         let t = Tree {
@@ -124,17 +133,154 @@ impl Tree {
     /// This function automatically handles the following:
     /// * *DFS operations*: If the share has been opened as a DFS referral share, the create operation will modify the file name to include the DFS path.
     ///     That is, assuming it is NOT prefixed with "\\". This is rquired for a proper DFS referral file open. ("DFS normalization", MS-SMB2 2.2.13 + 3.3.5.9)
+    /// * *Symbolic links*: If [`FileCreateArgs::follow_symlinks`] is set, and the server stops on a relative symbolic link in the path,
+    ///     the link is followed, and the create is retried with the resolved path. Absolute links, and links pointing outside of the share, are not followed.
     pub async fn create(&self, file_name: &str, args: &FileCreateArgs) -> crate::Result<Resource> {
+        // Same as the maximum number of links followed in a path on Linux (MAXSYMLINKS).
+        const MAX_SYMLINKS_FOLLOWED: usize = 40;
+
         let info = self.handler.info()?;
-        Resource::create(
-            file_name,
-            &self.handler,
-            args,
-            &self.conn_info,
-            info.share_type,
-            info.share_flags.dfs(),
-        )
-        .await
+        let mut file_name = file_name.to_string();
+        for _ in 0..=MAX_SYMLINKS_FOLLOWED {
+            match Resource::create(&file_name, &self.handler, args, &self.conn_info, info).await {
+                Err(Error::ReceivedErrorMessage(Status::U32_STOPPED_ON_SYMLINK, error))
+                    if args.follow_symlinks =>
+                {
+                    let symlink_error = error
+                        .find_context(ErrorId::Default)
+                        .ok_or_else(|| {
+                            Error::InvalidMessage("Symbolic link error has no data".to_string())
+                        })?
+                        .as_symlink_error()?;
+                    let resolved = resolve_symlink(&file_name, &symlink_error)?;
+                    log::debug!("Following symbolic link: {file_name} -> {resolved}");
+                    file_name = resolved;
+                }
+                result => return result,
+            }
+        }
+
+        Err(Error::InvalidState(format!(
+            "Too many symbolic links in the path of {file_name}"
+        )))
+    }
+
+    /// Reclaims a durable or persistent open, after the connection it was opened on was lost.
+    /// # Arguments
+    /// * `durable` - The durable state of the open, as returned by [`ResourceHandle::durable_handle`][crate::ResourceHandle::durable_handle].
+    /// # Returns
+    /// * A [Resource] object representing the reclaimed open.
+    /// # Notes
+    /// * The tree must be connected to the same share the resource was opened on, using the same credentials.
+    ///   The server discards the open once the durable timeout elapses.
+    pub async fn reopen(&self, durable: &DurableHandle) -> crate::Result<Resource> {
+        let info = self.handler.info()?;
+        Resource::reopen(durable, &self.handler, &self.conn_info, info).await
+    }
+
+    /// Submits a print job to the printer share.
+    /// # Arguments
+    /// * `job_name` - The name of the print file. Servers usually display it as the job's document name.
+    /// * `data` - The data to print, passed to the printer as-is.
+    /// # Notes
+    /// * The tree must be connected to a printer share.
+    /// * See [`Printer`][crate::Printer] for spooling a job in several writes.
+    pub async fn print(&self, job_name: &str, data: &[u8]) -> crate::Result<()> {
+        if self.handler.info()?.share_type != ShareType::Print {
+            return Err(Error::InvalidArgument(
+                "Print jobs can only be submitted to printer shares.".to_string(),
+            ));
+        }
+
+        let printer: Printer = self
+            .create(job_name, &FileCreateArgs::make_print_job())
+            .await?
+            .try_into()
+            .map_err(|(e, _)| e)?;
+        let spooled = printer.write_all(data).await;
+        printer.close().await?;
+        spooled
+    }
+
+    /// Creates a symbolic link on the share.
+    /// # Arguments
+    /// * `link_name` - The name of the link to create.
+    /// * `target` - The target of the link. Targets starting with a backslash are absolute NT paths (e.g. `\\??\\C:\\dir`),
+    ///     and any other target is relative to the directory containing the link.
+    /// * `directory` - Whether the target is a directory.
+    /// # Notes
+    /// * Creating symbolic links usually requires a privilege on the server.
+    pub async fn create_symlink(
+        &self,
+        link_name: &str,
+        target: &str,
+        directory: bool,
+    ) -> crate::Result<()> {
+        let data = ReparseData::Symlink(SymbolicLinkReparseBuffer::new(
+            target,
+            !target.starts_with('\\'),
+        ));
+        let link = self
+            .create(
+                link_name,
+                &FileCreateArgs {
+                    options: CreateOptions::new()
+                        .with_open_reparse_point(true)
+                        .with_directory_file(directory)
+                        .with_non_directory_file(!directory),
+                    attributes: FileAttributes::new().with_directory(directory),
+                    desired_access: FileAccessMask::new()
+                        .with_file_write_data(true)
+                        .with_file_write_attributes(true)
+                        .with_delete(true),
+                    ..FileCreateArgs::make_create_new(Default::default(), Default::default())
+                },
+            )
+            .await?;
+        let link = link.handle();
+
+        let set_result = link.set_reparse_point(&data).await;
+        if set_result.is_err() {
+            // Do not leave an empty file or directory behind.
+            link.set_info(FileDispositionInformation {
+                delete_pending: true.into(),
+            })
+            .await?;
+        }
+        link.close().await?;
+        set_result
+    }
+
+    /// Returns the target of a symbolic link on the share.
+    ///
+    /// Mount points, and symbolic links created by NFS or WSL, are supported as well.
+    /// See [`ResourceHandle::get_reparse_point`][crate::ResourceHandle::get_reparse_point] for reading any reparse point.
+    pub async fn read_link(&self, link_name: &str) -> crate::Result<String> {
+        let link = self
+            .create(
+                link_name,
+                &FileCreateArgs {
+                    options: CreateOptions::new().with_open_reparse_point(true),
+                    ..FileCreateArgs::make_open_existing(
+                        FileAccessMask::new().with_file_read_attributes(true),
+                    )
+                },
+            )
+            .await?;
+        let link = link.handle();
+
+        let data = link.get_reparse_point().await;
+        link.close().await?;
+        data?
+            .link_target()
+            .map(str::to_string)
+            .ok_or_else(|| Error::InvalidArgument(format!("{link_name} is not a symbolic link")))
+    }
+
+    /// Starts a chain of compounded requests on the tree.
+    /// See [`Compound`] for more information.
+    pub fn compound(&self) -> Compound<'_> {
+        Compound::new(self)
     }
 
     /// A wrapper around [Tree::create] that creates a file on the remote server.
@@ -152,6 +298,7 @@ impl Tree {
                 options: CreateOptions::new(),
                 desired_access,
                 attributes: FileAttributes::new(),
+                ..Default::default()
             },
         )
         .await
@@ -172,6 +319,7 @@ impl Tree {
                 options: CreateOptions::new().with_directory_file(true),
                 desired_access,
                 attributes: FileAttributes::new().with_directory(true),
+                ..Default::default()
             },
         )
         .await
@@ -188,6 +336,24 @@ impl Tree {
             .await
     }
 
+    /// (Internal)
+    ///
+    /// Reclaims the opens of a tree that was connected over a lost connection,
+    /// binding them to the current tree.
+    ///
+    /// Opens that are not durable, or that fail to be reclaimed, are marked as lost.
+    #[maybe_async]
+    pub(crate) async fn reclaim_opens(&self, lost: &Tree) -> crate::Result<()> {
+        let info = self.handler.info()?;
+        for open in lost.handler.take_opens()? {
+            match open.reclaim(&self.handler, &self.conn_info, info).await {
+                Ok(()) => log::debug!("Reclaimed open of {}", open.name()),
+                Err(e) => log::warn!("Failed to reclaim open of {}: {e}", open.name()),
+            }
+        }
+        Ok(())
+    }
+
     pub fn is_dfs_root(&self) -> crate::Result<bool> {
         let info = self.handler.info()?;
         Ok(info.share_flags.dfs_root() && info.share_flags.dfs())
@@ -254,6 +420,9 @@ pub(crate) struct TreeMessageHandler {
 
     tree_name: String,
     info: TreeConnectInfo,
+
+    /// The opens made over the tree, to be reclaimed if the connection is lost.
+    opens: std::sync::Mutex<Vec<Weak<ResourceMessageHandle>>>,
 }
 
 impl TreeMessageHandler {
@@ -270,9 +439,24 @@ impl TreeMessageHandler {
             upstream: upstream.clone(),
             info,
             tree_name,
+            opens: Default::default(),
         })
     }
 
+    /// Tracks an open made over the tree.
+    pub fn track_open(&self, open: &Arc<ResourceMessageHandle>) -> crate::Result<()> {
+        let mut opens = self.opens.lock()?;
+        opens.retain(|o| o.strong_count() > 0);
+        opens.push(Arc::downgrade(open));
+        Ok(())
+    }
+
+    /// Removes all the opens tracked by the tree, returning those still alive.
+    pub fn take_opens(&self) -> crate::Result<Vec<Arc<ResourceMessageHandle>>> {
+        let opens = std::mem::take(&mut *self.opens.lock()?);
+        Ok(opens.iter().filter_map(Weak::upgrade).collect())
+    }
+
     #[maybe_async]
     async fn _disconnect(upstream: Upstream, tree_id: u32, encrypt: bool) -> crate::Result<()> {
         // send and receive tree disconnect request & response.
@@ -0,0 +1,303 @@
+//! Compounded requests, sent to the server in a single transport frame.
+//!
+//! Reference: MS-SMB2 3.2.4.1.4
+
+use maybe_async::*;
+use smb_fscc::*;
+use smb_msg::*;
+
+use crate::{
+    Error, FileCreateArgs,
+    msg_handler::{IncomingMessage, MessageHandler, OutgoingMessage, ReceiveOptions},
+    resource::{Durability, OplockRequest},
+};
+
+use super::Tree;
+
+/// A chain of requests, sent to the server in a single transport frame,
+/// and answered by the server with a single frame as well.
+///
+/// Requests are either *unrelated*, and processed by the server independently,
+/// or *related* to the previous request in the chain, and operate on the file it opened.
+/// Related requests use [`FileId::FULL`] in place of the file ID,
+/// so chains like create → query info → close take a single round trip.
+///
+/// Each request added to the chain returns a [`CompoundSlot`], used to take its typed result
+/// from the [`CompoundResponses`] of [`Compound::send`].
+///
+/// ```no_run
+/// # use smb::*;
+/// # fn main() {}
+/// # #[cfg(feature = "async")]
+/// # async fn example(tree: &Tree) -> smb::Result<()> {
+/// let mut compound = tree.compound();
+/// let open_args = FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true));
+/// compound.create("file.txt", &open_args)?;
+/// let basic_info = compound.query_info::<FileBasicInformation>()?;
+/// compound.close()?;
+/// let mut responses = compound.send().await?;
+/// let basic_info = responses.take(basic_info)?;
+/// # Ok(()) }
+/// ```
+///
+/// # Notes
+/// * Compounding requires the server to support the large MTU capability (SMB 2.1 and above).
+/// * The chain is signed or encrypted according to the session and share, like any other request.
+/// * Opens made in a chain are not tracked by the client. Make sure to close them in the chain.
+pub struct Compound<'a> {
+    tree: &'a Tree,
+    requests: Vec<PlainRequest>,
+}
+
+/// Identifies the result of a request in a [`Compound`],
+/// and parses it to the type of the request's response.
+pub struct CompoundSlot<T> {
+    index: usize,
+    parse: fn(ResponseContent) -> crate::Result<T>,
+}
+
+impl<T> std::fmt::Debug for CompoundSlot<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompoundSlot")
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Compound<'a> {
+    /// Creates a new, empty [`Compound`] on the provided [`Tree`].
+    pub(crate) fn new(tree: &'a Tree) -> Self {
+        Self {
+            tree,
+            requests: vec![],
+        }
+    }
+
+    /// Adds a create request to the chain, starting a new set of related requests.
+    ///
+    /// Oplocks and durability are not supported, since the open is not tracked by the client.
+    pub fn create(
+        &mut self,
+        file_name: &str,
+        args: &FileCreateArgs,
+    ) -> crate::Result<CompoundSlot<CreateResponse>> {
+        if file_name.starts_with("\\") {
+            return Err(Error::InvalidArgument(
+                "Resource name cannot start with a backslash.".to_string(),
+            ));
+        }
+        if args.oplock != OplockRequest::None || args.durability != Durability::None {
+            return Err(Error::InvalidArgument(
+                "Oplocks and durability are not supported in compounded requests.".to_string(),
+            ));
+        }
+
+        let info = self.tree.handler.info()?;
+        let mut request = PlainRequest::new(
+            CreateRequest {
+                requested_oplock_level: OplockLevel::None,
+                impersonation_level: args.impersonation_level,
+                desired_access: args.desired_access,
+                file_attributes: args.attributes,
+                share_access: args.share_access_for(info.share_type),
+                create_disposition: args.disposition,
+                create_options: args.options,
+                name: file_name.into(),
+                contexts: args
+                    .contexts
+                    .iter()
+                    .cloned()
+                    .chain(args.snapshot.map(|s| TimewarpToken::from(s).into()))
+                    .collect::<Vec<_>>()
+                    .into(),
+            }
+            .into(),
+        );
+        request
+            .header
+            .flags
+            .set_dfs_operation(info.share_flags.dfs());
+
+        Ok(self.push_request(request, |content| Ok(content.to_create()?)))
+    }
+
+    /// Adds a read request on the file opened by the previous requests in the chain.
+    ///
+    /// Returns the data read. The read fails if `offset` is at or beyond the end of the file.
+    pub fn read(&mut self, offset: u64, length: u32) -> crate::Result<CompoundSlot<Vec<u8>>> {
+        self.push_related(
+            ReadRequest {
+                flags: ReadFlags::new(),
+                length,
+                offset,
+                file_id: FileId::FULL,
+                minimum_count: 0,
+            }
+            .into(),
+            |content| Ok(content.to_read()?.buffer),
+        )
+    }
+
+    /// Adds a query info request on the file opened by the previous requests in the chain.
+    ///
+    /// See [`ResourceHandle::query_info`][crate::ResourceHandle::query_info] for more information.
+    pub fn query_info<T>(&mut self) -> crate::Result<CompoundSlot<T>>
+    where
+        T: QueryFileInfoValue,
+    {
+        let conn_info = &self.tree.conn_info;
+        let output_buffer_length = conn_info
+            .negotiation
+            .max_transact_size
+            .min(conn_info.config.default_transaction_size());
+        self.push_related(
+            QueryInfoRequest {
+                info_type: InfoType::File,
+                info_class: QueryInfoClass::File(T::CLASS_ID),
+                output_buffer_length,
+                additional_info: AdditionalInfo::new(),
+                flags: QueryInfoFlags::new()
+                    .with_restart_scan(true)
+                    .with_return_single_entry(true),
+                file_id: FileId::FULL,
+                data: GetInfoRequestData::None(()),
+            }
+            .into(),
+            |content| {
+                Ok(content
+                    .to_queryinfo()?
+                    .parse(InfoType::File)?
+                    .as_file()?
+                    .parse(T::CLASS_ID)?
+                    .try_into()?)
+            },
+        )
+    }
+
+    /// Adds a close request on the file opened by the previous requests in the chain.
+    pub fn close(&mut self) -> crate::Result<CompoundSlot<CloseResponse>> {
+        self.push_related(
+            CloseRequest {
+                file_id: FileId::FULL,
+            }
+            .into(),
+            |content| Ok(content.to_close()?),
+        )
+    }
+
+    /// Adds an unrelated request to the chain, processed by the server independently.
+    ///
+    /// The response content is returned as-is.
+    pub fn push(&mut self, content: RequestContent) -> CompoundSlot<ResponseContent> {
+        self.push_request(PlainRequest::new(content), Ok)
+    }
+
+    /// Adds a request related to the previous request in the chain.
+    ///
+    /// Use [`FileId::FULL`] in the request, to operate on the file opened by the previous requests.
+    pub fn push_related<T>(
+        &mut self,
+        content: RequestContent,
+        parse: fn(ResponseContent) -> crate::Result<T>,
+    ) -> crate::Result<CompoundSlot<T>> {
+        if self.requests.is_empty() {
+            return Err(Error::InvalidArgument(
+                "A related request must follow another request in the chain.".to_string(),
+            ));
+        }
+
+        let mut request = PlainRequest::new(content);
+        request.header.flags.set_related_operations(true);
+        Ok(self.push_request(request, parse))
+    }
+
+    fn push_request<T>(
+        &mut self,
+        request: PlainRequest,
+        parse: fn(ResponseContent) -> crate::Result<T>,
+    ) -> CompoundSlot<T> {
+        self.requests.push(request);
+        CompoundSlot {
+            index: self.requests.len() - 1,
+            parse,
+        }
+    }
+
+    /// Returns the number of requests in the chain.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns whether the chain has no requests.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Sends the chain, and waits for the responses of all the requests.
+    ///
+    /// A failure of a single request does not fail the whole chain -
+    /// it is returned when taking the request's result from the [`CompoundResponses`].
+    /// Note that the server fails related requests following a failed request.
+    #[maybe_async]
+    pub async fn send(self) -> crate::Result<CompoundResponses> {
+        let mut requests = self.requests.into_iter();
+        let first = requests.next().ok_or_else(|| {
+            Error::InvalidArgument("Cannot send an empty compound request.".to_string())
+        })?;
+
+        let mut msg = OutgoingMessage::new(first.content).with_compound(requests.collect());
+        msg.message.header.flags = first.header.flags;
+
+        let handler = &self.tree.handler;
+        let sent = handler.sendo(msg).await?;
+
+        let mut responses = vec![];
+        for msg_id in std::iter::once(sent.msg_id).chain(sent.compound_msg_ids) {
+            let response = handler
+                .recvo(
+                    ReceiveOptions::new()
+                        .with_msg_id_filter(msg_id)
+                        .with_allow_async(true),
+                )
+                .await;
+            responses.push(Some(response));
+        }
+
+        Ok(CompoundResponses { responses })
+    }
+}
+
+/// The responses of a sent [`Compound`].
+#[derive(Debug)]
+pub struct CompoundResponses {
+    responses: Vec<Option<crate::Result<IncomingMessage>>>,
+}
+
+impl CompoundResponses {
+    /// Takes the result of a request in the chain, parsed to the type of its response.
+    ///
+    /// The result of each request may only be taken once.
+    pub fn take<T>(&mut self, slot: CompoundSlot<T>) -> crate::Result<T> {
+        let response = self
+            .responses
+            .get_mut(slot.index)
+            .and_then(Option::take)
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!(
+                    "Response #{} was already taken, or is not part of the chain.",
+                    slot.index
+                ))
+            })??;
+        (slot.parse)(response.message.content)
+    }
+
+    /// Returns the number of responses.
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Returns whether there are no responses.
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+}
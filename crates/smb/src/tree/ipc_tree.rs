@@ -1,8 +1,14 @@
+use std::time::Duration;
+
 use crate::Error;
+use crate::msg_handler::{MessageHandler, ReceiveOptions};
 
 use super::Tree;
 use maybe_async::maybe_async;
-use smb_msg::{NetworkInterfaceInfo, QueryNetworkInterfaceInfoRequest};
+use smb_msg::{
+    FileId, FsctlCodes, IoctlRequest, IoctlRequestFlags, NetworkInterfaceInfo, PipeWaitRequest,
+    QueryNetworkInterfaceInfoRequest, Status,
+};
 
 pub struct IpcTreeRef<'a> {
     tree: &'a Tree,
@@ -35,4 +41,45 @@ impl<'a> IpcTreeRef<'a> {
 
         Ok(interface_info.into())
     }
+
+    /// Waits until an instance of a named pipe is available for connection.
+    /// # Arguments
+    /// * `pipe_name` - The name of the pipe, without the `\pipe\` prefix.
+    /// * `timeout` - The maximum time to wait, or `None` to wait indefinitely.
+    /// # Returns
+    /// Whether an instance of the pipe is available. `false` is returned if the timeout elapsed first.
+    /// [`Status::ObjectNameNotFound`] is returned if no such pipe exists.
+    #[maybe_async]
+    pub async fn wait_named_pipe(
+        &self,
+        pipe_name: &str,
+        timeout: Option<Duration>,
+    ) -> crate::Result<bool> {
+        // The timeout is specified in units of 100 milliseconds.
+        let timeout_units = timeout.map(|timeout| timeout.as_millis().div_ceil(100) as u64);
+        let response = self
+            .tree
+            .handler
+            .send_recvo(
+                IoctlRequest {
+                    ctl_code: FsctlCodes::PipeWait as u32,
+                    file_id: FileId::FULL,
+                    max_input_response: 0,
+                    max_output_response: 0,
+                    flags: IoctlRequestFlags::new().with_is_fsctl(true),
+                    buffer: PipeWaitRequest {
+                        timeout: timeout_units.unwrap_or_default(),
+                        timeout_specified: timeout_units.is_some().into(),
+                        name: pipe_name.into(),
+                    }
+                    .into(),
+                }
+                .into(),
+                ReceiveOptions::new()
+                    .with_status(&[Status::Success, Status::IoTimeout])
+                    .with_allow_async(true),
+            )
+            .await?;
+        Ok(response.message.header.status == Status::Success as u32)
+    }
 }
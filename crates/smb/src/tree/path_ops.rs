@@ -0,0 +1,198 @@
+//! File system operations by path, modelled after the [`std::fs`] functions.
+//!
+//! Errors returned by the server may be classified using [`Error::kind`][crate::Error::kind],
+//! which maps them to the [`std::io::ErrorKind`]s the matching [`std::fs`] functions return.
+
+use maybe_async::*;
+use smb_fscc::{
+    DirAccessMask, FileAccessMask, FileAttributes, FileDirectoryInformation,
+    FileDispositionInformation, FileLinkInformation, FileRenameInformation,
+};
+use smb_msg::{CreateDisposition, CreateOptions};
+
+use super::Tree;
+use crate::FileCreateArgs;
+
+#[maybe_async(AFIT)]
+impl Tree {
+    /// Renames (moves) a file or directory on the share.
+    /// # Arguments
+    /// * `from` - The path of the file or directory to rename.
+    /// * `to` - The new path, relative to the root of the share.
+    /// * `replace` - Whether to replace an existing file at `to`. Directories are never replaced.
+    /// # Notes
+    /// * Symbolic links are renamed themselves, rather than their targets.
+    pub async fn rename(&self, from: &str, to: &str, replace: bool) -> crate::Result<()> {
+        let resource = self
+            .create(
+                from,
+                &FileCreateArgs {
+                    options: CreateOptions::new().with_open_reparse_point(true),
+                    ..FileCreateArgs::make_open_existing(FileAccessMask::new().with_delete(true))
+                },
+            )
+            .await?;
+        let handle = resource.handle();
+        let result = handle
+            .set_info(FileRenameInformation {
+                replace_if_exists: replace.into(),
+                root_directory: 0,
+                file_name: to.into(),
+            })
+            .await;
+        handle.close().await?;
+        result
+    }
+
+    /// Creates a new hard link to an existing file on the share.
+    /// # Arguments
+    /// * `original` - The path of the existing file.
+    /// * `link` - The path of the new link, relative to the root of the share.
+    /// # Notes
+    /// * Fails if `link` already exists, like [`std::fs::hard_link`].
+    pub async fn hard_link(&self, original: &str, link: &str) -> crate::Result<()> {
+        let resource = self
+            .create(
+                original,
+                &FileCreateArgs {
+                    options: CreateOptions::new().with_non_directory_file(true),
+                    ..FileCreateArgs::make_open_existing(
+                        FileAccessMask::new().with_file_write_attributes(true),
+                    )
+                },
+            )
+            .await?;
+        let handle = resource.handle();
+        let result = handle
+            .set_info(FileLinkInformation {
+                replace_if_exists: false.into(),
+                file_name: link.into(),
+            })
+            .await;
+        handle.close().await?;
+        result
+    }
+
+    /// Removes a file from the share.
+    ///
+    /// Symbolic links are removed themselves, rather than their targets.
+    /// Fails with [`Status::FileIsADirectory`][smb_msg::Status::FileIsADirectory] if the path is a directory; see [`Tree::remove_dir`].
+    pub async fn remove_file(&self, path: &str) -> crate::Result<()> {
+        self.remove(path, false).await
+    }
+
+    /// Removes an empty directory from the share.
+    ///
+    /// Fails with [`Status::DirectoryNotEmpty`][smb_msg::Status::DirectoryNotEmpty] if the directory is not empty; see [`Tree::remove_dir_all`].
+    pub async fn remove_dir(&self, path: &str) -> crate::Result<()> {
+        self.remove(path, true).await
+    }
+
+    /// Creates a directory and all of its missing parents on the share.
+    ///
+    /// Succeeds if the directory already exists, like [`std::fs::create_dir_all`].
+    pub async fn create_dir_all(&self, path: &str) -> crate::Result<()> {
+        let mut current = String::with_capacity(path.len());
+        for component in path.split('\\').filter(|c| !c.is_empty()) {
+            if !current.is_empty() {
+                current.push('\\');
+            }
+            current.push_str(component);
+
+            let dir = self
+                .create(
+                    &current,
+                    &FileCreateArgs {
+                        disposition: CreateDisposition::OpenIf,
+                        options: CreateOptions::new().with_directory_file(true),
+                        attributes: FileAttributes::new().with_directory(true),
+                        ..FileCreateArgs::make_open_existing(
+                            FileAccessMask::new().with_file_read_attributes(true),
+                        )
+                    },
+                )
+                .await?;
+            dir.handle().close().await?;
+        }
+        Ok(())
+    }
+
+    /// Removes a directory on the share, after removing all of its contents.
+    ///
+    /// Symbolic links and other reparse points in the directory are removed, rather than followed.
+    /// # Notes
+    /// * The contents are removed one by one, so the operation is not atomic:
+    ///   on failure, some of the contents may have already been removed.
+    pub async fn remove_dir_all(&self, path: &str) -> crate::Result<()> {
+        // Directories are removed after their contents, so each is visited twice:
+        // once to remove its files and queue its subdirectories, and once to remove it.
+        let mut pending = vec![(path.to_string(), false)];
+        while let Some((dir_path, emptied)) = pending.pop() {
+            if emptied {
+                self.remove_dir(&dir_path).await?;
+                continue;
+            }
+
+            pending.push((dir_path.clone(), true));
+            let dir = self
+                .create(
+                    &dir_path,
+                    &FileCreateArgs {
+                        options: CreateOptions::new()
+                            .with_directory_file(true)
+                            .with_open_reparse_point(true),
+                        ..FileCreateArgs::make_open_existing(
+                            DirAccessMask::new().with_list_directory(true).into(),
+                        )
+                    },
+                )
+                .await?
+                .unwrap_dir();
+            let entries = dir.query_all::<FileDirectoryInformation>("*").await;
+            dir.close().await?;
+
+            for entry in entries? {
+                let name = entry.file_name.to_string();
+                if name == "." || name == ".." {
+                    continue;
+                }
+                let entry_path = if dir_path.is_empty() {
+                    name
+                } else {
+                    format!("{dir_path}\\{name}")
+                };
+                let attributes = entry.file_attributes;
+                if attributes.directory() && !attributes.reparse_point() {
+                    pending.push((entry_path, false));
+                } else {
+                    // Links to directories are directories themselves, and are removed as such.
+                    self.remove(&entry_path, attributes.directory()).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// (Internal)
+    ///
+    /// Removes a file or a directory, by marking it for deletion and closing it.
+    /// Marking a directory that is not empty fails, so the error is reported before it is closed.
+    async fn remove(&self, path: &str, directory: bool) -> crate::Result<()> {
+        let resource = self
+            .create(
+                path,
+                &FileCreateArgs {
+                    options: CreateOptions::new()
+                        .with_open_reparse_point(true)
+                        .with_directory_file(directory)
+                        .with_non_directory_file(!directory),
+                    ..FileCreateArgs::make_open_existing(FileAccessMask::new().with_delete(true))
+                },
+            )
+            .await?;
+        let handle = resource.handle();
+        let result = handle.set_info(FileDispositionInformation::default()).await;
+        handle.close().await?;
+        result
+    }
+}
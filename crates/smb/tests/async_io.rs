@@ -0,0 +1,108 @@
+#![cfg(feature = "async")]
+use serial_test::serial;
+use smb::*;
+use smb_rpc::{
+    interface::{RpcInterface, SrvSvc},
+    pdu::{DcRpcCoPktBind, DcRpcCoPktBindContextElement, DceRpcCoPktFlags, DceRpcCoRequestPkt},
+    syntax::TransferSyntax,
+};
+use std::{io::SeekFrom, result::Result};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const FILE_NAME: &str = "async_io_test.txt";
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+#[serial]
+async fn test_async_file() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let file = tree
+        .create_file(
+            FILE_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    let mut file = AsyncFile::new(file);
+
+    file.write_all(b"Hello, world!").await?;
+    file.flush().await?;
+    assert_eq!(file.position(), 13);
+
+    let pos = file.seek(SeekFrom::Start(7)).await?;
+    assert_eq!(pos, 7);
+    let mut world = [0u8; 5];
+    file.read_exact(&mut world).await?;
+    assert_eq!(&world, b"world");
+
+    let pos = file.seek(SeekFrom::End(-13)).await?;
+    assert_eq!(pos, 0);
+    let mut content = String::new();
+    file.read_to_string(&mut content).await?;
+    assert_eq!(content, "Hello, world!");
+
+    file.get_ref().close().await?;
+    tree.remove_file(FILE_NAME).await?;
+    client.close().await?;
+    Ok(())
+}
+
+/// Returns a DCE/RPC bind request for the srvsvc interface, over the NDR transfer syntax.
+fn srvsvc_bind() -> Vec<u8> {
+    const PACKED_DREP: u32 = 0x10;
+    DceRpcCoRequestPkt::new(
+        DcRpcCoPktBind {
+            max_xmit_frag: 4280,
+            max_recv_frag: 4280,
+            assoc_group_id: 0,
+            context_elements: vec![DcRpcCoPktBindContextElement {
+                context_id: 0,
+                abstract_syntax: <SrvSvc<PipeRpcConnection> as RpcInterface<_>>::SYNTAX_ID,
+                transfer_syntaxes: vec![TransferSyntax::Ndr20.syntax_id()],
+            }],
+        }
+        .into(),
+        1,
+        DceRpcCoPktFlags::new()
+            .with_first_frag(true)
+            .with_last_frag(true),
+        PACKED_DREP,
+    )
+    .try_into()
+    .unwrap()
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+#[serial]
+async fn test_async_pipe() -> Result<(), Box<dyn std::error::Error>> {
+    const BIND_ACK: u8 = 12;
+    let (client, path) = make_server_connection("IPC$", None).await?;
+    let pipe = client.open_pipe(path.server(), "srvsvc").await?;
+    let mut pipe = AsyncPipe::new(pipe);
+
+    let bind = srvsvc_bind();
+    pipe.write_all(&bind).await?;
+    pipe.flush().await?;
+
+    let mut response = vec![0u8; 1024];
+    let read = pipe.read(&mut response).await?;
+    assert!(read >= 16);
+    assert_eq!(response[2], BIND_ACK);
+    let frag_length = u16::from_le_bytes([response[8], response[9]]) as usize;
+    assert_eq!(frag_length, read);
+
+    // Writes complete in the background, so their errors are reported by the next operation.
+    pipe.get_ref().close().await?;
+    let written = pipe.write(&bind).await?;
+    assert_eq!(written, bind.len());
+    assert!(pipe.flush().await.is_err());
+
+    client.close().await?;
+    Ok(())
+}
@@ -11,6 +11,9 @@ impl TestEnv {
     pub const PASSWORD: &'static str = "SMB_RUST_TESTS_PASSWORD";
     pub const DEFAULT_PASSWORD: &'static str = "123456";
 
+    /// When set, tests that add and delete shares are skipped, for servers that do not allow it.
+    pub const SKIP_SHARE_ADD: &'static str = "SMB_RUST_TESTS_SKIP_SHARE_ADD";
+
     pub const GUEST_USER: &'static str = "/GUEST";
     pub const GUEST_PASSWORD: &'static str = "";
 }
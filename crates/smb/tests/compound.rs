@@ -0,0 +1,62 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const COMPOUND_FILE_NAME: &str = "compound.txt";
+const COMPOUND_FILE_DATA: &[u8] = b"Hello from a compounded read!";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_compound_create_read_close() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+
+    let file = client
+        .create_file(
+            &share_path.clone().with_path(COMPOUND_FILE_NAME),
+            &FileCreateArgs::make_overwrite(Default::default(), Default::default()),
+        )
+        .await?
+        .unwrap_file();
+    file.write_at(COMPOUND_FILE_DATA, 0).await?;
+    file.close().await?;
+
+    let tree = client.get_tree(&share_path).await?;
+    let mut compound = tree.compound();
+    let create = compound.create(
+        COMPOUND_FILE_NAME,
+        &FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true)),
+    )?;
+    let std_info = compound.query_info::<FileStandardInformation>()?;
+    let read = compound.read(0, 1024)?;
+    let close = compound.close()?;
+    let mut responses = compound.send().await?;
+    assert_eq!(responses.len(), 4);
+
+    responses.take(create)?;
+    assert_eq!(
+        responses.take(std_info)?.end_of_file,
+        COMPOUND_FILE_DATA.len() as u64
+    );
+    assert_eq!(responses.take(read)?, COMPOUND_FILE_DATA);
+    responses.take(close)?;
+
+    // A failure of the first request fails the related requests that follow it.
+    let mut compound = tree.compound();
+    compound.create(
+        "compound_missing.txt",
+        &FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true)),
+    )?;
+    let close = compound.close()?;
+    let mut responses = compound.send().await?;
+    assert!(responses.take(close).is_err());
+
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const EXCLUSIVE_FILE_NAME: &str = "exclusive_open.txt";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_exclusive_open_with_contexts() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let file_path = share_path.with_path(EXCLUSIVE_FILE_NAME);
+
+    let file = client
+        .create_file(
+            &file_path,
+            &FileCreateArgs {
+                share_access: Some(ShareAccessFlags::new()),
+                contexts: vec![
+                    AllocationSize {
+                        allocation_size: 0x10000,
+                    }
+                    .into(),
+                ],
+                ..FileCreateArgs::make_overwrite(Default::default(), Default::default())
+            },
+        )
+        .await?
+        .unwrap_file();
+    assert!(CreateContextResponseData::first_mxac(file.create_contexts()).is_some());
+
+    // The file is not shared, so any other open fails.
+    let other_open = client
+        .create_file(
+            &file_path,
+            &FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true)),
+        )
+        .await;
+    match other_open {
+        Err(Error::ReceivedErrorMessage(status, _)) => {
+            assert_eq!(status, Status::U32_SHARING_VIOLATION)
+        }
+        _ => panic!("Expected a sharing violation"),
+    }
+
+    file.set_info(FileDispositionInformation {
+        delete_pending: true.into(),
+    })
+    .await?;
+    file.close().await?;
+    client.close().await?;
+    Ok(())
+}
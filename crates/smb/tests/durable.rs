@@ -0,0 +1,104 @@
+#![cfg(not(feature = "single_threaded"))]
+use std::time::Duration;
+
+use serial_test::serial;
+use smb::{Durability, Error, FileBasicInformation, FileCreateArgs, LeaseRequest, OplockRequest};
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const DURABLE_FILE_NAME: &str = "durable_v2.txt";
+const RECONNECT_FILE_NAME: &str = "durable_reconnect.txt";
+const LOST_FILE_NAME: &str = "durable_reconnect_lost.txt";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_durable_v2_granted() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let file = client
+        .create_file(
+            &share_path.with_path(DURABLE_FILE_NAME),
+            &FileCreateArgs {
+                oplock: OplockRequest::Lease(LeaseRequest::rwh()),
+                durability: Durability::DurableV2 {
+                    timeout: Duration::from_secs(60),
+                },
+                ..FileCreateArgs::make_overwrite(Default::default(), Default::default())
+            },
+        )
+        .await?
+        .unwrap_file();
+
+    let durable = file
+        .durable_handle()?
+        .expect("Durability should be granted for a handle-caching lease");
+    assert!(durable.create_guid().is_some());
+    assert!(durable.timeout().is_some());
+    assert!(!durable.is_persistent());
+    assert_eq!(durable.name(), DURABLE_FILE_NAME);
+
+    file.close().await?;
+    assert!(file.durable_handle().is_err());
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_reconnect_reclaims_durable() -> Result<(), Box<dyn std::error::Error>> {
+    const CONTENT: &[u8] = b"survives reconnection";
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let durable = client
+        .create_file(
+            &share_path.clone().with_path(RECONNECT_FILE_NAME),
+            &FileCreateArgs {
+                oplock: OplockRequest::Lease(LeaseRequest::rwh()),
+                durability: Durability::DurableV2 {
+                    timeout: Duration::from_secs(60),
+                },
+                ..FileCreateArgs::make_overwrite(Default::default(), Default::default())
+            },
+        )
+        .await?
+        .unwrap_file();
+    durable.write_block(CONTENT, 0, None).await?;
+    let not_durable = client
+        .create_file(
+            &share_path.clone().with_path(LOST_FILE_NAME),
+            &FileCreateArgs::make_overwrite(Default::default(), Default::default()),
+        )
+        .await?
+        .unwrap_file();
+
+    // Drop the transport, without closing the session or the opens.
+    client
+        .get_connection(share_path.server())
+        .await?
+        .close()
+        .await?;
+    let mut data = vec![0u8; CONTENT.len()];
+    let stopped = durable.read_block(&mut data, 0, None, false).await;
+    assert_eq!(
+        stopped.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::NotConnected)
+    );
+    let stopped = durable.query_info::<FileBasicInformation>().await;
+    assert!(matches!(stopped, Err(Error::ReconnectRequired(_))));
+
+    // Accessing the share through the client reconnects, and reclaims the durable open.
+    client.get_tree(&share_path).await?;
+    let read = durable.read_block(&mut data, 0, None, false).await?;
+    assert_eq!(&data[..read], CONTENT);
+    let lost = not_durable.query_info::<FileBasicInformation>().await;
+    assert!(matches!(lost, Err(Error::HandleLost(_))));
+
+    durable.close().await?;
+    client.close().await?;
+    Ok(())
+}
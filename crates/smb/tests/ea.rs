@@ -0,0 +1,51 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const FILE_NAME: &str = "ea_test.txt";
+const EA_NAME: &str = "user.smb_rs_test";
+const EA_VALUE: &[u8] = b"extended attribute value";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_set_get_remove_ea() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let file = tree
+        .create_file(
+            FILE_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+
+    file.set_ea(EA_NAME, EA_VALUE, EaFlags::new()).await?;
+    let ea = file.get_ea(EA_NAME).await?.ok_or("EA not found")?;
+    assert_eq!(ea.ea_value, EA_VALUE);
+    let eas = file.eas().await?.collect::<Vec<_>>();
+    assert!(
+        eas.iter()
+            .any(|ea| ea.ea_name.to_string().eq_ignore_ascii_case(EA_NAME))
+    );
+
+    file.remove_ea(EA_NAME).await?;
+    let ea = file.get_ea(EA_NAME).await?;
+    assert!(ea.is_none());
+
+    file.set_info(FileDispositionInformation {
+        delete_pending: true.into(),
+    })
+    .await?;
+    file.close().await?;
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const TEST_DIR: &str = "fs_test";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_fs_helpers() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let dir = share_path.clone().with_path(TEST_DIR);
+    client.create_dir_all(&dir).await?;
+
+    let file = dir.clone().with_add_path("file.txt");
+    let exists = fs::exists(&client, &file).await?;
+    assert!(!exists);
+    fs::write(&client, &file, "Hello, ").await?;
+    fs::append(&client, &file, "world!").await?;
+    let content = fs::read_to_string(&client, &file).await?;
+    assert_eq!(content, "Hello, world!");
+
+    let metadata = fs::metadata(&client, &file).await?;
+    assert!(metadata.is_file());
+    assert_eq!(metadata.len, "Hello, world!".len() as u64);
+
+    let copy = dir.clone().with_add_path("copy.txt");
+    let copied = fs::copy(&client, &file, &copy).await?;
+    assert_eq!(copied, metadata.len);
+    let copied_data = fs::read(&client, &copy).await?;
+    assert_eq!(copied_data, b"Hello, world!");
+
+    let mut names = fs::read_dir(&client, &dir)
+        .await?
+        .into_iter()
+        .map(|entry| entry.name)
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["copy.txt", "file.txt"]);
+
+    client.remove_dir_all(&dir).await?;
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const FILE_NAME: &str = "large_io_test.bin";
+/// Larger than the maximum read & write sizes of common servers (8 MiB).
+const DATA_SIZE: usize = 20 * 1024 * 1024 + 123;
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_large_read_write() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let file = tree
+        .create_file(
+            FILE_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+
+    let data = (0..DATA_SIZE).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let written = file.write_block(&data, 0, None).await?;
+    assert_eq!(written, DATA_SIZE);
+
+    // Reading past the end of the file returns only the available data.
+    let mut read_data = vec![0u8; DATA_SIZE + 1024];
+    let read = file.read_block(&mut read_data, 0, None, false).await?;
+    assert_eq!(read, DATA_SIZE);
+    assert!(read_data[..read] == data[..]);
+
+    file.close().await?;
+    tree.remove_file(FILE_NAME).await?;
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use serial_test::serial;
+use smb::{FileCreateArgs, LockMode};
+use smb_fscc::*;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const LOCKED_FILE_NAME: &str = "byte_range_lock.txt";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_lock_conflict() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let file_path = share_path.with_path(LOCKED_FILE_NAME);
+
+    let file = client
+        .create_file(
+            &file_path,
+            &FileCreateArgs::make_overwrite(Default::default(), Default::default()),
+        )
+        .await?
+        .unwrap_file();
+    let other_file = client
+        .create_file(
+            &file_path,
+            &FileCreateArgs::make_open_existing(
+                FileAccessMask::new()
+                    .with_generic_read(true)
+                    .with_generic_write(true),
+            ),
+        )
+        .await?
+        .unwrap_file();
+
+    let lock = file.lock(0..100, LockMode::Exclusive, true).await?;
+    let conflicting_lock = other_file.try_lock(50..150, LockMode::Shared).await?;
+    assert!(conflicting_lock.is_none());
+    // Ranges that do not overlap may be locked by other opens.
+    let other_lock = other_file
+        .try_lock(100..200, LockMode::Exclusive)
+        .await?
+        .expect("Non-overlapping range should be lockable");
+
+    lock.unlock().await?;
+    let shared_lock = other_file
+        .try_lock(50..100, LockMode::Shared)
+        .await?
+        .expect("Range should be lockable after unlocking");
+    assert_eq!(shared_lock.mode(), LockMode::Shared);
+
+    shared_lock.unlock().await?;
+    other_lock.unlock().await?;
+    other_file.close().await?;
+    file.close().await?;
+    Ok(())
+}
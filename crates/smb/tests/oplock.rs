@@ -0,0 +1,114 @@
+#![cfg(not(feature = "single_threaded"))]
+use serial_test::serial;
+use smb::{BreakAction, FileCreateArgs, LeaseRequest, OplockRequest, sync_helpers::*};
+use smb_fscc::*;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const LEASED_FILE_NAME: &str = "lease_break.txt";
+const SHARED_LEASE_FILE_NAME: &str = "shared_lease_break.txt";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_lease_break_acknowledged() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let file_path = share_path.with_path(LEASED_FILE_NAME);
+
+    let breaks = Arc::new(Semaphore::new(0));
+    let breaks_clone = breaks.clone();
+    let file = client
+        .create_file(
+            &file_path,
+            &FileCreateArgs {
+                oplock: OplockRequest::Lease(LeaseRequest::rwh()),
+                on_oplock_break: Some(Arc::new(move |brk| {
+                    log::info!("Lease break received: {brk:?}");
+                    breaks_clone.add_permits(1);
+                    BreakAction::Acknowledge
+                })),
+                ..FileCreateArgs::make_overwrite(Default::default(), Default::default())
+            },
+        )
+        .await?
+        .unwrap_file();
+    assert!(file.oplock()?.can_cache_writes());
+
+    // Opening the file from another client must break the write caching of the lease.
+    let (other_client, _) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let other_file = other_client
+        .create_file(
+            &file_path,
+            &FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true)),
+        )
+        .await?
+        .unwrap_file();
+
+    breaks.acquire_many(1).await?.forget();
+    assert!(!file.oplock()?.can_cache_writes());
+
+    other_file.close().await?;
+    file.close().await?;
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_shared_lease_break() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let file_path = share_path.with_path(SHARED_LEASE_FILE_NAME);
+
+    // Both opens of the file share the lease, and both must receive its break.
+    let lease = LeaseRequest::rwh();
+    let breaks = Arc::new(Semaphore::new(0));
+    let open_args = |create: bool| {
+        let breaks = breaks.clone();
+        let args = if create {
+            FileCreateArgs::make_overwrite(Default::default(), Default::default())
+        } else {
+            FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true))
+        };
+        FileCreateArgs {
+            oplock: OplockRequest::Lease(lease),
+            on_oplock_break: Some(Arc::new(move |brk| {
+                log::info!("Lease break received: {brk:?}");
+                breaks.add_permits(1);
+                BreakAction::Acknowledge
+            })),
+            ..args
+        }
+    };
+    let file = client
+        .create_file(&file_path, &open_args(true))
+        .await?
+        .unwrap_file();
+    let same_lease_file = client
+        .create_file(&file_path, &open_args(false))
+        .await?
+        .unwrap_file();
+
+    let (other_client, _) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let other_file = other_client
+        .create_file(
+            &file_path,
+            &FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true)),
+        )
+        .await?
+        .unwrap_file();
+
+    breaks.acquire_many(2).await?.forget();
+    assert!(!file.oplock()?.can_cache_writes());
+    assert!(!same_lease_file.oplock()?.can_cache_writes());
+
+    other_file.close().await?;
+    same_lease_file.close().await?;
+    file.close().await?;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const TEST_DIR: &str = "path_ops_test";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_path_ops() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let nested = format!("{TEST_DIR}\\a\\b");
+    tree.create_dir_all(&nested).await?;
+    // Creating existing directories succeeds.
+    tree.create_dir_all(&nested).await?;
+
+    let file_name = format!("{nested}\\file.txt");
+    let file = tree
+        .create_file(
+            &file_name,
+            CreateDisposition::Create,
+            FileAccessMask::new().with_generic_write(true),
+        )
+        .await?;
+    file.handle().close().await?;
+
+    let renamed = format!("{TEST_DIR}\\a\\renamed.txt");
+    tree.rename(&file_name, &renamed, false).await?;
+    let missing = tree.remove_file(&file_name).await;
+    assert_eq!(
+        missing.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::NotFound)
+    );
+
+    let not_empty = tree.remove_dir(&format!("{TEST_DIR}\\a")).await;
+    assert_eq!(
+        not_empty.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::DirectoryNotEmpty)
+    );
+
+    tree.remove_dir(&nested).await?;
+    tree.remove_file(&renamed).await?;
+
+    tree.create_dir_all(&nested).await?;
+    tree.remove_dir_all(TEST_DIR).await?;
+    let removed = tree
+        .open_existing(
+            TEST_DIR,
+            FileAccessMask::new().with_file_read_attributes(true),
+        )
+        .await;
+    assert!(matches!(removed, Err(e) if e.kind() == std::io::ErrorKind::NotFound));
+
+    client.close().await?;
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_hard_link() -> Result<(), Box<dyn std::error::Error>> {
+    const CONTENT: &[u8] = b"hard link content";
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let original = "hard_link_original.txt";
+    let link = "hard_link_link.txt";
+    // Clean up leftovers of previous runs.
+    tree.remove_file(link).await.ok();
+
+    let file = tree
+        .create_file(
+            original,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    file.write_block(CONTENT, 0, None).await?;
+    file.close().await?;
+
+    tree.hard_link(original, link).await?;
+    // Linking over an existing file fails.
+    let exists = tree.hard_link(original, link).await;
+    assert_eq!(
+        exists.map_err(|e| e.kind()),
+        Err(std::io::ErrorKind::AlreadyExists)
+    );
+
+    let linked = tree
+        .open_existing(link, FileAccessMask::new().with_generic_read(true))
+        .await?
+        .unwrap_file();
+    let info = linked.query_info::<FileStandardInformation>().await?;
+    assert_eq!(info.number_of_links, 2);
+    let mut data = vec![0; CONTENT.len()];
+    let read = linked.read_block(&mut data, 0, None, false).await?;
+    assert_eq!(&data[..read], CONTENT);
+    linked.close().await?;
+
+    // The link outlives the original name.
+    tree.remove_file(original).await?;
+    let linked = tree
+        .open_existing(link, FileAccessMask::new().with_generic_read(true))
+        .await?
+        .unwrap_file();
+    let info = linked.query_info::<FileStandardInformation>().await?;
+    assert_eq!(info.number_of_links, 1);
+    assert_eq!(info.end_of_file, CONTENT.len() as u64);
+    linked.close().await?;
+    tree.remove_file(link).await?;
+
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::make_server_connection;
+
+const PIPE_NAME: &str = "srvsvc";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_pipe_wait_peek_mode() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, path) = make_server_connection("IPC$", None).await?;
+
+    let available = client
+        .wait_named_pipe(path.server(), PIPE_NAME, Some(Duration::from_secs(1)))
+        .await?;
+    assert!(available);
+
+    let pipe = client.open_pipe(path.server(), PIPE_NAME).await?;
+    let mode = pipe.pipe_mode().await?;
+    assert_eq!(mode.read_mode, PipeReadMode::Message);
+
+    pipe.set_pipe_mode(PipeReadMode::Message, PipeCompletionMode::Queue)
+        .await?;
+
+    let peeked = pipe.peek().await?;
+    assert_eq!(peeked.read_data_available, 0);
+    assert!(peeked.data.is_empty());
+
+    pipe.close().await?;
+    client.close().await?;
+    Ok(())
+}
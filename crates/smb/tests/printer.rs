@@ -0,0 +1,25 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_print_requires_printer_share() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+
+    // The test server has no printer shares; make sure disk shares are not spooled to.
+    let print_result = client
+        .print(&share_path.with_path("print_job.txt"), b"Hello, printer!")
+        .await;
+    assert!(matches!(print_result, Err(Error::InvalidArgument(_))));
+
+    client.close().await?;
+    Ok(())
+}
@@ -1,7 +1,20 @@
-#![cfg(feature = "test-ndr64")]
+//! RPC tests, over the IPC$ share.
+//!
+//! Samba does not support NDR64, so running these against it exercises the NDR 2.0 transfer syntax.
 
 mod common;
+use common::{TestConstants, TestEnv, make_server_connection};
 use serial_test::serial;
+use smb::{FileBasicInformation, FileCreateArgs, PipeRpcConnection};
+use smb_rpc::{
+    interface::{
+        FileInfo3, LsaRpc, PolicyInformationClass, ShareInfo, ShareInfo2, ShareInfoLevel,
+        ShareKind, ShareType, SidNameUse,
+    },
+    ndr64::{NdrPtr, NdrString},
+    pdu::DceRpcAuthLevel,
+};
+use sspi::{AuthIdentity, Secret, Username};
 
 #[test_log::test(maybe_async::test(
     not(feature = "async"),
@@ -10,7 +23,7 @@ use serial_test::serial;
 #[serial]
 async fn test_shares_enum() -> smb::Result<()> {
     let (client, path) = make_server_connection("IPC$", None).await?;
-    let shares = client.list_shares(&path.server).await?;
+    let shares = client.list_shares(path.server()).await?;
     assert!(
         shares
             .iter()
@@ -21,3 +34,261 @@ async fn test_shares_enum() -> smb::Result<()> {
     );
     Ok(())
 }
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_share_get_info() -> smb::Result<()> {
+    let (client, path) = make_server_connection("IPC$", None).await?;
+
+    let info = client
+        .get_share_info(
+            path.server(),
+            TestConstants::DEFAULT_SHARE,
+            ShareInfoLevel::Info1,
+        )
+        .await?;
+    let ShareInfo::Info1(info) = info else {
+        panic!("Expected level 1 share info, got: {info:?}");
+    };
+    assert_eq!(
+        info.netname.as_ref().unwrap().to_string(),
+        TestConstants::DEFAULT_SHARE
+    );
+    assert_eq!(info.share_type.kind(), ShareKind::Disk);
+
+    let info = client
+        .get_share_info(
+            path.server(),
+            TestConstants::DEFAULT_SHARE,
+            ShareInfoLevel::Info2,
+        )
+        .await?;
+    let ShareInfo::Info2(info) = info else {
+        panic!("Expected level 2 share info, got: {info:?}");
+    };
+    assert_eq!(
+        info.netname.as_ref().unwrap().to_string(),
+        TestConstants::DEFAULT_SHARE
+    );
+    assert_eq!(info.share_type.kind(), ShareKind::Disk);
+    assert!(
+        info.path
+            .as_ref()
+            .unwrap()
+            .to_string()
+            .ends_with(TestConstants::DEFAULT_SHARE)
+    );
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_share_add_delete() -> smb::Result<()> {
+    const SHARE_NAME: &str = "RpcTestShare";
+    if std::env::var_os(TestEnv::SKIP_SHARE_ADD).is_some() {
+        log::info!("Skipping, since {} is set", TestEnv::SKIP_SHARE_ADD);
+        return Ok(());
+    }
+    let (client, path) = make_server_connection("IPC$", None).await?;
+
+    // Clean up leftovers of previous runs.
+    client.delete_share(path.server(), SHARE_NAME).await.ok();
+
+    let ndr_string =
+        |value: &str| -> NdrPtr<NdrString<u16>> { value.parse::<NdrString<u16>>().unwrap().into() };
+    client
+        .add_share(
+            path.server(),
+            ShareInfo::Info2(ShareInfo2 {
+                netname: ndr_string(SHARE_NAME),
+                share_type: ShareType::new().with_kind(ShareKind::Disk).into(),
+                remark: ndr_string("Added by smb-rs tests"),
+                permissions: 0.into(),
+                max_uses: ShareInfo2::<smb_rpc::ndr64::Ndr64>::UNLIMITED_USES.into(),
+                current_uses: 0.into(),
+                path: ndr_string("/shares/RpcTestShare"),
+                passwd: None.into(),
+            }),
+        )
+        .await?;
+
+    let shares = client.list_shares(path.server()).await?;
+    assert!(
+        shares
+            .iter()
+            .any(|s| s.netname.as_ref().unwrap().to_string() == SHARE_NAME)
+    );
+
+    client.delete_share(path.server(), SHARE_NAME).await?;
+
+    let shares = client.list_shares(path.server()).await?;
+    assert!(
+        !shares
+            .iter()
+            .any(|s| s.netname.as_ref().unwrap().to_string() == SHARE_NAME)
+    );
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_close_open_file() -> smb::Result<()> {
+    const FILE_NAME: &str = "rpc_open_file.txt";
+    let (client, path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let file = client
+        .create_file(
+            &path.clone().with_path(FILE_NAME),
+            &FileCreateArgs::make_overwrite(Default::default(), Default::default()),
+        )
+        .await?
+        .unwrap_file();
+
+    let sessions = client.list_sessions(path.server()).await?;
+    assert!(!sessions.is_empty());
+
+    let is_test_file = |f: &FileInfo3| {
+        f.pathname
+            .as_ref()
+            .is_some_and(|p| p.to_string().ends_with(FILE_NAME))
+    };
+    let files = client.list_open_files(path.server(), None).await?;
+    let open_file = files
+        .iter()
+        .find(|f| is_test_file(f))
+        .expect("The test file should be listed as open");
+    client
+        .close_open_file(path.server(), **open_file.id)
+        .await?;
+
+    let files = client.list_open_files(path.server(), None).await?;
+    assert!(!files.iter().any(is_test_file));
+    let closed = file.query_info::<FileBasicInformation>().await;
+    assert!(closed.is_err());
+
+    client.close().await?;
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_server_info() -> smb::Result<()> {
+    let (client, path) = make_server_connection("IPC$", None).await?;
+    let info = client.server_info(path.server()).await?;
+
+    const PLATFORM_ID_NT: u32 = 500;
+    assert_eq!(**info.platform_id, PLATFORM_ID_NT);
+    assert!(!info.name.as_ref().unwrap().to_string().is_empty());
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_remote_time() -> smb::Result<()> {
+    let (client, path) = make_server_connection("IPC$", None).await?;
+    let time_of_day = client.remote_time(path.server()).await?;
+
+    // The test server runs on the local clock.
+    let diff = time::OffsetDateTime::now_utc() - time_of_day.date_time();
+    assert!(
+        diff.abs() < time::Duration::minutes(5),
+        "Unexpected server time: {time_of_day:?}"
+    );
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_lookup_sids_names() -> smb::Result<()> {
+    let administrators: smb::SID = "S-1-5-32-544".parse().unwrap();
+
+    let (client, path) = make_server_connection("IPC$", None).await?;
+    let accounts = client
+        .lookup_sids(path.server(), std::slice::from_ref(&administrators))
+        .await?;
+    let account = accounts[0]
+        .as_ref()
+        .expect("Administrators should be translated");
+    assert_eq!(account.sid, administrators);
+    assert_eq!(account.kind, SidNameUse::Alias);
+    assert!(
+        account
+            .to_string()
+            .eq_ignore_ascii_case(r"BUILTIN\Administrators")
+    );
+
+    // Translate back using a new client, so the name is not cached.
+    let (client, path) = make_server_connection("IPC$", None).await?;
+    let accounts = client
+        .lookup_names(
+            path.server(),
+            &[r"BUILTIN\Administrators", "smb-rs-no-such-account"],
+        )
+        .await?;
+    assert_eq!(
+        accounts[0].as_ref().map(|account| &account.sid),
+        Some(&administrators)
+    );
+    assert!(accounts[1].is_none());
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_bind_authenticated() -> smb::Result<()> {
+    let user = std::env::var(TestEnv::USER).unwrap_or(TestEnv::DEFAULT_USER.to_string());
+    let password =
+        std::env::var(TestEnv::PASSWORD).unwrap_or(TestEnv::DEFAULT_PASSWORD.to_string());
+    let administrators: smb::SID = "S-1-5-32-544".parse().unwrap();
+
+    let (client, path) = make_server_connection("IPC$", None).await?;
+    for auth_level in [
+        DceRpcAuthLevel::Connect,
+        DceRpcAuthLevel::PacketIntegrity,
+        DceRpcAuthLevel::PacketPrivacy,
+    ] {
+        log::info!("Binding to lsarpc with auth level {auth_level:?}");
+        let identity = AuthIdentity {
+            username: Username::parse(&user).unwrap(),
+            password: Secret::from(password.clone()),
+        };
+        let mut lsarpc: LsaRpc<PipeRpcConnection> = client
+            .open_pipe(path.server(), "lsarpc")
+            .await?
+            .bind_authenticated(identity, auth_level)
+            .await?;
+
+        // Multiple calls make sure the sequence numbers of protected PDUs are kept in sync.
+        let policy_handle = lsarpc.lsar_open_policy2(path.server()).await?;
+        let domain = lsarpc
+            .lsar_query_information_policy(&policy_handle, PolicyInformationClass::AccountDomain)
+            .await?;
+        assert!(domain.sid.is_some());
+        let accounts = lsarpc
+            .lsar_lookup_sids2(&policy_handle, std::slice::from_ref(&administrators))
+            .await?;
+        assert!(accounts[0].is_some());
+        lsarpc.lsar_close(policy_handle).await?;
+    }
+    Ok(())
+}
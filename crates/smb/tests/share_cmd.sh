@@ -0,0 +1,26 @@
+#!/bin/sh
+# Adds or deletes a share of the test server, as the "add share command" and "delete share command"
+# that samba runs for the srvsvc NetrShareAdd and NetrShareDel calls:
+#   share_cmd.sh add <smb.conf> <share> <path> <comment> <max connections>
+#   share_cmd.sh delete <smb.conf> <share>
+set -e
+
+mode=$1
+conf=$2
+share=$3
+
+case "$mode" in
+add)
+    mkdir -p "$4"
+    chmod 777 "$4"
+    printf '\n[%s]\n   path = %s\n   comment = %s\n   read only = no\n' "$share" "$4" "$5" >>"$conf"
+    ;;
+delete)
+    # Drop the section of the share, up to the next section.
+    awk -v section="[$share]" '/^\[/ { skip = (tolower($0) == tolower(section)) } !skip' "$conf" >"$conf.tmp"
+    mv "$conf.tmp" "$conf"
+    ;;
+*)
+    exit 1
+    ;;
+esac
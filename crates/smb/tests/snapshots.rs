@@ -0,0 +1,35 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_enumerate_snapshots() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+
+    let dir = client
+        .create_file(
+            &share_path,
+            &FileCreateArgs::make_open_existing(
+                DirAccessMask::new().with_list_directory(true).into(),
+            ),
+        )
+        .await?
+        .unwrap_dir();
+    // The test server may have no snapshots; listing them must succeed regardless.
+    let snapshots = dir.enumerate_snapshots().await?;
+    for snapshot in snapshots {
+        assert_eq!(snapshot.to_string().parse::<Snapshot>()?, snapshot);
+    }
+
+    dir.close().await?;
+    client.close().await?;
+    Ok(())
+}
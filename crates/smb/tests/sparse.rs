@@ -0,0 +1,55 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const FILE_NAME: &str = "sparse_test.bin";
+const FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_sparse_file() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let file = tree
+        .create_file(
+            FILE_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    file.set_sparse(true).await?;
+    file.write_block(&vec![0xaa; FILE_SIZE as usize], 0, None)
+        .await?;
+
+    let hole = 1024 * 1024..3 * 1024 * 1024;
+    file.punch_hole(hole.clone()).await?;
+
+    let mut data = vec![0u8; 4096];
+    let read = file.read_block(&mut data, hole.start, None, false).await?;
+    assert_eq!(read, data.len());
+    assert!(data.iter().all(|&b| b == 0));
+
+    let ranges = file.allocated_ranges().await?;
+    assert!(!ranges.is_empty());
+    assert!(ranges.iter().all(|range| range.end <= FILE_SIZE));
+    // Servers may keep the hole partially allocated, at their allocation granularity.
+    let allocated = ranges
+        .iter()
+        .map(|range| range.end - range.start)
+        .sum::<u64>();
+    assert!(allocated < FILE_SIZE);
+
+    file.close().await?;
+    tree.remove_file(FILE_NAME).await?;
+    client.close().await?;
+    Ok(())
+}
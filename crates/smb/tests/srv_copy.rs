@@ -0,0 +1,117 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const SOURCE_NAME: &str = "srv_copy_source.bin";
+const DEST_NAME: &str = "srv_copy_dest.bin";
+const FILE_SIZE: usize = 3 * 1024 * 1024 + 123;
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_srv_copy_range() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let content = (0..FILE_SIZE).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let source = tree
+        .create_file(
+            SOURCE_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    source.write_block(&content, 0, None).await?;
+
+    // Write-only destination, copied using FSCTL_SRV_COPYCHUNK_WRITE.
+    let destination = tree
+        .create_file(
+            DEST_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new()
+                .with_file_write_data(true)
+                .with_synchronize(true),
+        )
+        .await?
+        .unwrap_file();
+    let copied = destination
+        .srv_copy_range(&source, 1000, 0, FILE_SIZE as u64)
+        .await?;
+    assert_eq!(copied, (FILE_SIZE - 1000) as u64);
+    destination.close().await?;
+
+    // Copy the whole file, falling back from offload copy where unsupported.
+    let destination = tree
+        .create_file(
+            DEST_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    let copied = destination.copy_from(&source).await?;
+    assert_eq!(copied, FILE_SIZE as u64);
+
+    let mut data = vec![0u8; FILE_SIZE];
+    let read = destination.read_block(&mut data, 0, None, false).await?;
+    assert_eq!(read, FILE_SIZE);
+    assert!(data == content);
+
+    destination.close().await?;
+    source.close().await?;
+    tree.remove_file(DEST_NAME).await?;
+    tree.remove_file(SOURCE_NAME).await?;
+    client.close().await?;
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_clone_from() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let content = (0..FILE_SIZE).map(|i| (i % 241) as u8).collect::<Vec<_>>();
+    let source = tree
+        .create_file(
+            SOURCE_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    source.write_block(&content, 0, None).await?;
+
+    // Servers without block cloning support fall back to a server-side copy.
+    let destination = tree
+        .create_file(
+            DEST_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    destination.clone_from(&source).await?;
+
+    let mut data = vec![0u8; FILE_SIZE];
+    let read = destination.read_block(&mut data, 0, None, false).await?;
+    assert_eq!(read, FILE_SIZE);
+    assert!(data == content);
+
+    destination.close().await?;
+    source.close().await?;
+    tree.remove_file(DEST_NAME).await?;
+    tree.remove_file(SOURCE_NAME).await?;
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use serial_test::serial;
+use smb::resource::{CopyOptions, GetLen, block_copy_with_options};
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const FILE_NAME: &str = "streams_test.txt";
+const STREAM_NAME: &str = "Zone.Identifier";
+const STREAM_DATA: &[u8] = b"[ZoneTransfer]\r\nZoneId=3\r\n";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_streams() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let file = tree
+        .create_file(
+            FILE_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    file.write_block(b"default data", 0, None).await?;
+
+    let stream = file
+        .open_stream(
+            STREAM_NAME,
+            &FileCreateArgs::make_overwrite(Default::default(), Default::default()),
+        )
+        .await?;
+    stream.write_block(STREAM_DATA, 0, None).await?;
+    stream.close().await?;
+
+    let streams = file.streams().await?;
+    assert!(streams.iter().any(|s| s.is_default()));
+    let named = streams
+        .iter()
+        .find(|s| s.name == STREAM_NAME)
+        .ok_or("stream not listed")?;
+    assert_eq!(named.size, STREAM_DATA.len() as u64);
+
+    // Streams are also opened by their full path.
+    let stream = tree
+        .create(
+            &format!("{FILE_NAME}:{STREAM_NAME}:$DATA"),
+            &FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true)),
+        )
+        .await?
+        .unwrap_file();
+    let mut data = vec![0u8; STREAM_DATA.len()];
+    let read = stream.read_block(&mut data, 0, None, false).await?;
+    assert_eq!(&data[..read], STREAM_DATA);
+    stream.close().await?;
+
+    let invalid = tree
+        .create(
+            &format!("{FILE_NAME}:{STREAM_NAME}:$EA"),
+            &FileCreateArgs::make_open_existing(FileAccessMask::new().with_generic_read(true)),
+        )
+        .await;
+    assert!(matches!(invalid, Err(Error::InvalidArgument(_))));
+
+    file.delete_stream(STREAM_NAME).await?;
+    let streams = file.streams().await?;
+    assert!(!streams.iter().any(|s| s.name == STREAM_NAME));
+
+    file.set_info(FileDispositionInformation {
+        delete_pending: true.into(),
+    })
+    .await?;
+    file.close().await?;
+    client.close().await?;
+    Ok(())
+}
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_block_copy_streams() -> Result<(), Box<dyn std::error::Error>> {
+    const DEST_NAME: &str = "streams_test_copy.txt";
+    const DEFAULT_DATA: &[u8] = b"default data";
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    let source = tree
+        .create_file(
+            FILE_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    source.write_block(DEFAULT_DATA, 0, None).await?;
+    let stream = source
+        .open_stream(
+            STREAM_NAME,
+            &FileCreateArgs::make_overwrite(Default::default(), Default::default()),
+        )
+        .await?;
+    stream.write_block(STREAM_DATA, 0, None).await?;
+    stream.close().await?;
+
+    let destination = tree
+        .create_file(
+            DEST_NAME,
+            CreateDisposition::OverwriteIf,
+            FileAccessMask::new().with_generic_all(true),
+        )
+        .await?
+        .unwrap_file();
+    let options = CopyOptions {
+        streams: true,
+        ..Default::default()
+    };
+    #[cfg(not(feature = "single_threaded"))]
+    block_copy_with_options(source, destination, 0, options).await?;
+    #[cfg(feature = "single_threaded")]
+    block_copy_with_options(source, destination, options, None)?;
+
+    let copy = tree
+        .open_existing(DEST_NAME, FileAccessMask::new().with_generic_all(true))
+        .await?
+        .unwrap_file();
+    let len = copy.get_len().await?;
+    assert_eq!(len, DEFAULT_DATA.len() as u64);
+    let named = copy
+        .streams()
+        .await?
+        .into_iter()
+        .find(|s| s.name == STREAM_NAME)
+        .ok_or("stream not copied")?;
+    assert_eq!(named.size, STREAM_DATA.len() as u64);
+
+    copy.set_info(FileDispositionInformation::default()).await?;
+    copy.close().await?;
+    tree.remove_file(FILE_NAME).await?;
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use serial_test::serial;
+use smb::*;
+use std::result::Result;
+mod common;
+
+use common::TestConstants;
+use common::make_server_connection;
+
+const LINK_NAME: &str = "symlink_test_link";
+const LINK_TARGET: &str = "symlink_test_target.txt";
+
+#[test_log::test(maybe_async::test(
+    not(feature = "async"),
+    async(feature = "async", tokio::test(flavor = "multi_thread"))
+))]
+#[serial]
+async fn test_create_and_read_symlink() -> Result<(), Box<dyn std::error::Error>> {
+    let (client, share_path) = make_server_connection(TestConstants::DEFAULT_SHARE, None).await?;
+    let tree = client.get_tree(&share_path).await?;
+
+    tree.create_symlink(LINK_NAME, LINK_TARGET, false).await?;
+    let target = tree.read_link(LINK_NAME).await?;
+    assert_eq!(target, LINK_TARGET);
+
+    // Remove the link itself, rather than its (missing) target.
+    let link = tree
+        .create(
+            LINK_NAME,
+            &FileCreateArgs {
+                options: CreateOptions::new().with_open_reparse_point(true),
+                ..FileCreateArgs::make_open_existing(FileAccessMask::new().with_delete(true))
+            },
+        )
+        .await?;
+    link.handle()
+        .set_info(FileDispositionInformation {
+            delete_pending: true.into(),
+        })
+        .await?;
+    link.handle().close().await?;
+
+    client.close().await?;
+    Ok(())
+}
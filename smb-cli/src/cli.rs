@@ -3,7 +3,7 @@ use clap::{Parser, Subcommand, ValueEnum};
 use smb::connection::MultiChannelConfig;
 use smb::transport::config::*;
 use smb::{
-    ClientConfig, ConnectionConfig,
+    ClientConfig, ConnectionConfig, ReconnectPolicy,
     connection::{AuthMethodsConfig, EncryptionMode},
 };
 use smb::{Dialect, Guid};
@@ -21,6 +21,9 @@ pub struct Cli {
     /// Disables DFS referral resolution.
     #[arg(long)]
     pub no_dfs: bool,
+    /// Disables re-establishing lost connections.
+    #[arg(long)]
+    pub no_reconnect: bool,
 
     /// Configures multi-channel support.
     #[arg(long, default_value_t = MultiChannelMode::default())]
@@ -138,6 +141,11 @@ impl Cli {
             #[cfg(feature = "rdma")]
             rdma_type: self.rdma_type.map(|x| x.into()),
             client_guid: Guid::generate(),
+            reconnect: if self.no_reconnect {
+                ReconnectPolicy::disabled()
+            } else {
+                ReconnectPolicy::default()
+            },
             connection: ConnectionConfig {
                 max_dialect: Some(Dialect::MAX),
                 encryption_mode: EncryptionMode::Allowed,
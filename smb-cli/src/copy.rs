@@ -22,6 +22,14 @@ pub struct CopyCmd {
     #[arg(short, long)]
     pub force: bool,
 
+    /// Also copy the named data streams of the file, when copying between remote files.
+    #[arg(long)]
+    pub streams: bool,
+
+    /// Copy only the allocated ranges of the source, keeping its holes as holes in the destination.
+    #[arg(long)]
+    pub sparse: bool,
+
     /// Source path
     pub from: Path,
     /// Destination path
@@ -147,28 +155,44 @@ impl CopyFile {
     }
 
     #[maybe_async]
-    async fn copy_to(self, to: CopyFile, client: &Client) -> Result<(), smb::Error> {
+    async fn copy_to(self, to: CopyFile, client: &Client, cmd: &CopyCmd) -> Result<(), smb::Error> {
         use CopyFileValue::*;
 
         let channel_jobs = self._get_channel_to_jobs_map(&to, client).await?;
 
+        let options = CopyOptions {
+            sparse: cmd.sparse,
+            streams: cmd.streams,
+        };
+        // Copies between servers are usually transferred through the client, so they use the
+        // parallel copy and its progress bar, rather than the sequential fallback of `copy_from`.
+        let same_server = match (&self.path, &to.path) {
+            (Path::Remote(from), Path::Remote(to)) => {
+                from.server().eq_ignore_ascii_case(to.server())
+            }
+            _ => false,
+        };
         match self.value {
             Local(from_local) => match to.value {
                 Local(_) => unreachable!(),
-                Remote(to_remote) => Self::do_copy(from_local, to_remote, channel_jobs).await?,
+                Remote(to_remote) => {
+                    Self::do_copy(from_local, to_remote, channel_jobs, options).await?
+                }
             },
             Remote(from_remote) => match to.value {
-                Local(to_local) => Self::do_copy(from_remote, to_local, channel_jobs).await?,
+                Local(to_local) => {
+                    Self::do_copy(from_remote, to_local, channel_jobs, options).await?
+                }
                 Remote(to_remote) => {
-                    if to.path.as_remote().unwrap().server()
-                        == self.path.as_remote().unwrap().server()
-                        && to.path.as_remote().unwrap().share()
-                            == self.path.as_remote().unwrap().share()
-                    {
-                        // Use server-side copy if both files are on the same server
-                        to_remote.srv_copy(&from_remote).await?
+                    // Server-side copies do not preserve holes.
+                    if same_server && !cmd.sparse {
+                        if cmd.streams {
+                            from_remote.copy_streams(&to_remote).await?;
+                        }
+                        // Offloads the copy to the server where possible
+                        to_remote.copy_from(&from_remote).await?;
                     } else {
-                        Self::do_copy(from_remote, to_remote, channel_jobs).await?
+                        Self::do_copy(from_remote, to_remote, channel_jobs, options).await?
                     }
                 }
             },
@@ -179,15 +203,16 @@ impl CopyFile {
     #[maybe_async]
     #[cfg(not(feature = "single_threaded"))]
     pub async fn do_copy<
-        F: ReadAtChannel + GetLen + Send + Sync + 'static,
-        T: WriteAtChannel + SetLen + Send + Sync + 'static,
+        F: ReadAtChannel + GetLen + GetAllocatedRanges + CopyStreams<T> + Send + Sync + 'static,
+        T: WriteAtChannel + SetLen + SetSparse + Send + Sync + 'static,
     >(
         from: F,
         to: T,
         channel_jobs: HashMap<Option<u32>, usize>,
+        options: CopyOptions,
     ) -> smb::Result<()> {
-        let state = prepare_parallel_copy(&from, &to, channel_jobs).await?;
-        let state = Arc::new(state);
+        let state =
+            Arc::new(prepare_parallel_copy_with_options(&from, &to, channel_jobs, options).await?);
         let progress_handle = Self::progress(state.clone());
         start_parallel_copy(from, to, state).await?;
 
@@ -200,19 +225,20 @@ impl CopyFile {
 
     /// Single-threaded copy implementation.
     #[cfg(feature = "single_threaded")]
-    pub fn do_copy<F: ReadAtChannel + GetLen, T: WriteAtChannel + SetLen>(
+    pub fn do_copy<
+        F: ReadAtChannel + GetLen + GetAllocatedRanges + CopyStreams<T>,
+        T: WriteAtChannel + SetLen + SetSparse,
+    >(
         from: F,
         to: T,
         _channels: HashMap<Option<u32>, usize>,
+        options: CopyOptions,
     ) -> smb::Result<()> {
         let progress = Self::make_progress_bar(from.get_len()?);
-        block_copy_progress(
-            from,
-            to,
-            Some(&move |current| {
-                progress.set_position(current);
-            }),
-        )
+        let progress_callback = move |current| {
+            progress.set_position(current);
+        };
+        block_copy_with_options(from, to, options, Some(&progress_callback))
     }
 
     /// Async progress bar task starter.
@@ -264,7 +290,7 @@ pub async fn copy(cmd: &CopyCmd, cli: &Cli) -> Result<(), Box<dyn Error>> {
     let from = CopyFile::open(&cmd.from, &client, cli, cmd, true).await?;
     let to = CopyFile::open(&cmd.to, &client, cli, cmd, false).await?;
 
-    let copy_ok = from.copy_to(to, &client).await;
+    let copy_ok = from.copy_to(to, &client, cmd).await;
 
     client.close().await?;
 
@@ -87,25 +87,18 @@ pub async fn info(cmd: &InfoCmd, cli: &Cli) -> Result<(), Box<dyn Error>> {
             log::info!("  - Last access time: {}", info.last_access_time);
             if cmd.show_ea {
                 log::info!("  - Extended Attributes (EA):");
-                let basic_ea_info = file.query_info::<smb::FileEaInformation>().await?;
-                if basic_ea_info.ea_size > 0 {
-                    let ea_info = file
-                        .query_full_ea_info_with_options(
-                            vec![],
-                            Some(basic_ea_info.ea_size as usize),
-                        )
-                        .await?;
-                    ea_info.iter().for_each(|ea| {
-                        log::info!(
-                            "       - name='{}', size={} bytes, flags={:?}",
-                            ea.ea_name,
-                            ea.ea_value.len(),
-                            ea.flags
-                        );
-                    });
-                } else {
+                let mut eas = file.eas().await?.peekable();
+                if eas.peek().is_none() {
                     log::info!("       (no EAs present)");
                 }
+                eas.for_each(|ea| {
+                    log::info!(
+                        "       - name='{}', size={} bytes, flags={:?}",
+                        ea.ea_name,
+                        ea.ea_value.len(),
+                        ea.flags
+                    );
+                });
             }
             file.close().await?;
         }
@@ -131,6 +124,10 @@ pub async fn info(cmd: &InfoCmd, cli: &Cli) -> Result<(), Box<dyn Error>> {
             log::info!("Pipe");
             p.close().await?;
         }
+        Resource::Printer(p) => {
+            log::info!("Printer");
+            p.close().await?;
+        }
     };
 
     client.close().await?;
@@ -24,6 +24,9 @@ pub enum SecuritySubCommand {
 pub struct GetSecurityCmd {
     #[arg(long)]
     pub dacl: bool,
+    /// Translate the SIDs of the security descriptor to account names, using the LSA of the server.
+    #[arg(long)]
+    pub resolve_names: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -97,7 +100,7 @@ async fn open_resource(
     security_cmd: &SecurityCmd,
     cli: &Cli,
     access: FileAccessMask,
-) -> std::result::Result<Resource, Box<dyn std::error::Error>> {
+) -> std::result::Result<(Client, Resource), Box<dyn std::error::Error>> {
     let client = Client::new(cli.make_smb_client_config()?);
 
     if security_cmd.path.share().is_none() || security_cmd.path.share().unwrap().is_empty() {
@@ -117,7 +120,7 @@ async fn open_resource(
             &FileCreateArgs::make_open_existing(access),
         )
         .await?;
-    Ok(resource)
+    Ok((client, resource))
 }
 
 #[inline]
@@ -126,6 +129,7 @@ fn resource_handle(resource: &Resource) -> &ResourceHandle {
         Resource::File(f) => f.handle(),
         Resource::Directory(d) => d.handle(),
         Resource::Pipe(p) => p.handle(),
+        Resource::Printer(p) => p.handle(),
     }
 }
 
@@ -136,7 +140,7 @@ pub async fn get_security(
     cli: &Cli,
 ) -> std::result::Result<(), Box<dyn std::error::Error>> {
     let access = FileAccessMask::new().with_read_control(true);
-    let resource = open_resource(security_cmd, cli, access).await?;
+    let (client, resource) = open_resource(security_cmd, cli, access).await?;
     let resource_handle = resource_handle(&resource);
 
     let additional_info = AdditionalInfo::new().with_dacl_security_information(cmd.dacl);
@@ -146,10 +150,50 @@ pub async fn get_security(
     // TODO: pretty print
     log::info!("{:#?}", security_info);
 
+    if cmd.resolve_names {
+        let sids = security_sids(&security_info);
+        let accounts = client
+            .lookup_sids(security_cmd.path.server(), &sids)
+            .await?;
+        log::info!("Accounts:");
+        for (sid, account) in sids.iter().zip(accounts) {
+            match account {
+                Some(account) => log::info!("{sid} => {account}"),
+                None => log::info!("{sid} => (unknown)"),
+            }
+        }
+    }
+
     resource_handle.close().await?;
     Ok(())
 }
 
+/// Returns the distinct SIDs of the owner, group and ACEs of a security descriptor.
+fn security_sids(security_info: &SecurityDescriptor) -> Vec<SID> {
+    let ace_sids = [&security_info.dacl, &security_info.sacl]
+        .into_iter()
+        .flatten()
+        .flat_map(|acl| acl.ace.iter())
+        .filter_map(|ace| match &ace.value {
+            AceValue::AccessAllowed(ace)
+            | AceValue::AccessDenied(ace)
+            | AceValue::SystemAudit(ace) => Some(&ace.sid),
+            _ => None,
+        });
+
+    let mut sids: Vec<SID> = vec![];
+    for sid in [&security_info.owner_sid, &security_info.group_sid]
+        .into_iter()
+        .flatten()
+        .chain(ace_sids)
+    {
+        if !sids.contains(sid) {
+            sids.push(sid.clone());
+        }
+    }
+    sids
+}
+
 #[maybe_async::maybe_async]
 pub async fn set_security(
     cmd: &SetSecurityCmd,
@@ -162,7 +206,7 @@ pub async fn set_security(
         .with_read_control(true)
         .with_write_dacl(write_dacl);
 
-    let resource = open_resource(security_cmd, cli, access).await?;
+    let (_client, resource) = open_resource(security_cmd, cli, access).await?;
     let resource_handle = resource_handle(&resource);
 
     // Query only the required information ot perform the update